@@ -0,0 +1,54 @@
+-- Cabang menjadi data sendiri: alamat, koordinat, jam operasional dan kontak. motors.branch,
+-- orders.pilih_cabang dan users.branch_id mengacu ke branches.name (ganti nama cabang ikut berubah).
+-- Jalankan setelah create_email_templates_table.sql dan add_user_branch.sql.
+ALTER TABLE branches ALTER COLUMN address DROP NOT NULL;
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS email TEXT;
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION CHECK (latitude BETWEEN -90 AND 90);
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION CHECK (longitude BETWEEN -180 AND 180);
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS opening_time TIME;
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS closing_time TIME;
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE branches ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_branches_name_lower ON branches(LOWER(TRIM(name)));
+
+-- Nama cabang yang selama ini hanya ada sebagai teks bebas dijadikan cabang
+INSERT INTO branches (name)
+SELECT DISTINCT ON (LOWER(TRIM(s.name))) TRIM(s.name)
+FROM (
+    SELECT branch AS name FROM motors
+    UNION ALL SELECT pilih_cabang FROM orders
+    UNION ALL SELECT branch_id FROM users
+) s
+WHERE s.name IS NOT NULL AND TRIM(s.name) <> ''
+  AND NOT EXISTS (SELECT 1 FROM branches b WHERE LOWER(TRIM(b.name)) = LOWER(TRIM(s.name)))
+ORDER BY LOWER(TRIM(s.name)), TRIM(s.name);
+
+-- Samakan penulisan nama cabang dengan tabel branches
+UPDATE motors SET branch = NULL WHERE TRIM(branch) = '';
+UPDATE users SET branch_id = NULL WHERE TRIM(branch_id) = '';
+UPDATE motors m SET branch = b.name FROM branches b
+WHERE LOWER(TRIM(b.name)) = LOWER(TRIM(m.branch)) AND m.branch <> b.name;
+UPDATE orders o SET pilih_cabang = b.name FROM branches b
+WHERE LOWER(TRIM(b.name)) = LOWER(TRIM(o.pilih_cabang)) AND o.pilih_cabang <> b.name;
+UPDATE users u SET branch_id = b.name FROM branches b
+WHERE LOWER(TRIM(b.name)) = LOWER(TRIM(u.branch_id)) AND u.branch_id <> b.name;
+
+-- NOT VALID dulu supaya data baru langsung dicek; VALIDATE gagal jika masih ada data lama yang
+-- tidak cocok (rapikan dulu, constraint tetap berlaku untuk data baru)
+ALTER TABLE motors DROP CONSTRAINT IF EXISTS motors_branch_fkey;
+ALTER TABLE motors ADD CONSTRAINT motors_branch_fkey
+    FOREIGN KEY (branch) REFERENCES branches(name) ON UPDATE CASCADE NOT VALID;
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_pilih_cabang_fkey;
+ALTER TABLE orders ADD CONSTRAINT orders_pilih_cabang_fkey
+    FOREIGN KEY (pilih_cabang) REFERENCES branches(name) ON UPDATE CASCADE NOT VALID;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_branch_id_fkey;
+ALTER TABLE users ADD CONSTRAINT users_branch_id_fkey
+    FOREIGN KEY (branch_id) REFERENCES branches(name) ON UPDATE CASCADE NOT VALID;
+
+ALTER TABLE motors VALIDATE CONSTRAINT motors_branch_fkey;
+ALTER TABLE orders VALIDATE CONSTRAINT orders_pilih_cabang_fkey;
+ALTER TABLE users VALIDATE CONSTRAINT users_branch_id_fkey;
+
+CREATE INDEX IF NOT EXISTS idx_motors_branch ON motors(branch);
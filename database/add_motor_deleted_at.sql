@@ -0,0 +1,6 @@
+-- Hapus motor (DELETE /api/motors/:id) jadi soft delete. Baris motor tetap ada supaya order,
+-- ulasan dan unit lama masih merujuk ke motornya; admin bisa mengembalikan lewat
+-- POST /api/motors/:id/restore.
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_motors_not_deleted ON motors(motor_id) WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+-- Thumbnail webp foto motor (POST /api/motors/:id/image): {"card": url, "detail": url, "zoom": url}.
+-- NULL selama thumbnail masih dibuat atau jika image_url menunjuk ke gambar eksternal.
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS image_thumbnails JSONB;
@@ -0,0 +1,3 @@
+-- Index untuk filter / urutan harga di GET /api/motors (min_price, max_price, sort=price_asc / price_desc)
+CREATE INDEX IF NOT EXISTS idx_motors_price_per_day ON motors(price_per_day, motor_id);
+CREATE INDEX IF NOT EXISTS idx_motors_branch_lower ON motors(LOWER(TRIM(branch)));
@@ -0,0 +1,9 @@
+-- Tarif mingguan / bulanan per motor, dan rincian harga yang dihitung saat order dibuat
+ALTER TABLE motors
+    ADD COLUMN IF NOT EXISTS price_per_week INTEGER CHECK (price_per_week >= 0),
+    ADD COLUMN IF NOT EXISTS price_per_month INTEGER CHECK (price_per_month >= 0);
+
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS rental_days INTEGER,
+    ADD COLUMN IF NOT EXISTS total_price BIGINT,
+    ADD COLUMN IF NOT EXISTS price_breakdown JSONB;
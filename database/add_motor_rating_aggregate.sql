@@ -0,0 +1,53 @@
+-- Ringkasan rating per motor, diperbarui otomatis oleh trigger setiap ada ulasan baru / berubah / dihapus
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS rating_avg DOUBLE PRECISION;
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS rating_count INT NOT NULL DEFAULT 0;
+
+CREATE OR REPLACE FUNCTION refresh_motor_rating(target_motor_id INT) RETURNS VOID AS $$
+BEGIN
+    IF target_motor_id IS NULL THEN
+        RETURN;
+    END IF;
+
+    UPDATE motors m
+    SET rating_avg = r.avg_rating,
+        rating_count = r.total
+    FROM (
+        SELECT AVG(rating)::float8 AS avg_rating, COUNT(*)::int AS total
+        FROM order_reviews
+        WHERE motor_id = target_motor_id
+    ) r
+    WHERE m.motor_id = target_motor_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION order_reviews_refresh_motor_rating() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM refresh_motor_rating(OLD.motor_id);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND (TG_OP = 'INSERT' OR NEW.motor_id IS DISTINCT FROM OLD.motor_id
+                                          OR NEW.rating <> OLD.rating) THEN
+        PERFORM refresh_motor_rating(NEW.motor_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_order_reviews_motor_rating ON order_reviews;
+CREATE TRIGGER trg_order_reviews_motor_rating
+    AFTER INSERT OR UPDATE OF rating, motor_id OR DELETE ON order_reviews
+    FOR EACH ROW EXECUTE FUNCTION order_reviews_refresh_motor_rating();
+
+-- Isi ringkasan untuk ulasan yang sudah ada
+UPDATE motors m
+SET rating_avg = r.avg_rating,
+    rating_count = r.total
+FROM (
+    SELECT motor_id, AVG(rating)::float8 AS avg_rating, COUNT(*)::int AS total
+    FROM order_reviews
+    WHERE motor_id IS NOT NULL
+    GROUP BY motor_id
+) r
+WHERE m.motor_id = r.motor_id;
+
+CREATE INDEX IF NOT EXISTS idx_motors_rating ON motors(rating_avg DESC NULLS LAST, rating_count DESC);
@@ -0,0 +1,11 @@
+-- Full-text search katalog motor (GET /api/motors?search=). Bobot: nama > tipe / cabang > deskripsi.
+-- Konfigurasi 'simple' supaya nama model & kota tidak di-stem.
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', COALESCE(motor_name, '')), 'A') ||
+        setweight(to_tsvector('simple', COALESCE(motor_type, '')), 'B') ||
+        setweight(to_tsvector('simple', COALESCE(branch, '')), 'B') ||
+        setweight(to_tsvector('simple', COALESCE(description, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_motors_search_vector ON motors USING GIN (search_vector);
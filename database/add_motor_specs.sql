@@ -0,0 +1,8 @@
+-- Spesifikasi motor untuk ditampilkan dan difilter di list motor (contoh: ?transmission=matic)
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS engine_cc INTEGER CHECK (engine_cc BETWEEN 50 AND 2000);
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS transmission TEXT CHECK (transmission IN ('matic', 'semi_automatic', 'manual'));
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS fuel_capacity DOUBLE PRECISION CHECK (fuel_capacity > 0 AND fuel_capacity <= 50); -- liter
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS seat_height INTEGER CHECK (seat_height BETWEEN 500 AND 1200); -- mm
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS year INTEGER CHECK (year >= 1980);
+
+CREATE INDEX IF NOT EXISTS idx_motors_transmission ON motors(transmission, engine_cc) WHERE deleted_at IS NULL;
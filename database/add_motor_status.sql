@@ -0,0 +1,14 @@
+-- Status motor menggantikan flag available: active (bisa dibooking), maintenance (sedang
+-- diperbaiki), reserved (disisihkan) dan retired (tidak disewakan lagi). Diubah lewat
+-- PATCH /api/motors/:id/status. Kolom available tetap ada sebagai kolom turunan
+-- (status = 'active') supaya query dan client lama tetap jalan.
+ALTER TABLE motors ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'active'
+    CHECK (status IN ('active', 'maintenance', 'reserved', 'retired'));
+
+-- Motor yang sebelumnya dinonaktifkan dianggap sedang maintenance
+UPDATE motors SET status = 'maintenance' WHERE available IS FALSE;
+
+ALTER TABLE motors DROP COLUMN available;
+ALTER TABLE motors ADD COLUMN available BOOLEAN GENERATED ALWAYS AS (status = 'active') STORED;
+
+CREATE INDEX IF NOT EXISTS idx_motors_status ON motors(status);
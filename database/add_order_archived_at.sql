@@ -0,0 +1,6 @@
+-- Hapus order = arsipkan (DELETE /api/orders/:id), supaya riwayat keuangan tidak hilang.
+-- Order yang diarsipkan disembunyikan dari list order; admin melihatnya di GET /api/orders/archived.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS archived_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_orders_archived_at ON orders(archived_at DESC) WHERE archived_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Index untuk cursor (keyset) pagination list order: urut tanggal_booking, waktu_booking, id menurun
+CREATE INDEX IF NOT EXISTS idx_orders_booked_keyset
+    ON orders(tanggal_booking DESC, waktu_booking DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_orders_user_booked_keyset
+    ON orders(user_id, tanggal_booking DESC, waktu_booking DESC, id DESC);
@@ -0,0 +1,16 @@
+-- Deposit jaminan: nominal per tipe motor, dicatat per order (diterima / dikembalikan + potongan kerusakan)
+CREATE TABLE IF NOT EXISTS motor_type_deposits (
+    motor_type TEXT PRIMARY KEY,
+    amount INTEGER NOT NULL CHECK (amount >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS deposit_amount INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS deposit_status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (deposit_status IN ('pending', 'held', 'released')),
+    ADD COLUMN IF NOT EXISTS deposit_deduction INTEGER,
+    ADD COLUMN IF NOT EXISTS deposit_deduction_reason TEXT,
+    ADD COLUMN IF NOT EXISTS deposit_held_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deposit_released_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deposit_handled_by UUID REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,6 @@
+-- Index untuk filter list order admin (GET /api/orders/all?status=&branch=&date_from=&date_to=)
+-- dan urutan default terbaru dulu
+CREATE INDEX IF NOT EXISTS idx_orders_branch_status ON orders(LOWER(TRIM(pilih_cabang)), status);
+CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(status);
+CREATE INDEX IF NOT EXISTS idx_orders_rental_period ON orders(tanggal_peminjaman, tanggal_pengembalian);
+CREATE INDEX IF NOT EXISTS idx_orders_booked_at ON orders(tanggal_booking DESC, waktu_booking DESC);
@@ -0,0 +1,12 @@
+-- Cara serah terima motor: pickup = customer ambil & kembalikan di cabang (tanpa alamat / ongkir),
+-- delivery = motor diantar dan dijemput ke alamat customer
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS fulfillment_mode TEXT NOT NULL DEFAULT 'delivery';
+
+ALTER TABLE orders ALTER COLUMN alamat_pengantaran DROP NOT NULL;
+ALTER TABLE orders ALTER COLUMN alamat_pengembalian DROP NOT NULL;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_fulfillment_mode_check;
+ALTER TABLE orders ADD CONSTRAINT orders_fulfillment_mode_check CHECK (
+    fulfillment_mode = 'pickup'
+    OR (fulfillment_mode = 'delivery' AND alamat_pengantaran IS NOT NULL AND alamat_pengembalian IS NOT NULL)
+);
@@ -0,0 +1,8 @@
+-- Reservasi sementara (hold): status 'held' dengan batas waktu, dilepas otomatis setelah kedaluwarsa
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS hold_expires_at TIMESTAMPTZ;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_status_check;
+ALTER TABLE orders ADD CONSTRAINT orders_status_check
+    CHECK (status IN ('held', 'pending', 'confirmed', 'picked_up', 'returned', 'completed', 'cancelled')) NOT VALID;
+
+CREATE INDEX IF NOT EXISTS idx_orders_hold_expires_at ON orders(hold_expires_at) WHERE status = 'held';
@@ -0,0 +1,11 @@
+-- Referensi motor yang asli di orders (sebelumnya hanya teks pilih_motor / motor_price)
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS motor_id INTEGER REFERENCES motors(motor_id) ON DELETE SET NULL;
+
+-- Isi motor_id untuk order lama berdasarkan nama / slug motor
+UPDATE orders o
+SET motor_id = m.motor_id
+FROM motors m
+WHERE o.motor_id IS NULL
+  AND (LOWER(TRIM(o.pilih_motor)) = LOWER(m.motor_name) OR TRIM(o.pilih_motor) = m.motor_slug);
+
+CREATE INDEX IF NOT EXISTS idx_orders_motor_id ON orders(motor_id);
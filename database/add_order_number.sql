@@ -0,0 +1,35 @@
+-- Nomor order berurutan per tahun (SMT-2025-000123), menggantikan kode BWK yang bisa bentrok.
+-- Counter disimpan per tahun dan dinaikkan di dalam transaksi booking, jadi booking yang
+-- gagal / rollback tidak meninggalkan nomor yang bolong (beda dengan SEQUENCE).
+CREATE TABLE IF NOT EXISTS order_number_counters (
+    year INT PRIMARY KEY,
+    last_value INT NOT NULL
+);
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS order_number TEXT;
+
+-- Beri nomor untuk order lama sesuai urutan waktu booking
+WITH numbered AS (
+    SELECT id,
+           EXTRACT(YEAR FROM tanggal_booking)::int AS year,
+           ROW_NUMBER() OVER (
+               PARTITION BY EXTRACT(YEAR FROM tanggal_booking)
+               ORDER BY tanggal_booking, waktu_booking, id
+           ) AS seq
+    FROM orders
+    WHERE order_number IS NULL
+)
+UPDATE orders o
+SET order_number = 'SMT-' || n.year || '-' || LPAD(n.seq::text, 6, '0')
+FROM numbered n
+WHERE o.id = n.id;
+
+INSERT INTO order_number_counters (year, last_value)
+SELECT EXTRACT(YEAR FROM tanggal_booking)::int, COUNT(*)
+FROM orders
+GROUP BY 1
+ON CONFLICT (year) DO UPDATE SET last_value = GREATEST(order_number_counters.last_value, EXCLUDED.last_value);
+
+ALTER TABLE orders ALTER COLUMN order_number SET NOT NULL;
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_order_number_key;
+ALTER TABLE orders ADD CONSTRAINT orders_order_number_key UNIQUE (order_number);
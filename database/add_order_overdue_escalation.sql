@@ -0,0 +1,12 @@
+-- Eskalasi keterlambatan pengembalian motor (job overdue): pengingat saat jadwal kembali, teguran kedua,
+-- lalu status 'overdue' + notifikasi staff cabang. overdue_stage mencatat tahap terakhir yang sudah dikirim
+-- (0 = belum, 1 = pengingat, 2 = teguran kedua, 3 = ditandai overdue) supaya tiap tahap hanya sekali.
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS overdue_stage SMALLINT NOT NULL DEFAULT 0;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_status_check;
+ALTER TABLE orders ADD CONSTRAINT orders_status_check
+    CHECK (status IN ('held', 'pending', 'confirmed', 'picked_up', 'overdue', 'returned', 'completed', 'cancelled')) NOT VALID;
+
+CREATE INDEX IF NOT EXISTS idx_orders_overdue_due
+    ON orders((tanggal_pengembalian + jam_pengembalian))
+    WHERE status IN ('picked_up', 'overdue');
@@ -0,0 +1,11 @@
+-- Cegah satu motor dibooking dua kali untuk periode yang bertabrakan (order cancelled tidak dihitung).
+-- Jalankan setelah add_order_motor_id.sql. Jika ada data lama yang sudah bentrok, rapikan dulu sebelum menjalankan ini.
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_no_overlapping_motor_booking;
+ALTER TABLE orders ADD CONSTRAINT orders_no_overlapping_motor_booking
+    EXCLUDE USING gist (
+        motor_id WITH =,
+        tsrange(tanggal_peminjaman + jam_peminjaman, tanggal_pengembalian + jam_pengembalian, '[)') WITH &&
+    )
+    WHERE (motor_id IS NOT NULL AND status <> 'cancelled');
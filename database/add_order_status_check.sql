@@ -0,0 +1,8 @@
+-- Status order mengikuti enum OrderStatus di model/orders.rs
+UPDATE orders SET status = LOWER(TRIM(status)) WHERE status <> LOWER(TRIM(status));
+UPDATE orders SET status = 'picked_up' WHERE status IN ('picked up', 'pickedup', 'ongoing');
+
+-- NOT VALID: data lama dengan status lain tidak memblokir migrasi, tapi insert / update baru tetap dicek
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_status_check;
+ALTER TABLE orders ADD CONSTRAINT orders_status_check
+    CHECK (status IN ('pending', 'confirmed', 'picked_up', 'returned', 'completed', 'cancelled')) NOT VALID;
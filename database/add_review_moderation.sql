@@ -0,0 +1,44 @@
+-- Moderasi ulasan: hanya ulasan approved yang tampil dan dihitung di rating motor.
+-- Jalankan setelah add_motor_rating_aggregate.sql.
+ALTER TABLE order_reviews ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'approved'
+    CHECK (status IN ('pending', 'approved', 'hidden'));
+ALTER TABLE order_reviews ADD COLUMN IF NOT EXISTS moderation_note TEXT;
+ALTER TABLE order_reviews ADD COLUMN IF NOT EXISTS moderated_by UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE order_reviews ADD COLUMN IF NOT EXISTS moderated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_order_reviews_status ON order_reviews(status, created_at DESC);
+
+CREATE OR REPLACE FUNCTION refresh_motor_rating(target_motor_id INT) RETURNS VOID AS $$
+BEGIN
+    IF target_motor_id IS NULL THEN
+        RETURN;
+    END IF;
+
+    UPDATE motors m
+    SET rating_avg = r.avg_rating,
+        rating_count = r.total
+    FROM (
+        SELECT AVG(rating)::float8 AS avg_rating, COUNT(*)::int AS total
+        FROM order_reviews
+        WHERE motor_id = target_motor_id AND status = 'approved'
+    ) r
+    WHERE m.motor_id = target_motor_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION order_reviews_refresh_motor_rating() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM refresh_motor_rating(OLD.motor_id);
+    END IF;
+    IF TG_OP = 'INSERT' OR (TG_OP = 'UPDATE' AND NEW.motor_id IS DISTINCT FROM OLD.motor_id) THEN
+        PERFORM refresh_motor_rating(NEW.motor_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_order_reviews_motor_rating ON order_reviews;
+CREATE TRIGGER trg_order_reviews_motor_rating
+    AFTER INSERT OR UPDATE OF rating, motor_id, status OR DELETE ON order_reviews
+    FOR EACH ROW EXECUTE FUNCTION order_reviews_refresh_motor_rating();
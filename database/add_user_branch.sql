@@ -0,0 +1,6 @@
+-- Staff per cabang. Nama cabang masih teks bebas (sama dengan motors.branch dan orders.pilih_cabang).
+-- Admin melihat semua cabang; staff tanpa branch_id tidak bisa mengakses data pengelolaan.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS branch_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_users_branch_id ON users(branch_id) WHERE branch_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_orders_pilih_cabang ON orders(LOWER(TRIM(pilih_cabang)));
@@ -0,0 +1,10 @@
+-- Hapus akun oleh user sendiri (DELETE /api/users/me).
+-- Data pribadi dianonimkan, baris user tetap ada supaya order lama tidak kehilangan pemiliknya.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS account_deletion_audit (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Login dengan Google (POST /api/auth/google): subject id akun Google yang terhubung ke user.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS google_sub TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_google_sub ON users(google_sub) WHERE google_sub IS NOT NULL;
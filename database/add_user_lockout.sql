@@ -0,0 +1,4 @@
+-- Lockout akun setelah login gagal berulang (unlock otomatis setelah locked_until lewat,
+-- atau manual via POST /api/admin/users/{id}/unlock).
+ALTER TABLE users ADD COLUMN IF NOT EXISTS failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
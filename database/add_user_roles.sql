@@ -0,0 +1,9 @@
+-- Role user untuk access control: admin, staff, customer.
+-- User yang sudah ada otomatis menjadi customer.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'customer';
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE users ADD CONSTRAINT users_role_check CHECK (role IN ('admin', 'staff', 'customer'));
+
+-- Contoh promote user menjadi admin:
+-- UPDATE users SET role = 'admin' WHERE username = 'admin';
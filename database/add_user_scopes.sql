@@ -0,0 +1,3 @@
+-- Permission (scope) khusus per user. NULL = pakai scope bawaan role
+-- (admin: semua, staff: orders/motors/reports, customer: tidak ada).
+ALTER TABLE users ADD COLUMN IF NOT EXISTS scopes TEXT[];
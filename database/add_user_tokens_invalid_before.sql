@@ -0,0 +1,2 @@
+-- Access token dengan iat sebelum waktu ini ditolak (diisi saat ganti / reset password).
+ALTER TABLE users ADD COLUMN IF NOT EXISTS tokens_invalid_before TIMESTAMPTZ;
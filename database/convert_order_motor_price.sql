@@ -0,0 +1,16 @@
+-- orders.motor_price dari teks tampilan ("Rp 50.000/hari", atau beberapa harga dipisah koma untuk
+-- booking rombongan) menjadi angka rupiah: total tarif harian semua motor di order. Teks "Rp .../hari"
+-- sekarang hanya dibuat saat menyusun response (format_price_per_day).
+CREATE FUNCTION pg_temp.parse_motor_price(price TEXT) RETURNS BIGINT AS $$
+    SELECT COALESCE(SUM(NULLIF(regexp_replace(part, '[^0-9]', '', 'g'), '')::BIGINT), 0)
+    FROM regexp_split_to_table(COALESCE(price, ''), ',') AS part
+$$ LANGUAGE SQL IMMUTABLE;
+
+ALTER TABLE orders ALTER COLUMN motor_price TYPE BIGINT USING pg_temp.parse_motor_price(motor_price);
+ALTER TABLE orders ALTER COLUMN motor_price SET DEFAULT 0;
+
+-- Order lama sebelum ada pricing engine: total dihitung di server dari tarif harian x lama sewa
+UPDATE orders
+SET rental_days = GREATEST(tanggal_pengembalian - tanggal_peminjaman, 1),
+    total_price = motor_price * GREATEST(tanggal_pengembalian - tanggal_peminjaman, 1)
+WHERE total_price IS NULL AND motor_price > 0;
@@ -0,0 +1,25 @@
+-- Stok aksesoris add-on (helm, jas hujan, holder HP) per cabang. quantity = total yang dimiliki
+-- cabang, available = yang ada di cabang (quantity - yang sedang dipinjam customer).
+CREATE TABLE IF NOT EXISTS accessory_stock (
+    branch TEXT NOT NULL REFERENCES branches(name) ON UPDATE CASCADE ON DELETE CASCADE,
+    addon_code TEXT NOT NULL REFERENCES addons(code) ON UPDATE CASCADE ON DELETE CASCADE,
+    quantity INTEGER NOT NULL CHECK (quantity >= 0),
+    available INTEGER NOT NULL, -- bisa minus jika staff menyerahkan lebih banyak dari yang tercatat
+    low_stock_threshold INTEGER NOT NULL DEFAULT 2 CHECK (low_stock_threshold >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (branch, addon_code)
+);
+
+-- Aksesoris yang dibawa customer per order (dicatat saat pickup, returned_at diisi saat kembali)
+CREATE TABLE IF NOT EXISTS order_accessories (
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    branch TEXT NOT NULL,
+    addon_code TEXT NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    checked_out_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    returned_at TIMESTAMPTZ,
+    PRIMARY KEY (order_id, addon_code),
+    FOREIGN KEY (branch, addon_code) REFERENCES accessory_stock(branch, addon_code) ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_accessories_open ON order_accessories(branch, addon_code) WHERE returned_at IS NULL;
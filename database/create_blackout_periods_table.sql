@@ -0,0 +1,15 @@
+-- Kalender tutup booking (libur nasional, hari servis armada) per motor, per cabang, atau semua cabang
+CREATE TABLE IF NOT EXISTS blackout_periods (
+    id UUID PRIMARY KEY,
+    branch TEXT,                                                   -- NULL = semua cabang
+    motor_id INTEGER REFERENCES motors(motor_id) ON DELETE CASCADE, -- NULL = semua motor di cabang
+    starts_at TIMESTAMP NOT NULL,
+    ends_at TIMESTAMP NOT NULL,
+    reason TEXT NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_blackout_periods_range ON blackout_periods USING gist (tsrange(starts_at, ends_at, '[)'));
+CREATE INDEX IF NOT EXISTS idx_blackout_periods_motor_id ON blackout_periods(motor_id);
@@ -0,0 +1,9 @@
+-- Zona pengantaran: ongkir ditentukan dari kata kunci (kecamatan / kelurahan / kota) yang muncul di alamat.
+-- Alamat yang tidak cocok dengan zona mana pun dikenai DELIVERY_DEFAULT_FEE.
+CREATE TABLE IF NOT EXISTS delivery_zones (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT '{}',
+    fee INTEGER NOT NULL CHECK (fee >= 0),
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
@@ -0,0 +1,25 @@
+-- Sopir (opsi "dengan sopir" saat booking) dan sopir yang ditugaskan ke order
+CREATE TABLE IF NOT EXISTS drivers (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    phone TEXT NOT NULL,
+    daily_rate INTEGER NOT NULL CHECK (daily_rate >= 0),
+    branch TEXT,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS with_driver BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS driver_id UUID REFERENCES drivers(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_orders_driver_id ON orders(driver_id);
+
+-- Satu sopir tidak boleh bertugas di dua order yang periodenya bertabrakan
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_no_overlapping_driver_booking;
+ALTER TABLE orders ADD CONSTRAINT orders_no_overlapping_driver_booking
+    EXCLUDE USING gist (
+        driver_id WITH =,
+        tsrange(tanggal_peminjaman + jam_peminjaman, tanggal_pengembalian + jam_pengembalian, '[)') WITH &&
+    )
+    WHERE (driver_id IS NOT NULL AND status <> 'cancelled');
@@ -0,0 +1,19 @@
+-- Alamat cabang untuk email konfirmasi (nama sama dengan orders.pilih_cabang / motors.branch)
+CREATE TABLE IF NOT EXISTS branches (
+    name TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    phone TEXT
+);
+
+-- Template email order per bahasa; menimpa template bawaan di kode (src/notifications.rs)
+CREATE TABLE IF NOT EXISTS email_templates (
+    template_key TEXT NOT NULL, -- order_created, order_confirmed
+    language TEXT NOT NULL,     -- id, en
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (template_key, language)
+);
+
+-- Bahasa email untuk order, dari header Accept-Language saat booking
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS language TEXT NOT NULL DEFAULT 'id';
@@ -0,0 +1,36 @@
+-- Zona operasional per cabang (lingkaran: titik pusat + radius meter). Unit yang berada di luar semua
+-- zona aktif cabangnya memicu alert out_of_zone; cabang tanpa zona tidak dicek.
+CREATE TABLE IF NOT EXISTS geofence_zones (
+    id UUID PRIMARY KEY,
+    branch TEXT NOT NULL REFERENCES branches(name) ON UPDATE CASCADE ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    center_latitude DOUBLE PRECISION NOT NULL CHECK (center_latitude BETWEEN -90 AND 90),
+    center_longitude DOUBLE PRECISION NOT NULL CHECK (center_longitude BETWEEN -180 AND 180),
+    radius_m INTEGER NOT NULL CHECK (radius_m BETWEEN 50 AND 200000),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_geofence_zones_branch ON geofence_zones(branch) WHERE active;
+
+-- Alert dari telemetry: unit keluar zona (out_of_zone) atau bergerak tanpa order aktif
+-- (moving_without_order). Hanya satu alert terbuka per unit + jenis.
+CREATE TABLE IF NOT EXISTS geofence_alerts (
+    id UUID PRIMARY KEY,
+    unit_id UUID NOT NULL REFERENCES motor_units(id) ON DELETE CASCADE,
+    branch TEXT,
+    kind TEXT NOT NULL CHECK (kind IN ('out_of_zone', 'moving_without_order')),
+    order_number TEXT,
+    latitude DOUBLE PRECISION NOT NULL,
+    longitude DOUBLE PRECISION NOT NULL,
+    speed_kmh REAL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_geofence_alerts_open ON geofence_alerts(unit_id, kind) WHERE resolved_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_geofence_alerts_created ON geofence_alerts(created_at DESC);
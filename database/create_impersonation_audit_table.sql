@@ -0,0 +1,15 @@
+-- Admin impersonation (POST /api/admin/impersonate/{user_id}).
+ALTER TABLE user_sessions ADD COLUMN IF NOT EXISTS impersonated_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE TABLE IF NOT EXISTS impersonation_audit (
+    id UUID PRIMARY KEY,
+    admin_id UUID NOT NULL REFERENCES users(id),
+    target_user_id UUID NOT NULL REFERENCES users(id),
+    session_id UUID REFERENCES user_sessions(id) ON DELETE SET NULL,
+    reason TEXT,
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_impersonation_audit_target ON impersonation_audit(target_user_id, created_at DESC);
@@ -0,0 +1,20 @@
+-- Laporan kerusakan / kecelakaan per order dan unit motor
+CREATE TABLE IF NOT EXISTS incidents (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    motor_id INT REFERENCES motors(motor_id) ON DELETE SET NULL,
+    severity TEXT NOT NULL CHECK (severity IN ('minor', 'moderate', 'major')),
+    description TEXT NOT NULL,
+    photos TEXT[] NOT NULL DEFAULT '{}', -- URL foto
+    estimated_cost BIGINT CHECK (estimated_cost >= 0),
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_progress', 'resolved')),
+    resolution_notes TEXT,
+    reported_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reported_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ,
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_incidents_order_id ON incidents(order_id);
+CREATE INDEX IF NOT EXISTS idx_incidents_unresolved ON incidents(reported_at) WHERE status <> 'resolved';
@@ -0,0 +1,16 @@
+-- Pilihan asuransi sewa (biaya per motor per hari) dan asuransi yang dipilih order
+CREATE TABLE IF NOT EXISTS insurance_tiers (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    daily_fee INTEGER NOT NULL CHECK (daily_fee >= 0),
+    coverage TEXT NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+INSERT INTO insurance_tiers (code, name, daily_fee, coverage) VALUES
+    ('basic', 'Basic', 5000, 'Kerusakan ringan hingga Rp 500.000'),
+    ('standard', 'Standard', 10000, 'Kerusakan hingga Rp 2.000.000, termasuk kaca spion dan lampu'),
+    ('premium', 'Premium', 20000, 'Kerusakan hingga Rp 5.000.000 dan kehilangan motor (risiko sendiri Rp 1.000.000)')
+ON CONFLICT (code) DO NOTHING;
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS insurance_tier TEXT REFERENCES insurance_tiers(code);
@@ -0,0 +1,11 @@
+-- Peringatan unit menipis per cabang + tipe motor di tanggal ramai (weekend / musim). Satu baris per
+-- kombinasi; admin dikabari lagi hanya jika unit kosongnya makin sedikit.
+CREATE TABLE IF NOT EXISTS low_availability_alerts (
+    branch TEXT NOT NULL,
+    motor_type TEXT NOT NULL,
+    demand_date DATE NOT NULL,
+    quantity BIGINT NOT NULL,
+    available BIGINT NOT NULL,
+    notified_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (branch, motor_type, demand_date)
+);
@@ -0,0 +1,9 @@
+-- Motor favorit customer (POST/DELETE /api/motors/:id/favorite, GET /api/users/me/favorites)
+CREATE TABLE IF NOT EXISTS motor_favorites (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    motor_id INTEGER NOT NULL REFERENCES motors(motor_id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, motor_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_motor_favorites_user ON motor_favorites(user_id, created_at DESC);
@@ -0,0 +1,21 @@
+-- Riwayat transfer motor antar cabang. Transfer dengan tanggal efektif di masa depan berstatus
+-- scheduled dan dijalankan job berkala. Jalankan setelah add_branch_details.sql.
+CREATE TABLE IF NOT EXISTS motor_transfers (
+    id UUID PRIMARY KEY,
+    motor_id INTEGER NOT NULL REFERENCES motors(motor_id) ON DELETE CASCADE,
+    from_branch TEXT REFERENCES branches(name) ON UPDATE CASCADE,
+    to_branch TEXT NOT NULL REFERENCES branches(name) ON UPDATE CASCADE,
+    effective_date DATE NOT NULL,
+    status TEXT NOT NULL DEFAULT 'scheduled' CHECK (status IN ('scheduled', 'completed', 'cancelled')),
+    units_moved INTEGER,
+    notes TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_motor_transfers_motor_id ON motor_transfers(motor_id, effective_date DESC);
+
+-- Satu transfer terjadwal per motor
+CREATE UNIQUE INDEX IF NOT EXISTS idx_motor_transfers_scheduled ON motor_transfers(motor_id)
+    WHERE status = 'scheduled';
@@ -0,0 +1,40 @@
+-- Unit fisik motor (plat nomor). Tabel motors menjadi katalog model motor; satu model bisa punya
+-- beberapa unit. Booking memesan satu unit yang kosong. Model tanpa unit tetap dihitung satu kendaraan.
+-- Jalankan setelah add_order_overlap_constraint.sql dan create_order_motors_table.sql.
+CREATE TABLE IF NOT EXISTS motor_units (
+    id UUID PRIMARY KEY,
+    motor_id INTEGER NOT NULL REFERENCES motors(motor_id) ON DELETE CASCADE,
+    plate_number TEXT NOT NULL,
+    vin TEXT,
+    year INTEGER CHECK (year BETWEEN 1900 AND 2100),
+    branch TEXT, -- cabang tempat unit saat ini berada
+    status TEXT NOT NULL DEFAULT 'available' CHECK (status IN ('available', 'maintenance', 'retired')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_motor_units_plate_number ON motor_units(UPPER(REPLACE(plate_number, ' ', '')));
+CREATE UNIQUE INDEX IF NOT EXISTS idx_motor_units_vin ON motor_units(UPPER(vin)) WHERE vin IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_motor_units_motor_id ON motor_units(motor_id, status);
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS unit_id UUID REFERENCES motor_units(id) ON DELETE SET NULL;
+ALTER TABLE order_motors ADD COLUMN IF NOT EXISTS unit_id UUID REFERENCES motor_units(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_order_motors_unit_id ON order_motors(unit_id);
+
+-- Satu model dengan beberapa unit boleh dibooking bersamaan; bentrok dicek per unit.
+-- Order tanpa unit (model yang belum dipecah) tetap dicek per motor seperti sebelumnya.
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_no_overlapping_motor_booking;
+ALTER TABLE orders ADD CONSTRAINT orders_no_overlapping_motor_booking
+    EXCLUDE USING gist (
+        motor_id WITH =,
+        tsrange(tanggal_peminjaman + jam_peminjaman, tanggal_pengembalian + jam_pengembalian, '[)') WITH &&
+    )
+    WHERE (motor_id IS NOT NULL AND unit_id IS NULL AND status <> 'cancelled');
+
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS orders_no_overlapping_unit_booking;
+ALTER TABLE orders ADD CONSTRAINT orders_no_overlapping_unit_booking
+    EXCLUDE USING gist (
+        unit_id WITH =,
+        tsrange(tanggal_peminjaman + jam_peminjaman, tanggal_pengembalian + jam_pengembalian, '[)') WITH &&
+    )
+    WHERE (unit_id IS NOT NULL AND status <> 'cancelled');
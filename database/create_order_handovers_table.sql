@@ -0,0 +1,14 @@
+-- Checklist serah terima motor saat pickup dan return, beserta foto sebagai bukti kondisi motor
+CREATE TABLE IF NOT EXISTS order_handovers (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('pickup', 'return')),
+    body_condition TEXT NOT NULL,
+    mirrors_ok BOOLEAN NOT NULL,
+    helmet_count INT NOT NULL CHECK (helmet_count >= 0),
+    notes TEXT,
+    photos TEXT[] NOT NULL DEFAULT '{}', -- URL foto
+    recorded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, kind)
+);
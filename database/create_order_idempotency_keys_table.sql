@@ -0,0 +1,11 @@
+-- Idempotency-Key untuk POST /api/orders: retry dari client mengembalikan response booking yang pertama
+CREATE TABLE IF NOT EXISTS order_idempotency_keys (
+    key TEXT PRIMARY KEY,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE, -- NULL untuk guest checkout
+    request_hash TEXT NOT NULL,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    response_body JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_idempotency_keys_created_at ON order_idempotency_keys(created_at);
@@ -0,0 +1,7 @@
+-- Invoice PDF per order, disimpan supaya bisa di-download ulang dengan isi yang sama
+CREATE TABLE IF NOT EXISTS order_invoices (
+    order_id UUID PRIMARY KEY REFERENCES orders(id) ON DELETE CASCADE,
+    invoice_number TEXT NOT NULL UNIQUE,
+    pdf BYTEA NOT NULL,
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
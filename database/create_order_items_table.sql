@@ -0,0 +1,27 @@
+-- Add-on sewa (katalog) dan item tambahan per order (add-on, ongkir, dll)
+CREATE TABLE IF NOT EXISTS addons (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    price INTEGER NOT NULL CHECK (price >= 0),
+    per_day BOOLEAN NOT NULL DEFAULT FALSE, -- TRUE = harga dikali lama sewa
+    active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+INSERT INTO addons (code, name, price, per_day) VALUES
+    ('helmet', 'Helm kedua', 5000, TRUE),
+    ('raincoat', 'Jas hujan', 10000, FALSE),
+    ('phone_holder', 'Holder HP', 5000, FALSE)
+ON CONFLICT (code) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS order_items (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    quantity BIGINT NOT NULL CHECK (quantity > 0),
+    unit_price BIGINT NOT NULL,
+    subtotal BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_items_order_id ON order_items(order_id);
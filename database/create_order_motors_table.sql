@@ -0,0 +1,23 @@
+-- Motor per order: satu order bisa berisi beberapa motor (keluarga / rombongan).
+-- orders.motor_id tetap menyimpan motor pertama untuk kompatibilitas.
+CREATE TABLE IF NOT EXISTS order_motors (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    motor_id INTEGER REFERENCES motors(motor_id) ON DELETE SET NULL,
+    motor_name TEXT NOT NULL,
+    rental_subtotal BIGINT NOT NULL DEFAULT 0,
+    deposit_amount INTEGER NOT NULL DEFAULT 0,
+    position INTEGER NOT NULL DEFAULT 0, -- urutan motor di order, 0 = motor utama (orders.motor_id)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, motor_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_motors_motor_id ON order_motors(motor_id);
+
+-- Order lama: satu baris dari motor_id yang sudah ada
+INSERT INTO order_motors (id, order_id, motor_id, motor_name, rental_subtotal, deposit_amount)
+SELECT gen_random_uuid(), o.id, o.motor_id, o.pilih_motor,
+       COALESCE((o.price_breakdown ->> 'rentalSubtotal')::BIGINT, 0), o.deposit_amount
+FROM orders o
+WHERE o.motor_id IS NOT NULL
+ON CONFLICT (order_id, motor_id) DO NOTHING;
@@ -0,0 +1,15 @@
+-- Log notifikasi yang dikirim untuk order (email konfirmasi, pengingat pengembalian, dll), termasuk yang
+-- gagal terkirim. Dipakai timeline order (GET /api/orders/:id/timeline).
+CREATE TABLE IF NOT EXISTS order_notifications (
+    id BIGSERIAL PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    channel TEXT NOT NULL DEFAULT 'email',
+    template_key TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('sent', 'failed')),
+    error TEXT,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_notifications_order_id ON order_notifications(order_id, sent_at);
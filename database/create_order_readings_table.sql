@@ -0,0 +1,10 @@
+-- Odometer dan level bensin saat pickup / return, untuk biaya per km dan jadwal servis motor
+CREATE TABLE IF NOT EXISTS order_readings (
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('pickup', 'return')),
+    odometer_km INT NOT NULL CHECK (odometer_km >= 0),
+    fuel_percent INT NOT NULL CHECK (fuel_percent BETWEEN 0 AND 100),
+    recorded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (order_id, kind)
+);
@@ -0,0 +1,21 @@
+-- Refund order yang menunggu diproses alur pembayaran. kind = asal refund (early_return = motor
+-- dikembalikan sebelum jadwal, selisih harga dihitung ulang untuk lama sewa sebenarnya).
+CREATE TABLE IF NOT EXISTS order_refunds (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('early_return')),
+    amount BIGINT NOT NULL CHECK (amount >= 0),
+    original_total BIGINT NOT NULL,
+    recalculated_total BIGINT NOT NULL,
+    recalculated_breakdown JSONB,
+    original_rental_end TIMESTAMP,
+    returned_at TIMESTAMP,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'processed', 'rejected')),
+    reason TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_refunds_order_id ON order_refunds(order_id);
+CREATE INDEX IF NOT EXISTS idx_order_refunds_pending ON order_refunds(created_at) WHERE status = 'pending';
@@ -0,0 +1,11 @@
+-- Ulasan customer setelah sewa selesai, satu per order; ditampilkan di detail motor
+CREATE TABLE IF NOT EXISTS order_reviews (
+    order_id UUID PRIMARY KEY REFERENCES orders(id) ON DELETE CASCADE,
+    motor_id INT REFERENCES motors(motor_id) ON DELETE SET NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rating INT NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_reviews_motor_id ON order_reviews(motor_id, created_at DESC);
@@ -0,0 +1,12 @@
+-- Riwayat perubahan status order (GET /api/orders/:id/history)
+CREATE TABLE IF NOT EXISTS order_status_history (
+    id BIGSERIAL PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    old_status TEXT, -- NULL = order baru dibuat
+    new_status TEXT NOT NULL,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL, -- NULL = otomatis oleh sistem
+    note TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_status_history_order_id ON order_status_history(order_id, changed_at);
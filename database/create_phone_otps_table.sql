@@ -0,0 +1,15 @@
+-- Verifikasi nomor HP via OTP (POST /api/auth/otp/request & /api/auth/otp/verify).
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone_verified_at TIMESTAMPTZ;
+
+-- phone disimpan dalam format 62xxxxxxxxxx; code_hash = SHA-256 dari "phone:kode"
+CREATE TABLE IF NOT EXISTS phone_otps (
+    id UUID PRIMARY KEY,
+    phone TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_phone_otps_phone ON phone_otps(phone, created_at DESC);
@@ -0,0 +1,20 @@
+-- Aturan harga dinamis (musim ramai / weekend) yang dipakai saat menghitung harga booking.
+-- multiplier_percent: 120 = tarif sewa naik 20%, 90 = diskon 10%. branch NULL = semua cabang;
+-- aturan khusus cabang mengalahkan aturan semua cabang di tanggal yang sama.
+CREATE TABLE IF NOT EXISTS pricing_rules (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('season', 'weekend')),
+    starts_on DATE,
+    ends_on DATE,
+    branch TEXT REFERENCES branches(name) ON UPDATE CASCADE ON DELETE CASCADE,
+    multiplier_percent INTEGER NOT NULL CHECK (multiplier_percent BETWEEN 1 AND 1000),
+    priority INTEGER NOT NULL DEFAULT 0,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (kind <> 'season' OR (starts_on IS NOT NULL AND ends_on IS NOT NULL)),
+    CHECK (ends_on IS NULL OR starts_on IS NULL OR ends_on >= starts_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_pricing_rules_active ON pricing_rules(starts_on, ends_on) WHERE active;
@@ -0,0 +1,15 @@
+-- Kode promo untuk potongan harga booking (persen atau nominal tetap)
+CREATE TABLE IF NOT EXISTS promo_codes (
+    code TEXT PRIMARY KEY, -- huruf besar, contoh: LEBARAN25
+    description TEXT,
+    percent_off INT CHECK (percent_off BETWEEN 1 AND 100),
+    amount_off INT CHECK (amount_off > 0),
+    min_total BIGINT NOT NULL DEFAULT 0,
+    valid_from DATE,
+    valid_until DATE,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (percent_off IS NOT NULL OR amount_off IS NOT NULL)
+);
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS promo_code TEXT REFERENCES promo_codes(code) ON DELETE SET NULL;
@@ -0,0 +1,8 @@
+-- Minimal / maksimal lama sewa (hari) per tipe motor. Aturan global ada di MIN_RENTAL_DAYS / MAX_RENTAL_DAYS.
+CREATE TABLE IF NOT EXISTS rental_duration_rules (
+    motor_type TEXT PRIMARY KEY,
+    min_days INTEGER CHECK (min_days >= 0),
+    max_days INTEGER CHECK (max_days >= 1),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (min_days IS NULL OR max_days IS NULL OR min_days <= max_days)
+);
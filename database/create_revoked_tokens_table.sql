@@ -0,0 +1,10 @@
+-- Access token yang dicabut lewat POST /api/logout (dicek di setiap route yang butuh login).
+-- Row boleh dihapus setelah expires_at lewat karena token aslinya sudah tidak valid.
+CREATE TABLE IF NOT EXISTS revoked_tokens (
+    jti TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
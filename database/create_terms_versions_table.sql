@@ -0,0 +1,15 @@
+-- Versi syarat & ketentuan sewa + versi yang disetujui customer di setiap order (waktu dan IP)
+CREATE TABLE IF NOT EXISTS terms_versions (
+    version TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    published_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_terms_versions_published_at ON terms_versions(published_at DESC);
+
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS terms_version TEXT REFERENCES terms_versions(version),
+    ADD COLUMN IF NOT EXISTS terms_accepted_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS terms_accepted_ip TEXT;
@@ -0,0 +1,25 @@
+-- Jadwal dan riwayat perawatan per unit motor. Unit tidak bisa dibooking selama jendela perawatan
+-- yang masih scheduled / in_progress. Jalankan setelah create_motor_units_table.sql.
+CREATE TABLE IF NOT EXISTS unit_maintenance (
+    id UUID PRIMARY KEY,
+    unit_id UUID NOT NULL REFERENCES motor_units(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('oil_change', 'tire', 'brake', 'service', 'other')),
+    status TEXT NOT NULL DEFAULT 'scheduled' CHECK (status IN ('scheduled', 'in_progress', 'completed', 'cancelled')),
+    starts_at TIMESTAMP,
+    ends_at TIMESTAMP,
+    performed_at TIMESTAMP,
+    odometer_km INTEGER CHECK (odometer_km >= 0),
+    cost BIGINT CHECK (cost >= 0),
+    notes TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at IS NULL OR starts_at IS NULL OR ends_at > starts_at),
+    -- Jadwal wajib punya jendela waktu; catatan servis selesai wajib punya waktu servis
+    CHECK (status NOT IN ('scheduled', 'in_progress') OR (starts_at IS NOT NULL AND ends_at IS NOT NULL)),
+    CHECK (status <> 'completed' OR performed_at IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_unit_maintenance_unit_id ON unit_maintenance(unit_id, starts_at);
+CREATE INDEX IF NOT EXISTS idx_unit_maintenance_open ON unit_maintenance(starts_at)
+    WHERE status IN ('scheduled', 'in_progress');
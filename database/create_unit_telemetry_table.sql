@@ -0,0 +1,18 @@
+-- Titik GPS dari tracker unit motor (POST /api/telemetry, API key scope telemetry:write).
+-- Tabel time-series: append-only, satu titik per unit per waktu rekam.
+CREATE TABLE IF NOT EXISTS unit_telemetry (
+    unit_id UUID NOT NULL REFERENCES motor_units(id) ON DELETE CASCADE,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    latitude DOUBLE PRECISION NOT NULL CHECK (latitude BETWEEN -90 AND 90),
+    longitude DOUBLE PRECISION NOT NULL CHECK (longitude BETWEEN -180 AND 180),
+    speed_kmh REAL,
+    heading REAL,
+    accuracy_m REAL,
+    battery_percent SMALLINT,
+    api_key_id UUID REFERENCES api_keys(id) ON DELETE SET NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (unit_id, recorded_at)
+);
+
+-- BRIN cocok untuk data yang masuk berurutan waktu (query rentang / pembersihan data lama)
+CREATE INDEX IF NOT EXISTS idx_unit_telemetry_recorded_brin ON unit_telemetry USING BRIN (recorded_at);
@@ -0,0 +1,10 @@
+-- Password sekarang disimpan sebagai hash Argon2 (PHC string, ~100 karakter).
+-- Pastikan kolom cukup panjang untuk menampung hash.
+ALTER TABLE users ALTER COLUMN password_hash TYPE TEXT;
+
+-- Row lama yang masih plain text tidak perlu diubah manual:
+-- backend akan meng-hash ulang password tersebut saat user berhasil login.
+-- Query ini menampilkan berapa user yang belum ter-migrasi.
+SELECT COUNT(*) AS plain_text_passwords
+FROM users
+WHERE password_hash NOT LIKE '$argon2%';
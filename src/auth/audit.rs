@@ -0,0 +1,147 @@
+use axum::http::HeaderMap;
+use sqlx::PgPool;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::config;
+
+// Jenis event autentikasi yang dicatat di tabel auth_events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthEvent {
+    LoginSuccess,
+    LoginFailure,
+    LoginLocked,
+    TokenRefresh,
+    RefreshTokenReuse,
+    Logout,
+    PasswordChange,
+    PasswordReset,
+    Impersonation,
+    AccountDeleted,
+}
+
+impl AuthEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthEvent::LoginSuccess => "login_success",
+            AuthEvent::LoginFailure => "login_failure",
+            AuthEvent::LoginLocked => "login_locked",
+            AuthEvent::TokenRefresh => "token_refresh",
+            AuthEvent::RefreshTokenReuse => "refresh_token_reuse",
+            AuthEvent::Logout => "logout",
+            AuthEvent::PasswordChange => "password_change",
+            AuthEvent::PasswordReset => "password_reset",
+            AuthEvent::Impersonation => "impersonation",
+            AuthEvent::AccountDeleted => "account_deleted",
+        }
+    }
+}
+
+// Alamat / jaringan reverse proxy yang dipercaya (TRUSTED_PROXIES), format IP tunggal atau CIDR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedProxy {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix_len)).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix_len)).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (ip, prefix) = match value.split_once('/') {
+            Some((ip, prefix)) => (ip, Some(prefix)),
+            None => (value, None),
+        };
+        let network: IpAddr = ip.parse().map_err(|_| format!("Invalid proxy address: {}", value))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix.parse().ok().filter(|len| *len <= max_prefix)
+                .ok_or_else(|| format!("Invalid proxy prefix: {}", value))?,
+            None => max_prefix,
+        };
+
+        Ok(Self { network, prefix_len })
+    }
+}
+
+// IP client. X-Forwarded-For hanya dipercaya jika koneksi datang dari proxy di TRUSTED_PROXIES;
+// daftar dibaca dari kanan dan hop pertama yang bukan proxy terpercaya dianggap IP client
+// (entri di kirinya bisa dipalsukan client). Tanpa proxy terpercaya dipakai alamat koneksi.
+pub fn client_ip(headers: &HeaderMap, addr: &SocketAddr) -> IpAddr {
+    forwarded_client_ip(headers, addr.ip(), &config::get().trusted_proxies)
+}
+
+fn forwarded_client_ip(headers: &HeaderMap, peer: IpAddr, trusted: &[TrustedProxy]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted.iter().any(|proxy| proxy.contains(ip));
+    if !is_trusted(peer) {
+        return peer;
+    }
+
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect();
+
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        // Entri yang tidak valid berarti header sudah tidak bisa dipercaya dari titik ini
+        let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+
+    client
+}
+
+// Catat event autentikasi beserta IP dan user agent. Gagal mencatat tidak boleh
+// menggagalkan request, jadi error cukup di-log.
+pub async fn record(
+    pool: &PgPool,
+    event: AuthEvent,
+    user_id: Option<Uuid>,
+    ip: Option<IpAddr>,
+    headers: &HeaderMap,
+    details: serde_json::Value,
+) {
+    let user_agent = headers.get("user-agent").and_then(|value| value.to_str().ok());
+
+    if let Err(e) = sqlx::query(
+        "INSERT INTO auth_events (id, event, user_id, ip_address, user_agent, details)
+         VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+    .bind(Uuid::new_v4())
+    .bind(event.as_str())
+    .bind(user_id)
+    .bind(ip.map(|ip| ip.to_string()))
+    .bind(user_agent)
+    .bind(details)
+    .execute(pool)
+    .await
+    {
+        println!("⚠️  Gagal mencatat auth event {}: {}", event.as_str(), e);
+    }
+}
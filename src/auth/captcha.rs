@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use std::net::IpAddr;
+
+const TURNSTILE_VERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+const RECAPTCHA_VERIFY_URL: &str = "https://www.google.com/recaptcha/api/siteverify";
+
+// Penyedia captcha yang didukung (dipilih lewat env CAPTCHA_PROVIDER)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptchaProvider {
+    Turnstile,
+    Recaptcha,
+}
+
+impl CaptchaProvider {
+    fn verify_url(&self) -> &'static str {
+        match self {
+            CaptchaProvider::Turnstile => TURNSTILE_VERIFY_URL,
+            CaptchaProvider::Recaptcha => RECAPTCHA_VERIFY_URL,
+        }
+    }
+}
+
+impl std::str::FromStr for CaptchaProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "turnstile" => Ok(CaptchaProvider::Turnstile),
+            "recaptcha" => Ok(CaptchaProvider::Recaptcha),
+            other => Err(format!("Unknown captcha provider: {}", other)),
+        }
+    }
+}
+
+// Response siteverify (format sama untuk Turnstile dan reCAPTCHA)
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+    #[serde(rename = "error-codes", default)]
+    error_codes: Vec<String>,
+}
+
+// Verifikasi token captcha dari frontend ke server penyedia captcha
+pub async fn verify(
+    provider: CaptchaProvider,
+    secret: &str,
+    token: &str,
+    remote_ip: Option<IpAddr>,
+) -> Result<(), String> {
+    let mut form = vec![("secret", secret.to_string()), ("response", token.to_string())];
+    if let Some(ip) = remote_ip {
+        form.push(("remoteip", ip.to_string()));
+    }
+
+    let response = reqwest::Client::new()
+        .post(provider.verify_url())
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| format!("Gagal menghubungi server captcha: {}", e))?;
+
+    let result: SiteVerifyResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Response captcha tidak valid: {}", e))?;
+
+    if !result.success {
+        return Err(format!("Captcha tidak valid ({})", result.error_codes.join(", ")));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+
+use crate::auth::token::{bearer_token, random_token};
+use crate::config;
+
+pub const ACCESS_COOKIE: &str = "sentor_access";
+pub const REFRESH_COOKIE: &str = "sentor_refresh";
+pub const CSRF_COOKIE: &str = "sentor_csrf";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+// Path refresh cookie dibatasi ke /api/auth (refresh + logout) supaya tidak ikut terkirim ke request API lain
+const REFRESH_COOKIE_PATH: &str = "/api/auth";
+// Path lama refresh cookie, tetap dihapus saat logout supaya cookie dari versi sebelumnya tidak tertinggal
+const LEGACY_REFRESH_COOKIE_PATH: &str = "/api";
+
+// Mode penyimpanan token di frontend (env AUTH_SESSION_MODE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionMode {
+    Bearer, // token di body response, frontend kirim header Authorization
+    Cookie, // token di cookie HttpOnly + proteksi CSRF (double submit cookie)
+}
+
+impl std::str::FromStr for SessionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "bearer" => Ok(SessionMode::Bearer),
+            "cookie" => Ok(SessionMode::Cookie),
+            other => Err(format!("Unknown session mode: {}", other)),
+        }
+    }
+}
+
+pub fn cookie_mode() -> bool {
+    config::get().session_mode == SessionMode::Cookie
+}
+
+// Ambil nilai cookie dari header Cookie
+pub fn get_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+// Access token dari header Authorization, atau dari cookie jika mode cookie aktif.
+// Request yang memakai cookie dan bukan GET/HEAD/OPTIONS wajib mengirim header X-CSRF-Token
+// yang sama dengan cookie CSRF (403 jika tidak cocok).
+pub fn request_access_token<'a>(method: &Method, headers: &'a HeaderMap) -> Result<&'a str, StatusCode> {
+    if let Some(token) = bearer_token(headers) {
+        return Ok(token);
+    }
+
+    if !cookie_mode() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let token = get_cookie(headers, ACCESS_COOKIE).ok_or(StatusCode::UNAUTHORIZED)?;
+    verify_csrf(method, headers)?;
+    Ok(token)
+}
+
+// Double submit cookie: header X-CSRF-Token harus sama dengan cookie CSRF
+pub fn verify_csrf(method: &Method, headers: &HeaderMap) -> Result<(), StatusCode> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(());
+    }
+
+    let cookie = get_cookie(headers, CSRF_COOKIE);
+    let header = headers.get(CSRF_HEADER).and_then(|value| value.to_str().ok());
+
+    match (cookie, header) {
+        (Some(cookie), Some(header)) if !cookie.is_empty() && cookie == header => Ok(()),
+        _ => {
+            println!("⛔ CSRF token missing or mismatched");
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+fn build_cookie(name: &str, value: &str, path: &str, max_age: i64, http_only: bool) -> HeaderValue {
+    let mut cookie = format!("{}={}; Path={}; Max-Age={}; SameSite=Lax", name, value, path, max_age);
+    if http_only {
+        cookie.push_str("; HttpOnly");
+    }
+    if config::get().cookie_secure {
+        cookie.push_str("; Secure");
+    }
+    HeaderValue::from_str(&cookie).expect("cookie value is valid ASCII")
+}
+
+// Set-Cookie untuk sesi baru: access + refresh token (HttpOnly) dan token CSRF (bisa dibaca JS)
+pub fn session_cookies(access_token: &str, refresh_token: &str) -> HeaderMap {
+    let cfg = config::get();
+    let mut headers = HeaderMap::new();
+
+    headers.append(
+        header::SET_COOKIE,
+        build_cookie(ACCESS_COOKIE, access_token, "/", cfg.access_token_ttl_secs, true),
+    );
+    headers.append(
+        header::SET_COOKIE,
+        build_cookie(REFRESH_COOKIE, refresh_token, REFRESH_COOKIE_PATH, cfg.refresh_token_ttl_secs, true),
+    );
+    headers.append(
+        header::SET_COOKIE,
+        build_cookie(CSRF_COOKIE, &random_token(), "/", cfg.refresh_token_ttl_secs, false),
+    );
+
+    headers
+}
+
+// Set-Cookie untuk menghapus semua cookie sesi (logout)
+pub fn clear_session_cookies() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.append(header::SET_COOKIE, build_cookie(ACCESS_COOKIE, "", "/", 0, true));
+    headers.append(header::SET_COOKIE, build_cookie(REFRESH_COOKIE, "", REFRESH_COOKIE_PATH, 0, true));
+    headers.append(header::SET_COOKIE, build_cookie(REFRESH_COOKIE, "", LEGACY_REFRESH_COOKIE_PATH, 0, true));
+    headers.append(header::SET_COOKIE, build_cookie(CSRF_COOKIE, "", "/", 0, false));
+    headers
+}
@@ -0,0 +1,243 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::Json as RespJson,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::cookie::request_access_token;
+use crate::auth::token::verify_access_token;
+use crate::model::user::{Role, UserStatus};
+
+// User yang sudah terautentikasi dari header "Authorization: Bearer <token>".
+// Cukup tambahkan `user: AuthUser` di parameter handler; request tanpa token valid otomatis 401.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub role: Role,
+    pub session_id: Option<Uuid>,
+    pub impersonated_by: Option<Uuid>, // id admin jika request memakai token impersonation
+    pub branch_id: Option<String>, // cabang tempat staff bertugas (sama dengan motors.branch / orders.pilih_cabang)
+    pub scopes: Vec<String>, // permission dari access token
+}
+
+impl AuthUser {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    // Pastikan access token memuat scope tertentu (403 jika tidak)
+    pub fn require_scope(&self, scope: &str) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+        if self.has_scope(scope) {
+            return Ok(());
+        }
+
+        println!("⛔ User {} ({}) missing scope {}", self.id, self.role.as_str(), scope);
+        Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": format!("Missing required permission: {}", scope)
+        }))))
+    }
+
+    // Pastikan user adalah admin atau staff (403 jika bukan)
+    pub fn require_staff(&self) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+        if self.role.is_staff() {
+            return Ok(());
+        }
+
+        println!("⛔ User {} ({}) tried to access a staff-only endpoint", self.id, self.role.as_str());
+        Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Admin or staff access required"
+        }))))
+    }
+
+    // Pastikan user adalah admin (403 jika bukan)
+    pub fn require_admin(&self) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+        if self.role == Role::Admin {
+            return Ok(());
+        }
+
+        println!("⛔ User {} ({}) tried to access an admin-only endpoint", self.id, self.role.as_str());
+        Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Admin access required"
+        }))))
+    }
+
+    // Pastikan data user `user_id` milik user ini sendiri, atau user adalah admin (403 jika bukan)
+    pub fn require_self_or_admin(&self, user_id: Uuid) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+        if self.id == user_id || self.role == Role::Admin {
+            return Ok(());
+        }
+
+        println!("⛔ User {} ({}) tried to access user {}", self.id, self.role.as_str(), user_id);
+        Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "You can only access your own account"
+        }))))
+    }
+
+    // Cabang yang boleh dikelola: None = semua cabang (admin), Some = hanya cabang milik staff
+    pub fn branch_scope(&self) -> Result<Option<&str>, (StatusCode, RespJson<serde_json::Value>)> {
+        match self.role {
+            Role::Admin => Ok(None),
+            Role::Staff => self.branch_id.as_deref().map(Some).ok_or_else(|| {
+                println!("⛔ Staff {} has no branch assigned", self.id);
+                (StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+                    "error": "Staff account has no branch assigned"
+                })))
+            }),
+            Role::Customer => {
+                self.require_staff()?;
+                Ok(None)
+            }
+        }
+    }
+
+    // Pastikan data milik cabang ini boleh dikelola oleh user (admin selalu boleh)
+    pub fn require_branch(&self, branch: Option<&str>) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+        let Some(own_branch) = self.branch_scope()? else {
+            return Ok(());
+        };
+
+        if branch.is_some_and(|branch| same_branch(branch, own_branch)) {
+            return Ok(());
+        }
+
+        println!("⛔ Staff {} ({}) tried to manage branch {:?}", self.id, own_branch, branch);
+        Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Staff can only manage their own branch"
+        }))))
+    }
+}
+
+// Nama cabang masih berupa teks bebas, jadi bandingkan tanpa memperhatikan huruf besar/kecil dan spasi
+pub fn same_branch(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+// Data user + sesi yang dicek setiap request terautentikasi
+#[derive(sqlx::FromRow)]
+struct AuthRow {
+    role: String,
+    status: String,
+    branch_id: Option<String>,
+    tokens_invalid_before: Option<DateTime<Utc>>,
+    found_session: Option<Uuid>,
+    session_revoked_at: Option<DateTime<Utc>>,
+}
+
+// Error 403 untuk akun yang ditangguhkan / diblokir, dengan `code` supaya frontend bisa menampilkan pesan khusus
+pub fn account_status_error(status: UserStatus) -> (StatusCode, RespJson<serde_json::Value>) {
+    let (code, message) = match status {
+        UserStatus::Suspended => ("account_suspended", "Account is suspended"),
+        _ => ("account_banned", "Account is banned"),
+    };
+    (StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+        "error": message,
+        "code": code
+    })))
+}
+
+fn auth_error(status: StatusCode) -> (StatusCode, RespJson<serde_json::Value>) {
+    let message = match status {
+        StatusCode::INTERNAL_SERVER_ERROR => "Database error",
+        StatusCode::FORBIDDEN => "Invalid CSRF token",
+        _ => "Authentication required",
+    };
+    (status, RespJson(serde_json::json!({"error": message})))
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, RespJson<serde_json::Value>);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Sudah diverifikasi oleh middleware require_scope
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
+        let pool = parts
+            .extensions
+            .get::<PgPool>()
+            .cloned()
+            .ok_or_else(|| auth_error(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+        let token = request_access_token(&parts.method, &parts.headers).map_err(|status| {
+            println!("❌ Authentication failed: missing token or invalid CSRF token");
+            auth_error(status)
+        })?;
+
+        let claims = verify_access_token(&pool, token).await.map_err(|status| {
+            println!("❌ Authentication failed: invalid or revoked token");
+            auth_error(status)
+        })?;
+
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| auth_error(StatusCode::UNAUTHORIZED))?;
+
+        let session_id = match claims.sid.as_deref() {
+            Some(sid) => Some(Uuid::parse_str(sid).map_err(|_| auth_error(StatusCode::UNAUTHORIZED))?),
+            None => None,
+        };
+
+        // Pastikan user masih ada di database, sekaligus ambil role, status akun, dan status sesinya
+        let row: Option<AuthRow> = sqlx::query_as(
+            "SELECT u.role, u.status, u.branch_id, u.tokens_invalid_before,
+                    s.id AS found_session, s.revoked_at AS session_revoked_at
+             FROM users u
+             LEFT JOIN user_sessions s ON s.id = $2 AND s.user_id = u.id
+             WHERE u.id = $1"
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            auth_error(StatusCode::INTERNAL_SERVER_ERROR)
+        })?;
+
+        let AuthRow { role, status, branch_id, tokens_invalid_before, found_session, session_revoked_at } =
+            row.ok_or_else(|| auth_error(StatusCode::UNAUTHORIZED))?;
+
+        // Akun yang ditangguhkan / diblokir admin ditolak dengan kode error khusus
+        let status = status.parse::<UserStatus>().unwrap_or(UserStatus::Active);
+        if status != UserStatus::Active {
+            println!("⛔ Authentication rejected: account {} is {}", user_id, status.as_str());
+            return Err(account_status_error(status));
+        }
+
+        // Sesi (device) yang sudah dicabut tidak boleh dipakai lagi
+        if session_id.is_some() && (found_session.is_none() || session_revoked_at.is_some()) {
+            println!("❌ Authentication failed: session revoked");
+            return Err(auth_error(StatusCode::UNAUTHORIZED));
+        }
+
+        // Token terbit sebelum ganti / reset password sudah tidak berlaku
+        if let Some(invalid_before) = tokens_invalid_before {
+            if claims.iat < invalid_before.timestamp() {
+                println!("❌ Authentication failed: token issued before password change");
+                return Err(auth_error(StatusCode::UNAUTHORIZED));
+            }
+        }
+
+        let role = role
+            .parse::<Role>()
+            .map_err(|_| auth_error(StatusCode::UNAUTHORIZED))?;
+
+        let impersonated_by = claims.act.as_deref().and_then(|act| Uuid::parse_str(act).ok());
+
+        Ok(AuthUser {
+            id: user_id,
+            role,
+            session_id,
+            impersonated_by,
+            branch_id,
+            scopes: claims.scopes,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+const TOKENINFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+
+// Data user dari Google ID token yang sudah diverifikasi
+#[derive(Debug, Deserialize)]
+pub struct GoogleIdentity {
+    pub sub: String,
+    pub email: String,
+    pub name: Option<String>,
+}
+
+// Response endpoint tokeninfo Google (field boolean dikirim sebagai string)
+#[derive(Debug, Deserialize)]
+struct TokenInfo {
+    iss: String,
+    aud: String,
+    sub: String,
+    email: Option<String>,
+    email_verified: Option<String>,
+    name: Option<String>,
+}
+
+// Verifikasi Google ID token lewat endpoint tokeninfo (signature + expiry dicek oleh Google),
+// lalu pastikan token memang diterbitkan untuk client id aplikasi ini.
+pub async fn verify_id_token(id_token: &str, client_id: &str) -> Result<GoogleIdentity, String> {
+    let response = reqwest::Client::new()
+        .get(TOKENINFO_URL)
+        .query(&[("id_token", id_token)])
+        .send()
+        .await
+        .map_err(|e| format!("Gagal menghubungi Google: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err("ID token Google tidak valid".to_string());
+    }
+
+    let info: TokenInfo = response
+        .json()
+        .await
+        .map_err(|e| format!("Response Google tidak valid: {}", e))?;
+
+    if info.aud != client_id {
+        return Err("ID token bukan untuk aplikasi ini".to_string());
+    }
+
+    if info.iss != "accounts.google.com" && info.iss != "https://accounts.google.com" {
+        return Err("Issuer ID token tidak valid".to_string());
+    }
+
+    let email = info.email.ok_or("ID token tidak berisi email")?;
+    if info.email_verified.as_deref() != Some("true") {
+        return Err("Email Google belum terverifikasi".to_string());
+    }
+
+    Ok(GoogleIdentity {
+        sub: info.sub,
+        email,
+        name: info.name,
+    })
+}
@@ -0,0 +1,151 @@
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::auth::password::hash_password;
+use crate::auth::token::{hash_token, random_token};
+use crate::model::user::{is_valid_email, normalize_phone};
+
+// Data kontak tamu yang booking tanpa login
+#[derive(Debug, Clone)]
+pub struct GuestContact {
+    pub full_name: String,
+    pub phone: String,
+    pub email: String,
+}
+
+// Customer walk-in yang dibuatkan booking oleh staff di cabang (cukup nama + nomor HP)
+#[derive(Debug, Clone)]
+pub struct WalkInContact {
+    pub full_name: String,
+    pub phone: String,
+}
+
+// Ambil data customer walk-in dari payload POST /api/admin/orders: "customer": {"fullName", "phone"}
+pub fn parse_walk_in_contact(value: &serde_json::Value) -> Result<WalkInContact, String> {
+    let field = |name: &str| {
+        value
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or(format!("Missing customer.{}", name))
+    };
+
+    let full_name = field("fullName")?;
+    let phone = normalize_phone(&field("phone")?).ok_or("Invalid customer.phone format")?;
+
+    Ok(WalkInContact { full_name, phone })
+}
+
+// Ambil data tamu (guestName, guestPhone, guestEmail) dari payload booking
+pub fn parse_guest_contact(payload: &serde_json::Value) -> Result<GuestContact, String> {
+    let field = |name: &str| {
+        payload
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or(format!("Missing {}", name))
+    };
+
+    let full_name = field("guestName")?;
+    let phone = normalize_phone(&field("guestPhone")?).ok_or("Invalid guestPhone format")?;
+    let email = field("guestEmail")?.to_lowercase();
+    if !is_valid_email(&email) {
+        return Err("Invalid guestEmail format".to_string());
+    }
+
+    Ok(GuestContact { full_name, phone, email })
+}
+
+// Buat akun guest (tidak bisa login) untuk menampung order tamu, beserta token klaim.
+// Token klaim plain text hanya dikembalikan sekali ke tamu; yang disimpan hanya hash-nya.
+pub async fn create_guest(conn: &mut PgConnection, contact: &GuestContact) -> Result<(Uuid, String), sqlx::Error> {
+    let guest_id = Uuid::new_v4();
+    let claim_token = random_token();
+    let short_id = guest_id.simple().to_string()[..12].to_string();
+
+    // Password acak yang tidak diketahui siapa pun, jadi akun guest tidak bisa dipakai login
+    let password_hash = hash_password(&random_token()).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+    sqlx::query(
+        "INSERT INTO users (id, full_name, username, email, phone, password_hash, is_guest)
+         VALUES ($1, $2, $3, $4, '', $5, TRUE)"
+    )
+    .bind(guest_id)
+    .bind(&contact.full_name)
+    .bind(format!("guest_{}", short_id))
+    .bind(format!("guest+{}@guest.invalid", guest_id))
+    .bind(password_hash)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO guest_checkouts (id, guest_user_id, full_name, phone, email, claim_token_hash)
+         VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+    .bind(Uuid::new_v4())
+    .bind(guest_id)
+    .bind(&contact.full_name)
+    .bind(&contact.phone)
+    .bind(&contact.email)
+    .bind(hash_token(&claim_token))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok((guest_id, claim_token))
+}
+
+// Buat akun customer walk-in (tidak bisa login, seperti akun guest) untuk booking yang dibuat staff.
+// Tanpa email: notifikasi email ke customer ini akan gagal dan hanya tercatat di log notifikasi.
+pub async fn create_walk_in_customer(conn: &mut PgConnection, contact: &WalkInContact) -> Result<Uuid, sqlx::Error> {
+    let customer_id = Uuid::new_v4();
+    let short_id = customer_id.simple().to_string()[..12].to_string();
+    let password_hash = hash_password(&random_token()).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+    sqlx::query(
+        "INSERT INTO users (id, full_name, username, email, phone, password_hash, is_guest)
+         VALUES ($1, $2, $3, $4, $5, $6, TRUE)"
+    )
+    .bind(customer_id)
+    .bind(&contact.full_name)
+    .bind(format!("walkin_{}", short_id))
+    .bind(format!("walkin+{}@guest.invalid", customer_id))
+    .bind(&contact.phone)
+    .bind(password_hash)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(customer_id)
+}
+
+// Pindahkan semua order milik guest ke akun user yang sudah register.
+// Hasil None jika token klaim tidak valid atau sudah pernah dipakai.
+pub async fn claim_guest_orders(
+    conn: &mut PgConnection,
+    claim_token: &str,
+    user_id: Uuid,
+) -> Result<Option<u64>, sqlx::Error> {
+    let guest: Option<(Uuid,)> = sqlx::query_as(
+        "UPDATE guest_checkouts SET claimed_by = $2, claimed_at = NOW()
+         WHERE claim_token_hash = $1 AND claimed_at IS NULL
+         RETURNING guest_user_id"
+    )
+    .bind(hash_token(claim_token))
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((guest_id,)) = guest else {
+        return Ok(None);
+    };
+
+    let moved = sqlx::query("UPDATE orders SET user_id = $1 WHERE user_id = $2")
+        .bind(user_id)
+        .bind(guest_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    Ok(Some(moved))
+}
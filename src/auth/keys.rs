@@ -0,0 +1,119 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use jsonwebtoken::{DecodingKey, EncodingKey};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+// Prefix DER SubjectPublicKeyInfo untuk public key Ed25519 (diikuti 32 byte key)
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+// Kunci asimetris (Ed25519) untuk menandatangani JWT dan dipublikasikan lewat JWKS.
+//
+// JWT_KEYS_DIR berisi pasangan `<kid>.pem` (private key PKCS#8) dan `<kid>.pub.pem` (public key).
+// Token baru ditandatangani dengan key JWT_SIGNING_KEY_ID; semua `*.pub.pem` tetap diterima
+// dan dipublikasikan, jadi rotasi cukup: tambah pasangan key baru, ganti JWT_SIGNING_KEY_ID,
+// lalu hapus public key lama setelah token lama kedaluwarsa.
+// Tanpa konfigurasi ini token tetap memakai HS256 dengan JWT_SECRET.
+pub struct KeyStore {
+    pub signing: Option<(String, EncodingKey)>,
+    verifying: HashMap<String, DecodingKey>,
+    jwks: serde_json::Value,
+}
+
+static KEYS: OnceLock<KeyStore> = OnceLock::new();
+
+pub fn get() -> &'static KeyStore {
+    KEYS.get_or_init(KeyStore::from_env)
+}
+
+impl KeyStore {
+    fn from_env() -> Self {
+        let mut store = KeyStore {
+            signing: None,
+            verifying: HashMap::new(),
+            jwks: serde_json::json!({ "keys": [] }),
+        };
+
+        let Ok(dir) = std::env::var("JWT_KEYS_DIR") else {
+            return store;
+        };
+
+        let mut jwks = Vec::new();
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("⚠️  Gagal membaca JWT_KEYS_DIR {}: {}", dir, e);
+                return store;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(kid) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".pub.pem"))
+                .map(str::to_string)
+            else {
+                continue;
+            };
+
+            match load_public_key(&path) {
+                Ok((decoding_key, raw)) => {
+                    jwks.push(serde_json::json!({
+                        "kty": "OKP",
+                        "crv": "Ed25519",
+                        "alg": "EdDSA",
+                        "use": "sig",
+                        "kid": kid,
+                        "x": URL_SAFE_NO_PAD.encode(raw),
+                    }));
+                    store.verifying.insert(kid, decoding_key);
+                }
+                Err(e) => eprintln!("⚠️  Public key {} tidak valid: {}", path.display(), e),
+            }
+        }
+
+        if let Ok(kid) = std::env::var("JWT_SIGNING_KEY_ID") {
+            let path = Path::new(&dir).join(format!("{}.pem", kid));
+            let loaded = std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|pem| EncodingKey::from_ed_pem(&pem).map_err(|e| e.to_string()));
+
+            match loaded {
+                Ok(key) if store.verifying.contains_key(&kid) => store.signing = Some((kid, key)),
+                Ok(_) => eprintln!("⚠️  Public key {}.pub.pem tidak ditemukan, signing key diabaikan", kid),
+                Err(e) => eprintln!("⚠️  Private key {} tidak valid: {}", path.display(), e),
+            }
+        }
+
+        println!("🔑 JWKS: {} public key, signing key: {:?}", jwks.len(), store.signing.as_ref().map(|(kid, _)| kid));
+        store.jwks = serde_json::json!({ "keys": jwks });
+        store
+    }
+
+    pub fn decoding_key(&self, kid: &str) -> Option<&DecodingKey> {
+        self.verifying.get(kid)
+    }
+
+    pub fn jwks(&self) -> &serde_json::Value {
+        &self.jwks
+    }
+}
+
+// Baca public key Ed25519 (PEM SubjectPublicKeyInfo), kembalikan DecodingKey dan 32 byte key mentah
+fn load_public_key(path: &Path) -> Result<(DecodingKey, Vec<u8>), String> {
+    let pem = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let body: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+    let der = STANDARD.decode(body.trim()).map_err(|e| e.to_string())?;
+
+    let raw = der
+        .strip_prefix(&ED25519_SPKI_PREFIX[..])
+        .filter(|raw| raw.len() == 32)
+        .ok_or("bukan public key Ed25519")?
+        .to_vec();
+
+    let decoding_key = DecodingKey::from_ed_pem(pem.as_bytes()).map_err(|e| e.to_string())?;
+    Ok((decoding_key, raw))
+}
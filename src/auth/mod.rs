@@ -0,0 +1,16 @@
+// Autentikasi: hashing password, JWT access token, refresh token, dan extractor AuthUser
+pub mod audit;
+pub mod captcha;
+pub mod cookie;
+pub mod extractor;
+pub mod google;
+pub mod guest;
+pub mod keys;
+pub mod password;
+pub mod password_policy;
+pub mod rate_limit;
+pub mod scope;
+pub mod session;
+pub mod token;
+
+pub use extractor::AuthUser;
@@ -0,0 +1,28 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+
+// Hash password dengan Argon2 (format PHC string, salt sudah termasuk di dalamnya)
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+// Cek password terhadap hash yang tersimpan.
+// Row lama masih berisi plain text, jadi yang bukan PHC string dibandingkan langsung.
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    if !is_hashed(stored) {
+        return password == stored;
+    }
+
+    match PasswordHash::new(stored) {
+        Ok(parsed) => Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok(),
+        Err(_) => false,
+    }
+}
+
+// Hash Argon2 selalu diawali "$argon2"
+pub fn is_hashed(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use crate::config::{self, env_or};
+
+// Password paling umum yang selalu ditolak, ditambah daftar dari PASSWORD_DENYLIST_FILE jika ada
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "password1", "password123", "12345678", "123456789", "1234567890", "qwerty123",
+    "qwertyuiop", "11111111", "00000000", "iloveyou", "admin123", "welcome1", "abc12345",
+    "sentor123", "sewamotor", "bismillah", "indonesia",
+];
+
+// Aturan password, dibaca dari config supaya bisa diperketat tanpa compile ulang
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub denylist: HashSet<String>,
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Self {
+        let mut denylist: HashSet<String> = COMMON_PASSWORDS.iter().map(|p| p.to_string()).collect();
+
+        if let Ok(path) = std::env::var("PASSWORD_DENYLIST_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => denylist.extend(
+                    contents
+                        .lines()
+                        .map(|line| line.trim().to_lowercase())
+                        .filter(|line| !line.is_empty()),
+                ),
+                Err(e) => eprintln!("⚠️  Gagal membaca PASSWORD_DENYLIST_FILE {}: {}", path, e),
+            }
+        }
+
+        Self {
+            min_length: env_or("PASSWORD_MIN_LENGTH", 8),
+            require_digit: env_or("PASSWORD_REQUIRE_DIGIT", false),
+            require_symbol: env_or("PASSWORD_REQUIRE_SYMBOL", false),
+            denylist,
+        }
+    }
+
+    // Cek password terhadap semua aturan; hasilnya daftar pelanggaran (kosong = valid)
+    pub fn violations(&self, password: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            violations.push(format!("must be at least {} characters", self.min_length));
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push("must contain a digit".to_string());
+        }
+        if self.require_symbol && password.chars().all(|c| c.is_alphanumeric()) {
+            violations.push("must contain a symbol".to_string());
+        }
+        if self.denylist.contains(&password.to_lowercase()) {
+            violations.push("is too common".to_string());
+        }
+
+        violations
+    }
+}
+
+// Validasi password baru (register, reset, ganti password) dengan policy dari config
+pub fn validate(password: &str) -> Result<(), Vec<String>> {
+    let violations = config::get().password_policy.violations(password);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
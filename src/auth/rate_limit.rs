@@ -0,0 +1,79 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config;
+
+// Rate limiter sliding window sederhana di memory (per key: IP / username)
+pub struct RateLimiter {
+    max_hits: usize,
+    window: Duration,
+    hits: Mutex<HitLog>,
+}
+
+struct HitLog {
+    by_key: HashMap<String, VecDeque<Instant>>,
+    last_sweep: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(max_hits: usize, window: Duration) -> Self {
+        Self {
+            max_hits,
+            window,
+            hits: Mutex::new(HitLog { by_key: HashMap::new(), last_sweep: Instant::now() }),
+        }
+    }
+
+    // Catat satu hit untuk key. Err berisi sisa detik sampai boleh mencoba lagi.
+    pub fn check(&self, key: &str) -> Result<(), u64> {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Buang key yang sudah tidak punya hit aktif supaya map tidak tumbuh terus. Cukup sekali per
+        // window: key yang dibuang pasti sudah kedaluwarsa, jadi map paling besar = jumlah key dalam
+        // dua window, dan request lain tidak ikut membayar retain O(n).
+        if now.duration_since(hits.last_sweep) >= self.window {
+            let window = self.window;
+            hits.by_key.retain(|_, times| times.back().is_some_and(|t| now.duration_since(*t) < window));
+            hits.last_sweep = now;
+        }
+
+        let times = hits.by_key.entry(key.to_string()).or_default();
+        while times.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+            times.pop_front();
+        }
+
+        if times.len() >= self.max_hits {
+            let oldest = *times.front().unwrap_or(&now);
+            let retry_after = self.window.saturating_sub(now.duration_since(oldest));
+            return Err(retry_after.as_secs().max(1));
+        }
+
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+// Kumpulan limiter untuk endpoint login, register & permintaan OTP
+pub struct AuthRateLimits {
+    pub login_per_ip: RateLimiter,
+    pub login_per_username: RateLimiter,
+    pub register_per_ip: RateLimiter,
+    pub otp_per_ip: RateLimiter,
+}
+
+pub type SharedAuthRateLimits = Arc<AuthRateLimits>;
+
+impl AuthRateLimits {
+    pub fn from_config() -> SharedAuthRateLimits {
+        let cfg = config::get();
+        let window = Duration::from_secs(cfg.rate_limit_window_secs);
+        Arc::new(Self {
+            login_per_ip: RateLimiter::new(cfg.login_limit_per_ip, window),
+            login_per_username: RateLimiter::new(cfg.login_limit_per_username, window),
+            register_per_ip: RateLimiter::new(cfg.register_limit_per_ip, window),
+            otp_per_ip: RateLimiter::new(cfg.otp_limit_per_ip, window),
+        })
+    }
+}
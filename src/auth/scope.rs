@@ -0,0 +1,39 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::model::user::Role;
+
+// Permission yang disematkan ke access token (claim `scopes`)
+pub const ORDERS_READ: &str = "orders:read";
+pub const ORDERS_WRITE: &str = "orders:write";
+pub const MOTORS_WRITE: &str = "motors:write";
+pub const REPORTS_READ: &str = "reports:read";
+pub const USERS_WRITE: &str = "users:write";
+
+pub const ALL_SCOPES: &[&str] = &[ORDERS_READ, ORDERS_WRITE, MOTORS_WRITE, REPORTS_READ, USERS_WRITE];
+
+// Scope bawaan per role. Customer tidak punya scope pengelolaan; akses ke order miliknya
+// sendiri tetap dicek lewat kepemilikan di handler.
+pub fn default_scopes(role: Role) -> Vec<String> {
+    let scopes: &[&str] = match role {
+        Role::Admin => ALL_SCOPES,
+        Role::Staff => &[ORDERS_READ, ORDERS_WRITE, MOTORS_WRITE, REPORTS_READ],
+        Role::Customer => &[],
+    };
+    scopes.iter().map(|scope| scope.to_string()).collect()
+}
+
+// Scope untuk token user: kolom users.scopes (staff dengan akses terbatas) jika di-set,
+// selain itu scope bawaan role-nya
+pub async fn user_scopes(pool: &PgPool, user_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    let (role, scopes): (String, Option<Vec<String>>) =
+        sqlx::query_as("SELECT role, scopes FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(match scopes {
+        Some(scopes) => scopes,
+        None => role.parse().map(default_scopes).unwrap_or_default(),
+    })
+}
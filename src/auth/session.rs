@@ -0,0 +1,64 @@
+use std::net::IpAddr;
+
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+// Buat sesi login baru (satu per device) dengan info user agent dan IP
+pub async fn create_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    user_agent: Option<&str>,
+    ip: Option<IpAddr>,
+) -> Result<Uuid, sqlx::Error> {
+    let session_id = Uuid::new_v4();
+
+    sqlx::query(
+        "INSERT INTO user_sessions (id, user_id, user_agent, ip_address, last_used_at)
+         VALUES ($1, $2, $3, $4, NOW())"
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(user_agent.map(|ua| ua.chars().take(512).collect::<String>()))
+    .bind(ip.map(|ip| ip.to_string()))
+    .execute(pool)
+    .await?;
+
+    Ok(session_id)
+}
+
+// Buat sesi impersonation (admin bertindak sebagai user), ditandai dengan impersonated_by
+pub async fn create_impersonation_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    admin_id: Uuid,
+    user_agent: Option<&str>,
+    ip: Option<IpAddr>,
+) -> Result<Uuid, sqlx::Error> {
+    let session_id = create_session(pool, user_id, user_agent, ip).await?;
+
+    sqlx::query("UPDATE user_sessions SET impersonated_by = $1 WHERE id = $2")
+        .bind(admin_id)
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+
+    Ok(session_id)
+}
+
+// Cabut satu sesi milik user beserta refresh token-nya. Hasil false jika sesi tidak ditemukan.
+pub async fn revoke_session(conn: &mut PgConnection, user_id: Uuid, session_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL"
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE session_id = $1 AND revoked_at IS NULL")
+        .bind(session_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,183 @@
+use axum::http::{HeaderMap, StatusCode};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::keys;
+use crate::config;
+
+// Isi (claims) access token JWT
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String, // user_id
+    pub jti: String, // id unik token, dipakai untuk revocation saat logout
+    #[serde(default)]
+    pub sid: Option<String>, // id sesi / device (tabel user_sessions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<String>, // id admin jika token hasil impersonation
+    #[serde(default)]
+    pub scopes: Vec<String>, // permission pengelolaan (lihat auth::scope)
+    pub iat: i64,
+    pub exp: i64,
+}
+
+// Buat access token JWT (EdDSA / HS256) untuk user pada sesi tertentu
+pub fn create_access_token(
+    user_id: Uuid,
+    session_id: Uuid,
+    scopes: Vec<String>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    encode_access_token(user_id, session_id, scopes, config::get().access_token_ttl_secs, None)
+}
+
+// Access token impersonation: admin bertindak sebagai user lain, umur token pendek
+pub fn create_impersonation_token(
+    user_id: Uuid,
+    session_id: Uuid,
+    admin_id: Uuid,
+    scopes: Vec<String>,
+    ttl_secs: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    encode_access_token(user_id, session_id, scopes, ttl_secs, Some(admin_id))
+}
+
+fn encode_access_token(
+    user_id: Uuid,
+    session_id: Uuid,
+    scopes: Vec<String>,
+    ttl_secs: i64,
+    actor: Option<Uuid>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let cfg = config::get();
+    let now = Utc::now();
+    let claims = Claims {
+        sub: user_id.to_string(),
+        jti: Uuid::new_v4().to_string(),
+        sid: Some(session_id.to_string()),
+        act: actor.map(|id| id.to_string()),
+        scopes,
+        iat: now.timestamp(),
+        exp: (now + Duration::seconds(ttl_secs)).timestamp(),
+    };
+
+    // Pakai key Ed25519 (bisa diverifikasi service lain lewat JWKS) jika dikonfigurasi, selain itu HS256
+    match &keys::get().signing {
+        Some((kid, key)) => {
+            let mut header = Header::new(Algorithm::EdDSA);
+            header.kid = Some(kid.clone());
+            encode(&header, &claims, key)
+        }
+        None => encode(&Header::default(), &claims, &EncodingKey::from_secret(cfg.jwt_secret.as_bytes())),
+    }
+}
+
+// Validasi access token (signature + expiry) dan ambil claims di dalamnya
+pub fn decode_access_token(token: &str) -> Option<Claims> {
+    let cfg = config::get();
+    let header = decode_header(token).ok()?;
+
+    // Token EdDSA diverifikasi dengan public key sesuai kid. Token HS256 hanya diterima jika key Ed25519
+    // belum dikonfigurasi, atau selama masa migrasi HS256_ACCEPT_UNTIL belum lewat.
+    let secret_key;
+    let (algorithm, key) = match header.alg {
+        Algorithm::EdDSA => (Algorithm::EdDSA, keys::get().decoding_key(header.kid.as_deref()?)?),
+        Algorithm::HS256 => {
+            if keys::get().signing.is_some() && cfg.hs256_accept_until.is_none_or(|until| Utc::now() >= until) {
+                return None;
+            }
+            secret_key = DecodingKey::from_secret(cfg.jwt_secret.as_bytes());
+            (Algorithm::HS256, &secret_key)
+        }
+        _ => return None,
+    };
+
+    let mut validation = Validation::new(algorithm);
+    validation.leeway = cfg.token_leeway_secs;
+
+    decode::<Claims>(token, key, &validation)
+    .ok()
+    .map(|data| data.claims)
+}
+
+// Validasi access token lengkap: signature, expiry, dan belum dicabut lewat logout
+pub async fn verify_access_token(pool: &PgPool, token: &str) -> Result<Claims, StatusCode> {
+    let claims = decode_access_token(token).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let revoked = sqlx::query("SELECT 1 FROM revoked_tokens WHERE jti = $1")
+        .bind(&claims.jti)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .is_some();
+
+    if revoked {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(claims)
+}
+
+// Ambil bearer token dari header Authorization
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+}
+
+// Token (refresh / reset password) disimpan dalam bentuk hash SHA-256, bukan plain text
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Token acak 256-bit (hex) untuk refresh token / reset password
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Generate refresh token acak baru dan simpan ke tabel refresh_tokens
+pub async fn issue_refresh_token(conn: &mut PgConnection, user_id: Uuid, session_id: Uuid) -> Result<(Uuid, String), sqlx::Error> {
+    let token = random_token();
+    let id = Uuid::new_v4();
+    let expires_at = Utc::now() + Duration::seconds(config::get().refresh_token_ttl_secs);
+
+    sqlx::query(
+        "INSERT INTO refresh_tokens (id, user_id, session_id, token_hash, expires_at) VALUES ($1, $2, $3, $4, $5)"
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(session_id)
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .execute(conn)
+    .await?;
+
+    Ok((id, token))
+}
+
+// Cabut semua sesi user: access token yang terbit sebelum sekarang ditolak extractor,
+// semua sesi dan refresh token aktif dicabut. Dipakai saat ganti / reset password.
+pub async fn revoke_all_user_tokens(conn: &mut PgConnection, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET tokens_invalid_before = date_trunc('second', NOW()) WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE user_sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use sqlx::PgConnection;
+
+// Kode booking yang ditampilkan ke customer dan dipakai di semua response, email, invoice,
+// dan webhook: SMT-<tahun>-<nomor urut 6 digit>, contoh SMT-2025-000123.
+const PREFIX: &str = "SMT";
+
+pub fn format_booking_code(year: i32, seq: i32) -> String {
+    format!("{}-{}-{:06}", PREFIX, year, seq)
+}
+
+// Ambil kode booking berikutnya untuk tahun berjalan. Counter per tahun dinaikkan dengan row lock
+// di dalam transaksi booking, jadi booking bersamaan tidak mendapat nomor yang sama dan nomor
+// tidak bolong saat booking gagal (rollback). Keunikan tetap dijaga constraint orders_order_number_key.
+pub async fn next_booking_code(conn: &mut PgConnection) -> Result<String, sqlx::Error> {
+    let (year, seq): (i32, i32) = sqlx::query_as(
+        "INSERT INTO order_number_counters (year, last_value)
+         VALUES (EXTRACT(YEAR FROM CURRENT_DATE)::int, 1)
+         ON CONFLICT (year) DO UPDATE SET last_value = order_number_counters.last_value + 1
+         RETURNING year, last_value"
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(format_booking_code(year, seq))
+}
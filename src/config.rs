@@ -0,0 +1,201 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, NaiveTime, Utc};
+
+use crate::auth::audit::TrustedProxy;
+use crate::auth::captcha::CaptchaProvider;
+use crate::auth::cookie::SessionMode;
+use crate::auth::password_policy::PasswordPolicy;
+
+pub const DEFAULT_JWT_SECRET: &str = "dev-secret-ganti-di-env";
+
+// Konfigurasi aplikasi, dibaca sekali dari environment variable (.env)
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub app_env: String, // "development" / "dev" = mode lokal; selain itu dianggap production
+    pub jwt_secret: String,
+    pub hs256_accept_until: Option<DateTime<Utc>>, // masa migrasi: token HS256 lama masih diterima sampai waktu ini walau key Ed25519 sudah aktif
+    pub access_token_ttl_secs: i64,
+    pub refresh_token_ttl_secs: i64,
+    pub token_leeway_secs: u64, // toleransi selisih jam (clock skew) saat validasi exp/nbf JWT
+    pub password_reset_ttl_secs: i64,
+    pub frontend_url: String,
+    pub otp_ttl_secs: i64,
+    pub otp_max_attempts: i32,
+    pub google_client_id: Option<String>,
+    pub rate_limit_window_secs: u64,
+    pub login_limit_per_ip: usize,
+    pub login_limit_per_username: usize,
+    pub register_limit_per_ip: usize,
+    pub otp_limit_per_ip: usize, // permintaan OTP per IP per window, menahan SMS pumping
+    pub trusted_proxies: Vec<TrustedProxy>, // reverse proxy yang boleh mengisi X-Forwarded-For (kosong = abaikan header)
+    pub lockout_threshold: i32,
+    pub lockout_duration_secs: i64,
+    pub impersonation_ttl_secs: i64,
+    pub captcha: Option<(CaptchaProvider, String)>, // provider + secret key, None = captcha nonaktif
+    pub session_mode: SessionMode,
+    pub cookie_secure: bool,
+    pub password_policy: PasswordPolicy,
+    pub idempotency_key_ttl_secs: i64,
+    pub pending_order_ttl_secs: i64, // order pending lebih lama dari ini dibatalkan otomatis (0 = nonaktif)
+    pub order_expiry_interval_secs: u64,
+    pub hold_ttl_secs: i64, // lama motor ditahan oleh POST /api/orders/hold
+    pub overdue_check_interval_secs: u64, // 0 = eskalasi keterlambatan pengembalian nonaktif
+    pub overdue_second_notice_hours: i64, // teguran kedua sekian jam setelah jadwal kembali
+    pub overdue_flag_hours: i64, // tandai overdue + kabari staff cabang sekian jam setelah jadwal kembali
+    pub min_rental_days: i64, // minimal lama sewa global (0 = tanpa minimal)
+    pub max_rental_days: i64, // maksimal lama sewa global tanpa persetujuan manajer
+    pub early_return_refund_percent: i64, // persen selisih harga yang dikembalikan saat motor kembali lebih awal
+    pub branch_hours: (NaiveTime, NaiveTime), // jam operasional cabang untuk pengambilan motor
+    pub delivery_default_fee: i64, // ongkir untuk alamat di luar semua zona pengantaran
+    pub invoice_tax_percent: i64,
+    pub mileage_included_km_per_day: i64, // jatah km gratis per hari sewa
+    pub mileage_overage_rate: i64, // biaya per km di atas jatah (0 = biaya kelebihan km nonaktif)
+    pub webhook_dispatch_interval_secs: u64,
+    pub review_auto_approve: bool, // false = ulasan baru menunggu persetujuan admin sebelum tampil
+    pub motor_transfer_interval_secs: u64, // cek transfer motor terjadwal yang sudah efektif (0 = nonaktif)
+    pub low_availability_interval_secs: u64, // cek unit menipis di tanggal ramai (0 = nonaktif)
+    pub low_availability_threshold: i64, // kabari admin jika unit kosong per cabang + tipe di bawah angka ini
+    pub low_availability_lookahead_days: i64,
+    pub media_dir: String, // folder file upload (foto motor + thumbnail), dilayani di /media
+    pub max_image_upload_bytes: usize,
+    pub geofence_moving_speed_kmh: f32, // unit tanpa order aktif yang melaju di atas ini dianggap dipakai tanpa izin
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self {
+            app_env: std::env::var("APP_ENV").unwrap_or_else(|_| "production".to_string()).trim().to_lowercase(),
+            jwt_secret: std::env::var("JWT_SECRET").unwrap_or_else(|_| DEFAULT_JWT_SECRET.to_string()),
+            hs256_accept_until: hs256_accept_until_from_env(),
+            access_token_ttl_secs: env_or("ACCESS_TOKEN_TTL_SECS", 15 * 60),
+            refresh_token_ttl_secs: env_or("REFRESH_TOKEN_TTL_SECS", 30 * 24 * 60 * 60),
+            token_leeway_secs: env_or("TOKEN_LEEWAY_SECS", 30),
+            password_reset_ttl_secs: env_or("PASSWORD_RESET_TTL_SECS", 60 * 60),
+            frontend_url: std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:8000".to_string()),
+            otp_ttl_secs: env_or("OTP_TTL_SECS", 5 * 60),
+            otp_max_attempts: env_or("OTP_MAX_ATTEMPTS", 5),
+            google_client_id: std::env::var("GOOGLE_CLIENT_ID").ok().filter(|v| !v.is_empty()),
+            rate_limit_window_secs: env_or("RATE_LIMIT_WINDOW_SECS", 15 * 60),
+            login_limit_per_ip: env_or("LOGIN_LIMIT_PER_IP", 20),
+            login_limit_per_username: env_or("LOGIN_LIMIT_PER_USERNAME", 5),
+            register_limit_per_ip: env_or("REGISTER_LIMIT_PER_IP", 5),
+            otp_limit_per_ip: env_or("OTP_LIMIT_PER_IP", 5),
+            trusted_proxies: trusted_proxies_from_env(),
+            lockout_threshold: env_or("LOCKOUT_THRESHOLD", 5),
+            lockout_duration_secs: env_or("LOCKOUT_DURATION_SECS", 15 * 60),
+            impersonation_ttl_secs: env_or("IMPERSONATION_TTL_SECS", 15 * 60),
+            captcha: captcha_from_env(),
+            session_mode: env_or("AUTH_SESSION_MODE", SessionMode::Bearer),
+            cookie_secure: env_or("COOKIE_SECURE", true),
+            password_policy: PasswordPolicy::from_env(),
+            idempotency_key_ttl_secs: env_or("IDEMPOTENCY_KEY_TTL_SECS", 24 * 60 * 60),
+            pending_order_ttl_secs: env_or("PENDING_ORDER_TTL_SECS", 2 * 60 * 60),
+            order_expiry_interval_secs: env_or("ORDER_EXPIRY_INTERVAL_SECS", 5 * 60),
+            hold_ttl_secs: env_or("HOLD_TTL_SECS", 15 * 60),
+            overdue_check_interval_secs: env_or("OVERDUE_CHECK_INTERVAL_SECS", 5 * 60),
+            overdue_second_notice_hours: env_or("OVERDUE_SECOND_NOTICE_HOURS", 3),
+            overdue_flag_hours: env_or("OVERDUE_FLAG_HOURS", 12),
+            min_rental_days: env_or("MIN_RENTAL_DAYS", 0),
+            max_rental_days: env_or("MAX_RENTAL_DAYS", 30),
+            early_return_refund_percent: env_or("EARLY_RETURN_REFUND_PERCENT", 100),
+            branch_hours: branch_hours_from_env(),
+            delivery_default_fee: env_or("DELIVERY_DEFAULT_FEE", 50_000),
+            invoice_tax_percent: env_or("INVOICE_TAX_PERCENT", 0),
+            mileage_included_km_per_day: env_or("MILEAGE_INCLUDED_KM_PER_DAY", 150),
+            mileage_overage_rate: env_or("MILEAGE_OVERAGE_RATE", 0),
+            webhook_dispatch_interval_secs: env_or("WEBHOOK_DISPATCH_INTERVAL_SECS", 10),
+            review_auto_approve: env_or("REVIEW_AUTO_APPROVE", true),
+            motor_transfer_interval_secs: env_or("MOTOR_TRANSFER_INTERVAL_SECS", 15 * 60),
+            low_availability_interval_secs: env_or("LOW_AVAILABILITY_INTERVAL_SECS", 6 * 60 * 60),
+            low_availability_threshold: env_or("LOW_AVAILABILITY_THRESHOLD", 2),
+            low_availability_lookahead_days: env_or("LOW_AVAILABILITY_LOOKAHEAD_DAYS", 14),
+            media_dir: std::env::var("MEDIA_DIR").unwrap_or_else(|_| "uploads".to_string()),
+            max_image_upload_bytes: env_or("MAX_IMAGE_UPLOAD_BYTES", 8 * 1024 * 1024),
+            geofence_moving_speed_kmh: env_or("GEOFENCE_MOVING_SPEED_KMH", 10.0),
+        }
+    }
+}
+
+impl Config {
+    pub fn is_dev(&self) -> bool {
+        matches!(self.app_env.as_str(), "development" | "dev")
+    }
+}
+
+// HS256_ACCEPT_UNTIL (RFC 3339, mis. 2026-11-01T00:00:00Z): batas akhir masa migrasi HS256 -> Ed25519
+fn hs256_accept_until_from_env() -> Option<DateTime<Utc>> {
+    let value = std::env::var("HS256_ACCEPT_UNTIL").ok().filter(|v| !v.trim().is_empty())?;
+    match DateTime::parse_from_rfc3339(value.trim()) {
+        Ok(until) => Some(until.with_timezone(&Utc)),
+        Err(_) => {
+            eprintln!("⚠️  HS256_ACCEPT_UNTIL tidak valid ({}), token HS256 tidak diterima selama key Ed25519 aktif", value);
+            None
+        }
+    }
+}
+
+// TRUSTED_PROXIES: daftar IP / CIDR reverse proxy dipisah koma, mis. "127.0.0.1,10.0.0.0/8"
+fn trusted_proxies_from_env() -> Vec<TrustedProxy> {
+    std::env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|value| !value.trim().is_empty())
+        .filter_map(|value| match value.parse() {
+            Ok(proxy) => Some(proxy),
+            Err(e) => {
+                eprintln!("⚠️  TRUSTED_PROXIES: {}, diabaikan", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Captcha aktif hanya jika CAPTCHA_PROVIDER dan CAPTCHA_SECRET sama-sama di-set
+fn captcha_from_env() -> Option<(CaptchaProvider, String)> {
+    let provider = std::env::var("CAPTCHA_PROVIDER").ok().filter(|v| !v.is_empty())?;
+    let secret = std::env::var("CAPTCHA_SECRET").ok().filter(|v| !v.is_empty())?;
+
+    match provider.parse() {
+        Ok(provider) => Some((provider, secret)),
+        Err(e) => {
+            eprintln!("⚠️  {}, captcha dinonaktifkan", e);
+            None
+        }
+    }
+}
+
+// Jam operasional cabang dari BRANCH_OPEN_TIME / BRANCH_CLOSE_TIME (format HH:MM), default 07:00-21:00
+fn branch_hours_from_env() -> (NaiveTime, NaiveTime) {
+    let time_or = |key: &str, default: NaiveTime| match std::env::var(key) {
+        Ok(value) => NaiveTime::parse_from_str(value.trim(), "%H:%M").unwrap_or_else(|_| {
+            eprintln!("⚠️  {} tidak valid ({}), pakai nilai default", key, value);
+            default
+        }),
+        Err(_) => default,
+    };
+
+    (
+        time_or("BRANCH_OPEN_TIME", NaiveTime::from_hms_opt(7, 0, 0).unwrap()),
+        time_or("BRANCH_CLOSE_TIME", NaiveTime::from_hms_opt(21, 0, 0).unwrap()),
+    )
+}
+
+// Ambil konfigurasi global (di-load saat pertama kali dipanggil)
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::from_env)
+}
+
+// Baca env var dan parse ke tipe T, pakai default jika kosong / tidak valid
+pub fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    match std::env::var(key) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            eprintln!("⚠️  {} tidak valid ({}), pakai nilai default", key, value);
+            default
+        }),
+        Err(_) => default,
+    }
+}
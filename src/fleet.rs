@@ -0,0 +1,28 @@
+use tokio::sync::broadcast;
+
+use crate::model::telemetry::FleetUnit;
+
+// Antrean update per koneksi WebSocket; client yang terlalu lambat melewatkan update lama
+const FLEET_CHANNEL_CAPACITY: usize = 1024;
+
+// Siaran posisi unit terbaru dari POST /api/telemetry ke semua dashboard yang membuka
+// GET /api/admin/fleet/live (disisipkan sebagai Extension di main.rs)
+#[derive(Clone)]
+pub struct FleetFeed(broadcast::Sender<FleetUnit>);
+
+impl Default for FleetFeed {
+    fn default() -> Self {
+        Self(broadcast::channel(FLEET_CHANNEL_CAPACITY).0)
+    }
+}
+
+impl FleetFeed {
+    // Tidak apa-apa jika belum ada dashboard yang mendengarkan
+    pub fn publish(&self, unit: FleetUnit) {
+        let _ = self.0.send(unit);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<FleetUnit> {
+        self.0.subscribe()
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{NaiveDateTime, Utc};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfLayerReference};
+
+use crate::pricing::{format_rupiah, PriceBreakdown};
+
+// Data yang dicetak di invoice
+pub struct InvoiceData {
+    pub invoice_number: String,
+    pub customer_name: String,
+    pub customer_phone: String,
+    pub customer_email: String,
+    pub motor_name: String,
+    pub branch: String,
+    pub rental_start: NaiveDateTime,
+    pub rental_end: NaiveDateTime,
+    pub breakdown: PriceBreakdown,
+    pub insurance: Option<InvoiceInsurance>,
+    pub tax_percent: i64,
+}
+
+// Asuransi yang dipilih customer beserta cakupannya
+pub struct InvoiceInsurance {
+    pub name: String,
+    pub coverage: String,
+}
+
+fn tier_label(tier: &str) -> &str {
+    match tier {
+        "daily" => "Sewa harian",
+        "weekly" => "Sewa mingguan",
+        "monthly" => "Sewa bulanan",
+        other => other,
+    }
+}
+
+// Render invoice A4 sederhana: header, data customer, rincian item, subtotal, pajak, total
+pub fn render_invoice(data: &InvoiceData) -> Result<Vec<u8>, String> {
+    let (doc, page, layer) = PdfDocument::new(
+        format!("Invoice {}", data.invoice_number),
+        Mm(210.0),
+        Mm(297.0),
+        "Invoice",
+    );
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let text = |layer: &PdfLayerReference, value: &str, size: f32, x: f32, y: f32, font: &IndirectFontRef| {
+        layer.use_text(value, size, Mm(x), Mm(y), font);
+    };
+
+    // Header
+    text(&layer, "SENTOR SEWA MOTOR", 20.0, 20.0, 272.0, &bold);
+    text(&layer, "INVOICE", 14.0, 160.0, 272.0, &bold);
+    text(&layer, &format!("No: {}", data.invoice_number), 10.0, 150.0, 264.0, &regular);
+    text(&layer, &format!("Tanggal: {}", Utc::now().format("%d-%m-%Y")), 10.0, 150.0, 258.0, &regular);
+
+    // Customer & sewa
+    let mut y = 244.0;
+    text(&layer, "Ditagihkan kepada", 11.0, 20.0, y, &bold);
+    for line in [&data.customer_name, &data.customer_phone, &data.customer_email] {
+        y -= 6.0;
+        text(&layer, line, 10.0, 20.0, y, &regular);
+    }
+
+    y -= 12.0;
+    text(&layer, "Detail sewa", 11.0, 20.0, y, &bold);
+    let mut details = vec![
+        format!("Motor: {}", data.motor_name),
+        format!("Cabang: {}", data.branch),
+        format!(
+            "Periode: {} s/d {} ({} hari)",
+            data.rental_start.format("%d-%m-%Y %H:%M"),
+            data.rental_end.format("%d-%m-%Y %H:%M"),
+            data.breakdown.rental_days
+        ),
+    ];
+    if let Some(insurance) = &data.insurance {
+        details.push(format!("Asuransi: {} ({})", insurance.name, insurance.coverage));
+    }
+    for line in &details {
+        y -= 6.0;
+        text(&layer, line, 10.0, 20.0, y, &regular);
+    }
+
+    // Tabel item
+    y -= 14.0;
+    text(&layer, "Item", 10.0, 20.0, y, &bold);
+    text(&layer, "Qty", 10.0, 115.0, y, &bold);
+    text(&layer, "Harga", 10.0, 135.0, y, &bold);
+    text(&layer, "Subtotal", 10.0, 165.0, y, &bold);
+
+    let rows = data.breakdown.lines.iter()
+        .map(|line| {
+            let label = match &line.motor_name {
+                Some(motor) => format!("{} - {}", tier_label(&line.tier), motor),
+                None => tier_label(&line.tier).to_string(),
+            };
+            (label, line.quantity, line.unit_price, line.subtotal)
+        })
+        .chain(data.breakdown.items.iter()
+            .map(|item| (item.name.clone(), item.quantity, item.unit_price, item.subtotal)));
+    for (name, quantity, unit_price, subtotal) in rows {
+        y -= 7.0;
+        text(&layer, &name, 10.0, 20.0, y, &regular);
+        text(&layer, &quantity.to_string(), 10.0, 115.0, y, &regular);
+        text(&layer, &format_rupiah(unit_price), 10.0, 135.0, y, &regular);
+        text(&layer, &format_rupiah(subtotal), 10.0, 165.0, y, &regular);
+    }
+
+    // Total
+    let subtotal = data.breakdown.total;
+    let tax = subtotal * data.tax_percent / 100;
+    y -= 14.0;
+    for (label, amount, font) in [
+        ("Subtotal".to_string(), subtotal, &regular),
+        (format!("Pajak ({}%)", data.tax_percent), tax, &regular),
+        ("Total".to_string(), subtotal + tax, &bold),
+    ] {
+        text(&layer, &label, 10.0, 135.0, y, font);
+        text(&layer, &format_rupiah(amount), 10.0, 165.0, y, font);
+        y -= 7.0;
+    }
+
+    text(&layer, "Terima kasih telah menyewa di Sentor Sewa Motor.", 9.0, 20.0, 20.0, &regular);
+
+    doc.save_to_bytes().map_err(|e| e.to_string())
+}
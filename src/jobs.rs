@@ -0,0 +1,517 @@
+use std::time::Duration;
+
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, Weekday};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config;
+use crate::mailer::SharedMailer;
+use crate::model::orders::OrderStatus;
+use crate::model::webhook::ORDER_CANCELLED;
+use crate::routes::motor::availability_by_branch_type;
+use crate::routes::orders::expire_holds;
+use crate::routes::transfers::apply_due_transfers;
+use crate::notifications::log_notification;
+use crate::webhooks;
+
+// Order pending yang dibatalkan otomatis, beserta kontak customer untuk notifikasi
+#[derive(sqlx::FromRow)]
+struct ExpiredOrder {
+    id: Uuid,
+    order_number: String,
+    pilih_motor: String,
+    tanggal_peminjaman: NaiveDate,
+    full_name: String,
+    email: String,
+}
+
+// Order yang motornya belum dikembalikan dan baru naik tahap eskalasi, beserta kontak customer
+#[derive(sqlx::FromRow)]
+struct OverdueOrder {
+    id: Uuid,
+    order_number: String,
+    pilih_motor: String,
+    pilih_cabang: String,
+    rental_end: NaiveDateTime,
+    full_name: String,
+    email: String,
+}
+
+// Tahap eskalasi keterlambatan pengembalian (disimpan di orders.overdue_stage)
+#[derive(Debug, Clone, Copy)]
+enum OverdueStage {
+    Reminder,     // tepat di jadwal kembali
+    SecondNotice, // OVERDUE_SECOND_NOTICE_HOURS setelah jadwal kembali
+    Flagged,      // OVERDUE_FLAG_HOURS setelah jadwal kembali: status overdue + kabari staff cabang
+}
+
+impl OverdueStage {
+    fn level(self) -> i16 {
+        match self {
+            OverdueStage::Reminder => 1,
+            OverdueStage::SecondNotice => 2,
+            OverdueStage::Flagged => 3,
+        }
+    }
+
+    fn hours_after_due(self) -> i64 {
+        let cfg = config::get();
+        match self {
+            OverdueStage::Reminder => 0,
+            OverdueStage::SecondNotice => cfg.overdue_second_notice_hours,
+            OverdueStage::Flagged => cfg.overdue_flag_hours,
+        }
+    }
+
+    // Kunci notifikasi customer di order_notifications
+    fn notification_key(self) -> &'static str {
+        match self {
+            OverdueStage::Reminder => "return_reminder",
+            OverdueStage::SecondNotice => "return_second_notice",
+            OverdueStage::Flagged => "order_overdue",
+        }
+    }
+
+    fn history_note(self) -> &'static str {
+        match self {
+            OverdueStage::Reminder => "Pengingat pengembalian dikirim ke customer",
+            OverdueStage::SecondNotice => "Teguran kedua keterlambatan pengembalian dikirim ke customer",
+            OverdueStage::Flagged => "Ditandai overdue: motor belum dikembalikan, staff cabang dikabari",
+        }
+    }
+}
+
+// Jalankan job berkala yang melepas hold kedaluwarsa dan membatalkan order pending yang terlalu lama
+// tidak dikonfirmasi, supaya motornya kembali tersedia. Auto-cancel pending nonaktif jika
+// PENDING_ORDER_TTL_SECS = 0.
+pub fn spawn_order_expiry(pool: PgPool, mailer: SharedMailer) {
+    let cfg = config::get();
+    if cfg.pending_order_ttl_secs > 0 {
+        println!(
+            "⏱️  Auto-cancel order pending setelah {} detik (cek tiap {} detik)",
+            cfg.pending_order_ttl_secs, cfg.order_expiry_interval_secs
+        );
+    } else {
+        println!("⏸️  Auto-cancel order pending dinonaktifkan (PENDING_ORDER_TTL_SECS = 0)");
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(cfg.order_expiry_interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+
+            // Hold (POST /api/orders/hold) yang kedaluwarsa dilepas supaya motornya tersedia lagi
+            match pool.acquire().await {
+                Ok(mut conn) => match expire_holds(&mut conn, None).await {
+                    Ok(0) => {}
+                    Ok(expired) => println!("🧹 {} hold kedaluwarsa dilepas", expired),
+                    Err(e) => eprintln!("⚠️  Gagal melepas hold kedaluwarsa: {}", e),
+                },
+                Err(e) => eprintln!("⚠️  Gagal melepas hold kedaluwarsa: {}", e),
+            }
+
+            if cfg.pending_order_ttl_secs <= 0 {
+                continue;
+            }
+
+            match cancel_stale_pending_orders(&pool, cfg.pending_order_ttl_secs).await {
+                Ok(orders) => {
+                    if !orders.is_empty() {
+                        println!("🧹 {} order pending dibatalkan otomatis", orders.len());
+                    }
+                    for order in orders {
+                        notify_cancelled(&pool, &mailer, &order).await;
+                    }
+                }
+                Err(e) => eprintln!("⚠️  Auto-cancel order pending gagal: {}", e),
+            }
+        }
+    });
+}
+
+// Jalankan job berkala yang mengeskalasi motor yang belum dikembalikan: pengingat saat jadwal kembali,
+// teguran kedua, lalu status overdue + notifikasi staff cabang. Nonaktif jika OVERDUE_CHECK_INTERVAL_SECS = 0.
+pub fn spawn_overdue_escalation(pool: PgPool, mailer: SharedMailer) {
+    let cfg = config::get();
+    if cfg.overdue_check_interval_secs == 0 {
+        println!("⏸️  Eskalasi keterlambatan pengembalian dinonaktifkan (OVERDUE_CHECK_INTERVAL_SECS = 0)");
+        return;
+    }
+    println!(
+        "⏰ Eskalasi keterlambatan pengembalian aktif: teguran kedua +{} jam, overdue +{} jam (cek tiap {} detik)",
+        cfg.overdue_second_notice_hours, cfg.overdue_flag_hours, cfg.overdue_check_interval_secs
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(cfg.overdue_check_interval_secs));
+        loop {
+            interval.tick().await;
+
+            // Tahap tertinggi dulu: order yang sudah sangat terlambat (misalnya server sempat mati)
+            // langsung ditandai overdue tanpa menerima pengingat yang sudah basi
+            for stage in [OverdueStage::Flagged, OverdueStage::SecondNotice, OverdueStage::Reminder] {
+                let orders = match escalate_overdue_orders(&pool, stage).await {
+                    Ok(orders) => orders,
+                    Err(e) => {
+                        eprintln!("⚠️  Eskalasi keterlambatan ({:?}) gagal: {}", stage, e);
+                        continue;
+                    }
+                };
+                if !orders.is_empty() {
+                    println!("⏰ {} order terlambat dikembalikan naik ke tahap {:?}", orders.len(), stage);
+                }
+
+                for order in orders {
+                    notify_overdue_customer(&pool, &mailer, &order, stage).await;
+                    if let OverdueStage::Flagged = stage {
+                        notify_overdue_staff(&pool, &mailer, &order).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+// Naikkan tahap eskalasi order picked_up yang jadwal kembalinya sudah lewat batas tahap ini, dan catat
+// ke order_status_history (changed_by NULL = sistem). Tahap Flagged sekaligus mengubah status ke overdue.
+async fn escalate_overdue_orders(pool: &PgPool, stage: OverdueStage) -> Result<Vec<OverdueOrder>, sqlx::Error> {
+    let new_status = match stage {
+        OverdueStage::Flagged => OrderStatus::Overdue,
+        OverdueStage::Reminder | OverdueStage::SecondNotice => OrderStatus::PickedUp,
+    };
+
+    sqlx::query_as(
+        "WITH escalated AS (
+             UPDATE orders o
+             SET overdue_stage = $1, status = $2
+             FROM users u
+             LEFT JOIN guest_checkouts g ON g.guest_user_id = u.id
+             WHERE u.id = o.user_id
+               AND o.status = $3
+               AND o.overdue_stage < $1
+               AND o.tanggal_pengembalian + o.jam_pengembalian <= LOCALTIMESTAMP - make_interval(hours => $4)
+             RETURNING o.id, o.order_number, o.pilih_motor, o.pilih_cabang,
+                       o.tanggal_pengembalian + o.jam_pengembalian AS rental_end,
+                       u.full_name, COALESCE(g.email, u.email) AS email
+         ), history AS (
+             INSERT INTO order_status_history (order_id, old_status, new_status, note)
+             SELECT id, $3, $2, $5 FROM escalated
+         )
+         SELECT * FROM escalated"
+    )
+    .bind(stage.level())
+    .bind(new_status.as_str())
+    .bind(OrderStatus::PickedUp.as_str())
+    .bind(stage.hours_after_due() as i32)
+    .bind(stage.history_note())
+    .fetch_all(pool)
+    .await
+}
+
+async fn notify_overdue_customer(pool: &PgPool, mailer: &SharedMailer, order: &OverdueOrder, stage: OverdueStage) {
+    let (subject, message) = match stage {
+        OverdueStage::Reminder => (
+            "Pengingat: waktunya mengembalikan motor",
+            "sudah waktunya dikembalikan. Silakan kembalikan motor ke cabang secepatnya.",
+        ),
+        OverdueStage::SecondNotice => (
+            "Motor belum dikembalikan",
+            "sudah melewati jadwal kembali dan belum kami terima. Segera kembalikan motor atau hubungi \
+             cabang jika butuh perpanjangan; keterlambatan dapat dikenakan biaya tambahan.",
+        ),
+        OverdueStage::Flagged => (
+            "Keterlambatan pengembalian motor",
+            "masih belum dikembalikan dan booking kamu kami tandai terlambat. Staff cabang akan segera \
+             menghubungi kamu. Keterlambatan dikenakan biaya tambahan sesuai ketentuan.",
+        ),
+    };
+    let body = format!(
+        "Halo {},\n\nMotor {} pada booking {} (jadwal kembali {} di cabang {}) {}\n\nTerima kasih,\nSentor Sewa Motor",
+        order.full_name, order.pilih_motor, order.order_number, order.rental_end, order.pilih_cabang, message
+    );
+
+    let result = mailer.send(&order.email, subject, &body).await;
+    log_notification(pool, order.id, stage.notification_key(), &order.email, subject, &result).await;
+    if let Err(e) = result {
+        eprintln!("⚠️  Gagal kirim notifikasi keterlambatan order {}: {}", order.id, e);
+    }
+}
+
+// Kabari staff aktif di cabang order (users.branch_id sama dengan orders.pilih_cabang)
+async fn notify_overdue_staff(pool: &PgPool, mailer: &SharedMailer, order: &OverdueOrder) {
+    let staff_emails: Vec<String> = match sqlx::query_scalar(
+        "SELECT email FROM users
+         WHERE role = 'staff' AND status = 'active' AND deleted_at IS NULL
+           AND LOWER(TRIM(branch_id)) = LOWER(TRIM($1))"
+    )
+    .bind(&order.pilih_cabang)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(emails) => emails,
+        Err(e) => {
+            eprintln!("⚠️  Gagal ambil staff cabang {} untuk order overdue {}: {}", order.pilih_cabang, order.id, e);
+            return;
+        }
+    };
+
+    if staff_emails.is_empty() {
+        println!("⚠️  Tidak ada staff aktif di cabang {} untuk order overdue {}", order.pilih_cabang, order.order_number);
+        return;
+    }
+
+    let subject = format!("Order {} overdue", order.order_number);
+    let body = format!(
+        "Order {} ({}) atas nama {} belum dikembalikan {} jam setelah jadwal kembali {}.\n\n\
+         Mohon hubungi customer dan tindak lanjuti pengembalian motor.",
+        order.order_number, order.pilih_motor, order.full_name, config::get().overdue_flag_hours, order.rental_end
+    );
+    for email in staff_emails {
+        let result = mailer.send(&email, &subject, &body).await;
+        log_notification(pool, order.id, "order_overdue_staff", &email, &subject, &result).await;
+        if let Err(e) = result {
+            eprintln!("⚠️  Gagal kirim alert overdue order {} ke {}: {}", order.id, email, e);
+        }
+    }
+}
+
+// Jalankan job berkala yang mengirim webhook order yang masih antre / perlu dicoba ulang
+pub fn spawn_webhook_dispatcher(pool: PgPool) {
+    let interval_secs = config::get().webhook_dispatch_interval_secs.max(1);
+    println!("📡 Webhook dispatcher aktif (cek tiap {} detik)", interval_secs);
+
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match webhooks::dispatch_due(&pool, &client).await {
+                Ok(0) => {}
+                Ok(sent) => println!("📡 {} webhook diproses", sent),
+                Err(e) => eprintln!("⚠️  Webhook dispatcher gagal: {}", e),
+            }
+        }
+    });
+}
+
+// Jalankan job berkala yang memindahkan motor ke cabang tujuan saat tanggal efektif transfernya tiba.
+// Nonaktif jika MOTOR_TRANSFER_INTERVAL_SECS = 0.
+pub fn spawn_motor_transfers(pool: PgPool) {
+    let interval_secs = config::get().motor_transfer_interval_secs;
+    if interval_secs == 0 {
+        println!("⏸️  Transfer motor terjadwal dinonaktifkan (MOTOR_TRANSFER_INTERVAL_SECS = 0)");
+        return;
+    }
+    println!("🚚 Transfer motor terjadwal aktif (cek tiap {} detik)", interval_secs);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match apply_due_transfers(&pool).await {
+                Ok(transfers) => {
+                    for transfer in transfers {
+                        println!(
+                            "🚚 Motor #{} dipindah ke cabang {} ({} unit)",
+                            transfer.motor_id, transfer.to_branch, transfer.units_moved.unwrap_or(0)
+                        );
+                    }
+                }
+                Err(e) => eprintln!("⚠️  Transfer motor terjadwal gagal: {}", e),
+            }
+        }
+    });
+}
+
+// Unit kosong per cabang + tipe motor di satu tanggal ramai yang di bawah LOW_AVAILABILITY_THRESHOLD
+#[derive(sqlx::FromRow)]
+struct LowAvailability {
+    branch: String,
+    motor_type: String,
+    demand_date: NaiveDate,
+    quantity: i64,
+    available: i64,
+}
+
+// Jalankan job berkala yang mengecek unit kosong per cabang dan tipe motor di tanggal ramai (weekend dan
+// musim dengan harga naik) dalam LOW_AVAILABILITY_LOOKAHEAD_DAYS ke depan, lalu mengabari admin supaya
+// armada bisa dipindah antar cabang sebelum hari-H. Nonaktif jika LOW_AVAILABILITY_INTERVAL_SECS = 0.
+pub fn spawn_low_availability_check(pool: PgPool, mailer: SharedMailer) {
+    let cfg = config::get();
+    if cfg.low_availability_interval_secs == 0 {
+        println!("⏸️  Peringatan unit menipis dinonaktifkan (LOW_AVAILABILITY_INTERVAL_SECS = 0)");
+        return;
+    }
+    println!(
+        "📉 Peringatan unit menipis aktif: < {} unit, {} hari ke depan (cek tiap {} detik)",
+        cfg.low_availability_threshold, cfg.low_availability_lookahead_days, cfg.low_availability_interval_secs
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(cfg.low_availability_interval_secs));
+        loop {
+            interval.tick().await;
+            match find_low_availability(&pool).await {
+                Ok(shortages) if shortages.is_empty() => {}
+                Ok(shortages) => {
+                    println!("📉 {} cabang / tipe motor kekurangan unit di tanggal ramai", shortages.len());
+                    notify_low_availability(&pool, &mailer, &shortages).await;
+                }
+                Err(e) => eprintln!("⚠️  Cek unit menipis gagal: {}", e),
+            }
+        }
+    });
+}
+
+// Tanggal ramai dalam jendela pengecekan: Sabtu / Minggu, atau tanggal yang kena aturan harga musim
+// dengan kenaikan harga (semua cabang maupun cabang tertentu)
+async fn high_demand_dates(pool: &PgPool, from: NaiveDate, to: NaiveDate) -> Result<Vec<NaiveDate>, sqlx::Error> {
+    let seasons: Vec<(NaiveDate, NaiveDate)> = sqlx::query_as(
+        "SELECT starts_on, ends_on FROM pricing_rules
+         WHERE active AND kind = 'season' AND multiplier_percent > 100
+           AND starts_on <= $2 AND ends_on >= $1"
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter(|date| {
+            matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+                || seasons.iter().any(|(start, end)| (*start..=*end).contains(date))
+        })
+        .collect())
+}
+
+// Cari kekurangan unit di tanggal ramai lalu catat ke low_availability_alerts. Hasilnya hanya yang baru
+// atau makin parah sejak peringatan terakhir, supaya admin tidak dikirimi hal yang sama tiap pengecekan.
+async fn find_low_availability(pool: &PgPool) -> Result<Vec<LowAvailability>, sqlx::Error> {
+    let cfg = config::get();
+    let today = chrono::Local::now().date_naive();
+    let until = today + Days::new(cfg.low_availability_lookahead_days.max(0) as u64);
+
+    let mut shortages = Vec::new();
+    for date in high_demand_dates(pool, today, until).await? {
+        let start = date.and_hms_opt(0, 0, 0).unwrap();
+        let end = start + chrono::Duration::days(1);
+        for (branch, motor_type, quantity, available) in availability_by_branch_type(pool, start, end).await? {
+            if available >= cfg.low_availability_threshold {
+                continue;
+            }
+            let recorded: Option<LowAvailability> = sqlx::query_as(
+                "INSERT INTO low_availability_alerts (branch, motor_type, demand_date, quantity, available)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (branch, motor_type, demand_date) DO UPDATE
+                 SET quantity = EXCLUDED.quantity, available = EXCLUDED.available, notified_at = NOW()
+                 WHERE EXCLUDED.available < low_availability_alerts.available
+                 RETURNING branch, motor_type, demand_date, quantity, available"
+            )
+            .bind(&branch)
+            .bind(&motor_type)
+            .bind(date)
+            .bind(quantity)
+            .bind(available)
+            .fetch_optional(pool)
+            .await?;
+            shortages.extend(recorded);
+        }
+    }
+
+    Ok(shortages)
+}
+
+// Satu email ringkasan ke semua admin aktif
+async fn notify_low_availability(pool: &PgPool, mailer: &SharedMailer, shortages: &[LowAvailability]) {
+    let admin_emails: Vec<String> = match sqlx::query_scalar(
+        "SELECT email FROM users WHERE role = 'admin' AND status = 'active' AND deleted_at IS NULL"
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(emails) => emails,
+        Err(e) => {
+            eprintln!("⚠️  Gagal ambil admin untuk peringatan unit menipis: {}", e);
+            return;
+        }
+    };
+
+    if admin_emails.is_empty() {
+        println!("⚠️  Tidak ada admin aktif untuk peringatan unit menipis");
+        return;
+    }
+
+    let lines: Vec<String> = shortages.iter()
+        .map(|s| format!(
+            "- {} ({}), cabang {}: {} dari {} unit tersedia",
+            s.demand_date.format("%d-%m-%Y"), s.motor_type, s.branch, s.available, s.quantity
+        ))
+        .collect();
+    let subject = format!("Unit motor menipis di {} tanggal ramai", shortages.len());
+    let body = format!(
+        "Unit yang masih bisa dibooking di bawah {} untuk:\n\n{}\n\n\
+         Pertimbangkan memindahkan unit dari cabang lain sebelum tanggal tersebut.",
+        config::get().low_availability_threshold,
+        lines.join("\n")
+    );
+    for email in admin_emails {
+        if let Err(e) = mailer.send(&email, &subject, &body).await {
+            eprintln!("⚠️  Gagal kirim peringatan unit menipis ke {}: {}", email, e);
+        }
+    }
+}
+
+async fn cancel_stale_pending_orders(pool: &PgPool, ttl_secs: i64) -> Result<Vec<ExpiredOrder>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Email tamu (guest checkout) ada di guest_checkouts, bukan di akun guest-nya
+    // Perubahan status sekaligus dicatat ke order_status_history (changed_by NULL = sistem)
+    let orders: Vec<ExpiredOrder> = sqlx::query_as(
+        "WITH cancelled AS (
+             UPDATE orders o
+             SET status = $1
+             FROM users u
+             LEFT JOIN guest_checkouts g ON g.guest_user_id = u.id
+             WHERE u.id = o.user_id
+               AND o.status = $2
+               AND o.tanggal_booking + o.waktu_booking < LOCALTIMESTAMP - make_interval(secs => $3)
+             RETURNING o.id, o.order_number, o.pilih_motor, o.tanggal_peminjaman, u.full_name, COALESCE(g.email, u.email) AS email
+         ), history AS (
+             INSERT INTO order_status_history (order_id, old_status, new_status, note)
+             SELECT id, $2, $1, 'Dibatalkan otomatis: belum dikonfirmasi dalam batas waktu' FROM cancelled
+         )
+         SELECT * FROM cancelled"
+    )
+    .bind(OrderStatus::Cancelled.as_str())
+    .bind(OrderStatus::Pending.as_str())
+    .bind(ttl_secs as f64)
+    .fetch_all(&mut tx)
+    .await?;
+
+    // Webhook order.cancelled diantre di transaksi yang sama: batal tersimpan berarti event-nya juga
+    for order in &orders {
+        webhooks::enqueue_order_event(&mut tx, ORDER_CANCELLED, order.id).await?;
+    }
+
+    tx.commit().await?;
+    Ok(orders)
+}
+
+async fn notify_cancelled(pool: &PgPool, mailer: &SharedMailer, order: &ExpiredOrder) {
+    let body = format!(
+        "Halo {},\n\nBooking {} ({}) untuk tanggal {} dibatalkan otomatis karena belum dikonfirmasi \
+         dalam batas waktu. Silakan buat booking baru jika masih ingin menyewa.\n\nTerima kasih,\nSentor Sewa Motor",
+        order.full_name, order.order_number, order.pilih_motor, order.tanggal_peminjaman
+    );
+
+    let subject = "Booking kamu dibatalkan otomatis";
+    let result = mailer.send(&order.email, subject, &body).await;
+    log_notification(pool, order.id, "order_auto_cancelled", &order.email, subject, &result).await;
+    if let Err(e) = result {
+        eprintln!("⚠️  Gagal kirim notifikasi pembatalan order {}: {}", order.id, e);
+    }
+}
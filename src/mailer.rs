@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+// Abstraksi pengiriman email, supaya provider bisa diganti tanpa mengubah handler
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<(), String>;
+}
+
+pub type SharedMailer = Arc<dyn Mailer>;
+
+// Mailer untuk development: email hanya dicetak ke console
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<(), String> {
+        println!("📧 [LogMailer] To: {} | Subject: {}\n{}", to, subject, body);
+        Ok(())
+    }
+}
+
+// Mailer via HTTP API provider (JSON: from, to, subject, text) dengan bearer API key
+pub struct HttpMailer {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+    from: String,
+}
+
+#[async_trait]
+impl Mailer for HttpMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&self.api_url)
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "from": self.from,
+                "to": to,
+                "subject": subject,
+                "text": body,
+            }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("Mail provider returned {}", response.status()));
+        }
+
+        Ok(())
+    }
+}
+
+// Pilih mailer dari env: MAILER_API_URL di-set -> HttpMailer, selain itu LogMailer
+pub fn from_env() -> SharedMailer {
+    match std::env::var("MAILER_API_URL") {
+        Ok(api_url) if !api_url.is_empty() => {
+            println!("📧 Mailer: HTTP provider ({})", api_url);
+            Arc::new(HttpMailer {
+                client: reqwest::Client::new(),
+                api_url,
+                api_key: std::env::var("MAILER_API_KEY").unwrap_or_default(),
+                from: std::env::var("MAIL_FROM").unwrap_or_else(|_| "no-reply@sentor.id".to_string()),
+            })
+        }
+        _ => {
+            println!("📧 Mailer: console log (MAILER_API_URL belum di-set)");
+            Arc::new(LogMailer)
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+// Prefix URL file upload, dilayani ServeDir dari MEDIA_DIR (lihat main.rs)
+pub const MEDIA_URL_PREFIX: &str = "/media";
+
+// Lebar maksimal thumbnail (px): kartu di katalog, halaman detail, dan zoom
+const CARD_WIDTH: u32 = 400;
+const DETAIL_WIDTH: u32 = 1024;
+const ZOOM_WIDTH: u32 = 2048;
+
+// URL thumbnail webp per ukuran, disimpan di motors.image_thumbnails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageThumbnails {
+    pub card: String,
+    pub detail: String,
+    pub zoom: String,
+}
+
+// Format gambar yang boleh diunggah; hasilnya ekstensi file original
+pub fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    match image::guess_format(bytes).ok()? {
+        ImageFormat::Jpeg => Some("jpg"),
+        ImageFormat::Png => Some("png"),
+        ImageFormat::WebP => Some("webp"),
+        _ => None,
+    }
+}
+
+// Folder dan URL file milik satu motor, contoh: uploads/motors/12 <-> /media/motors/12
+pub fn motor_media_dir(motor_id: i32) -> (PathBuf, String) {
+    (
+        Path::new(&config::get().media_dir).join("motors").join(motor_id.to_string()),
+        format!("{}/motors/{}", MEDIA_URL_PREFIX, motor_id),
+    )
+}
+
+// Kecilkan gambar (tidak pernah diperbesar) lalu simpan sebagai webp
+fn write_thumbnail(image: &DynamicImage, max_width: u32, path: &Path) -> Result<(), image::ImageError> {
+    let resized = if image.width() > max_width {
+        image.resize(max_width, u32::MAX, FilterType::Lanczos3)
+    } else {
+        image.clone()
+    };
+    // Encoder webp hanya menerima RGB / RGBA 8 bit
+    DynamicImage::ImageRgba8(resized.to_rgba8()).save_with_format(path, ImageFormat::WebP)
+}
+
+// Buat thumbnail webp semua ukuran dari file original: {stem}-card.webp, {stem}-detail.webp, ...
+// Berat (decode + resize), jadi dipanggil lewat spawn_blocking.
+pub fn generate_thumbnails(original: &Path, dir: &Path, url_dir: &str, stem: &str) -> Result<ImageThumbnails, image::ImageError> {
+    let image = image::open(original)?;
+
+    let thumbnail = |name: &str, max_width: u32| -> Result<String, image::ImageError> {
+        let file_name = format!("{}-{}.webp", stem, name);
+        write_thumbnail(&image, max_width, &dir.join(&file_name))?;
+        Ok(format!("{}/{}", url_dir, file_name))
+    };
+
+    Ok(ImageThumbnails {
+        card: thumbnail("card", CARD_WIDTH)?,
+        detail: thumbnail("detail", DETAIL_WIDTH)?,
+        zoom: thumbnail("zoom", ZOOM_WIDTH)?,
+    })
+}
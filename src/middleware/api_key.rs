@@ -0,0 +1,94 @@
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Json as RespJson, Response},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::token::hash_token;
+use crate::model::api_key::ApiClient;
+
+fn api_key_error(status: StatusCode, message: &str) -> Response {
+    (status, RespJson(serde_json::json!({"error": message}))).into_response()
+}
+
+// Middleware untuk route partner: validasi header X-Api-Key, sisipkan ApiClient ke
+// request extensions, lalu catat pemakaian (method, path, status) di api_key_usage.
+pub async fn require_api_key(mut req: Request, next: Next) -> Response {
+    let Some(pool) = req.extensions().get::<PgPool>().cloned() else {
+        return api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+    };
+
+    let Some(raw_key) = req
+        .headers()
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+    else {
+        return api_key_error(StatusCode::UNAUTHORIZED, "API key required");
+    };
+
+    let row: Option<(Uuid, String, Vec<String>)> = match sqlx::query_as(
+        "SELECT id, name, scopes FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL"
+    )
+    .bind(hash_token(&raw_key))
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            println!("❌ Database error: {}", e);
+            return api_key_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+        }
+    };
+
+    let Some((key_id, name, scopes)) = row else {
+        println!("❌ Invalid API key");
+        return api_key_error(StatusCode::UNAUTHORIZED, "Invalid API key");
+    };
+
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    req.extensions_mut().insert(ApiClient { key_id, name, scopes });
+
+    let response = next.run(req).await;
+
+    // Usage logging tidak boleh menggagalkan request partner
+    let status = response.status().as_u16() as i32;
+    if let Err(e) = sqlx::query(
+        "INSERT INTO api_key_usage (api_key_id, method, path, status) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(key_id)
+    .bind(&method)
+    .bind(&path)
+    .bind(status)
+    .execute(&pool)
+    .await
+    {
+        println!("⚠️  Gagal mencatat pemakaian API key: {}", e);
+    }
+
+    if let Err(e) = sqlx::query("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1")
+        .bind(key_id)
+        .execute(&pool)
+        .await
+    {
+        println!("⚠️  Gagal update last_used_at API key: {}", e);
+    }
+
+    response
+}
+
+// Pastikan API key punya scope yang dibutuhkan route (403 jika tidak)
+pub fn require_scope(client: &ApiClient, scope: &str) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    if client.has_scope(scope) {
+        return Ok(());
+    }
+
+    println!("⛔ API key {} ({}) missing scope {}", client.name, client.key_id, scope);
+    Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+        "error": format!("API key missing required scope: {}", scope)
+    }))))
+}
@@ -0,0 +1,28 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::auth::AuthUser;
+
+// Scope yang dibutuhkan sebuah route. Dipasang per route:
+// `post(handler).route_layer(middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope))`
+#[derive(Debug, Clone, Copy)]
+pub struct RequireScope(pub &'static str);
+
+// Middleware: tolak request jika access token tidak memuat scope yang dibutuhkan route.
+// AuthUser yang sudah diverifikasi disimpan ke extensions supaya handler tidak query ulang.
+pub async fn require_scope(
+    State(RequireScope(scope)): State<RequireScope>,
+    user: AuthUser,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if let Err(rejection) = user.require_scope(scope) {
+        return rejection.into_response();
+    }
+
+    req.extensions_mut().insert(user);
+    next.run(req).await
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// Stok aksesoris (add-on fisik: helm, jas hujan, holder HP) di satu cabang. `available` berkurang saat
+// motor dengan add-on diambil dan bertambah lagi saat dikembalikan.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessoryStock {
+    pub branch: String,
+    pub addon_code: String,
+    pub addon_name: String,
+    pub quantity: i32,
+    pub available: i32,
+    pub on_loan: i32,
+    pub low_stock_threshold: i32,
+    pub low_stock: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccessoryStockQuery {
+    pub branch: Option<String>,
+}
+
+// PUT /api/accessories/stock/:branch/:code: jumlah total yang dimiliki cabang (termasuk yang sedang
+// dipinjam customer)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetAccessoryStockRequest {
+    pub quantity: i32,
+    pub low_stock_threshold: Option<i32>,
+}
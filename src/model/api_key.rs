@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// API key untuk partner / mesin (travel agent, dll), terpisah dari token user
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+// Request untuk membuat API key baru
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+// Scope yang bisa diberikan ke API key
+pub const API_KEY_SCOPES: &[&str] = &["motors:read", "orders:read", "telemetry:write"];
+
+// Client yang terautentikasi lewat header X-Api-Key (disisipkan ke request extensions)
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    pub key_id: Uuid,
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+impl ApiClient {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Satu baris audit log autentikasi (tabel auth_events)
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AuthEventRecord {
+    pub id: Uuid,
+    pub event: String,
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+// Filter untuk GET /api/admin/auth-events
+#[derive(Debug, Deserialize)]
+pub struct AuthEventQuery {
+    pub user_id: Option<Uuid>,
+    pub event: Option<String>,
+    pub ip: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
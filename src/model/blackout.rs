@@ -0,0 +1,38 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Periode tutup booking (libur nasional, servis armada, ...). Berlaku untuk satu motor, satu cabang,
+// atau semua cabang jika branch dan motor_id sama-sama kosong.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackoutPeriod {
+    pub id: Uuid,
+    pub branch: Option<String>,
+    pub motor_id: Option<i32>,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub reason: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBlackoutRequest {
+    pub branch: Option<String>,
+    pub motor_id: Option<i32>,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: NaiveDateTime,
+    pub reason: String,
+}
+
+// Filter daftar blackout: periode yang bersinggungan dengan rentang tanggal from..to
+#[derive(Debug, Deserialize)]
+pub struct BlackoutQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub branch: Option<String>,
+    pub motor_id: Option<i32>,
+}
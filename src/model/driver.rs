@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Sopir yang bisa disewa bersama motor, tarifnya per hari
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub id: Uuid,
+    pub name: String,
+    pub phone: String,
+    pub daily_rate: i32,
+    pub branch: Option<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDriverRequest {
+    pub name: String,
+    pub phone: String,
+    pub daily_rate: i32,
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDriverRequest {
+    pub name: Option<String>,
+    pub phone: Option<String>,
+    pub daily_rate: Option<i32>,
+    pub branch: Option<String>,
+    pub active: Option<bool>,
+}
+
+// Admin / staff menugaskan sopir ke order
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignDriverRequest {
+    pub driver_id: Uuid,
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Area operasional cabang berbentuk lingkaran (titik pusat + radius). Unit dianggap di dalam area
+// jika berada di salah satu zona aktif cabangnya; cabang tanpa zona tidak dicek.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct GeofenceZone {
+    pub id: Uuid,
+    pub branch: String,
+    pub name: String,
+    pub center_latitude: f64,
+    pub center_longitude: f64,
+    pub radius_m: i32,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGeofenceZoneRequest {
+    pub branch: String,
+    pub name: String,
+    pub center_latitude: f64,
+    pub center_longitude: f64,
+    pub radius_m: i32,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGeofenceZoneRequest {
+    pub name: Option<String>,
+    pub center_latitude: Option<f64>,
+    pub center_longitude: Option<f64>,
+    pub radius_m: Option<i32>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeofenceZoneQuery {
+    pub branch: Option<String>,
+    pub include_inactive: Option<bool>,
+}
+
+// out_of_zone = unit keluar dari area cabangnya, moving_without_order = unit bergerak padahal
+// tidak sedang disewa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeofenceAlertKind {
+    OutOfZone,
+    MovingWithoutOrder,
+}
+
+impl GeofenceAlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GeofenceAlertKind::OutOfZone => "out_of_zone",
+            GeofenceAlertKind::MovingWithoutOrder => "moving_without_order",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct GeofenceAlert {
+    pub id: Uuid,
+    pub unit_id: Uuid,
+    pub motor_id: i32,
+    pub plate_number: String,
+    pub branch: Option<String>,
+    pub kind: String,
+    pub order_number: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub speed_kmh: Option<f32>,
+    pub recorded_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<Uuid>,
+    pub resolution_note: Option<String>,
+}
+
+// GET /api/geofences/alerts: default hanya alert yang belum ditangani
+#[derive(Debug, Deserialize)]
+pub struct GeofenceAlertQuery {
+    pub branch: Option<String>,
+    pub kind: Option<GeofenceAlertKind>,
+    pub include_resolved: Option<bool>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveGeofenceAlertRequest {
+    pub note: Option<String>,
+}
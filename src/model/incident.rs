@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Tingkat keparahan kerusakan / kecelakaan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentSeverity {
+    Minor,
+    Moderate,
+    Major,
+}
+
+impl IncidentSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentSeverity::Minor => "minor",
+            IncidentSeverity::Moderate => "moderate",
+            IncidentSeverity::Major => "major",
+        }
+    }
+}
+
+// Status penanganan insiden: open → in_progress → resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    Open,
+    InProgress,
+    Resolved,
+}
+
+impl IncidentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentStatus::Open => "open",
+            IncidentStatus::InProgress => "in_progress",
+            IncidentStatus::Resolved => "resolved",
+        }
+    }
+}
+
+impl std::str::FromStr for IncidentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(IncidentStatus::Open),
+            "in_progress" => Ok(IncidentStatus::InProgress),
+            "resolved" => Ok(IncidentStatus::Resolved),
+            other => Err(format!("Unknown incident status: {}", other)),
+        }
+    }
+}
+
+// Laporan kerusakan / kecelakaan pada order dan unit motornya
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Incident {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub order_number: String,
+    pub motor_id: Option<i32>,
+    pub motor_name: Option<String>,
+    pub branch: String,
+    pub severity: String,
+    pub description: String,
+    pub photos: Vec<String>, // URL foto
+    pub estimated_cost: Option<i64>,
+    pub status: String,
+    pub resolution_notes: Option<String>,
+    pub reported_by: Option<Uuid>,
+    pub reported_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateIncidentRequest {
+    pub severity: IncidentSeverity,
+    pub description: String,
+    #[serde(default)]
+    pub photos: Vec<String>,
+    pub estimated_cost: Option<i64>,
+}
+
+// Update penanganan insiden oleh staff; field yang tidak dikirim tidak diubah
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIncidentRequest {
+    pub status: Option<IncidentStatus>,
+    pub severity: Option<IncidentSeverity>,
+    pub estimated_cost: Option<i64>,
+    pub resolution_notes: Option<String>,
+}
+
+// Query string list insiden admin (default: hanya yang belum selesai)
+#[derive(Debug, Deserialize)]
+pub struct IncidentQuery {
+    pub status: Option<String>, // open, in_progress, resolved, atau "all"
+    pub branch: Option<String>,
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Jenis perawatan unit motor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceKind {
+    OilChange,
+    Tire,
+    Brake,
+    Service, // servis berkala / tune up
+    Other,
+}
+
+impl MaintenanceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceKind::OilChange => "oil_change",
+            MaintenanceKind::Tire => "tire",
+            MaintenanceKind::Brake => "brake",
+            MaintenanceKind::Service => "service",
+            MaintenanceKind::Other => "other",
+        }
+    }
+}
+
+// scheduled / in_progress = unit tidak bisa dibooking selama jendela perawatan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceStatus {
+    Scheduled,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl MaintenanceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceStatus::Scheduled => "scheduled",
+            MaintenanceStatus::InProgress => "in_progress",
+            MaintenanceStatus::Completed => "completed",
+            MaintenanceStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+// Satu jadwal / catatan perawatan unit. Catatan servis yang sudah lewat tidak punya jendela waktu.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceRecord {
+    pub id: Uuid,
+    pub unit_id: Uuid,
+    pub plate_number: String,
+    pub kind: String,
+    pub status: String,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub performed_at: Option<NaiveDateTime>,
+    pub odometer_km: Option<i32>,
+    pub cost: Option<i64>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Jadwalkan perawatan (startsAt + endsAt) atau catat servis yang sudah dilakukan (performedAt)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMaintenanceRequest {
+    pub kind: MaintenanceKind,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub performed_at: Option<NaiveDateTime>,
+    pub odometer_km: Option<i32>,
+    pub cost: Option<i64>,
+    pub notes: Option<String>,
+}
+
+// Ubah status (mulai / selesai / batal) dan isi hasil servis
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMaintenanceRequest {
+    pub status: Option<MaintenanceStatus>,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub performed_at: Option<NaiveDateTime>,
+    pub odometer_km: Option<i32>,
+    pub cost: Option<i64>,
+    pub notes: Option<String>,
+}
+
+// Jadwal perawatan mendatang lintas unit (default 14 hari ke depan)
+#[derive(Debug, Deserialize)]
+pub struct UpcomingMaintenanceQuery {
+    pub branch: Option<String>,
+    pub days: Option<i64>,
+}
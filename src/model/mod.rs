@@ -2,3 +2,19 @@ pub mod user;
 pub mod orders;
 pub mod motor;
 pub mod profils;
+pub mod api_key;
+pub mod auth_event;
+pub mod incident;
+pub mod webhook;
+
+pub mod driver;
+pub mod blackout;
+pub mod terms;
+pub mod branch;
+pub mod review;
+pub mod maintenance;
+pub mod transfer;
+pub mod pricing_rule;
+pub mod telemetry;
+pub mod geofence;
+pub mod accessory;
\ No newline at end of file
@@ -1,6 +1,11 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use uuid::Uuid;
 
+use crate::pricing::format_rupiah;
+
+// Model motor di katalog (nama, tipe, tarif); kendaraan fisiknya ada di MotorUnit
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Motor {
     pub motor_id: i32,
@@ -8,10 +13,242 @@ pub struct Motor {
     pub motor_name: String,
     pub motor_type: String,
     pub price_per_day: i32,
+    pub price_per_week: Option<i32>,
+    pub price_per_month: Option<i32>,
     pub description: Option<String>,
     pub image_url: Option<String>,
+    // URL thumbnail webp {card, detail, zoom}; null selama masih diproses atau untuk URL gambar eksternal
+    pub image_thumbnails: Option<serde_json::Value>,
+    // Turunan dari status (status = 'active'), dipertahankan untuk client lama
     pub available: Option<bool>,
+    pub status: String,
+    pub branch: Option<String>,
+    // Ringkasan ulasan, dijaga trigger di order_reviews
+    pub rating_avg: Option<f64>,
+    pub rating_count: i32,
+    // Spesifikasi: kapasitas mesin (cc), transmisi, tangki bensin (liter), tinggi jok (mm), tahun
+    pub engine_cc: Option<i32>,
+    pub transmission: Option<String>,
+    pub fuel_capacity: Option<f64>,
+    pub seat_height: Option<i32>,
+    pub year: Option<i32>,
+}
+
+// Motor di list motor beserta stok per cabang: jumlah unit dan yang masih bisa dibooking
+#[derive(Debug, Clone, Serialize)]
+pub struct MotorWithInventory {
+    #[serde(flatten)]
+    pub motor: Motor,
+    pub quantity: i64,
+    pub available_count: i64,
+    pub inventory: Vec<BranchInventory>,
+}
+
+// Stok satu model motor di satu cabang, contoh: 3 dari 5 unit tersedia
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchInventory {
+    pub branch: Option<String>,
+    pub quantity: i64,
+    pub available_count: i64,
+}
+
+// Jenis transmisi: matic, bebek (semi otomatis), atau kopling (manual)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transmission {
+    Matic,
+    SemiAutomatic,
+    Manual,
+}
+
+impl Transmission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transmission::Matic => "matic",
+            Transmission::SemiAutomatic => "semi_automatic",
+            Transmission::Manual => "manual",
+        }
+    }
+}
+
+// Status motor: active (bisa dibooking), maintenance (sedang diperbaiki), reserved (disisihkan,
+// misalnya untuk event) atau retired (tidak disewakan lagi)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotorStatus {
+    Active,
+    Maintenance,
+    Reserved,
+    Retired,
+}
+
+impl MotorStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MotorStatus::Active => "active",
+            MotorStatus::Maintenance => "maintenance",
+            MotorStatus::Reserved => "reserved",
+            MotorStatus::Retired => "retired",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(MotorStatus::Active),
+            "maintenance" => Some(MotorStatus::Maintenance),
+            "reserved" => Some(MotorStatus::Reserved),
+            "retired" => Some(MotorStatus::Retired),
+            _ => None,
+        }
+    }
+
+    // Perpindahan status yang diizinkan; retired adalah status akhir
+    pub fn can_transition_to(&self, next: MotorStatus) -> bool {
+        match (self, next) {
+            (MotorStatus::Retired, _) => false,
+            (current, next) => *current != next,
+        }
+    }
+}
+
+// Status unit motor: siap disewa, sedang servis, atau sudah tidak dipakai lagi
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotorUnitStatus {
+    Available,
+    Maintenance,
+    Retired,
+}
+
+impl MotorUnitStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MotorUnitStatus::Available => "available",
+            MotorUnitStatus::Maintenance => "maintenance",
+            MotorUnitStatus::Retired => "retired",
+        }
+    }
+}
+
+// Unit fisik dari satu model motor, dibedakan dari plat nomornya
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MotorUnit {
+    pub id: Uuid,
+    pub motor_id: i32,
+    pub plate_number: String,
+    pub vin: Option<String>,
+    pub year: Option<i32>,
+    pub branch: Option<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMotorUnitRequest {
+    pub plate_number: String,
+    pub vin: Option<String>,
+    pub year: Option<i32>,
+    pub branch: Option<String>,
+    pub status: Option<MotorUnitStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMotorUnitRequest {
+    pub plate_number: Option<String>,
+    pub vin: Option<String>,
+    pub year: Option<i32>,
+    pub branch: Option<String>,
+    pub status: Option<MotorUnitStatus>,
+}
+
+// Format harga harian ke teks seperti yang ditampilkan di frontend, contoh: "Rp 50.000/hari".
+// Harga disimpan sebagai angka rupiah; teks ini hanya dibuat saat menyusun response.
+pub fn format_price_per_day(price_per_day: i64) -> String {
+    format!("{}/hari", format_rupiah(price_per_day.max(0)))
+}
+
+// Ulasan customer untuk motor (dari order yang sudah selesai)
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MotorReview {
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub reviewer: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// Detail motor beserta ringkasan rating dan ulasan terbaru
+#[derive(Debug, Serialize)]
+pub struct MotorDetailResponse {
+    #[serde(flatten)]
+    pub motor: Motor,
+    pub rating_average: Option<f64>,
+    pub review_count: i64,
+    pub reviews: Vec<MotorReview>,
+}
+
+// Motor favorit customer beserta waktu ditandai
+#[derive(Debug, Serialize)]
+pub struct FavoriteMotor {
+    #[serde(flatten)]
+    pub motor: Motor,
+    pub favorited_at: DateTime<Utc>,
+}
+
+// Statistik pemakaian satu motor dalam periode laporan. Booking & pendapatan dihitung dari order
+// yang diambil di periode itu; utilisasi = jam tersewa / (jam periode x jumlah unit).
+#[derive(Debug, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MotorStats {
+    pub motor_id: i32,
+    pub motor_name: String,
+    pub motor_type: String,
+    pub branch: Option<String>,
+    pub fleet_size: i64,
+    pub bookings: i64,
+    pub revenue: i64,
+    pub booked_hours: f64,
+    pub utilization_percent: f64,
+}
+
+// Periode laporan statistik motor (default 30 hari terakhir sampai hari ini)
+#[derive(Debug, Deserialize)]
+pub struct MotorStatsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
     pub branch: Option<String>,
+    // revenue (default), bookings, utilization
+    pub sort: Option<String>,
+}
+
+// Nominal deposit jaminan per tipe motor
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MotorTypeDeposit {
+    pub motor_type: String,
+    pub amount: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetDepositRequest {
+    pub amount: i32,
+}
+
+// Aturan lama sewa per tipe motor (hari); kosong = ikut aturan global
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RentalDurationRule {
+    pub motor_type: String,
+    pub min_days: Option<i32>,
+    pub max_days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetDurationRuleRequest {
+    pub min_days: Option<i32>,
+    pub max_days: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,22 +257,49 @@ pub struct CreateMotorRequest {
     pub motor_name: String,
     pub motor_type: String,
     pub price_per_day: i32,
+    pub price_per_week: Option<i32>,
+    pub price_per_month: Option<i32>,
     pub description: Option<String>,
     pub image_url: Option<String>,
+    // Lama: available=false sama dengan status maintenance. status lebih diutamakan jika keduanya diisi.
     pub available: Option<bool>,
+    pub status: Option<MotorStatus>,
     pub branch: Option<String>,
+    pub engine_cc: Option<i32>,
+    pub transmission: Option<Transmission>,
+    pub fuel_capacity: Option<f64>,
+    pub seat_height: Option<i32>,
+    pub year: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+impl CreateMotorRequest {
+    // Status awal motor baru
+    pub fn initial_status(&self) -> MotorStatus {
+        match (self.status, self.available) {
+            (Some(status), _) => status,
+            (None, Some(false)) => MotorStatus::Maintenance,
+            (None, _) => MotorStatus::Active,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct UpdateMotorRequest {
     pub motor_slug: Option<String>,
     pub motor_name: Option<String>,
     pub motor_type: Option<String>,
     pub price_per_day: Option<i32>,
+    pub price_per_week: Option<i32>,
+    pub price_per_month: Option<i32>,
     pub description: Option<String>,
     pub image_url: Option<String>,
     pub available: Option<bool>,
     pub branch: Option<String>,
+    pub engine_cc: Option<i32>,
+    pub transmission: Option<Transmission>,
+    pub fuel_capacity: Option<f64>,
+    pub seat_height: Option<i32>,
+    pub year: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -44,11 +308,72 @@ pub struct MotorQuery {
     pub limit: Option<i32>,
     pub motor_type: Option<String>,
     pub available_only: Option<bool>,
+    // Rentang harga sewa harian (Rp) dan cabang
+    pub min_price: Option<i32>,
+    pub max_price: Option<i32>,
+    pub branch: Option<String>,
+    // Urutan: default motor_id (atau relevansi jika ada search), price_asc, price_desc, newest, rating
+    pub sort: Option<String>,
+    // Pencarian teks bebas di nama, tipe, deskripsi dan cabang, contoh: "nmax malang"
+    pub search: Option<String>,
+    // Hanya motor yang masih bisa dibooking di rentang tanggal ini (keduanya wajib diisi bersamaan)
+    pub available_from: Option<NaiveDate>,
+    pub available_to: Option<NaiveDate>,
+    // Filter spesifikasi, contoh: transmission=matic&min_cc=150
+    pub transmission: Option<Transmission>,
+    pub min_cc: Option<i32>,
+    pub max_cc: Option<i32>,
+    pub status: Option<MotorStatus>,
+}
+
+// PATCH /api/motors/:id/status
+#[derive(Debug, Deserialize)]
+pub struct UpdateMotorStatusRequest {
+    pub status: MotorStatus,
+}
+
+// POST /api/motors/import?dry_run=true hanya memvalidasi CSV tanpa menyimpan
+#[derive(Debug, Deserialize)]
+pub struct MotorImportQuery {
+    pub dry_run: Option<bool>,
+}
+
+// Hasil import satu baris CSV (row = nomor baris di file, header = baris 1)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MotorImportRow {
+    pub row: usize,
+    pub motor_slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub motor_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// GET /api/motors/:id/similar (default 4 motor)
+#[derive(Debug, Deserialize)]
+pub struct SimilarMotorQuery {
+    pub limit: Option<i64>,
+}
+
+// Rentang tanggal untuk cek ketersediaan motor (default: hari ini sampai 30 hari ke depan)
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+// Periode motor tidak bisa dibooking: sudah dibooking, atau blackout (dengan alasannya)
+#[derive(Debug, Serialize, FromRow)]
+pub struct UnavailablePeriod {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct MotorListResponse {
-    pub motors: Vec<Motor>,
+    pub motors: Vec<MotorWithInventory>,
     pub total: i64,
     pub page: i32,
     pub limit: i32,
@@ -72,10 +397,21 @@ impl Motor {
             motor_name,
             motor_type,
             price_per_day,
+            price_per_week: None,
+            price_per_month: None,
             description,
             image_url,
+            image_thumbnails: None,
+            status: if available == Some(false) { MotorStatus::Maintenance } else { MotorStatus::Active }.as_str().to_string(),
             available,
             branch,
+            rating_avg: None,
+            rating_count: 0,
+            engine_cc: None,
+            transmission: None,
+            fuel_capacity: None,
+            seat_height: None,
+            year: None,
         }
     }
 
@@ -1,69 +1,635 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
-use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::model::user::Role;
 
 // Model utama untuk Order (sesuai dengan database)
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Order {
     pub id: Uuid,
+    pub order_number: String,              // SMT-YYYY-NNNNNN, unik di database
     pub user_id: Uuid,
-    pub motor_id: i32,
+    pub motor_id: Option<i32>,             // FK ke motors (NULL untuk order lama yang belum ter-resolve)
     
     // Data peminjaman
     pub tanggal_peminjaman: NaiveDate,     // pickup_date
     pub jam_peminjaman: NaiveTime,         // pickup_time
-    pub alamat_pengantaran: String,        // pickup_address
+    pub alamat_pengantaran: Option<String>, // pickup_address (NULL untuk ambil di cabang)
     
     // Data pengembalian
     pub tanggal_pengembalian: NaiveDate,   // return_date
     pub jam_pengembalian: NaiveTime,       // return_time
-    pub alamat_pengembalian: String,       // return_address
+    pub alamat_pengembalian: Option<String>, // return_address (NULL untuk kembali di cabang)
     
     // Data booking
+    pub fulfillment_mode: String,          // pickup / delivery
     pub pilih_cabang: String,              // branch
     pub pilih_motor: String,               // motor_name
-    pub motor_price: String,               // harga motor
+    pub motor_price: i64,                  // tarif harian (Rp), jumlah semua motor di order
     pub status: String,
+    pub hold_expires_at: Option<DateTime<Utc>>, // hanya untuk status held
+
+    // Harga sewa (NULL untuk order lama sebelum ada pricing engine)
+    pub rental_days: Option<i32>,
+    pub total_price: Option<i64>,
+    pub price_breakdown: Option<serde_json::Value>, // PriceBreakdown: tier yang dipakai + subtotal
+    pub promo_code: Option<String>,
+
+    // Deposit jaminan
+    pub deposit_amount: i32,
+    pub deposit_status: String,
+    pub deposit_deduction: Option<i32>,
+    pub deposit_deduction_reason: Option<String>,
+    pub deposit_held_at: Option<DateTime<Utc>>,
+    pub deposit_released_at: Option<DateTime<Utc>>,
     
     // Metadata
+    pub language: String,                  // bahasa email ke customer (id / en)
     pub tanggal_booking: NaiveDate,
     pub waktu_booking: NaiveTime,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-// Response untuk frontend (sesuai dengan struktur yang diminta)
+// Status order beserta alur yang diperbolehkan:
+// pending → confirmed → picked_up → returned → completed, dan cancelled sebelum motor diambil.
+// held = motor ditahan sementara (POST /api/orders/hold) sampai dikonfirmasi atau kedaluwarsa.
+// overdue = motor belum dikembalikan lama setelah jadwal kembali (ditandai job eskalasi / staff).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Held,
+    Pending,
+    Confirmed,
+    PickedUp,
+    Overdue,
+    Returned,
+    Completed,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Held => "held",
+            OrderStatus::Pending => "pending",
+            OrderStatus::Confirmed => "confirmed",
+            OrderStatus::PickedUp => "picked_up",
+            OrderStatus::Overdue => "overdue",
+            OrderStatus::Returned => "returned",
+            OrderStatus::Completed => "completed",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // Status berikutnya yang boleh dipilih dari status ini
+    pub fn allowed_next(&self) -> &'static [OrderStatus] {
+        match self {
+            OrderStatus::Held | OrderStatus::Pending => &[OrderStatus::Confirmed, OrderStatus::Cancelled],
+            OrderStatus::Confirmed => &[OrderStatus::PickedUp, OrderStatus::Cancelled],
+            OrderStatus::PickedUp => &[OrderStatus::Returned, OrderStatus::Overdue],
+            OrderStatus::Overdue => &[OrderStatus::Returned],
+            OrderStatus::Returned => &[OrderStatus::Completed],
+            OrderStatus::Completed | OrderStatus::Cancelled => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        self.allowed_next().contains(&next)
+    }
+
+    // Status berikutnya yang boleh dipilih oleh role ini. Customer hanya boleh membatalkan order yang
+    // belum dikonfirmasi; konfirmasi, pickup, return, dan seterusnya dilakukan staff.
+    pub fn allowed_next_for(&self, role: Role) -> &'static [OrderStatus] {
+        if role.is_staff() {
+            return self.allowed_next();
+        }
+        match self {
+            OrderStatus::Held | OrderStatus::Pending => &[OrderStatus::Cancelled],
+            _ => &[],
+        }
+    }
+}
+
+impl std::str::FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "held" => Ok(OrderStatus::Held),
+            "pending" => Ok(OrderStatus::Pending),
+            "confirmed" => Ok(OrderStatus::Confirmed),
+            "picked_up" => Ok(OrderStatus::PickedUp),
+            "overdue" => Ok(OrderStatus::Overdue),
+            "returned" => Ok(OrderStatus::Returned),
+            "completed" => Ok(OrderStatus::Completed),
+            "cancelled" => Ok(OrderStatus::Cancelled),
+            other => Err(format!("Unknown order status: {}", other)),
+        }
+    }
+}
+
+// Baris order untuk tampilan admin (join users + motors)
+#[derive(Debug, Clone, FromRow)]
+pub struct AdminOrderRow {
+    pub id: Uuid,
+    pub order_number: String,
+    pub user_id: Uuid,
+    pub username: String,
+    pub tanggal_peminjaman: NaiveDate,
+    pub jam_peminjaman: NaiveTime,
+    pub alamat_pengantaran: Option<String>,
+    pub tanggal_pengembalian: NaiveDate,
+    pub jam_pengembalian: NaiveTime,
+    pub alamat_pengembalian: Option<String>,
+    pub fulfillment_mode: String,
+    pub pilih_cabang: String,
+    pub pilih_motor: String,
+    pub motor_price: i64,
+    pub status: String,
+    pub tanggal_booking: NaiveDate,
+    pub waktu_booking: NaiveTime,
+    pub motor_id: Option<i32>,
+    pub motor_name: Option<String>,
+    pub motor_image: Option<String>,
+    pub motor_type: Option<String>,
+}
+
+// Status deposit jaminan: pending (belum diterima) → held (dipegang cabang) → released (dikembalikan)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositStatus {
+    Pending,
+    Held,
+    Released,
+}
+
+impl DepositStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DepositStatus::Pending => "pending",
+            DepositStatus::Held => "held",
+            DepositStatus::Released => "released",
+        }
+    }
+}
+
+impl std::str::FromStr for DepositStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(DepositStatus::Pending),
+            "held" => Ok(DepositStatus::Held),
+            "released" => Ok(DepositStatus::Released),
+            other => Err(format!("Unknown deposit status: {}", other)),
+        }
+    }
+}
+
+// Cara serah terima motor: ambil & kembalikan di cabang, atau diantar & dijemput ke alamat customer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FulfillmentMode {
+    Pickup,
+    Delivery,
+}
+
+impl FulfillmentMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FulfillmentMode::Pickup => "pickup",
+            FulfillmentMode::Delivery => "delivery",
+        }
+    }
+}
+
+impl std::str::FromStr for FulfillmentMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pickup" => Ok(FulfillmentMode::Pickup),
+            "delivery" => Ok(FulfillmentMode::Delivery),
+            other => Err(format!("Unknown fulfillment mode: {}", other)),
+        }
+    }
+}
+
+// Request staff saat menerima deposit (amount kosong = nominal deposit yang tercatat di order)
+#[derive(Debug, Deserialize)]
+pub struct HoldDepositRequest {
+    pub amount: Option<i32>,
+}
+
+// Request staff saat mengembalikan deposit, dengan potongan untuk kerusakan jika ada
+#[derive(Debug, Deserialize)]
+pub struct ReleaseDepositRequest {
+    pub deduction: Option<i32>,
+    pub reason: Option<String>,
+}
+
+// Request staff saat motor dikembalikan sebelum jadwal (returnedAt kosong = sekarang)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EarlyReturnRequest {
+    pub returned_at: Option<NaiveDateTime>,
+    pub reason: Option<String>,
+}
+
+// Refund yang menunggu diproses alur pembayaran (misalnya selisih harga karena kembali lebih awal)
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRefund {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub kind: String,
+    pub amount: i64,
+    pub original_total: i64,
+    pub recalculated_total: i64,
+    pub status: String,
+    pub reason: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Visibilitas catatan order: internal hanya untuk staff, shared juga terlihat oleh customer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteVisibility {
+    Internal,
+    Shared,
+}
+
+impl NoteVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteVisibility::Internal => "internal",
+            NoteVisibility::Shared => "shared",
+        }
+    }
+}
+
+// Catatan pada order (misalnya "customer telat 2 jam")
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderNote {
+    pub id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub author_username: Option<String>,
+    pub body: String,
+    pub visibility: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrderNoteRequest {
+    pub body: String,
+    pub visibility: Option<NoteVisibility>, // default: internal untuk staff, shared untuk customer
+}
+
+// Jenis serah terima motor: saat diambil customer (pickup) atau saat dikembalikan (return)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoverKind {
+    Pickup,
+    Return,
+}
+
+impl HandoverKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HandoverKind::Pickup => "pickup",
+            HandoverKind::Return => "return",
+        }
+    }
+}
+
+// Checklist kondisi motor saat serah terima, sebagai bukti jika ada sengketa kerusakan
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderHandover {
+    pub id: Uuid,
+    pub kind: String,
+    pub body_condition: String,
+    pub mirrors_ok: bool,
+    pub helmet_count: i32,
+    pub notes: Option<String>,
+    pub photos: Vec<String>, // URL foto motor
+    pub recorded_by: Option<Uuid>,
+    pub recorded_by_username: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateHandoverRequest {
+    pub kind: HandoverKind,
+    pub body_condition: String,
+    pub mirrors_ok: bool,
+    pub helmet_count: i32,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub photos: Vec<String>,
+}
+
+// Odometer (km) dan bensin (persen tangki) saat pickup / return, untuk biaya per km dan jadwal servis
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderReading {
+    pub kind: String,
+    pub odometer_km: i32,
+    pub fuel_percent: i32,
+    pub recorded_by: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReadingRequest {
+    pub kind: HandoverKind,
+    pub odometer_km: i32,
+    pub fuel_percent: i32,
+}
+
+// Ulasan customer setelah sewa selesai (rating 1-5)
+#[derive(Debug, Deserialize)]
+pub struct CreateReviewRequest {
+    pub rating: i32,
+    pub comment: Option<String>,
+}
+
+// Request ubah status banyak order sekaligus
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkStatusRequest {
+    pub order_ids: Vec<Uuid>,
+    pub status: OrderStatus,
+    pub note: Option<String>,
+}
+
+// Add-on sewa yang bisa dipilih customer (helm kedua, jas hujan, holder HP, ...)
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Addon {
+    pub code: String,
+    pub name: String,
+    pub price: i32,
+    pub per_day: bool, // true = harga dikali lama sewa
+}
+
+// Pilihan asuransi sewa (basic / standard / premium), biaya per motor per hari
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct InsuranceTier {
+    pub code: String,
+    pub name: String,
+    pub daily_fee: i32,
+    pub coverage: String,
+    pub active: bool,
+}
+
+// Admin membuat / mengubah pilihan asuransi
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertInsuranceTierRequest {
+    pub name: String,
+    pub daily_fee: i32,
+    pub coverage: String,
+    pub active: Option<bool>,
+}
+
+// Add-on yang dipilih di form booking
+#[derive(Debug, Deserialize)]
+pub struct AddonSelection {
+    pub code: String,
+    pub quantity: Option<i64>,
+}
+
+// Baris item tambahan pada order (add-on, ongkir, dll), harga disalin saat order dibuat
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItem {
+    pub code: String,
+    pub name: String,
+    pub quantity: i64,
+    pub unit_price: i64,
+    pub subtotal: i64,
+}
+
+// Satu motor di dalam order (order bisa berisi beberapa motor), harga disalin saat order dibuat
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderMotor {
+    pub motor_id: Option<i32>,
+    pub motor_name: String,
+    pub rental_subtotal: i64,
+    pub deposit_amount: i32,
+    // Unit (plat nomor) yang dipesan; kosong untuk motor yang belum dipecah menjadi unit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plate_number: Option<String>,
+}
+
+// Satu baris riwayat perubahan status order
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatusHistory {
+    pub id: i64,
+    pub old_status: Option<String>,        // NULL = order baru dibuat
+    pub new_status: String,
+    pub changed_by: Option<Uuid>,          // NULL = perubahan otomatis oleh sistem
+    pub changed_by_username: Option<String>,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+// Satu kejadian di timeline order (GET /api/orders/:id/timeline). kind: status / payment / note /
+// incident / notification; details berisi data asli sesuai jenisnya.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub kind: String,
+    pub at: DateTime<Utc>,
+    pub actor_id: Option<Uuid>,            // NULL = sistem / customer tanpa akun
+    pub actor_username: Option<String>,
+    pub summary: String,
+    pub details: serde_json::Value,
+}
+
+// Filter jenis kejadian timeline, dipisah koma (contoh: ?kinds=status,payment)
+#[derive(Debug, Deserialize)]
+pub struct TimelineQuery {
+    pub kinds: Option<String>,
+}
+
+// Response untuk frontend (key camelCase sama seperti response order lainnya)
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OrderResponse {
-    pub id: String,
-    
+    pub id: Uuid,
+    #[serde(rename = "user_id")]
+    pub user_id: Uuid,
+    pub username: String,
+    pub booking_id: String,
+
     // Data peminjaman
-    pub tanggal_peminjaman: String,        // pickup_date
-    pub jam_peminjaman: String,            // pickup_time
-    pub alamat_pengantaran: String,        // pickup_address
-    
+    pub tanggal_peminjaman: NaiveDate,     // pickup_date
+    pub jam_peminjaman: NaiveTime,         // pickup_time
+    pub alamat_pengantaran: Option<String>, // pickup_address (NULL untuk ambil di cabang)
+
     // Data pengembalian
-    pub tanggal_pengembalian: String,      // return_date
-    pub jam_pengembalian: String,          // return_time
-    pub alamat_pengembalian: String,       // return_address
-    
+    pub tanggal_pengembalian: NaiveDate,   // return_date
+    pub jam_pengembalian: NaiveTime,       // return_time
+    pub alamat_pengembalian: Option<String>, // return_address (NULL untuk kembali di cabang)
+
     // Data booking
+    pub fulfillment_mode: String,          // pickup / delivery
     pub pilih_cabang: String,              // branch
     pub pilih_motor: String,               // motor_name
-    pub motor_price: String,               // harga motor
+    pub motor_price: String,               // tarif harian terformat, contoh "Rp 50.000/hari"
+    pub motor_price_amount: i64,           // tarif harian dalam rupiah
+    pub motor: serde_json::Value,          // detail motor dari tabel motors (null untuk order lama)
     pub status: String,
-    
+
     // Metadata booking
-    pub tanggal_booking: String,           // booking date
-    pub waktu_booking: String,             // booking time
+    pub tanggal_booking: NaiveDate,        // booking date
+    pub waktu_booking: NaiveTime,          // booking time
+}
+
+// Query string untuk list order admin: pagination + filter
+#[derive(Debug, Deserialize)]
+pub struct OrderQuery {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    pub status: Option<String>,
+    pub branch: Option<String>,
+    pub motor_id: Option<i32>,
+    pub date_from: Option<NaiveDate>, // order yang periode sewanya bersinggungan dengan rentang tanggal ini
+    pub date_to: Option<NaiveDate>,
+    pub after: Option<String>, // cursor dari next_cursor halaman sebelumnya (menggantikan page)
+}
+
+// Query string list order milik user sendiri: cursor pagination opsional (tanpa limit = semua order)
+#[derive(Debug, Deserialize)]
+pub struct OrderCursorQuery {
+    pub after: Option<String>,
+    pub limit: Option<i32>,
+}
+
+// Posisi order terakhir di satu halaman list order (keyset pagination). Urutan list: tanggal_booking,
+// waktu_booking, id menurun. Dikirim ke client sebagai string base64 di next_cursor.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderCursor {
+    pub tanggal_booking: NaiveDate,
+    pub waktu_booking: NaiveTime,
+    pub id: Uuid,
+}
+
+impl OrderCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}|{}", self.tanggal_booking, self.waktu_booking, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let mut parts = raw.splitn(3, '|');
+        Some(Self {
+            tanggal_booking: parts.next()?.parse().ok()?,
+            waktu_booking: parts.next()?.parse().ok()?,
+            id: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
+// Query string export CSV: order yang tanggal pickup-nya di rentang from..to
+#[derive(Debug, Deserialize)]
+pub struct OrderExportQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub branch: Option<String>,
+}
+
+// Query string untuk pencarian order: kode booking, nama, nomor HP, atau email customer
+#[derive(Debug, Deserialize)]
+pub struct OrderSearchQuery {
+    pub q: String,
 }
 
 // List response dengan pagination
 #[derive(Debug, Serialize)]
 pub struct OrderListResponse {
+    #[serde(rename = "data")] // frontend admin membaca list order dari key "data"
     pub orders: Vec<OrderResponse>,
     pub total: i64,
     pub page: i32,
     pub limit: i32,
+    pub next_cursor: Option<String>, // NULL = tidak ada halaman berikutnya
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OrderStatus::*;
+
+    const ALL: [OrderStatus; 8] = [Held, Pending, Confirmed, PickedUp, Overdue, Returned, Completed, Cancelled];
+
+    #[test]
+    fn allowed_next_follows_rental_flow() {
+        let cases: [(OrderStatus, &[OrderStatus]); 8] = [
+            (Held, &[Confirmed, Cancelled]),
+            (Pending, &[Confirmed, Cancelled]),
+            (Confirmed, &[PickedUp, Cancelled]),
+            (PickedUp, &[Returned, Overdue]),
+            (Overdue, &[Returned]),
+            (Returned, &[Completed]),
+            (Completed, &[]),
+            (Cancelled, &[]),
+        ];
+        for (from, expected) in cases {
+            assert_eq!(from.allowed_next(), expected, "from {:?}", from);
+            for next in ALL {
+                assert_eq!(from.can_transition_to(next), expected.contains(&next), "{:?} -> {:?}", from, next);
+            }
+        }
+    }
+
+    #[test]
+    fn allowed_next_for_staff_and_admin_matches_allowed_next() {
+        for role in [Role::Admin, Role::Staff] {
+            for from in ALL {
+                assert_eq!(from.allowed_next_for(role), from.allowed_next(), "{:?} from {:?}", role, from);
+            }
+        }
+    }
+
+    #[test]
+    fn allowed_next_for_customer_only_cancels_unconfirmed_orders() {
+        let cases: [(OrderStatus, &[OrderStatus]); 8] = [
+            (Held, &[Cancelled]),
+            (Pending, &[Cancelled]),
+            (Confirmed, &[]),
+            (PickedUp, &[]),
+            (Overdue, &[]),
+            (Returned, &[]),
+            (Completed, &[]),
+            (Cancelled, &[]),
+        ];
+        for (from, expected) in cases {
+            assert_eq!(from.allowed_next_for(Role::Customer), expected, "from {:?}", from);
+        }
+    }
+
+    #[test]
+    fn order_status_round_trips_through_str() {
+        for status in ALL {
+            assert_eq!(status.as_str().parse::<OrderStatus>(), Ok(status));
+        }
+        assert!("unknown".parse::<OrderStatus>().is_err());
+    }
 }
@@ -0,0 +1,70 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// season = rentang tanggal (musim ramai / libur), weekend = Sabtu & Minggu (opsional dibatasi tanggal)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingRuleKind {
+    Season,
+    Weekend,
+}
+
+impl PricingRuleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PricingRuleKind::Season => "season",
+            PricingRuleKind::Weekend => "weekend",
+        }
+    }
+}
+
+// Aturan harga dinamis: tarif sewa per hari dikali multiplier_percent / 100 (120 = naik 20%,
+// 90 = diskon 10%). Cabang kosong = berlaku di semua cabang.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingRule {
+    pub id: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub starts_on: Option<NaiveDate>,
+    pub ends_on: Option<NaiveDate>,
+    pub branch: Option<String>,
+    pub multiplier_percent: i32,
+    pub priority: i32,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePricingRuleRequest {
+    pub name: String,
+    pub kind: PricingRuleKind,
+    pub starts_on: Option<NaiveDate>,
+    pub ends_on: Option<NaiveDate>,
+    pub branch: Option<String>,
+    pub multiplier_percent: i32,
+    pub priority: Option<i32>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePricingRuleRequest {
+    pub name: Option<String>,
+    pub starts_on: Option<NaiveDate>,
+    pub ends_on: Option<NaiveDate>,
+    pub branch: Option<String>,
+    pub multiplier_percent: Option<i32>,
+    pub priority: Option<i32>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PricingRuleQuery {
+    pub branch: Option<String>,
+    pub include_inactive: Option<bool>,
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Status moderasi ulasan: hanya ulasan approved yang tampil dan dihitung di rating motor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Pending,
+    Approved,
+    Hidden,
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::Hidden => "hidden",
+        }
+    }
+}
+
+// Ulasan untuk halaman moderasi admin (id ulasan = id order yang diulas)
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ModeratedReview {
+    pub order_id: Uuid,
+    pub motor_id: Option<i32>,
+    pub motor_name: Option<String>,
+    pub user_id: Uuid,
+    pub reviewer: String,
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub status: String,
+    pub moderation_note: Option<String>,
+    pub moderated_by: Option<Uuid>,
+    pub moderated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Ulasan motor dari customer; orderId opsional (default: sewa selesai terbaru yang belum diulas)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMotorReviewRequest {
+    pub order_id: Option<Uuid>,
+    pub rating: i32,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerateReviewRequest {
+    pub status: ReviewStatus,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewQuery {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewModerationQuery {
+    pub status: Option<ReviewStatus>,
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Satu titik GPS dari tracker yang terpasang di unit motor
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPoint {
+    pub unit_id: Uuid,
+    pub recorded_at: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub speed_kmh: Option<f32>,
+    pub heading: Option<f32>,       // derajat dari utara, 0-360
+    pub accuracy_m: Option<f32>,
+    pub battery_percent: Option<i16>,
+}
+
+// POST /api/telemetry: satu batch bisa berisi titik dari beberapa unit sekaligus
+#[derive(Debug, Deserialize)]
+pub struct TelemetryBatchRequest {
+    pub points: Vec<TelemetryPoint>,
+}
+
+// Titik yang ditolak, index = posisi di array points
+#[derive(Debug, Serialize)]
+pub struct TelemetryRejection {
+    pub index: usize,
+    pub error: String,
+}
+
+// Posisi terakhir dan status satu unit untuk peta armada (GET /api/admin/fleet/live).
+// Posisi null jika tracker unit belum pernah mengirim data.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetUnit {
+    pub unit_id: Uuid,
+    pub motor_id: i32,
+    pub motor_name: String,
+    pub plate_number: String,
+    pub branch: Option<String>,
+    pub unit_status: String,
+    // Booking yang sedang berjalan (motor sudah diambil customer)
+    pub order_number: Option<String>,
+    pub order_status: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub speed_kmh: Option<f32>,
+    pub heading: Option<f32>,
+    pub battery_percent: Option<i16>,
+    pub recorded_at: Option<DateTime<Utc>>,
+}
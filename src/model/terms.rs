@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Satu versi syarat & ketentuan sewa. Versi yang berlaku = yang published_at-nya paling baru
+// (dan sudah lewat); customer harus menyetujui versi itu saat booking.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TermsVersion {
+    pub version: String,
+    pub title: String,
+    pub content: String,
+    pub published_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTermsVersionRequest {
+    pub version: String,
+    pub title: String,
+    pub content: String,
+    pub published_at: Option<DateTime<Utc>>, // kosong = berlaku sekarang
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// scheduled = menunggu tanggal efektif, dijalankan job motor transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Scheduled,
+    Completed,
+    Cancelled,
+}
+
+impl TransferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferStatus::Scheduled => "scheduled",
+            TransferStatus::Completed => "completed",
+            TransferStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+// Riwayat perpindahan motor (beserta unit-unitnya) antar cabang
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MotorTransfer {
+    pub id: Uuid,
+    pub motor_id: i32,
+    pub from_branch: Option<String>,
+    pub to_branch: String,
+    pub effective_date: NaiveDate,
+    pub status: String,
+    pub units_moved: Option<i32>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+// Tanpa effectiveDate (atau tanggal hari ini / lewat) motor langsung dipindahkan
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTransferRequest {
+    pub to_branch: String,
+    pub effective_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
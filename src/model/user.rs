@@ -12,5 +12,176 @@ pub struct User {
     pub email: String,
     pub phone: String,
     pub password_hash: String,
+    pub role: String,
+    pub status: String,
+    pub branch_id: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    pub tokens_invalid_before: Option<DateTime<Utc>>,
+    pub phone_verified_at: Option<DateTime<Utc>>,
+    pub google_sub: Option<String>,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub is_guest: bool,
     pub created_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+// Sesi login (device) milik user
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+// Request untuk ganti password user yang sedang login
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+// Request admin untuk menempatkan staff di cabang tertentu (null = lepas dari cabang)
+#[derive(Debug, Deserialize)]
+pub struct AssignBranchRequest {
+    pub branch_id: Option<String>,
+}
+
+// Request admin untuk membatasi permission user (null = kembali ke scope bawaan role)
+#[derive(Debug, Deserialize)]
+pub struct AssignScopesRequest {
+    pub scopes: Option<Vec<String>>,
+}
+
+// Role user untuk access control
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Staff,
+    Customer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Staff => "staff",
+            Role::Customer => "customer",
+        }
+    }
+
+    // Admin dan staff boleh mengakses endpoint pengelolaan (orders semua user, motor, profil)
+    pub fn is_staff(&self) -> bool {
+        matches!(self, Role::Admin | Role::Staff)
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(Role::Admin),
+            "staff" => Ok(Role::Staff),
+            "customer" => Ok(Role::Customer),
+            other => Err(format!("Unknown role: {}", other)),
+        }
+    }
+}
+// Status akun: suspended / banned tidak bisa login maupun memakai token yang sudah ada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    Active,
+    Suspended,
+    Banned,
+}
+
+impl UserStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserStatus::Active => "active",
+            UserStatus::Suspended => "suspended",
+            UserStatus::Banned => "banned",
+        }
+    }
+}
+
+impl std::str::FromStr for UserStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(UserStatus::Active),
+            "suspended" => Ok(UserStatus::Suspended),
+            "banned" => Ok(UserStatus::Banned),
+            other => Err(format!("Unknown user status: {}", other)),
+        }
+    }
+}
+
+// Request admin untuk mengubah status akun user
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserStatusRequest {
+    pub status: UserStatus,
+    pub reason: Option<String>,
+}
+
+// Normalisasi nomor HP Indonesia ke format 62xxxxxxxxxx (tanpa +, spasi, atau strip).
+// Hasil None jika bukan nomor yang valid.
+pub fn normalize_phone(phone: &str) -> Option<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    let normalized = if let Some(rest) = digits.strip_prefix('0') {
+        format!("62{}", rest)
+    } else if digits.starts_with('8') {
+        format!("62{}", digits)
+    } else {
+        digits
+    };
+
+    if normalized.starts_with("62") && (10..=15).contains(&normalized.len()) {
+        Some(normalized)
+    } else {
+        None
+    }
+}
+
+// Validasi sederhana format email: satu '@', bagian lokal tidak kosong, domain punya titik
+pub fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+        && domain.split('.').count() >= 2
+        && domain.split('.').all(|part| !part.is_empty())
+}
+
+// Username 3-30 karakter: huruf, angka, titik, atau underscore
+pub fn is_valid_username(username: &str) -> bool {
+    (3..=30).contains(&username.chars().count())
+        && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+// Error validasi per field, dikirim ke frontend supaya bisa ditampilkan di bawah input
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub error: String,
+}
+
+impl FieldError {
+    pub fn new(field: &'static str, error: impl Into<String>) -> Self {
+        Self { field, error: error.into() }
+    }
+}
+
+// Ekspresi SQL untuk menormalisasi kolom phone dengan aturan yang sama seperti normalize_phone
+pub const NORMALIZED_PHONE_SQL: &str =
+    "regexp_replace(regexp_replace(phone, '[^0-9]', '', 'g'), '^0', '62')";
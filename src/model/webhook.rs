@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Event order yang bisa di-subscribe oleh sistem luar (akuntansi, CRM, ...)
+pub const ORDER_CREATED: &str = "order.created";
+pub const ORDER_CONFIRMED: &str = "order.confirmed";
+pub const ORDER_CANCELLED: &str = "order.cancelled";
+pub const ORDER_COMPLETED: &str = "order.completed";
+
+pub const WEBHOOK_EVENTS: &[&str] = &[ORDER_CREATED, ORDER_CONFIRMED, ORDER_CANCELLED, ORDER_COMPLETED];
+
+// Subscription webhook. Secret dipakai untuk tanda tangan HMAC dan hanya ditampilkan sekali.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Request untuk mendaftarkan webhook baru
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+}
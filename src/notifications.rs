@@ -0,0 +1,171 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::mailer::SharedMailer;
+use crate::pricing::format_rupiah;
+
+// Template email order. Bawaan ada di kode (bahasa Indonesia dan Inggris) dan bisa ditimpa per
+// bahasa lewat tabel email_templates tanpa deploy ulang. Placeholder: {{customerName}},
+// {{bookingCode}}, {{motor}}, {{pickup}}, {{return}}, {{branch}}, {{branchAddress}}, {{total}}.
+pub const ORDER_CREATED: &str = "order_created";
+pub const ORDER_CONFIRMED: &str = "order_confirmed";
+
+pub const DEFAULT_LANGUAGE: &str = "id";
+pub const SUPPORTED_LANGUAGES: &[&str] = &["id", "en"];
+
+fn builtin_template(key: &str, language: &str) -> Option<(&'static str, &'static str)> {
+    match (key, language) {
+        (ORDER_CREATED, "en") => Some((
+            "Booking {{bookingCode}} received",
+            "Hi {{customerName}},\n\nWe have received your booking {{bookingCode}}.\n\n\
+             Motor: {{motor}}\nPickup: {{pickup}}\nReturn: {{return}}\nBranch: {{branch}} ({{branchAddress}})\n\
+             Total: {{total}}\n\nWe will let you know once it is confirmed.\n\nThank you,\nSentor Sewa Motor",
+        )),
+        (ORDER_CREATED, _) => Some((
+            "Booking {{bookingCode}} diterima",
+            "Halo {{customerName}},\n\nBooking {{bookingCode}} sudah kami terima.\n\n\
+             Motor: {{motor}}\nAmbil: {{pickup}}\nKembali: {{return}}\nCabang: {{branch}} ({{branchAddress}})\n\
+             Total: {{total}}\n\nKami akan mengabari setelah booking dikonfirmasi.\n\nTerima kasih,\nSentor Sewa Motor",
+        )),
+        (ORDER_CONFIRMED, "en") => Some((
+            "Booking {{bookingCode}} confirmed",
+            "Hi {{customerName}},\n\nYour booking {{bookingCode}} is confirmed.\n\n\
+             Motor: {{motor}}\nPickup: {{pickup}}\nReturn: {{return}}\nBranch: {{branch}} ({{branchAddress}})\n\
+             Total: {{total}}\n\nSee you at pickup!\n\nThank you,\nSentor Sewa Motor",
+        )),
+        (ORDER_CONFIRMED, _) => Some((
+            "Booking {{bookingCode}} dikonfirmasi",
+            "Halo {{customerName}},\n\nBooking {{bookingCode}} sudah dikonfirmasi.\n\n\
+             Motor: {{motor}}\nAmbil: {{pickup}}\nKembali: {{return}}\nCabang: {{branch}} ({{branchAddress}})\n\
+             Total: {{total}}\n\nSampai jumpa saat pengambilan motor!\n\nTerima kasih,\nSentor Sewa Motor",
+        )),
+        _ => None,
+    }
+}
+
+// Bahasa email dari header Accept-Language (misalnya "en-US,en;q=0.9"), default bahasa Indonesia
+pub fn language_from_header(accept_language: Option<&str>) -> &'static str {
+    accept_language
+        .into_iter()
+        .flat_map(|header| header.split(','))
+        .map(|tag| tag.split(';').next().unwrap_or_default().trim())
+        .filter_map(|tag| tag.split('-').next())
+        .find_map(|primary| SUPPORTED_LANGUAGES.iter().find(|lang| lang.eq_ignore_ascii_case(primary)))
+        .copied()
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+fn render(template: &str, values: &[(&str, String)]) -> String {
+    values.iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{{{}}}}}", key), value)
+    })
+}
+
+// Data order untuk email
+#[derive(sqlx::FromRow)]
+struct OrderEmailRow {
+    order_number: String,
+    full_name: String,
+    email: String,
+    language: String,
+    pilih_motor: String,
+    pilih_cabang: String,
+    branch_address: Option<String>,
+    rental_start: chrono::NaiveDateTime,
+    rental_end: chrono::NaiveDateTime,
+    total_price: Option<i64>,
+}
+
+async fn send_order_email(pool: &PgPool, mailer: &SharedMailer, order_id: Uuid, key: &str) -> Result<(), String> {
+    let order: OrderEmailRow = sqlx::query_as(
+        "SELECT o.order_number, COALESCE(g.full_name, u.full_name) AS full_name, COALESCE(g.email, u.email) AS email,
+                o.language, o.pilih_motor, o.pilih_cabang, b.address AS branch_address,
+                o.tanggal_peminjaman + o.jam_peminjaman AS rental_start,
+                o.tanggal_pengembalian + o.jam_pengembalian AS rental_end,
+                o.total_price
+         FROM orders o
+         JOIN users u ON u.id = o.user_id
+         LEFT JOIN guest_checkouts g ON g.guest_user_id = u.id
+         LEFT JOIN branches b ON LOWER(TRIM(b.name)) = LOWER(TRIM(o.pilih_cabang))
+         WHERE o.id = $1"
+    )
+    .bind(order_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let custom: Option<(String, String)> = sqlx::query_as(
+        "SELECT subject, body FROM email_templates WHERE template_key = $1 AND language = $2"
+    )
+    .bind(key)
+    .bind(&order.language)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let (subject, body) = match custom {
+        Some(template) => template,
+        None => builtin_template(key, &order.language)
+            .map(|(subject, body)| (subject.to_string(), body.to_string()))
+            .ok_or_else(|| format!("Unknown email template: {}", key))?,
+    };
+
+    let values = [
+        ("customerName", order.full_name),
+        ("bookingCode", order.order_number),
+        ("motor", order.pilih_motor),
+        ("pickup", order.rental_start.format("%d-%m-%Y %H:%M").to_string()),
+        ("return", order.rental_end.format("%d-%m-%Y %H:%M").to_string()),
+        ("branchAddress", order.branch_address.unwrap_or_else(|| order.pilih_cabang.clone())),
+        ("branch", order.pilih_cabang),
+        ("total", order.total_price.map(format_rupiah).unwrap_or_else(|| "-".to_string())),
+    ];
+
+    let subject = render(&subject, &values);
+    let result = mailer.send(&order.email, &subject, &render(&body, &values)).await;
+    log_notification(pool, order_id, key, &order.email, &subject, &result).await;
+    result
+}
+
+// Catat notifikasi order (terkirim / gagal) ke order_notifications untuk timeline order.
+// Gagal mencatat tidak menggagalkan pengiriman.
+pub async fn log_notification(
+    pool: &PgPool,
+    order_id: Uuid,
+    template_key: &str,
+    recipient: &str,
+    subject: &str,
+    result: &Result<(), String>,
+) {
+    let (status, error) = match result {
+        Ok(()) => ("sent", None),
+        Err(e) => ("failed", Some(e.as_str())),
+    };
+
+    let logged = sqlx::query(
+        "INSERT INTO order_notifications (order_id, channel, template_key, recipient, subject, status, error)
+         VALUES ($1, 'email', $2, $3, $4, $5, $6)"
+    )
+    .bind(order_id)
+    .bind(template_key)
+    .bind(recipient)
+    .bind(subject)
+    .bind(status)
+    .bind(error)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = logged {
+        eprintln!("⚠️  Gagal mencatat notifikasi {} untuk order {}: {}", template_key, order_id, e);
+    }
+}
+
+// Kirim email order di background supaya response booking tidak menunggu provider email
+pub fn notify_order(pool: &PgPool, mailer: &SharedMailer, order_id: Uuid, key: &'static str) {
+    let (pool, mailer) = (pool.clone(), mailer.clone());
+    tokio::spawn(async move {
+        if let Err(e) = send_order_email(&pool, &mailer, order_id, key).await {
+            eprintln!("⚠️  Gagal kirim email {} untuk order {}: {}", key, order_id, e);
+        }
+    });
+}
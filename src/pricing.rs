@@ -0,0 +1,508 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::model::pricing_rule::{PricingRule, PricingRuleKind};
+
+// Tarif sewa satu motor: harian wajib, mingguan / bulanan opsional
+#[derive(Debug, Clone, Copy)]
+pub struct RateCard {
+    pub per_day: i32,
+    pub per_week: Option<i32>,
+    pub per_month: Option<i32>,
+}
+
+// Satu baris rincian harga, contoh: 2 x mingguan @ Rp 300.000
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceLine {
+    pub tier: String, // daily / weekly / monthly
+    pub quantity: i64,
+    pub unit_price: i64,
+    pub subtotal: i64,
+    // Motor yang disewa dengan tarif ini (order bisa berisi beberapa motor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motor_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motor_name: Option<String>,
+}
+
+// Biaya tambahan di luar sewa motor (add-on seperti helm kedua / jas hujan, ongkir, dll)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceItem {
+    pub code: String,
+    pub name: String,
+    pub quantity: i64,
+    pub unit_price: i64,
+    pub subtotal: i64,
+}
+
+// Rincian harga sewa yang disimpan di order dan ditampilkan ke customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceBreakdown {
+    pub rental_days: i64,
+    pub lines: Vec<PriceLine>,
+    pub rental_subtotal: i64,
+    pub items: Vec<PriceItem>,
+    pub total: i64,
+}
+
+impl PriceBreakdown {
+    // Tambahkan biaya tambahan dan perbarui total
+    pub fn add_item(&mut self, code: &str, name: &str, quantity: i64, unit_price: i64) {
+        let subtotal = unit_price * quantity;
+        self.items.push(PriceItem {
+            code: code.to_string(),
+            name: name.to_string(),
+            quantity,
+            unit_price,
+            subtotal,
+        });
+        self.total += subtotal;
+    }
+
+    // Hapus biaya tambahan dengan kode tertentu (jika ada) dan perbarui total
+    pub fn remove_item(&mut self, code: &str) {
+        let removed: i64 = self.items.iter().filter(|item| item.code == code).map(|item| item.subtotal).sum();
+        self.items.retain(|item| item.code != code);
+        self.total -= removed;
+    }
+
+    // Gabungkan harga sewa motor lain (hasil quote) ke rincian ini dan perbarui total
+    pub fn add_rental(&mut self, other: PriceBreakdown) {
+        self.rental_subtotal += other.rental_subtotal;
+        self.total += other.rental_subtotal;
+        self.lines.extend(other.lines);
+    }
+
+    // Tandai semua baris tarif sebagai milik motor tertentu
+    pub fn for_motor(mut self, motor_id: i32, motor_name: &str) -> Self {
+        for line in &mut self.lines {
+            line.motor_id = Some(motor_id);
+            line.motor_name = Some(motor_name.to_string());
+        }
+        self
+    }
+
+    // Subtotal sewa satu motor di order ini
+    pub fn motor_subtotal(&self, motor_id: i32) -> i64 {
+        self.lines.iter()
+            .filter(|line| line.motor_id == Some(motor_id))
+            .map(|line| line.subtotal)
+            .sum()
+    }
+}
+
+// Kilometer di luar jatah sewa (jatah = km per hari x lama sewa), tidak pernah negatif
+pub fn excess_km(mileage_km: i64, rental_days: i64, included_km_per_day: i64) -> i64 {
+    (mileage_km - included_km_per_day * rental_days.max(1)).max(0)
+}
+
+// Hitung ulang harga untuk lama sewa yang lebih pendek (motor dikembalikan lebih awal). Sewa tiap motor
+// dihitung ulang dengan tarif termurah untuk `days` hari, tapi tidak pernah lebih mahal dari yang sudah
+// dibayar untuk motor itu; motor yang tarifnya tidak ada di `rates` tetap dihitung seperti semula.
+// Item per hari (kode di `per_day_items`, misalnya asuransi / sopir) ikut dipotong sesuai lama sewa,
+// item lain (add-on, ongkir, promo) tidak berubah.
+pub fn recalculate_for_days(
+    original: &PriceBreakdown,
+    rates: &[(i32, RateCard)],
+    days: i64,
+    per_day_items: &[&str],
+) -> PriceBreakdown {
+    let days = days.clamp(1, original.rental_days.max(1));
+    let mut recalculated = PriceBreakdown {
+        rental_days: days,
+        lines: Vec::new(),
+        rental_subtotal: 0,
+        items: Vec::new(),
+        total: 0,
+    };
+
+    let mut motor_ids: Vec<Option<i32>> = Vec::new();
+    for line in &original.lines {
+        if !motor_ids.contains(&line.motor_id) {
+            motor_ids.push(line.motor_id);
+        }
+    }
+
+    for motor_id in motor_ids {
+        let charged: Vec<PriceLine> = original.lines.iter()
+            .filter(|line| line.motor_id == motor_id)
+            .cloned()
+            .collect();
+        let charged_subtotal: i64 = charged.iter().map(|line| line.subtotal).sum();
+
+        let requoted = motor_id
+            .and_then(|id| rates.iter().find(|(rate_id, _)| *rate_id == id))
+            .map(|&(id, rate)| {
+                let name = charged[0].motor_name.clone().unwrap_or_default();
+                quote(rate, days).for_motor(id, &name)
+            })
+            .filter(|requoted| requoted.rental_subtotal < charged_subtotal);
+
+        recalculated.add_rental(requoted.unwrap_or(PriceBreakdown {
+            rental_days: days,
+            lines: charged,
+            rental_subtotal: charged_subtotal,
+            items: Vec::new(),
+            total: charged_subtotal,
+        }));
+    }
+
+    let original_days = original.rental_days.max(1);
+    for item in &original.items {
+        let per_day = per_day_items.contains(&item.code.as_str()) && item.quantity % original_days == 0;
+        let quantity = if per_day { item.quantity / original_days * days } else { item.quantity };
+        recalculated.add_item(&item.code, &item.name, quantity, item.unit_price);
+    }
+
+    recalculated
+}
+
+// Kode item rincian harga untuk penyesuaian dari aturan harga dinamis
+pub const PRICING_RULE_ITEM_CODE: &str = "pricing_rule";
+
+// Aturan harga yang berlaku di satu tanggal. Aturan tidak ditumpuk: aturan khusus cabang mengalahkan
+// aturan semua cabang, lalu prioritas tertinggi, lalu multiplier terbesar.
+pub fn rule_for_date(rules: &[PricingRule], date: NaiveDate) -> Option<&PricingRule> {
+    let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+    rules.iter()
+        .filter(|rule| rule.active)
+        .filter(|rule| rule.starts_on.is_none_or(|start| date >= start) && rule.ends_on.is_none_or(|end| date <= end))
+        .filter(|rule| rule.kind != PricingRuleKind::Weekend.as_str() || weekend)
+        .max_by_key(|rule| (rule.branch.is_some(), rule.priority, rule.multiplier_percent))
+}
+
+// Terapkan aturan harga ke sewa motor: tiap hari sewa (mulai `start`) kena selisih tarif rata-rata
+// per hari sesuai aturan yang berlaku hari itu. Selisihnya masuk rincian sebagai satu item per aturan
+// (positif = tambahan musim ramai / weekend, negatif = diskon), sebelum add-on dan promo.
+pub fn apply_pricing_rules(breakdown: &mut PriceBreakdown, start: NaiveDate, rules: &[PricingRule]) {
+    let days = breakdown.rental_days.max(1);
+    let mut applied: Vec<(&PricingRule, i64)> = Vec::new();
+    for offset in 0..days {
+        let Some(rule) = rule_for_date(rules, start + chrono::Duration::days(offset)) else {
+            continue;
+        };
+        match applied.iter_mut().find(|(applied_rule, _)| applied_rule.id == rule.id) {
+            Some((_, count)) => *count += 1,
+            None => applied.push((rule, 1)),
+        }
+    }
+
+    for (rule, rule_days) in applied {
+        let percent = i64::from(rule.multiplier_percent) - 100;
+        let adjustment = breakdown.rental_subtotal * rule_days * percent / (100 * days);
+        if adjustment == 0 {
+            continue;
+        }
+        let name = format!("{} ({}{}%, {} hari)", rule.name, if percent > 0 { "+" } else { "" }, percent, rule_days);
+        breakdown.add_item(PRICING_RULE_ITEM_CODE, &name, 1, adjustment);
+    }
+}
+
+// Potongan promo: persen dari total, atau nominal tetap. Tidak pernah melebihi total.
+pub fn promo_discount(total: i64, percent_off: Option<i32>, amount_off: Option<i32>) -> i64 {
+    let discount = match (percent_off, amount_off) {
+        (Some(percent), _) => total * i64::from(percent.clamp(0, 100)) / 100,
+        (None, Some(amount)) => i64::from(amount.max(0)),
+        (None, None) => 0,
+    };
+    discount.min(total.max(0))
+}
+
+// Format nominal rupiah, contoh: 1234567 -> "Rp 1.234.567"
+pub fn format_rupiah(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(c);
+    }
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{}Rp {}", sign, grouped)
+}
+
+// Lama sewa dalam hari, dibulatkan ke atas (sewa 25 jam dihitung 2 hari), minimal 1 hari
+pub fn rental_days(start: NaiveDateTime, end: NaiveDateTime) -> i64 {
+    let minutes = (end - start).num_minutes().max(1);
+    (minutes + 24 * 60 - 1) / (24 * 60)
+}
+
+// Pilih kombinasi tarif harian / mingguan / bulanan termurah yang menutupi seluruh lama sewa.
+// Boleh melebihi lama sewa jika memang lebih murah (misalnya 6 hari dihitung 1 minggu).
+pub fn quote(rates: RateCard, days: i64) -> PriceBreakdown {
+    let days = days.max(1);
+    let tiers: Vec<(&'static str, i64, i64)> = [
+        Some(("daily", 1, rates.per_day)),
+        rates.per_week.map(|price| ("weekly", 7, price)),
+        rates.per_month.map(|price| ("monthly", 30, price)),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(tier, length, price)| (tier, length, i64::from(price.max(0))))
+    .collect();
+
+    // best[d] = (biaya termurah untuk menutupi d hari, tier terakhir yang dipakai)
+    let n = days as usize;
+    let mut best: Vec<(i64, usize)> = vec![(0, 0); n + 1];
+    for d in 1..=n {
+        best[d] = tiers
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, length, price))| (best[d.saturating_sub(length as usize)].0 + price, i))
+            .min_by_key(|&(cost, _)| cost)
+            .expect("daily tier always exists");
+    }
+
+    // Telusuri balik pilihan tier untuk membentuk rincian
+    let mut counts = vec![0i64; tiers.len()];
+    let mut d = n;
+    while d > 0 {
+        let tier = best[d].1;
+        counts[tier] += 1;
+        d = d.saturating_sub(tiers[tier].1 as usize);
+    }
+
+    let lines: Vec<PriceLine> = tiers
+        .iter()
+        .zip(counts)
+        .filter(|(_, quantity)| *quantity > 0)
+        .rev()
+        .map(|(&(tier, _, price), quantity)| PriceLine {
+            tier: tier.to_string(),
+            quantity,
+            unit_price: price,
+            subtotal: price * quantity,
+            motor_id: None,
+            motor_name: None,
+        })
+        .collect();
+
+    let rental_subtotal = lines.iter().map(|line| line.subtotal).sum();
+    PriceBreakdown {
+        rental_days: days,
+        lines,
+        rental_subtotal,
+        items: Vec::new(),
+        total: rental_subtotal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    const DAILY_ONLY: RateCard = RateCard { per_day: 100_000, per_week: None, per_month: None };
+    const WITH_WEEK: RateCard = RateCard { per_day: 100_000, per_week: Some(500_000), per_month: None };
+    const WITH_MONTH: RateCard = RateCard { per_day: 100_000, per_week: Some(500_000), per_month: Some(1_800_000) };
+
+    // Tanggal di bulan Maret 2026 (1 Maret = Minggu, 6 Maret = Jumat, 7 Maret = Sabtu)
+    fn march(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn rule(name: &str, kind: PricingRuleKind, multiplier_percent: i32) -> PricingRule {
+        PricingRule {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            kind: kind.as_str().to_string(),
+            starts_on: None,
+            ends_on: None,
+            branch: None,
+            multiplier_percent,
+            priority: 0,
+            active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn tiers(breakdown: &PriceBreakdown) -> Vec<(&str, i64)> {
+        breakdown.lines.iter().map(|line| (line.tier.as_str(), line.quantity)).collect()
+    }
+
+    #[test]
+    fn quote_six_days_is_priced_as_one_week() {
+        let breakdown = quote(WITH_WEEK, 6);
+        assert_eq!(tiers(&breakdown), vec![("weekly", 1)]);
+        assert_eq!(breakdown.rental_subtotal, 500_000);
+        assert_eq!(breakdown.total, 500_000);
+        assert_eq!(breakdown.rental_days, 6);
+    }
+
+    #[test]
+    fn quote_combines_tiers_cheapest_first() {
+        assert_eq!(quote(DAILY_ONLY, 6).rental_subtotal, 600_000);
+
+        let nine_days = quote(WITH_WEEK, 9);
+        assert_eq!(tiers(&nine_days), vec![("weekly", 1), ("daily", 2)]);
+        assert_eq!(nine_days.rental_subtotal, 700_000);
+
+        let thirty_days = quote(WITH_MONTH, 30);
+        assert_eq!(tiers(&thirty_days), vec![("monthly", 1)]);
+        assert_eq!(thirty_days.rental_subtotal, 1_800_000);
+    }
+
+    #[test]
+    fn quote_charges_at_least_one_day() {
+        let breakdown = quote(DAILY_ONLY, 0);
+        assert_eq!(breakdown.rental_days, 1);
+        assert_eq!(breakdown.rental_subtotal, 100_000);
+    }
+
+    #[test]
+    fn rental_days_rounds_partial_days_up() {
+        let start = march(1).and_hms_opt(9, 0, 0).unwrap();
+        let hours = |h| start + chrono::Duration::hours(h);
+        assert_eq!(rental_days(start, hours(24)), 1);
+        assert_eq!(rental_days(start, hours(25)), 2);
+        assert_eq!(rental_days(start, hours(48)), 2);
+        assert_eq!(rental_days(start, start + chrono::Duration::minutes(1)), 1);
+        assert_eq!(rental_days(start, start), 1);
+        assert_eq!(rental_days(start, hours(-5)), 1);
+    }
+
+    #[test]
+    fn recalculate_for_days_requotes_early_return_and_per_day_items() {
+        let mut original = quote(WITH_WEEK, 7).for_motor(1, "Honda Beat");
+        original.add_item("insurance", "Asuransi", 7, 10_000);
+        original.add_item("helmet", "Helm kedua", 1, 25_000);
+        assert_eq!(original.total, 595_000);
+
+        let recalculated = recalculate_for_days(&original, &[(1, WITH_WEEK)], 3, &["insurance"]);
+        assert_eq!(recalculated.rental_days, 3);
+        assert_eq!(tiers(&recalculated), vec![("daily", 3)]);
+        assert_eq!(recalculated.lines[0].motor_id, Some(1));
+        assert_eq!(recalculated.lines[0].motor_name.as_deref(), Some("Honda Beat"));
+        assert_eq!(recalculated.rental_subtotal, 300_000);
+
+        let item = |code: &str| recalculated.items.iter().find(|item| item.code == code).unwrap().quantity;
+        assert_eq!(item("insurance"), 3);
+        assert_eq!(item("helmet"), 1);
+        assert_eq!(recalculated.total, 300_000 + 30_000 + 25_000);
+    }
+
+    #[test]
+    fn recalculate_for_days_never_costs_more_than_charged() {
+        let original = quote(WITH_WEEK, 7).for_motor(1, "Honda Beat");
+
+        // Tanpa tarif mingguan, 6 hari harian (Rp 600.000) lebih mahal dari yang sudah dibayar
+        let recalculated = recalculate_for_days(&original, &[(1, DAILY_ONLY)], 6, &[]);
+        assert_eq!(tiers(&recalculated), vec![("weekly", 1)]);
+        assert_eq!(recalculated.rental_subtotal, 500_000);
+
+        // Motor yang tarifnya tidak diketahui tetap dihitung seperti semula
+        let recalculated = recalculate_for_days(&original, &[], 3, &[]);
+        assert_eq!(recalculated.rental_subtotal, 500_000);
+
+        // Lama sewa tidak pernah melebihi lama sewa awal
+        let recalculated = recalculate_for_days(&original, &[(1, WITH_WEEK)], 10, &[]);
+        assert_eq!(recalculated.rental_days, 7);
+        assert_eq!(recalculated.rental_subtotal, 500_000);
+    }
+
+    #[test]
+    fn rule_for_date_applies_weekend_rules_on_weekends_only() {
+        let rules = vec![rule("Akhir pekan", PricingRuleKind::Weekend, 120)];
+        assert!(rule_for_date(&rules, march(6)).is_none());
+        assert_eq!(rule_for_date(&rules, march(7)).unwrap().name, "Akhir pekan");
+        assert_eq!(rule_for_date(&rules, march(8)).unwrap().name, "Akhir pekan");
+    }
+
+    #[test]
+    fn rule_for_date_respects_date_range_and_active_flag() {
+        let season = PricingRule {
+            starts_on: Some(march(1)),
+            ends_on: Some(march(10)),
+            ..rule("Lebaran", PricingRuleKind::Season, 150)
+        };
+        let inactive = PricingRule { active: false, ..rule("Nonaktif", PricingRuleKind::Season, 200) };
+        let rules = vec![season, inactive];
+
+        assert!(rule_for_date(&rules, NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()).is_none());
+        assert_eq!(rule_for_date(&rules, march(1)).unwrap().name, "Lebaran");
+        assert_eq!(rule_for_date(&rules, march(10)).unwrap().name, "Lebaran");
+        assert!(rule_for_date(&rules, march(11)).is_none());
+    }
+
+    #[test]
+    fn rule_for_date_prefers_branch_then_priority_then_multiplier() {
+        let global_weekend = PricingRule { priority: 10, ..rule("Akhir pekan", PricingRuleKind::Weekend, 120) };
+        let global_season = rule("Musim ramai", PricingRuleKind::Season, 150);
+        let branch_season = PricingRule {
+            branch: Some("Cabang Test".to_string()),
+            ..rule("Musim ramai cabang", PricingRuleKind::Season, 110)
+        };
+
+        // Prioritas lebih tinggi menang walaupun multiplier lebih kecil
+        let rules = vec![global_season.clone(), global_weekend.clone()];
+        assert_eq!(rule_for_date(&rules, march(7)).unwrap().name, "Akhir pekan");
+        assert_eq!(rule_for_date(&rules, march(6)).unwrap().name, "Musim ramai");
+
+        // Aturan cabang mengalahkan aturan semua cabang walaupun prioritasnya lebih rendah
+        let rules = vec![global_weekend, global_season.clone(), branch_season];
+        assert_eq!(rule_for_date(&rules, march(7)).unwrap().name, "Musim ramai cabang");
+
+        // Prioritas sama: multiplier terbesar menang
+        let rules = vec![rule("Kecil", PricingRuleKind::Season, 105), global_season];
+        assert_eq!(rule_for_date(&rules, march(6)).unwrap().name, "Musim ramai");
+    }
+
+    #[test]
+    fn apply_pricing_rules_adds_one_item_per_rule() {
+        // Jumat - Minggu, weekend +20% hanya untuk Sabtu dan Minggu
+        let mut breakdown = quote(DAILY_ONLY, 3);
+        apply_pricing_rules(&mut breakdown, march(6), &[rule("Akhir pekan", PricingRuleKind::Weekend, 120)]);
+        assert_eq!(breakdown.items.len(), 1);
+        assert_eq!(breakdown.items[0].code, PRICING_RULE_ITEM_CODE);
+        assert_eq!(breakdown.items[0].name, "Akhir pekan (+20%, 2 hari)");
+        assert_eq!(breakdown.items[0].subtotal, 40_000);
+        assert_eq!(breakdown.total, 340_000);
+
+        let mut breakdown = quote(DAILY_ONLY, 3);
+        apply_pricing_rules(&mut breakdown, march(2), &[rule("Musim sepi", PricingRuleKind::Season, 90)]);
+        assert_eq!(breakdown.items[0].name, "Musim sepi (-10%, 3 hari)");
+        assert_eq!(breakdown.items[0].subtotal, -30_000);
+        assert_eq!(breakdown.total, 270_000);
+
+        // Tidak ada aturan yang berlaku: rincian tidak berubah
+        let mut breakdown = quote(DAILY_ONLY, 3);
+        apply_pricing_rules(&mut breakdown, march(2), &[rule("Akhir pekan", PricingRuleKind::Weekend, 120)]);
+        assert!(breakdown.items.is_empty());
+        assert_eq!(breakdown.total, 300_000);
+    }
+
+    #[test]
+    fn excess_km_is_never_negative() {
+        assert_eq!(excess_km(250, 2, 100), 50);
+        assert_eq!(excess_km(150, 2, 100), 0);
+        assert_eq!(excess_km(150, 0, 100), 50);
+    }
+
+    #[test]
+    fn promo_discount_is_capped_at_total() {
+        assert_eq!(promo_discount(200_000, Some(10), None), 20_000);
+        assert_eq!(promo_discount(200_000, Some(10), Some(50_000)), 20_000);
+        assert_eq!(promo_discount(200_000, None, Some(50_000)), 50_000);
+        assert_eq!(promo_discount(30_000, None, Some(50_000)), 30_000);
+        assert_eq!(promo_discount(200_000, Some(150), None), 200_000);
+        assert_eq!(promo_discount(200_000, None, Some(-5_000)), 0);
+        assert_eq!(promo_discount(200_000, None, None), 0);
+    }
+
+    #[test]
+    fn format_rupiah_groups_thousands() {
+        assert_eq!(format_rupiah(0), "Rp 0");
+        assert_eq!(format_rupiah(999), "Rp 999");
+        assert_eq!(format_rupiah(1_000), "Rp 1.000");
+        assert_eq!(format_rupiah(1_234_567), "Rp 1.234.567");
+        assert_eq!(format_rupiah(-500), "-Rp 500");
+        assert_eq!(format_rupiah(-1_234_567), "-Rp 1.234.567");
+        assert_eq!(format_rupiah(-100_000), "-Rp 100.000");
+    }
+}
@@ -0,0 +1,189 @@
+use axum::{
+    Router,
+    routing::{get, put},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use serde_json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::model::accessory::{AccessoryStock, AccessoryStockQuery, SetAccessoryStockRequest};
+use crate::routes::branches::find_branch;
+
+const ACCESSORY_STOCK_SELECT: &str = "SELECT s.branch, s.addon_code, a.name AS addon_name, s.quantity, s.available,
+            s.quantity - s.available AS on_loan, s.low_stock_threshold,
+            s.available <= s.low_stock_threshold AS low_stock, s.updated_at
+     FROM accessory_stock s JOIN addons a ON a.code = s.addon_code";
+
+pub fn accessory_router() -> Router {
+    Router::new()
+        .route("/api/accessories/stock", get(list_accessory_stock))                 // Admin / staff cabang
+        .route("/api/accessories/stock/:branch/:code", put(set_accessory_stock))    // Admin / staff cabang
+        .route("/api/accessories/low-stock", get(low_stock_report))                 // Admin
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Keluarkan aksesoris add-on order dari stok cabang saat motor diambil. Hanya add-on yang stoknya
+// dikelola di cabang itu yang dicatat; dipanggil sekali per order (pickup ulang tidak mengurangi lagi).
+pub(crate) async fn checkout_order_accessories(conn: &mut PgConnection, order_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "WITH wanted AS (
+             SELECT i.code, SUM(i.quantity)::int AS quantity
+             FROM order_items i JOIN addons a ON a.code = i.code
+             WHERE i.order_id = $1
+               AND NOT EXISTS (SELECT 1 FROM order_accessories oa WHERE oa.order_id = $1)
+             GROUP BY i.code
+         ), taken AS (
+             UPDATE accessory_stock s
+             SET available = s.available - w.quantity, updated_at = NOW()
+             FROM wanted w, orders o
+             WHERE o.id = $1 AND s.addon_code = w.code
+               AND LOWER(TRIM(s.branch)) = LOWER(TRIM(o.pilih_cabang))
+             RETURNING s.branch, s.addon_code, w.quantity
+         )
+         INSERT INTO order_accessories (order_id, branch, addon_code, quantity)
+         SELECT $1, branch, addon_code, quantity FROM taken"
+    )
+    .bind(order_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Kembalikan aksesoris yang dipinjam order ke stok cabang asalnya saat motor dikembalikan
+pub(crate) async fn return_order_accessories(conn: &mut PgConnection, order_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "WITH returned AS (
+             UPDATE order_accessories SET returned_at = NOW()
+             WHERE order_id = $1 AND returned_at IS NULL
+             RETURNING branch, addon_code, quantity
+         )
+         UPDATE accessory_stock s
+         SET available = s.available + r.quantity, updated_at = NOW()
+         FROM returned r
+         WHERE s.branch = r.branch AND s.addon_code = r.addon_code"
+    )
+    .bind(order_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Stok aksesoris per cabang; staff hanya melihat cabangnya
+async fn list_accessory_stock(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<AccessoryStockQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = match user.branch_scope()? {
+        Some(own_branch) => Some(own_branch),
+        None => params.branch.as_deref().map(str::trim).filter(|b| !b.is_empty()),
+    };
+
+    let stock: Vec<AccessoryStock> = sqlx::query_as(&format!(
+        "{} WHERE ($1::text IS NULL OR LOWER(TRIM(s.branch)) = LOWER(TRIM($1)))
+         ORDER BY s.branch, a.name",
+        ACCESSORY_STOCK_SELECT
+    ))
+    .bind(branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": stock
+    })))
+}
+
+// Atur jumlah total aksesoris di cabang (hasil stock opname / pembelian baru). Aksesoris yang sedang
+// dipinjam tetap dihitung dipinjam, jadi available = quantity baru - yang sedang dipinjam.
+async fn set_accessory_stock(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path((branch, code)): Path<(String, String)>,
+    Json(payload): Json<SetAccessoryStockRequest>,
+) -> Result<RespJson<AccessoryStock>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    user.require_branch(Some(branch.as_str()))?;
+
+    if payload.quantity < 0 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "quantity cannot be negative"}))));
+    }
+    if payload.low_stock_threshold.is_some_and(|threshold| threshold < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "lowStockThreshold cannot be negative"}))));
+    }
+
+    let branch = find_branch(&pool, &branch).await.map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Branch not found"}))))?;
+    let code = code.trim().to_lowercase();
+    let addon_exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM addons WHERE code = $1)")
+        .bind(&code)
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+    if !addon_exists {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": format!("Unknown addon: {}", code)}))));
+    }
+
+    sqlx::query(
+        "INSERT INTO accessory_stock (branch, addon_code, quantity, available, low_stock_threshold)
+         VALUES ($1, $2, $3, $3, COALESCE($4, 2))
+         ON CONFLICT (branch, addon_code) DO UPDATE
+         SET available = accessory_stock.available + (EXCLUDED.quantity - accessory_stock.quantity),
+             quantity = EXCLUDED.quantity,
+             low_stock_threshold = COALESCE($4, accessory_stock.low_stock_threshold),
+             updated_at = NOW()"
+    )
+    .bind(&branch.name)
+    .bind(&code)
+    .bind(payload.quantity)
+    .bind(payload.low_stock_threshold)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let stock: AccessoryStock = sqlx::query_as(&format!("{} WHERE s.branch = $1 AND s.addon_code = $2", ACCESSORY_STOCK_SELECT))
+        .bind(&branch.name)
+        .bind(&code)
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    println!("🪖 Accessory stock {} at {} set to {} ({} available) by {}", code, stock.branch, stock.quantity, stock.available, user.id);
+    Ok(RespJson(stock))
+}
+
+// Laporan aksesoris yang stok tersedianya sudah di bawah / sama dengan batas minimum, semua cabang,
+// yang paling kurang di atas
+async fn low_stock_report(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let stock: Vec<AccessoryStock> = sqlx::query_as(&format!(
+        "{} WHERE s.available <= s.low_stock_threshold
+         ORDER BY s.available - s.low_stock_threshold, s.branch, a.name",
+        ACCESSORY_STOCK_SELECT
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": stock,
+        "total": stock.len()
+    })))
+}
@@ -0,0 +1,816 @@
+use axum::{
+    Router,
+    routing::{get, post, put, delete},
+    extract::{ConnectInfo, Extension, Json, Path, Query},
+    http::{StatusCode, HeaderMap},
+    middleware,
+    response::Json as RespJson,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use serde_json;
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::auth::audit::{self, client_ip, AuthEvent};
+use crate::auth::scope::{self, default_scopes, ALL_SCOPES};
+use crate::auth::session::create_impersonation_session;
+use crate::auth::token::{create_impersonation_token, hash_token, random_token};
+use crate::config;
+use crate::middleware::scope::{require_scope, RequireScope};
+use crate::model::api_key::{ApiKey, CreateApiKeyRequest, API_KEY_SCOPES};
+use crate::model::auth_event::{AuthEventQuery, AuthEventRecord};
+use crate::model::motor::{MotorStats, MotorStatsQuery};
+use crate::model::webhook::{CreateWebhookRequest, WebhookSubscription, WEBHOOK_EVENTS};
+use crate::routes::fleet::fleet_live;
+use crate::model::user::{AssignBranchRequest, AssignScopesRequest, Role, UpdateUserStatusRequest, UserStatus};
+
+// Payload opsional untuk impersonation (alasan dicatat di audit)
+#[derive(Debug, Deserialize)]
+pub struct ImpersonateRequest {
+    pub reason: Option<String>,
+}
+
+// Create admin router (di-nest ke /api/admin)
+pub fn admin_router() -> Router {
+    Router::new()
+        .merge(user_management_router())
+        .route("/api-keys", post(create_api_key))       // POST /api/admin/api-keys
+        .route("/api-keys", get(list_api_keys))         // GET /api/admin/api-keys
+        .route("/api-keys/:id", delete(revoke_api_key)) // DELETE /api/admin/api-keys/{id}
+        .route("/impersonate/:user_id", post(impersonate_user)) // POST /api/admin/impersonate/{user_id}
+        .route("/auth-events", get(list_auth_events))  // GET /api/admin/auth-events
+        .route("/webhooks", post(create_webhook))       // POST /api/admin/webhooks
+        .route("/webhooks", get(list_webhooks))         // GET /api/admin/webhooks
+        .route("/webhooks/:id", delete(delete_webhook)) // DELETE /api/admin/webhooks/{id}
+        .route("/motors/stats", get(motor_stats))       // GET /api/admin/motors/stats
+        .route("/fleet/live", get(fleet_live))          // GET /api/admin/fleet/live (WebSocket)
+}
+
+// Route pengelolaan akun user, butuh scope users:write
+fn user_management_router() -> Router {
+    Router::new()
+        .route("/users/:id/unlock", post(unlock_user))  // POST /api/admin/users/{id}/unlock
+        .route("/users/:id/branch", put(assign_branch)) // PUT /api/admin/users/{id}/branch
+        .route("/users/:id/scopes", put(assign_scopes)) // PUT /api/admin/users/{id}/scopes
+        .route("/users/:id/status", put(update_user_status)) // PUT /api/admin/users/{id}/status
+        .route_layer(middleware::from_fn_with_state(RequireScope(scope::USERS_WRITE), require_scope))
+}
+
+// Buka kunci akun yang terkunci karena login gagal berulang
+async fn unlock_user(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let user_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid ID format"
+        })))
+    })?;
+
+    let result = sqlx::query("UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "User not found"
+        }))));
+    }
+
+    println!("🔓 User {} unlocked by admin {}", user_id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "User unlocked successfully"
+    })))
+}
+
+// Tempatkan staff di cabang tertentu; staff hanya bisa mengelola order & motor cabangnya
+async fn assign_branch(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<AssignBranchRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let user_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid ID format"
+        })))
+    })?;
+
+    let branch_id = request
+        .branch_id
+        .map(|branch| branch.trim().to_string())
+        .filter(|branch| !branch.is_empty());
+
+    let result = sqlx::query("UPDATE users SET branch_id = $1 WHERE id = $2")
+        .bind(&branch_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "User not found"
+        }))));
+    }
+
+    println!("🏢 User {} assigned to branch {:?} by admin {}", user_id, branch_id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Branch updated successfully",
+        "user_id": user_id,
+        "branch_id": branch_id
+    })))
+}
+
+// Atur permission (scope) khusus untuk user, misalnya staff yang hanya boleh mengelola order.
+// Berlaku untuk access token berikutnya (setelah refresh / login ulang).
+async fn assign_scopes(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<AssignScopesRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let user_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid ID format"
+        })))
+    })?;
+
+    if let Some(unknown) = request
+        .scopes
+        .iter()
+        .flatten()
+        .find(|scope| !ALL_SCOPES.contains(&scope.as_str()))
+    {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown scope: {}", unknown),
+            "allowed_scopes": ALL_SCOPES
+        }))));
+    }
+
+    let result = sqlx::query("UPDATE users SET scopes = $1 WHERE id = $2")
+        .bind(&request.scopes)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "User not found"
+        }))));
+    }
+
+    println!("🔑 Scopes for user {} set to {:?} by admin {}", user_id, request.scopes, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Scopes updated successfully",
+        "user_id": user_id,
+        "scopes": request.scopes
+    })))
+}
+
+// Ubah status akun (active / suspended / banned). Akun non-aktif langsung ditolak di setiap
+// request terautentikasi dan tidak bisa login / refresh token.
+async fn update_user_status(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateUserStatusRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let user_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid ID format"
+        })))
+    })?;
+
+    if user_id == user.id && request.status != UserStatus::Active {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Cannot suspend or ban your own account"
+        }))));
+    }
+
+    let result = sqlx::query(
+        "UPDATE users SET status = $1, status_reason = $2, status_changed_at = NOW(), status_changed_by = $3
+         WHERE id = $4"
+    )
+    .bind(request.status.as_str())
+    .bind(&request.reason)
+    .bind(user.id)
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "User not found"
+        }))));
+    }
+
+    println!("🚫 User {} status set to {} by admin {}", user_id, request.status.as_str(), user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "User status updated successfully",
+        "user_id": user_id,
+        "status": request.status,
+        "reason": request.reason
+    })))
+}
+
+// Buat API key partner baru. Key plain text hanya ditampilkan sekali di response ini.
+async fn create_api_key(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    if request.name.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Name is required"
+        }))));
+    }
+
+    if let Some(unknown) = request.scopes.iter().find(|scope| !API_KEY_SCOPES.contains(&scope.as_str())) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown scope: {}", unknown),
+            "allowed_scopes": API_KEY_SCOPES
+        }))));
+    }
+
+    let raw_key = format!("sk_{}", random_token());
+    let key_prefix: String = raw_key.chars().take(10).collect();
+
+    let api_key: ApiKey = sqlx::query_as(
+        "INSERT INTO api_keys (id, name, key_prefix, key_hash, scopes, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, name, key_prefix, scopes, created_by, created_at, last_used_at, revoked_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(request.name.trim())
+    .bind(&key_prefix)
+    .bind(hash_token(&raw_key))
+    .bind(&request.scopes)
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    println!("🔑 API key {} ({}) created by admin {}", api_key.name, api_key.key_prefix, user.id);
+    Ok(RespJson(serde_json::json!({
+        "api_key": api_key,
+        "key": raw_key,
+        "note": "Simpan key ini sekarang, key tidak bisa ditampilkan lagi"
+    })))
+}
+
+// List semua API key beserta jumlah pemakaian 30 hari terakhir
+async fn list_api_keys(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let keys: Vec<ApiKey> = sqlx::query_as(
+        "SELECT id, name, key_prefix, scopes, created_by, created_at, last_used_at, revoked_at
+         FROM api_keys ORDER BY created_at DESC"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let usage: Vec<(Uuid, i64)> = sqlx::query_as(
+        "SELECT api_key_id, COUNT(*) FROM api_key_usage
+         WHERE created_at > NOW() - INTERVAL '30 days'
+         GROUP BY api_key_id"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let data: Vec<serde_json::Value> = keys.iter().map(|key| {
+        let requests_30d = usage.iter().find(|(id, _)| *id == key.id).map(|(_, count)| *count).unwrap_or(0);
+        serde_json::json!({
+            "id": key.id,
+            "name": key.name,
+            "key_prefix": key.key_prefix,
+            "scopes": key.scopes,
+            "created_by": key.created_by,
+            "created_at": key.created_at,
+            "last_used_at": key.last_used_at,
+            "revoked_at": key.revoked_at,
+            "requests_30d": requests_30d
+        })
+    }).collect();
+
+    Ok(RespJson(serde_json::json!({
+        "api_keys": data,
+        "total": data.len()
+    })))
+}
+
+// Cabut API key partner
+async fn revoke_api_key(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let key_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid ID format"
+        })))
+    })?;
+
+    let result = sqlx::query("UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL")
+        .bind(key_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "API key not found"
+        }))));
+    }
+
+    println!("🔒 API key {} revoked by admin {}", key_id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "API key revoked successfully"
+    })))
+}
+
+// Admin mendapatkan token berumur pendek yang bertindak sebagai customer tertentu,
+// untuk mereproduksi masalah booking. Setiap pemakaian dicatat di impersonation_audit.
+async fn impersonate_user(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(user_id): Path<String>,
+    payload: Option<Json<ImpersonateRequest>>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    // Token impersonation tidak boleh dipakai untuk impersonation lagi
+    if user.impersonated_by.is_some() {
+        return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Cannot impersonate while impersonating"
+        }))));
+    }
+
+    let target_id = Uuid::parse_str(&user_id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid user ID format"
+        })))
+    })?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    let target: Option<(String, String)> = sqlx::query_as("SELECT username, role FROM users WHERE id = $1")
+        .bind(target_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let (username, role) = target.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "User not found"
+        })))
+    })?;
+
+    // Hanya akun customer yang boleh di-impersonate
+    if role.parse::<Role>().ok() != Some(Role::Customer) {
+        return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Only customer accounts can be impersonated"
+        }))));
+    }
+
+    let reason = payload.and_then(|Json(body)| body.reason);
+    let ttl_secs = config::get().impersonation_ttl_secs;
+    let user_agent = headers.get("user-agent").and_then(|value| value.to_str().ok());
+
+    let ip = client_ip(&headers, &addr);
+    let session_id = create_impersonation_session(&pool, target_id, user.id, user_agent, Some(ip))
+        .await
+        .map_err(db_error)?;
+
+    // Token impersonation hanya membawa scope customer (tanpa permission pengelolaan)
+    let token = create_impersonation_token(target_id, session_id, user.id, default_scopes(Role::Customer), ttl_secs).map_err(|e| {
+        println!("❌ JWT encode error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Failed to create token"
+        })))
+    })?;
+
+    let expires_at = Utc::now() + Duration::seconds(ttl_secs);
+
+    sqlx::query(
+        "INSERT INTO impersonation_audit (id, admin_id, target_user_id, session_id, reason, ip_address, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(Uuid::new_v4())
+    .bind(user.id)
+    .bind(target_id)
+    .bind(session_id)
+    .bind(&reason)
+    .bind(ip.to_string())
+    .bind(expires_at)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    audit::record(
+        &pool,
+        AuthEvent::Impersonation,
+        Some(target_id),
+        Some(ip),
+        &headers,
+        serde_json::json!({ "admin_id": user.id, "session_id": session_id, "reason": reason }),
+    )
+    .await;
+
+    println!("🎭 Admin {} impersonating user {} ({})", user.id, username, target_id);
+    Ok(RespJson(serde_json::json!({
+        "token": token,
+        "user_id": target_id,
+        "username": username,
+        "impersonated_by": user.id,
+        "expires_at": expires_at,
+        "expires_in": ttl_secs
+    })))
+}
+
+// Audit log autentikasi untuk investigasi pengambilalihan akun.
+// Filter: user_id, event, ip, from, to (RFC 3339), page, limit.
+async fn list_auth_events(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Query(query): Query<AuthEventQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = (page - 1) * limit;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    // Filter yang tidak diisi (NULL) otomatis diabaikan
+    let filter = "($1::uuid IS NULL OR e.user_id = $1)
+        AND ($2::text IS NULL OR e.event = $2)
+        AND ($3::text IS NULL OR e.ip_address = $3)
+        AND ($4::timestamptz IS NULL OR e.created_at >= $4)
+        AND ($5::timestamptz IS NULL OR e.created_at < $5)";
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM auth_events e WHERE {}", filter))
+        .bind(query.user_id)
+        .bind(&query.event)
+        .bind(&query.ip)
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let events: Vec<AuthEventRecord> = sqlx::query_as(&format!(
+        "SELECT e.id, e.event, e.user_id, u.username, e.ip_address, e.user_agent, e.details, e.created_at
+         FROM auth_events e
+         LEFT JOIN users u ON u.id = e.user_id
+         WHERE {}
+         ORDER BY e.created_at DESC
+         LIMIT $6 OFFSET $7",
+        filter
+    ))
+    .bind(query.user_id)
+    .bind(&query.event)
+    .bind(&query.ip)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "events": events,
+        "total": total,
+        "page": page,
+        "limit": limit
+    })))
+}
+
+// Daftarkan URL webhook untuk event order. Secret untuk verifikasi tanda tangan
+// (header X-Sentor-Signature) hanya ditampilkan sekali.
+async fn create_webhook(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let url = request.url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "URL must start with http:// or https://"
+        }))));
+    }
+
+    if request.events.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "At least one event is required",
+            "allowed_events": WEBHOOK_EVENTS
+        }))));
+    }
+
+    if let Some(unknown) = request.events.iter().find(|event| !WEBHOOK_EVENTS.contains(&event.as_str())) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown event: {}", unknown),
+            "allowed_events": WEBHOOK_EVENTS
+        }))));
+    }
+
+    let secret = format!("whsec_{}", random_token());
+
+    let webhook: WebhookSubscription = sqlx::query_as(
+        "INSERT INTO webhook_subscriptions (id, url, secret, events, created_by)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, url, events, active, created_by, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(url)
+    .bind(&secret)
+    .bind(&request.events)
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    println!("📡 Webhook {} ({}) created by admin {}", webhook.id, webhook.url, user.id);
+    Ok(RespJson(serde_json::json!({
+        "webhook": webhook,
+        "secret": secret,
+        "note": "Simpan secret ini sekarang, secret tidak bisa ditampilkan lagi"
+    })))
+}
+
+// Daftar webhook beserta jumlah delivery yang masih antre / gagal
+async fn list_webhooks(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let webhooks: Vec<WebhookSubscription> = sqlx::query_as(
+        "SELECT id, url, events, active, created_by, created_at
+         FROM webhook_subscriptions WHERE active = TRUE ORDER BY created_at DESC"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let stats: Vec<(Uuid, i64, i64)> = sqlx::query_as(
+        "SELECT subscription_id,
+                COUNT(*) FILTER (WHERE delivered_at IS NULL AND failed_at IS NULL),
+                COUNT(*) FILTER (WHERE failed_at IS NOT NULL AND created_at > NOW() - INTERVAL '7 days')
+         FROM webhook_deliveries
+         GROUP BY subscription_id"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let data: Vec<serde_json::Value> = webhooks.iter().map(|webhook| {
+        let (pending, failed_7d) = stats.iter()
+            .find(|(id, _, _)| *id == webhook.id)
+            .map(|(_, pending, failed)| (*pending, *failed))
+            .unwrap_or((0, 0));
+        serde_json::json!({
+            "id": webhook.id,
+            "url": webhook.url,
+            "events": webhook.events,
+            "created_by": webhook.created_by,
+            "created_at": webhook.created_at,
+            "pending_deliveries": pending,
+            "failed_deliveries_7d": failed_7d
+        })
+    }).collect();
+
+    Ok(RespJson(serde_json::json!({
+        "webhooks": data,
+        "total": data.len()
+    })))
+}
+
+// Nonaktifkan webhook; delivery yang masih antre tidak dikirim lagi
+async fn delete_webhook(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let webhook_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid ID format"
+        })))
+    })?;
+
+    let result = sqlx::query(
+        "WITH disabled AS (
+             UPDATE webhook_subscriptions SET active = FALSE WHERE id = $1 AND active = TRUE RETURNING id
+         ), dropped AS (
+             UPDATE webhook_deliveries SET failed_at = NOW(), last_error = 'Webhook dihapus'
+             WHERE subscription_id IN (SELECT id FROM disabled) AND delivered_at IS NULL AND failed_at IS NULL
+         )
+         SELECT id FROM disabled"
+    )
+    .bind(webhook_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    if result.is_none() {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Webhook not found"
+        }))));
+    }
+
+    println!("📡 Webhook {} deleted by admin {}", webhook_id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Webhook deleted successfully"
+    })))
+}
+
+// Statistik popularitas & utilisasi per motor dalam periode from..=to, untuk menentukan model mana
+// yang perlu ditambah unitnya dan mana yang sebaiknya dijual. Staff hanya melihat cabangnya.
+async fn motor_stats(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Query(query): Query<MotorStatsQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    user.require_scope(scope::REPORTS_READ)?;
+    let branch = user.branch_scope()?.or(query.branch.as_deref()).map(str::trim).filter(|b| !b.is_empty());
+
+    let to = query.to.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let from = query.from.unwrap_or(to - Duration::days(29));
+    if to < from || to - from > Duration::days(366) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Date range must be between 0 and 366 days"
+        }))));
+    }
+    let order_by = match query.sort.as_deref() {
+        None | Some("revenue") => "revenue DESC, bookings DESC",
+        Some("bookings") => "bookings DESC, revenue DESC",
+        Some("utilization") => "utilization_percent DESC, revenue DESC",
+        Some(other) => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": format!("Unsupported sort: {}", other),
+                "supportedSorts": ["revenue", "bookings", "utilization"]
+            }))));
+        }
+    };
+
+    let period_start = from.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let period_end = (to + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default();
+    let period_hours = (period_end - period_start).num_hours() as f64;
+
+    // Order pending / held / cancelled tidak dihitung
+    let stats: Vec<MotorStats> = sqlx::query_as(&format!(
+        "WITH usage AS (
+             SELECT l.motor_id,
+                    COUNT(DISTINCT o.id) FILTER (WHERE o.tanggal_peminjaman + o.jam_peminjaman >= $1) AS bookings,
+                    COALESCE(SUM(l.rental_subtotal) FILTER (WHERE o.tanggal_peminjaman + o.jam_peminjaman >= $1), 0)::BIGINT AS revenue,
+                    SUM(EXTRACT(EPOCH FROM LEAST(o.tanggal_pengembalian + o.jam_pengembalian, $2)
+                                         - GREATEST(o.tanggal_peminjaman + o.jam_peminjaman, $1))) / 3600.0 AS booked_hours
+             FROM orders o
+             JOIN order_motors l ON l.order_id = o.id
+             WHERE o.status IN ('confirmed', 'picked_up', 'overdue', 'returned', 'completed')
+               AND o.tanggal_peminjaman + o.jam_peminjaman < $2
+               AND o.tanggal_pengembalian + o.jam_pengembalian > $1
+             GROUP BY l.motor_id
+         ), fleet AS (
+             SELECT m.motor_id, m.motor_name, m.motor_type, m.branch,
+                    GREATEST((SELECT COUNT(*) FROM motor_units u WHERE u.motor_id = m.motor_id AND u.status <> 'retired'), 1) AS fleet_size,
+                    COALESCE(us.bookings, 0) AS bookings,
+                    COALESCE(us.revenue, 0) AS revenue,
+                    COALESCE(us.booked_hours, 0)::FLOAT8 AS booked_hours
+             FROM motors m
+             LEFT JOIN usage us ON us.motor_id = m.motor_id
+             WHERE m.deleted_at IS NULL
+               AND ($3::text IS NULL OR LOWER(TRIM(m.branch)) = LOWER(TRIM($3)))
+         )
+         SELECT *, ROUND((booked_hours * 100 / ($4 * fleet_size))::NUMERIC, 1)::FLOAT8 AS utilization_percent
+         FROM fleet
+         ORDER BY {}, motor_id",
+        order_by
+    ))
+    .bind(period_start)
+    .bind(period_end)
+    .bind(branch)
+    .bind(period_hours)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "from": from,
+        "to": to,
+        "branch": branch,
+        "totalBookings": stats.iter().map(|s| s.bookings).sum::<i64>(),
+        "totalRevenue": stats.iter().map(|s| s.revenue).sum::<i64>(),
+        "data": stats
+    })))
+}
@@ -1,14 +1,36 @@
 use axum::{
     Router,
-    routing::post,
-    extract::{Extension, Json},
-    http::StatusCode,
+    routing::{get, post},
+    extract::{ConnectInfo, Extension, Json},
+    http::{StatusCode, HeaderMap, Method},
     response::Json as RespJson,
 };
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+use std::net::{IpAddr, SocketAddr};
 use uuid::Uuid;
 
+use crate::auth::audit::{self, client_ip, AuthEvent};
+use crate::auth::cookie::{self, clear_session_cookies, cookie_mode, session_cookies, REFRESH_COOKIE};
+use crate::auth::guest::claim_guest_orders;
+use crate::auth::{captcha, google, keys, AuthUser};
+use crate::auth::password::{hash_password, is_hashed, verify_password};
+use crate::auth::password_policy;
+use crate::auth::rate_limit::SharedAuthRateLimits;
+use crate::auth::scope::user_scopes;
+use crate::auth::session::{create_session, revoke_session};
+use crate::auth::token::{
+    create_access_token, hash_token, issue_refresh_token, random_token, revoke_all_user_tokens,
+    verify_access_token,
+};
+use crate::config;
+use crate::mailer::SharedMailer;
+use crate::model::user::{
+    is_valid_email, is_valid_username, normalize_phone, FieldError, UserStatus, NORMALIZED_PHONE_SQL,
+};
+use crate::sms::SharedSmsSender;
+
 // Payload untuk register
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -17,21 +39,81 @@ pub struct RegisterRequest {
     pub email: String,
     pub phone: String,
     pub password: String,
+    #[serde(default)]
+    pub captcha_token: Option<String>, // wajib jika captcha diaktifkan lewat env
 }
 
-// Payload untuk login
+// Payload untuk login. `identifier` boleh berisi username, email, atau nomor HP
+// (field lama `username` tetap diterima untuk frontend versi sebelumnya)
 #[derive(Deserialize)]
 pub struct LoginRequest {
-    pub username: String,
+    #[serde(alias = "username")]
+    pub identifier: String,
     pub password: String,
 }
 
-// Response JWT
+// Payload untuk refresh token (di mode cookie refresh token diambil dari cookie)
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: Option<String>,
+}
+
+// Payload opsional untuk logout (ikut cabut refresh token dari device ini)
+#[derive(Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
+// Payload untuk lupa password
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+// Payload untuk reset password dengan token dari email
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+// Payload untuk minta kode OTP ke nomor HP
+#[derive(Deserialize)]
+pub struct OtpRequest {
+    pub phone: String,
+}
+
+// Payload untuk verifikasi kode OTP
+#[derive(Deserialize)]
+pub struct OtpVerifyRequest {
+    pub phone: String,
+    pub code: String,
+}
+
+// Payload untuk klaim order guest ke akun yang sedang login
+#[derive(Deserialize)]
+pub struct ClaimRequest {
+    pub claim_token: String,
+}
+
+// Payload login dengan Google (ID token dari Google Identity Services di frontend)
+#[derive(Deserialize)]
+pub struct GoogleLoginRequest {
+    pub id_token: String,
+}
+
+// Response JWT (di mode cookie token tidak dikirim di body, hanya lewat cookie HttpOnly)
 #[derive(Serialize)]
 pub struct TokenResponse {
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub token: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub refresh_token: String,
     pub user_id: String, // Tambahkan user_id untuk frontend
     pub username: String, // Tambahkan username juga
+    pub role: String, // Untuk menentukan menu admin di frontend
+    pub expires_in: i64, // umur access token (detik), supaya frontend bisa refresh sebelum expired
+    pub refresh_expires_in: i64,
 }
 
 // Buat router khusus auth
@@ -39,61 +121,1161 @@ pub fn auth_router() -> Router {
     Router::new()
         .route("/api/register", post(register))
         .route("/api/login", post(login))
+        .route("/api/auth/refresh", post(refresh))
+        .route("/api/auth/logout", post(logout))
+        .route("/api/logout", post(logout))           // Alias lama; mode cookie wajib lewat /api/auth/logout
+        .route("/api/auth/forgot-password", post(forgot_password))
+        .route("/api/auth/reset-password", post(reset_password))
+        .route("/api/auth/otp/request", post(request_otp))
+        .route("/api/auth/otp/verify", post(verify_otp))
+        .route("/api/auth/google", post(google_login))
+        .route("/api/auth/claim", post(claim_guest))
+        .route("/.well-known/jwks.json", get(jwks))
+}
+
+// Public key untuk verifikasi JWT oleh service lain (tanpa berbagi JWT_SECRET)
+pub async fn jwks() -> RespJson<serde_json::Value> {
+    RespJson(keys::get().jwks().clone())
+}
+
+fn too_many_requests(retry_after: u64) -> (StatusCode, String) {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        format!("Terlalu banyak percobaan, coba lagi dalam {} detik", retry_after),
+    )
+}
+
+// Cara mencari akun dari identifier login
+enum LoginIdentifier {
+    Username(String),
+    Email(String),
+    Phone(String),
+}
+
+impl LoginIdentifier {
+    // Email jika ada '@', nomor HP jika hanya berisi digit (boleh +, spasi, strip), selain itu username
+    fn parse(identifier: &str) -> Self {
+        let identifier = identifier.trim();
+
+        if identifier.contains('@') {
+            return LoginIdentifier::Email(identifier.to_lowercase());
+        }
+
+        let looks_like_phone = identifier.chars().any(|c| c.is_ascii_digit())
+            && identifier.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | ' ' | '-'));
+        if looks_like_phone {
+            if let Some(phone) = normalize_phone(identifier) {
+                return LoginIdentifier::Phone(phone);
+            }
+        }
+
+        LoginIdentifier::Username(identifier.to_string())
+    }
+
+    // Kondisi WHERE untuk mencari user (parameter $1)
+    fn where_clause(&self) -> String {
+        match self {
+            LoginIdentifier::Username(_) => "username = $1".to_string(),
+            LoginIdentifier::Email(_) => "LOWER(email) = $1".to_string(),
+            LoginIdentifier::Phone(_) => format!("{} = $1", NORMALIZED_PHONE_SQL),
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            LoginIdentifier::Username(value) | LoginIdentifier::Email(value) | LoginIdentifier::Phone(value) => value,
+        }
+    }
+}
+
+// Data user yang dibutuhkan saat login
+#[derive(sqlx::FromRow)]
+struct LoginRow {
+    id: Uuid,
+    username: String,
+    password_hash: String,
+    role: String,
+    failed_login_attempts: i32,
+    locked_until: Option<chrono::DateTime<Utc>>,
+}
+
+// Data refresh token beserta user dan sesinya
+#[derive(sqlx::FromRow)]
+struct RefreshRow {
+    id: Uuid,
+    user_id: Uuid,
+    session_id: Uuid,
+    username: String,
+    role: String,
+    expires_at: chrono::DateTime<Utc>,
+    revoked_at: Option<chrono::DateTime<Utc>>,
+    session_revoked_at: Option<chrono::DateTime<Utc>>,
+}
+
+// Error khusus akun terkunci (423) supaya frontend bisa menampilkan pesan lockout
+fn account_locked(locked_until: chrono::DateTime<Utc>) -> (StatusCode, String) {
+    let minutes = ((locked_until - Utc::now()).num_seconds().max(0) + 59) / 60;
+    (
+        StatusCode::LOCKED,
+        format!("Akun dikunci karena terlalu banyak percobaan login gagal. Coba lagi dalam {} menit", minutes),
+    )
+}
+
+// Catat login gagal; kunci akun sementara jika sudah mencapai batas percobaan
+async fn record_failed_login(pool: &PgPool, row: &LoginRow, ip: IpAddr, headers: &HeaderMap) -> (StatusCode, String) {
+    let cfg = config::get();
+
+    // Naikkan counter di database (bukan dari nilai yang dibaca saat login) supaya percobaan yang
+    // bersamaan tidak saling menimpa dan tetap terhitung semua
+    let attempts: i32 = sqlx::query_scalar(
+        "UPDATE users SET failed_login_attempts = failed_login_attempts + 1 WHERE id = $1 RETURNING failed_login_attempts"
+    )
+    .bind(row.id)
+    .fetch_one(pool)
+    .await
+    .unwrap_or_else(|e| {
+        println!("Database error: {}", e);
+        row.failed_login_attempts + 1
+    });
+
+    audit::record(
+        pool,
+        AuthEvent::LoginFailure,
+        Some(row.id),
+        Some(ip),
+        headers,
+        serde_json::json!({ "reason": "wrong_password", "attempts": attempts }),
+    )
+    .await;
+
+    if attempts >= cfg.lockout_threshold {
+        let locked_until = Utc::now() + chrono::Duration::seconds(cfg.lockout_duration_secs);
+        if let Err(e) = sqlx::query("UPDATE users SET failed_login_attempts = 0, locked_until = $1 WHERE id = $2")
+            .bind(locked_until)
+            .bind(row.id)
+            .execute(pool)
+            .await
+        {
+            println!("Database error: {}", e);
+        }
+        println!("🔒 Account {} locked until {}", row.username, locked_until);
+        audit::record(
+            pool,
+            AuthEvent::LoginLocked,
+            Some(row.id),
+            Some(ip),
+            headers,
+            serde_json::json!({ "locked_until": locked_until }),
+        )
+        .await;
+        return account_locked(locked_until);
+    }
+
+    (StatusCode::UNAUTHORIZED, "Username atau password salah".into())
+}
+
+// Buat pasangan access token + refresh token untuk sesi user
+// Refresh token disimpan lewat `conn` supaya rotasi di /api/auth/refresh bisa satu transaksi
+async fn issue_tokens(
+    pool: &PgPool,
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<(String, Uuid, String), (StatusCode, String)> {
+    // Scope dibaca ulang setiap kali token terbit, jadi perubahan permission berlaku saat refresh
+    let scopes = user_scopes(pool, user_id).await.map_err(|e| {
+        println!("Database error loading scopes: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Gagal membuat token".to_string())
+    })?;
+
+    let access_token = create_access_token(user_id, session_id, scopes).map_err(|e| {
+        println!("JWT encode error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Gagal membuat token".to_string())
+    })?;
+
+    let (refresh_id, refresh_token) = issue_refresh_token(conn, user_id, session_id).await.map_err(|e| {
+        println!("Database error saving refresh token: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Gagal membuat token".to_string())
+    })?;
+
+    Ok((access_token, refresh_id, refresh_token))
+}
+
+// Tolak penerbitan token untuk akun yang ditangguhkan / diblokir admin
+async fn ensure_account_active(pool: &PgPool, user_id: Uuid) -> Result<(), (StatusCode, String)> {
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+
+    match status.parse::<UserStatus>() {
+        Ok(UserStatus::Suspended) => Err((StatusCode::FORBIDDEN, "Akun kamu sedang ditangguhkan, hubungi admin".to_string())),
+        Ok(UserStatus::Banned) => Err((StatusCode::FORBIDDEN, "Akun kamu telah diblokir".to_string())),
+        _ => Ok(()),
+    }
 }
 
-// Handler register sederhana (tanpa hash untuk testing)
+// Mulai sesi login baru untuk device ini (user agent + IP) lalu terbitkan token-nya
+async fn start_session(
+    pool: &PgPool,
+    headers: &HeaderMap,
+    ip: IpAddr,
+    user_id: Uuid,
+) -> Result<(String, String), (StatusCode, String)> {
+    ensure_account_active(pool, user_id).await?;
+
+    let user_agent = headers.get("user-agent").and_then(|value| value.to_str().ok());
+
+    let session_id = create_session(pool, user_id, user_agent, Some(ip)).await.map_err(|e| {
+        println!("Database error creating session: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Gagal membuat sesi".to_string())
+    })?;
+
+    let mut conn = pool.acquire().await.map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+    let (token, _, refresh_token) = issue_tokens(pool, &mut conn, user_id, session_id).await?;
+    Ok((token, refresh_token))
+}
+
+// Bungkus TokenResponse sesuai mode sesi: mode cookie memindahkan token ke Set-Cookie
+fn token_response(mut response: TokenResponse) -> (HeaderMap, RespJson<TokenResponse>) {
+    if !cookie_mode() {
+        return (HeaderMap::new(), RespJson(response));
+    }
+
+    let cookies = session_cookies(&response.token, &response.refresh_token);
+    response.token.clear();
+    response.refresh_token.clear();
+    (cookies, RespJson(response))
+}
+
+// Response error validasi: daftar error per field
+fn field_errors(status: StatusCode, errors: Vec<FieldError>) -> (StatusCode, RespJson<serde_json::Value>) {
+    (status, RespJson(serde_json::json!({
+        "error": "Validation failed",
+        "errors": errors
+    })))
+}
+
+// Validasi payload register sebelum menyentuh database
+fn validate_register(payload: &RegisterRequest) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if payload.full_name.trim().is_empty() {
+        errors.push(FieldError::new("full_name", "is required"));
+    }
+    if !is_valid_username(&payload.username) {
+        errors.push(FieldError::new(
+            "username",
+            "must be 3-30 characters of letters, numbers, '.' or '_'",
+        ));
+    }
+    if !is_valid_email(payload.email.trim()) {
+        errors.push(FieldError::new("email", "invalid format"));
+    }
+    if normalize_phone(&payload.phone).is_none() {
+        errors.push(FieldError::new("phone", "invalid format"));
+    }
+    if let Err(violations) = password_policy::validate(&payload.password) {
+        errors.extend(violations.into_iter().map(|violation| FieldError::new("password", violation)));
+    }
+
+    errors
+}
+
+// Handler register
 pub async fn register(
     Extension(pool): Extension<PgPool>,
+    Extension(limits): Extension<SharedAuthRateLimits>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<RegisterRequest>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, (StatusCode, RespJson<serde_json::Value>)> {
     println!("Register attempt - Email: {}, Username: {}, Phone: {}", 
              payload.email, payload.username, payload.phone);
+
+    let ip = client_ip(&headers, &addr);
+    limits.register_per_ip.check(&ip.to_string()).map_err(|retry_after| {
+        println!("⛔ Register rate limit hit for IP {}", ip);
+        let (status, message) = too_many_requests(retry_after);
+        (status, RespJson(serde_json::json!({ "error": message })))
+    })?;
+
+    // Captcha (Turnstile / reCAPTCHA) untuk menahan pendaftaran oleh bot
+    if let Some((provider, secret)) = &config::get().captcha {
+        let token = payload.captcha_token.as_deref().filter(|t| !t.is_empty()).ok_or_else(|| {
+            field_errors(StatusCode::BAD_REQUEST, vec![FieldError::new("captcha_token", "is required")])
+        })?;
+
+        captcha::verify(*provider, secret, token, Some(ip)).await.map_err(|e| {
+            println!("🤖 Register captcha rejected for IP {}: {}", ip, e);
+            field_errors(StatusCode::BAD_REQUEST, vec![FieldError::new("captcha_token", "verification failed")])
+        })?;
+    }
+
+    let errors = validate_register(&payload);
+    if !errors.is_empty() {
+        println!("❌ Register validation failed: {:?}", errors);
+        return Err(field_errors(StatusCode::UNPROCESSABLE_ENTITY, errors));
+    }
+
+    let email = payload.email.trim().to_lowercase();
+    let phone = normalize_phone(&payload.phone).unwrap_or_default();
+
+    let db_error = |e: sqlx::Error| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    // Cek username / email yang sudah dipakai, supaya frontend dapat pesan per field
+    let (username_taken, email_taken): (bool, bool) = sqlx::query_as(
+        "SELECT
+            EXISTS(SELECT 1 FROM users WHERE LOWER(username) = LOWER($1)),
+            EXISTS(SELECT 1 FROM users WHERE LOWER(email) = $2)"
+    )
+    .bind(&payload.username)
+    .bind(&email)
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let mut conflicts = Vec::new();
+    if username_taken {
+        conflicts.push(FieldError::new("username", "already taken"));
+    }
+    if email_taken {
+        conflicts.push(FieldError::new("email", "already taken"));
+    }
+    if !conflicts.is_empty() {
+        println!("❌ Register conflict: {:?}", conflicts);
+        return Err(field_errors(StatusCode::CONFLICT, conflicts));
+    }
+
+    let password_hash = hash_password(&payload.password).map_err(|e| {
+        println!("Password hashing error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Gagal memproses password"
+        })))
+    })?;
     
     sqlx::query(
         "INSERT INTO users (id, full_name, username, email, phone, password_hash) VALUES ($1,$2,$3,$4,$5,$6)"
     )
     .bind(Uuid::new_v4())
-    .bind(payload.full_name)
-    .bind(payload.username)
-    .bind(payload.email)
-    .bind(payload.phone)
-    .bind(payload.password) // simpan plain text dulu untuk testing
+    .bind(payload.full_name.trim())
+    .bind(&payload.username)
+    .bind(&email)
+    .bind(&phone)
+    .bind(password_hash)
     .execute(&pool)
     .await
     .map_err(|e| {
-        println!("Database insert error: {}", e);
-        (StatusCode::CONFLICT, e.to_string())
+        // Race dengan request register lain: unique constraint tetap jadi penjaga terakhir
+        if let sqlx::Error::Database(db_err) = &e {
+            if db_err.code().as_deref() == Some("23505") {
+                let field = match db_err.constraint() {
+                    Some(name) if name.contains("email") => "email",
+                    _ => "username",
+                };
+                println!("❌ Register conflict on {}", field);
+                return field_errors(StatusCode::CONFLICT, vec![FieldError::new(field, "already taken")]);
+            }
+        }
+        db_error(e)
     })?;
 
     println!("User registered successfully!");
     Ok(StatusCode::CREATED)
 }
 
-// Handler login sederhana (tanpa JWT untuk testing)
+// Handler login
 pub async fn login(
     Extension(pool): Extension<PgPool>,
+    Extension(limits): Extension<SharedAuthRateLimits>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<RespJson<TokenResponse>, (StatusCode, String)> {
-    println!("Login attempt - Username: {}, Password: {}", payload.username, payload.password);
-    
-    let row: (Uuid, String) = sqlx::query_as(
-        "SELECT id, username FROM users WHERE username = $1 AND password_hash = $2"
-    )
-    .bind(&payload.username)
-    .bind(&payload.password) // cek plain text dulu
-    .fetch_one(&pool)
+) -> Result<(HeaderMap, RespJson<TokenResponse>), (StatusCode, String)> {
+    println!("Login attempt - Identifier: {}", payload.identifier);
+
+    let identifier = LoginIdentifier::parse(&payload.identifier);
+
+    // Throttle per IP dan per akun (identifier yang sudah dinormalisasi) untuk menahan credential stuffing
+    let ip = client_ip(&headers, &addr);
+    limits.login_per_ip.check(&ip.to_string()).map_err(|retry_after| {
+        println!("⛔ Login rate limit hit for IP {}", ip);
+        too_many_requests(retry_after)
+    })?;
+    limits.login_per_username.check(&identifier.value().to_lowercase()).map_err(|retry_after| {
+        println!("⛔ Login rate limit hit for identifier {}", payload.identifier);
+        too_many_requests(retry_after)
+    })?;
+
+    // Nomor HP bisa terdaftar di lebih dari satu akun lama; utamakan yang sudah terverifikasi
+    let row: Option<LoginRow> = sqlx::query_as(&format!(
+        "SELECT id, username, password_hash, role, failed_login_attempts, locked_until
+         FROM users WHERE {} AND deleted_at IS NULL
+         ORDER BY phone_verified_at DESC NULLS LAST, created_at
+         LIMIT 1",
+        identifier.where_clause()
+    ))
+    .bind(identifier.value())
+    .fetch_optional(&pool)
     .await
     .map_err(|e| {
         println!("Database error: {}", e);
-        (StatusCode::UNAUTHORIZED, "Username atau password salah".into())
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
     })?;
 
-    println!("Login successful for user: {} ({})", row.1, row.0);
+    let Some(row) = row else {
+        println!("Login failed, unknown identifier: {}", payload.identifier);
+        audit::record(
+            &pool,
+            AuthEvent::LoginFailure,
+            None,
+            Some(ip),
+            &headers,
+            serde_json::json!({ "reason": "unknown_identifier", "identifier": payload.identifier }),
+        )
+        .await;
+        return Err((StatusCode::UNAUTHORIZED, "Username atau password salah".into()));
+    };
+
+    // Akun yang sedang dikunci tidak boleh login walau password benar
+    if let Some(locked_until) = row.locked_until.filter(|until| *until > Utc::now()) {
+        println!("🔒 Login blocked, account {} locked until {}", row.username, locked_until);
+        audit::record(
+            &pool,
+            AuthEvent::LoginLocked,
+            Some(row.id),
+            Some(ip),
+            &headers,
+            serde_json::json!({ "locked_until": locked_until }),
+        )
+        .await;
+        return Err(account_locked(locked_until));
+    }
+
+    if !verify_password(&payload.password, &row.password_hash) {
+        println!("Login failed for user: {}", row.username);
+        return Err(record_failed_login(&pool, &row, ip, &headers).await);
+    }
+
+    if row.failed_login_attempts > 0 || row.locked_until.is_some() {
+        sqlx::query("UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = $1")
+            .bind(row.id)
+            .execute(&pool)
+            .await
+            .map_err(|e| {
+                println!("Database error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+            })?;
+    }
+
+    // Migrasi bertahap: row lama yang masih plain text di-hash ulang saat login berhasil
+    if !is_hashed(&row.password_hash) {
+        match hash_password(&payload.password) {
+            Ok(new_hash) => {
+                if let Err(e) = sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+                    .bind(new_hash)
+                    .bind(row.id)
+                    .execute(&pool)
+                    .await
+                {
+                    println!("⚠️  Gagal upgrade password hash untuk {}: {}", row.username, e);
+                } else {
+                    println!("🔐 Password hash untuk {} di-upgrade ke Argon2", row.username);
+                }
+            }
+            Err(e) => println!("⚠️  Gagal hash password untuk {}: {}", row.username, e),
+        }
+    }
+
+    let (token, refresh_token) = start_session(&pool, &headers, ip, row.id).await?;
+
+    audit::record(&pool, AuthEvent::LoginSuccess, Some(row.id), Some(ip), &headers, serde_json::json!({ "method": "password" })).await;
+    println!("Login successful for user: {} ({})", row.username, row.id);
     
     // Return token dengan user_id dan username untuk frontend
-    Ok(RespJson(TokenResponse { 
-        token: format!("dummy_token_for_{}", row.0),
-        user_id: row.0.to_string(),
-        username: row.1,
+    Ok(token_response(TokenResponse {
+        token,
+        refresh_token,
+        user_id: row.id.to_string(),
+        username: row.username,
+        role: row.role,
+        expires_in: config::get().access_token_ttl_secs,
+        refresh_expires_in: config::get().refresh_token_ttl_secs,
+    }))
+}
+
+// Refresh token yang sudah dirotasi dipakai lagi -> kemungkinan dicuri, cabut semua sesi user
+async fn refresh_token_reused(pool: &PgPool, row: &RefreshRow, ip: IpAddr, headers: &HeaderMap) -> (StatusCode, String) {
+    println!("🚨 Refresh token reuse terdeteksi untuk user {}", row.user_id);
+    audit::record(
+        pool,
+        AuthEvent::RefreshTokenReuse,
+        Some(row.user_id),
+        Some(ip),
+        headers,
+        serde_json::json!({ "session_id": row.session_id }),
+    )
+    .await;
+
+    let revoked = match pool.acquire().await {
+        Ok(mut conn) => revoke_all_user_tokens(&mut conn, row.user_id).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = revoked {
+        println!("Database error: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string());
+    }
+
+    (StatusCode::UNAUTHORIZED, "Refresh token sudah tidak berlaku".to_string())
+}
+
+// Tukar refresh token dengan access token baru (refresh token lama langsung dicabut / rotasi)
+pub async fn refresh(
+    Extension(pool): Extension<PgPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    method: Method,
+    headers: HeaderMap,
+    payload: Option<Json<RefreshRequest>>,
+) -> Result<(HeaderMap, RespJson<TokenResponse>), (StatusCode, String)> {
+    let ip = client_ip(&headers, &addr);
+
+    // Refresh token dari body, atau dari cookie HttpOnly (wajib disertai header CSRF)
+    let refresh_token = match payload.and_then(|Json(body)| body.refresh_token) {
+        Some(token) => token,
+        None if cookie_mode() => {
+            cookie::verify_csrf(&method, &headers)
+                .map_err(|status| (status, "CSRF token tidak valid".to_string()))?;
+            cookie::get_cookie(&headers, REFRESH_COOKIE)
+                .map(str::to_string)
+                .ok_or((StatusCode::UNAUTHORIZED, "Refresh token tidak valid".to_string()))?
+        }
+        None => return Err((StatusCode::BAD_REQUEST, "refresh_token wajib diisi".to_string())),
+    };
+    let token_hash = hash_token(&refresh_token);
+
+    let row: Option<RefreshRow> = sqlx::query_as(
+        "SELECT rt.id, rt.user_id, rt.session_id, u.username, u.role, rt.expires_at, rt.revoked_at,
+                s.revoked_at AS session_revoked_at
+         FROM refresh_tokens rt
+         JOIN users u ON u.id = rt.user_id
+         JOIN user_sessions s ON s.id = rt.session_id
+         WHERE rt.token_hash = $1"
+    )
+    .bind(&token_hash)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    let row = row.ok_or((StatusCode::UNAUTHORIZED, "Refresh token tidak valid".to_string()))?;
+
+    if row.revoked_at.is_some() {
+        return Err(refresh_token_reused(&pool, &row, ip, &headers).await);
+    }
+
+    if row.session_revoked_at.is_some() {
+        return Err((StatusCode::UNAUTHORIZED, "Sesi sudah diakhiri".to_string()));
+    }
+
+    if row.expires_at < Utc::now() {
+        return Err((StatusCode::UNAUTHORIZED, "Refresh token sudah kedaluwarsa".to_string()));
+    }
+
+    ensure_account_active(&pool, row.user_id).await?;
+
+    // Konsumsi token lama secara atomik: dua request refresh yang bersamaan dengan token yang sama
+    // hanya satu yang berhasil mencabutnya, yang lain diperlakukan sebagai pemakaian ulang
+    let mut tx = pool.begin().await.map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    let consumed: Option<Uuid> = sqlx::query_scalar(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL RETURNING id"
+    )
+    .bind(row.id)
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    if consumed.is_none() {
+        drop(tx);
+        return Err(refresh_token_reused(&pool, &row, ip, &headers).await);
+    }
+
+    let (token, new_id, refresh_token) = issue_tokens(&pool, &mut tx, row.user_id, row.session_id).await?;
+
+    sqlx::query("UPDATE refresh_tokens SET replaced_by = $1 WHERE id = $2")
+        .bind(new_id)
+        .bind(row.id)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+
+    tx.commit().await.map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    sqlx::query("UPDATE user_sessions SET last_used_at = NOW() WHERE id = $1")
+        .bind(row.session_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+
+    audit::record(
+        &pool,
+        AuthEvent::TokenRefresh,
+        Some(row.user_id),
+        Some(ip),
+        &headers,
+        serde_json::json!({ "session_id": row.session_id }),
+    )
+    .await;
+    println!("🔄 Token refreshed for user: {} ({})", row.username, row.user_id);
+
+    Ok(token_response(TokenResponse {
+        token,
+        refresh_token,
+        user_id: row.user_id.to_string(),
+        username: row.username,
+        role: row.role,
+        expires_in: config::get().access_token_ttl_secs,
+        refresh_expires_in: config::get().refresh_token_ttl_secs,
     }))
-}
\ No newline at end of file
+}
+
+// Logout: cabut access token dan sesi yang sedang dipakai (dan refresh token jika dikirim)
+pub async fn logout(
+    Extension(pool): Extension<PgPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    method: Method,
+    headers: HeaderMap,
+    payload: Option<Json<LogoutRequest>>,
+) -> Result<(HeaderMap, StatusCode), (StatusCode, String)> {
+    let token = cookie::request_access_token(&method, &headers)
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+
+    let claims = verify_access_token(&pool, token).await
+        .map_err(|status| (status, "Authentication required".to_string()))?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| (StatusCode::UNAUTHORIZED, "Authentication required".to_string()))?;
+    let expires_at = chrono::DateTime::<Utc>::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+
+    sqlx::query(
+        "INSERT INTO revoked_tokens (jti, user_id, expires_at) VALUES ($1, $2, $3) ON CONFLICT (jti) DO NOTHING"
+    )
+    .bind(&claims.jti)
+    .bind(user_id)
+    .bind(expires_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    // Akhiri sesi device ini (refresh token-nya ikut dicabut)
+    if let Some(session_id) = claims.sid.as_deref().and_then(|sid| Uuid::parse_str(sid).ok()) {
+        let mut conn = pool.acquire().await.map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+        revoke_session(&mut conn, user_id, session_id).await.map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+    }
+
+    let refresh_token = payload
+        .and_then(|Json(body)| body.refresh_token)
+        .or_else(|| cookie::get_cookie(&headers, REFRESH_COOKIE).map(str::to_string));
+
+    if let Some(refresh_token) = refresh_token {
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE token_hash = $1 AND user_id = $2 AND revoked_at IS NULL")
+            .bind(hash_token(&refresh_token))
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .map_err(|e| {
+                println!("Database error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+            })?;
+    }
+
+    // Bersihkan entri yang token aslinya sudah expired (tidak perlu dicek lagi)
+    if let Err(e) = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()").execute(&pool).await {
+        println!("⚠️  Gagal membersihkan revoked_tokens: {}", e);
+    }
+
+    audit::record(
+        &pool,
+        AuthEvent::Logout,
+        Some(user_id),
+        Some(client_ip(&headers, &addr)),
+        &headers,
+        serde_json::json!({ "session_id": claims.sid }),
+    )
+    .await;
+    println!("👋 User {} logged out", user_id);
+    let cookies = if cookie_mode() { clear_session_cookies() } else { HeaderMap::new() };
+    Ok((cookies, StatusCode::NO_CONTENT))
+}
+
+// Lupa password: kirim link reset ke email user.
+// Response selalu sama walau email tidak terdaftar, supaya tidak bisa dipakai untuk cek email.
+pub async fn forgot_password(
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, String)> {
+    let email = payload.email.trim().to_lowercase();
+    println!("🔑 Forgot password request for: {}", email);
+
+    let user: Option<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, full_name FROM users WHERE LOWER(email) = $1"
+    )
+    .bind(&email)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    if let Some((user_id, full_name)) = user {
+        let cfg = config::get();
+        let token = random_token();
+        let expires_at = Utc::now() + chrono::Duration::seconds(cfg.password_reset_ttl_secs);
+
+        // Token lama yang belum dipakai tidak berlaku lagi
+        sqlx::query("UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .map_err(|e| {
+                println!("Database error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+            })?;
+
+        sqlx::query(
+            "INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+
+        let link = format!("{}/reset-password?token={}", cfg.frontend_url.trim_end_matches('/'), token);
+        let body = format!(
+            "Halo {},\n\nKami menerima permintaan reset password akun Sentor kamu.\nKlik link berikut untuk membuat password baru (berlaku {} menit):\n\n{}\n\nAbaikan email ini jika kamu tidak meminta reset password.",
+            full_name,
+            cfg.password_reset_ttl_secs / 60,
+            link
+        );
+
+        if let Err(e) = mailer.send(&email, "Reset password Sentor", &body).await {
+            println!("⚠️  Gagal kirim email reset password ke {}: {}", email, e);
+        }
+    }
+
+    Ok(RespJson(serde_json::json!({
+        "message": "Jika email terdaftar, link reset password sudah dikirim"
+    })))
+}
+
+// Reset password memakai token sekali pakai dari email
+pub async fn reset_password(
+    Extension(pool): Extension<PgPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, String)> {
+    if let Err(violations) = password_policy::validate(&payload.new_password) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Password tidak memenuhi syarat: password {}", violations.join(", ")),
+        ));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    // Tandai token terpakai di query yang sama supaya tidak bisa dipakai dua kali
+    let row: Option<(Uuid,)> = sqlx::query_as(
+        "UPDATE password_reset_tokens SET used_at = NOW()
+         WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+         RETURNING user_id"
+    )
+    .bind(hash_token(&payload.token))
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    let (user_id,) = row.ok_or((
+        StatusCode::BAD_REQUEST,
+        "Token reset password tidak valid atau sudah kedaluwarsa".to_string(),
+    ))?;
+
+    let password_hash = hash_password(&payload.new_password).map_err(|e| {
+        println!("Password hashing error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Gagal memproses password".to_string())
+    })?;
+
+    sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+        .bind(password_hash)
+        .bind(user_id)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+
+    // Semua sesi lama harus login ulang dengan password baru
+    revoke_all_user_tokens(&mut tx, user_id)
+        .await
+        .map_err(|e| {
+            println!("Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+        })?;
+
+    tx.commit().await.map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    audit::record(
+        &pool,
+        AuthEvent::PasswordReset,
+        Some(user_id),
+        Some(client_ip(&headers, &addr)),
+        &headers,
+        serde_json::json!({}),
+    )
+    .await;
+    println!("🔐 Password reset for user {}", user_id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Password berhasil direset, silakan login kembali"
+    })))
+}
+
+// Kirim kode OTP 6 digit ke nomor HP (SMS / WhatsApp)
+pub async fn request_otp(
+    Extension(pool): Extension<PgPool>,
+    Extension(sms): Extension<SharedSmsSender>,
+    Extension(limits): Extension<SharedAuthRateLimits>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<OtpRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, String)> {
+    // Throttle per IP selain per nomor, supaya satu client tidak bisa memicu SMS ke banyak nomor
+    let ip = client_ip(&headers, &addr);
+    limits.otp_per_ip.check(&ip.to_string()).map_err(|retry_after| {
+        println!("⛔ OTP rate limit hit for IP {}", ip);
+        too_many_requests(retry_after)
+    })?;
+
+    let phone = normalize_phone(&payload.phone)
+        .ok_or((StatusCode::BAD_REQUEST, "Nomor HP tidak valid".to_string()))?;
+
+    // Batasi pengiriman ulang: minimal 60 detik sejak OTP terakhir
+    let recent: Option<(Uuid,)> = sqlx::query_as(
+        "SELECT id FROM phone_otps WHERE phone = $1 AND created_at > NOW() - INTERVAL '60 seconds'"
+    )
+    .bind(&phone)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    if recent.is_some() {
+        return Err((StatusCode::TOO_MANY_REQUESTS, "Tunggu 60 detik sebelum meminta OTP lagi".to_string()));
+    }
+
+    let cfg = config::get();
+    let code = format!("{:06}", rand::random::<u32>() % 1_000_000);
+    let expires_at = Utc::now() + chrono::Duration::seconds(cfg.otp_ttl_secs);
+
+    sqlx::query(
+        "INSERT INTO phone_otps (id, phone, code_hash, expires_at) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(Uuid::new_v4())
+    .bind(&phone)
+    .bind(hash_token(&format!("{}:{}", phone, code)))
+    .bind(expires_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    })?;
+
+    let message = format!(
+        "Kode verifikasi Sentor kamu: {}. Berlaku {} menit. Jangan berikan kode ini ke siapa pun.",
+        code,
+        cfg.otp_ttl_secs / 60
+    );
+
+    sms.send(&phone, &message).await.map_err(|e| {
+        println!("⚠️  Gagal kirim OTP ke {}: {}", phone, e);
+        (StatusCode::BAD_GATEWAY, "Gagal mengirim kode OTP".to_string())
+    })?;
+
+    println!("📱 OTP sent to {}", phone);
+    Ok(RespJson(serde_json::json!({
+        "message": "Kode OTP sudah dikirim",
+        "expires_in": cfg.otp_ttl_secs
+    })))
+}
+
+// Verifikasi kode OTP dan tandai nomor HP akun yang sedang login sebagai terverifikasi.
+// Hanya nomor milik akun itu sendiri yang bisa diverifikasi; akun lain dengan nomor sama tidak ikut.
+pub async fn verify_otp(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<OtpVerifyRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, String)> {
+    let phone = normalize_phone(&payload.phone)
+        .ok_or((StatusCode::BAD_REQUEST, "Nomor HP tidak valid".to_string()))?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    };
+
+    let (own_phone,): (Option<String>,) = sqlx::query_as("SELECT phone FROM users WHERE id = $1")
+        .bind(user.id)
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    if own_phone.as_deref().and_then(normalize_phone).as_deref() != Some(phone.as_str()) {
+        println!("⛔ User {} tried to verify phone {} that is not on their account", user.id, phone);
+        return Err((StatusCode::FORBIDDEN, "Nomor HP tidak sesuai dengan akun kamu".to_string()));
+    }
+
+    // OTP terbaru yang masih aktif untuk nomor ini
+    let otp: Option<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, code_hash FROM phone_otps
+         WHERE phone = $1 AND consumed_at IS NULL AND expires_at > NOW()
+         ORDER BY created_at DESC LIMIT 1"
+    )
+    .bind(&phone)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let (otp_id, code_hash) = otp.ok_or((
+        StatusCode::BAD_REQUEST,
+        "Kode OTP tidak valid atau sudah kedaluwarsa".to_string(),
+    ))?;
+
+    // Jatah percobaan dipakai dulu secara atomik sebelum kode dicek, supaya tebakan yang
+    // bersamaan tidak bisa melewati batas OTP_MAX_ATTEMPTS
+    let attempt: Option<(Uuid,)> = sqlx::query_as(
+        "UPDATE phone_otps SET attempts = attempts + 1 WHERE id = $1 AND attempts < $2 RETURNING id"
+    )
+    .bind(otp_id)
+    .bind(config::get().otp_max_attempts)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    if attempt.is_none() {
+        return Err((StatusCode::TOO_MANY_REQUESTS, "Terlalu banyak percobaan, minta kode OTP baru".to_string()));
+    }
+
+    if hash_token(&format!("{}:{}", phone, payload.code.trim())) != code_hash {
+        return Err((StatusCode::BAD_REQUEST, "Kode OTP salah".to_string()));
+    }
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let consumed = sqlx::query("UPDATE phone_otps SET consumed_at = NOW() WHERE id = $1 AND consumed_at IS NULL")
+        .bind(otp_id)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?
+        .rows_affected();
+
+    if consumed == 0 {
+        return Err((StatusCode::BAD_REQUEST, "Kode OTP tidak valid atau sudah kedaluwarsa".to_string()));
+    }
+
+    sqlx::query("UPDATE users SET phone_verified_at = NOW() WHERE id = $1")
+        .bind(user.id)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("✅ Phone {} verified for user {}", phone, user.id);
+    Ok(RespJson(serde_json::json!({
+        "verified": true,
+        "phone": phone
+    })))
+}
+
+// Login dengan akun Google: user dicari berdasarkan google_sub, lalu email (di-link),
+// dan dibuat baru jika belum ada.
+pub async fn google_login(
+    Extension(pool): Extension<PgPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<GoogleLoginRequest>,
+) -> Result<(HeaderMap, RespJson<TokenResponse>), (StatusCode, String)> {
+    let client_id = config::get().google_client_id.as_deref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Login Google belum dikonfigurasi".to_string(),
+    ))?;
+
+    let identity = google::verify_id_token(&payload.id_token, client_id).await.map_err(|e| {
+        println!("Google login rejected: {}", e);
+        (StatusCode::UNAUTHORIZED, e)
+    })?;
+
+    println!("Google login attempt - Email: {}", identity.email);
+
+    let db_error = |e: sqlx::Error| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    };
+
+    let existing: Option<(Uuid, String, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, username, role, google_sub FROM users
+         WHERE google_sub = $1 OR LOWER(email) = LOWER($2)
+         ORDER BY (google_sub = $1) DESC NULLS LAST
+         LIMIT 1"
+    )
+    .bind(&identity.sub)
+    .bind(&identity.email)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let (user_id, username, role) = match existing {
+        Some((id, username, role, google_sub)) => {
+            if google_sub.is_none() {
+                // User lama yang daftar pakai email yang sama -> link akun Google
+                sqlx::query("UPDATE users SET google_sub = $1 WHERE id = $2")
+                    .bind(&identity.sub)
+                    .bind(id)
+                    .execute(&pool)
+                    .await
+                    .map_err(db_error)?;
+                println!("🔗 Linked Google account to user {}", username);
+            }
+            (id, username, role)
+        }
+        None => {
+            let id = Uuid::new_v4();
+            let base = identity.email.split('@').next().unwrap_or("user").to_lowercase();
+            let username = format!("{}{}", base, &id.simple().to_string()[..4]);
+            let full_name = identity.name.clone().unwrap_or_else(|| base.clone());
+            // Password acak yang tidak diketahui siapa pun; user bisa set lewat lupa password
+            let password_hash = hash_password(&random_token()).map_err(|e| {
+                println!("Password hashing error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Gagal membuat akun".to_string())
+            })?;
+
+            let (role,): (String,) = sqlx::query_as(
+                "INSERT INTO users (id, full_name, username, email, phone, password_hash, google_sub)
+                 VALUES ($1, $2, $3, $4, '', $5, $6)
+                 RETURNING role"
+            )
+            .bind(id)
+            .bind(&full_name)
+            .bind(&username)
+            .bind(&identity.email)
+            .bind(password_hash)
+            .bind(&identity.sub)
+            .fetch_one(&pool)
+            .await
+            .map_err(db_error)?;
+
+            println!("🆕 Created user {} from Google account", username);
+            (id, username, role)
+        }
+    };
+
+    let ip = client_ip(&headers, &addr);
+    let (token, refresh_token) = start_session(&pool, &headers, ip, user_id).await?;
+
+    audit::record(&pool, AuthEvent::LoginSuccess, Some(user_id), Some(ip), &headers, serde_json::json!({ "method": "google" })).await;
+
+    println!("Google login successful for user: {} ({})", username, user_id);
+    Ok(token_response(TokenResponse {
+        token,
+        refresh_token,
+        user_id: user_id.to_string(),
+        username,
+        role,
+        expires_in: config::get().access_token_ttl_secs,
+        refresh_expires_in: config::get().refresh_token_ttl_secs,
+    }))
+}
+
+// Tautkan order yang dibuat sebagai tamu (guest checkout) ke akun yang sedang login
+pub async fn claim_guest(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<ClaimRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, String)> {
+    let db_error = |e: sqlx::Error| {
+        println!("Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let moved = claim_guest_orders(&mut tx, payload.claim_token.trim(), user.id)
+        .await
+        .map_err(db_error)?
+        .ok_or((StatusCode::BAD_REQUEST, "Token klaim tidak valid atau sudah dipakai".to_string()))?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🔗 {} guest order(s) claimed by user {}", moved, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Order berhasil ditautkan ke akun kamu",
+        "orders_claimed": moved
+    })))
+}
@@ -0,0 +1,193 @@
+use axum::{
+    Router,
+    routing::{delete, get, post},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use chrono::NaiveDateTime;
+use serde_json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::model::blackout::{BlackoutPeriod, BlackoutQuery, CreateBlackoutRequest};
+
+const BLACKOUT_COLUMNS: &str = "b.id, b.branch, b.motor_id, b.starts_at, b.ends_at, b.reason, b.created_by, b.created_at";
+
+pub fn blackout_router() -> Router {
+    Router::new()
+        .route("/api/blackouts", get(list_blackouts))          // Admin / staff
+        .route("/api/blackouts", post(create_blackout))        // Admin
+        .route("/api/blackouts/:id", delete(delete_blackout))  // Admin
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Blackout pertama yang menutup booking motor-motor ini di cabang `branch` pada periode start..end:
+// blackout untuk motornya, untuk cabang booking / cabang motornya, atau untuk semua cabang
+pub(crate) async fn find_blackout(
+    conn: &mut PgConnection,
+    motor_ids: &[i32],
+    branch: Option<&str>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Option<BlackoutPeriod>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {} FROM blackout_periods b
+         WHERE tsrange(b.starts_at, b.ends_at, '[)') && tsrange($3, $4, '[)')
+           AND (b.motor_id = ANY($1)
+                OR (b.motor_id IS NULL AND (
+                    b.branch IS NULL
+                    OR LOWER(b.branch) = LOWER($2)
+                    OR EXISTS (SELECT 1 FROM motors m WHERE m.motor_id = ANY($1) AND LOWER(m.branch) = LOWER(b.branch))
+                )))
+         ORDER BY b.starts_at
+         LIMIT 1",
+        BLACKOUT_COLUMNS
+    ))
+    .bind(motor_ids)
+    .bind(branch)
+    .bind(start)
+    .bind(end)
+    .fetch_optional(conn)
+    .await
+}
+
+// Tolak booking yang jatuh di periode blackout (409 beserta periode dan alasannya)
+pub(crate) async fn ensure_no_blackout(
+    conn: &mut PgConnection,
+    motor_ids: &[i32],
+    branch: Option<&str>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    match find_blackout(conn, motor_ids, branch, start, end).await.map_err(db_error)? {
+        None => Ok(()),
+        Some(blackout) => {
+            println!("⛔ Booking blocked by blackout {} ({})", blackout.id, blackout.reason);
+            Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+                "error": format!("Bookings are closed for the selected period: {}", blackout.reason),
+                "code": "blackout",
+                "blackout": {
+                    "start": blackout.starts_at,
+                    "end": blackout.ends_at,
+                    "reason": blackout.reason,
+                    "motorId": blackout.motor_id,
+                    "branch": blackout.branch
+                }
+            }))))
+        }
+    }
+}
+
+// Daftar blackout; staff hanya melihat blackout cabangnya dan yang berlaku untuk semua cabang
+async fn list_blackouts(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<BlackoutQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = user.branch_scope()?.or(params.branch.as_deref());
+
+    let blackouts: Vec<BlackoutPeriod> = sqlx::query_as(&format!(
+        "SELECT {} FROM blackout_periods b
+         LEFT JOIN motors m ON m.motor_id = b.motor_id
+         WHERE ($1::date IS NULL OR b.ends_at > $1::date)
+           AND ($2::date IS NULL OR b.starts_at < $2::date + 1)
+           AND ($3::text IS NULL
+                OR (b.branch IS NULL AND b.motor_id IS NULL)
+                OR LOWER(COALESCE(b.branch, m.branch)) = LOWER($3))
+           AND ($4::int IS NULL OR b.motor_id = $4)
+         ORDER BY b.starts_at",
+        BLACKOUT_COLUMNS
+    ))
+    .bind(params.from)
+    .bind(params.to)
+    .bind(branch)
+    .bind(params.motor_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": blackouts
+    })))
+}
+
+async fn create_blackout(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreateBlackoutRequest>,
+) -> Result<RespJson<BlackoutPeriod>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    if payload.ends_at <= payload.starts_at {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "endsAt must be after startsAt"}))));
+    }
+
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Reason is required"}))));
+    }
+
+    let branch = payload.branch.as_deref().map(str::trim).filter(|b| !b.is_empty());
+
+    let blackout: BlackoutPeriod = sqlx::query_as(&format!(
+        "WITH b AS (
+             INSERT INTO blackout_periods (id, branch, motor_id, starts_at, ends_at, reason, created_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING *
+         )
+         SELECT {} FROM b",
+        BLACKOUT_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(branch)
+    .bind(payload.motor_id)
+    .bind(payload.starts_at)
+    .bind(payload.ends_at)
+    .bind(reason)
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23503") => {
+            (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Motor not found"})))
+        }
+        _ => db_error(e),
+    })?;
+
+    println!("📅 Blackout {} ({} - {}) created by {}", blackout.id, blackout.starts_at, blackout.ends_at, user.id);
+    Ok(RespJson(blackout))
+}
+
+async fn delete_blackout(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let blackout_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid blackout ID"}))))?;
+
+    let result = sqlx::query("DELETE FROM blackout_periods WHERE id = $1")
+        .bind(blackout_id)
+        .execute(&pool)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Blackout not found"}))));
+    }
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Blackout dihapus"
+    })))
+}
@@ -0,0 +1,270 @@
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use chrono::NaiveTime;
+use serde_json;
+use sqlx::PgPool;
+
+use crate::auth::AuthUser;
+use crate::config;
+use crate::model::branch::{Branch, BranchQuery, CreateBranchRequest, UpdateBranchRequest};
+
+const BRANCH_COLUMNS: &str = "name, address, phone, email, latitude, longitude, opening_time, closing_time, active, created_at, updated_at";
+
+pub fn branch_router() -> Router {
+    Router::new()
+        .route("/api/branches", get(list_branches))            // Publik: cabang aktif
+        .route("/api/branches", post(create_branch))           // Admin
+        .route("/api/branches/:name", get(get_branch))         // Publik
+        .route("/api/branches/:name", put(update_branch))      // Admin
+        .route("/api/branches/:name", delete(delete_branch))   // Admin
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Cari cabang dari nama (tanpa membedakan huruf besar / kecil dan spasi di ujung)
+pub(crate) async fn find_branch(pool: &PgPool, name: &str) -> Result<Option<Branch>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {} FROM branches WHERE LOWER(TRIM(name)) = LOWER(TRIM($1))",
+        BRANCH_COLUMNS
+    ))
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+// Cabang tujuan booking harus terdaftar dan masih aktif. Hasilnya dipakai untuk nama cabang
+// yang disimpan di order (penulisan sesuai tabel branches) dan jam operasionalnya.
+pub(crate) async fn resolve_booking_branch(
+    pool: &PgPool,
+    name: &str,
+) -> Result<Branch, (StatusCode, RespJson<serde_json::Value>)> {
+    let branch = find_branch(pool, name).await.map_err(db_error)?.ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown branch: {}", name.trim()),
+            "code": "unknown_branch"
+        })))
+    })?;
+
+    if !branch.active {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Branch {} is not accepting bookings", branch.name),
+            "code": "branch_inactive"
+        }))));
+    }
+
+    Ok(branch)
+}
+
+// Jam operasional cabang untuk pengambilan motor; jam yang tidak diisi ikut konfigurasi global
+pub(crate) fn branch_hours(branch: Option<&Branch>) -> (NaiveTime, NaiveTime) {
+    let (open, close) = config::get().branch_hours;
+    match branch {
+        Some(branch) => (branch.opening_time.unwrap_or(open), branch.closing_time.unwrap_or(close)),
+        None => (open, close),
+    }
+}
+
+fn validate_branch_fields(
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    opening_time: Option<NaiveTime>,
+    closing_time: Option<NaiveTime>,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    if latitude.is_some_and(|lat| !(-90.0..=90.0).contains(&lat))
+        || longitude.is_some_and(|lng| !(-180.0..=180.0).contains(&lng))
+    {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid coordinates"}))));
+    }
+
+    if let (Some(open), Some(close)) = (opening_time, closing_time) {
+        if close <= open {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "closingTime must be after openingTime"}))));
+        }
+    }
+
+    Ok(())
+}
+
+fn trimmed(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+// Daftar cabang; cabang nonaktif hanya ditampilkan ke admin / staff yang memintanya
+async fn list_branches(
+    user: Option<AuthUser>,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<BranchQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let include_inactive = params.include_inactive.unwrap_or(false);
+    if include_inactive {
+        user.as_ref()
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, RespJson(serde_json::json!({"error": "Unauthorized"}))))?
+            .require_staff()?;
+    }
+
+    let branches: Vec<Branch> = sqlx::query_as(&format!(
+        "SELECT {} FROM branches WHERE ($1 OR active) ORDER BY name",
+        BRANCH_COLUMNS
+    ))
+    .bind(include_inactive)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": branches
+    })))
+}
+
+async fn get_branch(
+    Extension(pool): Extension<PgPool>,
+    Path(name): Path<String>,
+) -> Result<RespJson<Branch>, (StatusCode, RespJson<serde_json::Value>)> {
+    let branch = find_branch(&pool, &name).await.map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Branch not found"}))))?;
+
+    Ok(RespJson(branch))
+}
+
+async fn create_branch(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreateBranchRequest>,
+) -> Result<RespJson<Branch>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Branch name is required"}))));
+    }
+    validate_branch_fields(payload.latitude, payload.longitude, payload.opening_time, payload.closing_time)?;
+
+    let branch: Branch = sqlx::query_as(&format!(
+        "INSERT INTO branches (name, address, phone, email, latitude, longitude, opening_time, closing_time, active)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING {}",
+        BRANCH_COLUMNS
+    ))
+    .bind(name)
+    .bind(trimmed(&payload.address))
+    .bind(trimmed(&payload.phone))
+    .bind(trimmed(&payload.email))
+    .bind(payload.latitude)
+    .bind(payload.longitude)
+    .bind(payload.opening_time)
+    .bind(payload.closing_time)
+    .bind(payload.active.unwrap_or(true))
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Branch already exists"})))
+        }
+        _ => db_error(e),
+    })?;
+
+    println!("🏢 Branch {} created by {}", branch.name, user.id);
+    Ok(RespJson(branch))
+}
+
+async fn update_branch(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(name): Path<String>,
+    Json(payload): Json<UpdateBranchRequest>,
+) -> Result<RespJson<Branch>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let current = find_branch(&pool, &name).await.map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Branch not found"}))))?;
+
+    if payload.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Branch name cannot be empty"}))));
+    }
+    validate_branch_fields(
+        payload.latitude,
+        payload.longitude,
+        payload.opening_time.or(current.opening_time),
+        payload.closing_time.or(current.closing_time),
+    )?;
+
+    // Ganti nama ikut mengubah motors.branch / orders.pilih_cabang / users.branch_id (ON UPDATE CASCADE)
+    let branch: Branch = sqlx::query_as(&format!(
+        "UPDATE branches
+         SET name = COALESCE($2, name),
+             address = COALESCE($3, address),
+             phone = COALESCE($4, phone),
+             email = COALESCE($5, email),
+             latitude = COALESCE($6, latitude),
+             longitude = COALESCE($7, longitude),
+             opening_time = COALESCE($8, opening_time),
+             closing_time = COALESCE($9, closing_time),
+             active = COALESCE($10, active),
+             updated_at = NOW()
+         WHERE name = $1
+         RETURNING {}",
+        BRANCH_COLUMNS
+    ))
+    .bind(&current.name)
+    .bind(trimmed(&payload.name))
+    .bind(trimmed(&payload.address))
+    .bind(trimmed(&payload.phone))
+    .bind(trimmed(&payload.email))
+    .bind(payload.latitude)
+    .bind(payload.longitude)
+    .bind(payload.opening_time)
+    .bind(payload.closing_time)
+    .bind(payload.active)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Branch already exists"})))
+        }
+        _ => db_error(e),
+    })?;
+
+    println!("🏢 Branch {} updated by {}", branch.name, user.id);
+    Ok(RespJson(branch))
+}
+
+// Cabang yang masih dipakai motor / order / staff tidak bisa dihapus, cukup dinonaktifkan
+async fn delete_branch(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(name): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let result = sqlx::query("DELETE FROM branches WHERE LOWER(TRIM(name)) = LOWER(TRIM($1))")
+        .bind(&name)
+        .execute(&pool)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23503") => {
+                (StatusCode::CONFLICT, RespJson(serde_json::json!({
+                    "error": "Branch is still used by motors, orders or staff; deactivate it instead"
+                })))
+            }
+            _ => db_error(e),
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Branch not found"}))));
+    }
+
+    println!("🏢 Branch {} deleted by {}", name, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Cabang dihapus"
+    })))
+}
@@ -0,0 +1,338 @@
+use axum::{
+    Router,
+    routing::{delete, get, patch, post, put},
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use chrono::NaiveDateTime;
+use serde_json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::auth::scope;
+use crate::model::driver::{AssignDriverRequest, CreateDriverRequest, Driver, UpdateDriverRequest};
+use crate::model::orders::OrderStatus;
+use crate::pricing::PriceBreakdown;
+use crate::routes::orders::authorize_order_staff;
+
+const DRIVER_COLUMNS: &str = "id, name, phone, daily_rate, branch, active, created_at";
+
+pub(crate) const DRIVER_ITEM_CODE: &str = "driver";
+
+pub fn driver_router() -> Router {
+    Router::new()
+        .route("/api/drivers", get(list_drivers))                // Admin / staff
+        .route("/api/drivers", post(create_driver))              // Admin
+        .route("/api/drivers/:id", patch(update_driver))         // Admin
+        .route("/api/orders/:id/driver", put(assign_driver))     // Admin / staff: tugaskan sopir
+        .route("/api/orders/:id/driver", delete(unassign_driver)) // Admin / staff: lepas sopir
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Permintaan sopir di payload booking: `driverId` untuk sopir tertentu, atau `withDriver: true`
+// untuk sopir mana saja yang tersedia (dipilih yang tarifnya paling murah)
+pub(crate) enum DriverRequest {
+    Any,
+    Specific(Uuid),
+}
+
+pub(crate) fn parse_driver_request(payload: &serde_json::Value) -> Result<Option<DriverRequest>, (StatusCode, RespJson<serde_json::Value>)> {
+    match payload.get("driverId") {
+        None | Some(serde_json::Value::Null) => {}
+        Some(value) => {
+            let driver_id = value.as_str()
+                .and_then(|id| Uuid::parse_str(id.trim()).ok())
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid driverId"}))))?;
+            return Ok(Some(DriverRequest::Specific(driver_id)));
+        }
+    }
+
+    match payload.get("withDriver") {
+        None | Some(serde_json::Value::Null) | Some(serde_json::Value::Bool(false)) => Ok(None),
+        Some(serde_json::Value::Bool(true)) => Ok(Some(DriverRequest::Any)),
+        Some(_) => Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "withDriver must be a boolean"})))),
+    }
+}
+
+// Cari sopir aktif yang tidak sedang bertugas di order lain pada periode ini (selain `exclude_order`).
+// Baris sopir dikunci sampai transaksi selesai; constraint orders_no_overlapping_driver_booking
+// tetap menjadi jaring pengaman terakhir.
+pub(crate) async fn find_available_driver(
+    conn: &mut PgConnection,
+    request: &DriverRequest,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_order: Option<Uuid>,
+    branch: Option<&str>,
+) -> Result<Option<Driver>, sqlx::Error> {
+    let driver_id = match request {
+        DriverRequest::Any => None,
+        DriverRequest::Specific(id) => Some(*id),
+    };
+
+    sqlx::query_as(
+        "SELECT d.id, d.name, d.phone, d.daily_rate, d.branch, d.active, d.created_at
+         FROM drivers d
+         WHERE d.active = TRUE
+           AND ($1::uuid IS NULL OR d.id = $1)
+           AND ($1::uuid IS NOT NULL OR $5::text IS NULL OR d.branch IS NULL OR LOWER(d.branch) = LOWER($5))
+           AND NOT EXISTS (
+               SELECT 1 FROM orders o
+               WHERE o.driver_id = d.id
+                 AND o.status <> 'cancelled'
+                 AND ($4::uuid IS NULL OR o.id <> $4)
+                 AND tsrange(o.tanggal_peminjaman + o.jam_peminjaman, o.tanggal_pengembalian + o.jam_pengembalian, '[)')
+                     && tsrange($2, $3, '[)')
+           )
+         ORDER BY d.daily_rate, d.name
+         LIMIT 1
+         FOR UPDATE OF d"
+    )
+    .bind(driver_id)
+    .bind(start)
+    .bind(end)
+    .bind(exclude_order)
+    .bind(branch)
+    .fetch_optional(conn)
+    .await
+}
+
+// Sama dengan find_available_driver, tetapi 409 jika tidak ada sopir yang bisa bertugas
+pub(crate) async fn reserve_driver(
+    conn: &mut PgConnection,
+    request: &DriverRequest,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_order: Option<Uuid>,
+    branch: Option<&str>,
+) -> Result<Driver, (StatusCode, RespJson<serde_json::Value>)> {
+    find_available_driver(conn, request, start, end, exclude_order, branch)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            let error = match request {
+                DriverRequest::Any => "No driver available for the selected period",
+                DriverRequest::Specific(_) => "Driver is not available for the selected period",
+            };
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": error})))
+        })
+}
+
+// Biaya sopir: tarif harian x lama sewa
+pub(crate) fn apply_driver_fee(breakdown: &mut PriceBreakdown, driver: &Driver) {
+    let days = breakdown.rental_days;
+    breakdown.add_item(DRIVER_ITEM_CODE, &format!("Sopir ({})", driver.name), days, i64::from(driver.daily_rate));
+}
+
+// Daftar sopir; staff hanya melihat sopir cabangnya (dan sopir tanpa cabang)
+async fn list_drivers(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = user.branch_scope()?;
+
+    let drivers: Vec<Driver> = sqlx::query_as(&format!(
+        "SELECT {} FROM drivers
+         WHERE ($1::text IS NULL OR branch IS NULL OR LOWER(branch) = LOWER($1))
+         ORDER BY active DESC, name",
+        DRIVER_COLUMNS
+    ))
+    .bind(branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": drivers
+    })))
+}
+
+async fn create_driver(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreateDriverRequest>,
+) -> Result<RespJson<Driver>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let name = payload.name.trim();
+    let phone = payload.phone.trim();
+    if name.is_empty() || phone.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Name and phone are required"}))));
+    }
+    if payload.daily_rate < 0 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "dailyRate cannot be negative"}))));
+    }
+
+    let driver: Driver = sqlx::query_as(&format!(
+        "INSERT INTO drivers (id, name, phone, daily_rate, branch) VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+        DRIVER_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(name)
+    .bind(phone)
+    .bind(payload.daily_rate)
+    .bind(payload.branch.as_deref().map(str::trim).filter(|b| !b.is_empty()))
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    println!("🧑‍✈️ Driver {} ({}) created by {}", driver.id, driver.name, user.id);
+    Ok(RespJson(driver))
+}
+
+// Ubah data sopir; sopir dinonaktifkan dengan active = false (tidak dipilih untuk booking baru)
+async fn update_driver(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateDriverRequest>,
+) -> Result<RespJson<Driver>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let driver_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid driver ID"}))))?;
+
+    if payload.daily_rate.is_some_and(|rate| rate < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "dailyRate cannot be negative"}))));
+    }
+
+    let driver: Option<Driver> = sqlx::query_as(&format!(
+        "UPDATE drivers
+         SET name = COALESCE($1, name), phone = COALESCE($2, phone), daily_rate = COALESCE($3, daily_rate),
+             branch = COALESCE($4, branch), active = COALESCE($5, active)
+         WHERE id = $6
+         RETURNING {}",
+        DRIVER_COLUMNS
+    ))
+    .bind(payload.name.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+    .bind(payload.phone.as_deref().map(str::trim).filter(|p| !p.is_empty()))
+    .bind(payload.daily_rate)
+    .bind(payload.branch.as_deref().map(str::trim))
+    .bind(payload.active)
+    .bind(driver_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    driver
+        .map(RespJson)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Driver not found"}))))
+}
+
+// Order yang sopirnya diatur admin / staff
+#[derive(sqlx::FromRow)]
+struct DriverOrder {
+    status: String,
+    with_driver: bool,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+}
+
+// Ambil dan kunci order; sopir hanya bisa diatur selama order belum dikonfirmasi (held / pending)
+async fn lock_driver_order(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+) -> Result<DriverOrder, (StatusCode, RespJson<serde_json::Value>)> {
+    let order: DriverOrder = sqlx::query_as(
+        "SELECT status, with_driver,
+                tanggal_peminjaman + jam_peminjaman AS start, tanggal_pengembalian + jam_pengembalian AS end
+         FROM orders WHERE id = $1 FOR UPDATE"
+    )
+    .bind(order_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    if order.status != OrderStatus::Held.as_str() && order.status != OrderStatus::Pending.as_str() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Driver can only be changed on pending bookings",
+            "currentStatus": order.status
+        }))));
+    }
+
+    if !order.with_driver {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Booking did not request a driver"}))));
+    }
+
+    Ok(order)
+}
+
+// Tugaskan (atau ganti) sopir untuk order yang memesan sopir. Harga sopir tetap sesuai yang
+// disepakati saat booking.
+async fn assign_driver(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<AssignDriverRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let order = lock_driver_order(&mut tx, order_uuid).await?;
+
+    let driver = reserve_driver(
+        &mut tx, &DriverRequest::Specific(payload.driver_id), order.start, order.end, Some(order_uuid), None,
+    ).await?;
+
+    sqlx::query("UPDATE orders SET driver_id = $1 WHERE id = $2")
+        .bind(driver.id)
+        .bind(order_uuid)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23P01") => {
+                (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Driver is not available for the selected period"})))
+            }
+            _ => db_error(e),
+        })?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🧑‍✈️ Driver {} assigned to order {} by {}", driver.id, order_uuid, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Sopir berhasil ditugaskan",
+        "driver": driver
+    })))
+}
+
+// Lepas sopir dari order; order tetap memesan sopir dan perlu ditugaskan ulang
+async fn unassign_driver(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    lock_driver_order(&mut tx, order_uuid).await?;
+
+    sqlx::query("UPDATE orders SET driver_id = NULL WHERE id = $1")
+        .bind(order_uuid)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🧑‍✈️ Driver unassigned from order {} by {}", order_uuid, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Sopir dilepas dari booking"
+    })))
+}
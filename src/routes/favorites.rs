@@ -0,0 +1,111 @@
+use axum::{
+    Router,
+    routing::post,
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use chrono::{DateTime, Utc};
+use serde_json;
+use sqlx::{PgPool, Row};
+
+use crate::auth::AuthUser;
+use crate::model::motor::FavoriteMotor;
+use crate::routes::motor::{motor_from_row, MOTOR_COLUMNS};
+
+// GET /api/users/me/favorites didaftarkan di users_router
+pub fn favorite_router() -> Router {
+    Router::new()
+        .route("/api/motors/:id/favorite", post(add_favorite).delete(remove_favorite))
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Tandai motor sebagai favorit (idempoten: menandai dua kali tidak error)
+async fn add_favorite(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let result = sqlx::query(
+        "INSERT INTO motor_favorites (user_id, motor_id)
+         SELECT $1, motor_id FROM motors WHERE motor_id = $2 AND deleted_at IS NULL
+         ON CONFLICT (user_id, motor_id) DO NOTHING"
+    )
+    .bind(user.id)
+    .bind(motor_id)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    // Tidak ada baris baru: motor tidak ada, atau memang sudah jadi favorit
+    if result.rows_affected() == 0 {
+        let (exists,): (bool,) = sqlx::query_as("SELECT EXISTS (SELECT 1 FROM motors WHERE motor_id = $1 AND deleted_at IS NULL)")
+            .bind(motor_id)
+            .fetch_one(&pool)
+            .await
+            .map_err(db_error)?;
+        if !exists {
+            return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Motor not found"}))));
+        }
+    }
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "motorId": motor_id,
+        "favorited": true
+    })))
+}
+
+async fn remove_favorite(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    sqlx::query("DELETE FROM motor_favorites WHERE user_id = $1 AND motor_id = $2")
+        .bind(user.id)
+        .bind(motor_id)
+        .execute(&pool)
+        .await
+        .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "motorId": motor_id,
+        "favorited": false
+    })))
+}
+
+// Motor favorit user yang login, terbaru ditandai dulu. Motor yang sudah dihapus tidak ditampilkan.
+pub(crate) async fn list_my_favorites(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let rows = sqlx::query(&format!(
+        "SELECT {}, f.created_at AS favorited_at
+         FROM motor_favorites f JOIN motors USING (motor_id)
+         WHERE f.user_id = $1 AND motors.deleted_at IS NULL
+         ORDER BY f.created_at DESC",
+        MOTOR_COLUMNS
+    ))
+    .bind(user.id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let favorites: Vec<FavoriteMotor> = rows.iter()
+        .map(|row| FavoriteMotor {
+            motor: motor_from_row(row),
+            favorited_at: row.try_get::<DateTime<Utc>, _>("favorited_at").unwrap_or_else(|_| Utc::now()),
+        })
+        .collect();
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "total": favorites.len(),
+        "data": favorites
+    })))
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Extension,
+    },
+    http::StatusCode,
+    response::{Json as RespJson, Response},
+};
+use serde_json;
+use sqlx::PgPool;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::fleet::FleetFeed;
+use crate::model::telemetry::FleetUnit;
+
+// Posisi terakhir dan booking berjalan tiap unit yang belum pensiun. `unit_ids` membatasi ke unit
+// tertentu (dipakai setelah telemetry masuk), `branch` ke satu cabang (staff).
+pub(crate) async fn fleet_positions(
+    pool: &PgPool,
+    unit_ids: Option<&[Uuid]>,
+    branch: Option<&str>,
+) -> Result<Vec<FleetUnit>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT u.id AS unit_id, u.motor_id, m.motor_name, u.plate_number, COALESCE(u.branch, m.branch) AS branch,
+                u.status AS unit_status, o.order_number, o.status AS order_status,
+                t.latitude, t.longitude, t.speed_kmh, t.heading, t.battery_percent, t.recorded_at
+         FROM motor_units u
+         JOIN motors m ON m.motor_id = u.motor_id
+         LEFT JOIN LATERAL (
+             SELECT latitude, longitude, speed_kmh, heading, battery_percent, recorded_at
+             FROM unit_telemetry WHERE unit_id = u.id
+             ORDER BY recorded_at DESC LIMIT 1
+         ) t ON TRUE
+         LEFT JOIN LATERAL (
+             SELECT o.order_number, o.status FROM orders o
+             WHERE (o.unit_id = u.id OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.unit_id = u.id))
+               AND o.status IN ('picked_up', 'overdue')
+             ORDER BY o.tanggal_peminjaman DESC, o.jam_peminjaman DESC LIMIT 1
+         ) o ON TRUE
+         WHERE u.status <> 'retired' AND m.deleted_at IS NULL
+           AND ($1::uuid[] IS NULL OR u.id = ANY($1))
+           AND ($2::text IS NULL OR LOWER(TRIM(COALESCE(u.branch, m.branch))) = LOWER(TRIM($2)))
+         ORDER BY m.motor_name, u.plate_number"
+    )
+    .bind(unit_ids)
+    .bind(branch)
+    .fetch_all(pool)
+    .await
+}
+
+// Peta armada real-time (WebSocket). Pesan pertama {"type": "snapshot", "units": [...]} berisi semua
+// unit, lalu {"type": "position", "unit": {...}} setiap ada telemetry baru. Staff hanya melihat unit
+// di cabangnya.
+pub(crate) async fn fleet_live(
+    ws: WebSocketUpgrade,
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Extension(feed): Extension<FleetFeed>,
+) -> Result<Response, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = user.branch_scope()?.map(str::to_string);
+
+    // Subscribe sebelum snapshot supaya tidak ada update yang terlewat di antaranya
+    let updates = feed.subscribe();
+    let snapshot = fleet_positions(&pool, None, branch.as_deref()).await.map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    println!("🛰️ Fleet live opened by {} ({} units)", user.id, snapshot.len());
+    Ok(ws.on_upgrade(move |socket| stream_fleet(socket, snapshot, updates, branch)))
+}
+
+async fn stream_fleet(
+    mut socket: WebSocket,
+    snapshot: Vec<FleetUnit>,
+    mut updates: broadcast::Receiver<FleetUnit>,
+    branch: Option<String>,
+) {
+    let message = serde_json::json!({"type": "snapshot", "units": snapshot});
+    if socket.send(Message::Text(message.to_string())).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(unit) => {
+                    let visible = match branch.as_deref() {
+                        None => true,
+                        Some(branch) => unit.branch.as_deref().is_some_and(|b| b.trim().eq_ignore_ascii_case(branch.trim())),
+                    };
+                    if !visible {
+                        continue;
+                    }
+                    let message = serde_json::json!({"type": "position", "unit": unit});
+                    if socket.send(Message::Text(message.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    println!("⚠️  Fleet live client lagging, {} updates skipped", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // Ping dijawab otomatis; pesan lain dari dashboard diabaikan
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use serde_json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::config;
+use crate::mailer::SharedMailer;
+use crate::model::geofence::{
+    CreateGeofenceZoneRequest, GeofenceAlert, GeofenceAlertKind, GeofenceAlertQuery, GeofenceZone, GeofenceZoneQuery,
+    ResolveGeofenceAlertRequest, UpdateGeofenceZoneRequest,
+};
+use crate::model::telemetry::FleetUnit;
+use crate::routes::branches::find_branch;
+
+const GEOFENCE_ZONE_COLUMNS: &str = "id, branch, name, center_latitude, center_longitude, radius_m, active, created_at, updated_at";
+
+const GEOFENCE_ALERT_COLUMNS: &str = "a.id, a.unit_id, u.motor_id, u.plate_number, a.branch, a.kind, a.order_number,
+    a.latitude, a.longitude, a.speed_kmh, a.recorded_at, a.created_at, a.resolved_at, a.resolved_by, a.resolution_note";
+
+// Radius bumi rata-rata (meter) untuk rumus haversine
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+pub fn geofence_router() -> Router {
+    Router::new()
+        .route("/api/geofences", get(list_geofence_zones))                          // Admin
+        .route("/api/geofences", post(create_geofence_zone))                        // Admin
+        .route("/api/geofences/:id", put(update_geofence_zone))                     // Admin
+        .route("/api/geofences/:id", delete(delete_geofence_zone))                  // Admin
+        .route("/api/geofences/alerts", get(list_geofence_alerts))                  // Admin / staff cabang
+        .route("/api/geofences/alerts/:id/resolve", post(resolve_geofence_alert))   // Admin / staff cabang
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Jarak dua titik di permukaan bumi (meter)
+fn distance_m(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+fn validate_zone(latitude: f64, longitude: f64, radius_m: i32) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let valid_center = latitude.is_finite() && (-90.0..=90.0).contains(&latitude)
+        && longitude.is_finite() && (-180.0..=180.0).contains(&longitude);
+    if !valid_center {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid zone center coordinates"}))));
+    }
+    if !(50..=200_000).contains(&radius_m) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "radiusM must be between 50 and 200000"}))));
+    }
+    Ok(())
+}
+
+async fn list_geofence_zones(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<GeofenceZoneQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+    let branch = params.branch.as_deref().map(str::trim).filter(|b| !b.is_empty());
+
+    let zones: Vec<GeofenceZone> = sqlx::query_as(&format!(
+        "SELECT {} FROM geofence_zones
+         WHERE ($1 OR active)
+           AND ($2::text IS NULL OR LOWER(TRIM(branch)) = LOWER(TRIM($2)))
+         ORDER BY branch, name",
+        GEOFENCE_ZONE_COLUMNS
+    ))
+    .bind(params.include_inactive.unwrap_or(false))
+    .bind(branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": zones
+    })))
+}
+
+async fn create_geofence_zone(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreateGeofenceZoneRequest>,
+) -> Result<RespJson<GeofenceZone>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Zone name is required"}))));
+    }
+    validate_zone(payload.center_latitude, payload.center_longitude, payload.radius_m)?;
+    let branch = find_branch(&pool, payload.branch.trim()).await.map_err(db_error)?.ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Unknown branch: {}", payload.branch.trim())})))
+    })?;
+
+    let zone: GeofenceZone = sqlx::query_as(&format!(
+        "INSERT INTO geofence_zones (id, branch, name, center_latitude, center_longitude, radius_m, active)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING {}",
+        GEOFENCE_ZONE_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(&branch.name)
+    .bind(name)
+    .bind(payload.center_latitude)
+    .bind(payload.center_longitude)
+    .bind(payload.radius_m)
+    .bind(payload.active.unwrap_or(true))
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    println!("🗺️ Geofence zone {} ({}, {} m) created by {}", zone.name, zone.branch, zone.radius_m, user.id);
+    Ok(RespJson(zone))
+}
+
+async fn update_geofence_zone(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateGeofenceZoneRequest>,
+) -> Result<RespJson<GeofenceZone>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let current: GeofenceZone = sqlx::query_as(&format!("SELECT {} FROM geofence_zones WHERE id = $1", GEOFENCE_ZONE_COLUMNS))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Geofence zone not found"}))))?;
+
+    if payload.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Zone name cannot be empty"}))));
+    }
+    validate_zone(
+        payload.center_latitude.unwrap_or(current.center_latitude),
+        payload.center_longitude.unwrap_or(current.center_longitude),
+        payload.radius_m.unwrap_or(current.radius_m),
+    )?;
+
+    let zone: GeofenceZone = sqlx::query_as(&format!(
+        "UPDATE geofence_zones
+         SET name = COALESCE($2, name),
+             center_latitude = COALESCE($3, center_latitude),
+             center_longitude = COALESCE($4, center_longitude),
+             radius_m = COALESCE($5, radius_m),
+             active = COALESCE($6, active),
+             updated_at = NOW()
+         WHERE id = $1
+         RETURNING {}",
+        GEOFENCE_ZONE_COLUMNS
+    ))
+    .bind(id)
+    .bind(payload.name.as_deref().map(str::trim))
+    .bind(payload.center_latitude)
+    .bind(payload.center_longitude)
+    .bind(payload.radius_m)
+    .bind(payload.active)
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    println!("🗺️ Geofence zone {} updated by {}", zone.name, user.id);
+    Ok(RespJson(zone))
+}
+
+// Alert yang sudah tercatat tetap disimpan (zona di alert tidak direferensikan)
+async fn delete_geofence_zone(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let result = sqlx::query("DELETE FROM geofence_zones WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Geofence zone not found"}))));
+    }
+
+    println!("🗺️ Geofence zone {} deleted by {}", id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Zona geofence dihapus"
+    })))
+}
+
+// Alert terbaru dulu; staff hanya melihat alert cabangnya
+async fn list_geofence_alerts(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<GeofenceAlertQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = match user.branch_scope()? {
+        Some(own_branch) => Some(own_branch),
+        None => params.branch.as_deref().map(str::trim).filter(|b| !b.is_empty()),
+    };
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let alerts: Vec<GeofenceAlert> = sqlx::query_as(&format!(
+        "SELECT {} FROM geofence_alerts a
+         JOIN motor_units u ON u.id = a.unit_id
+         WHERE ($1 OR a.resolved_at IS NULL)
+           AND ($2::text IS NULL OR LOWER(TRIM(a.branch)) = LOWER(TRIM($2)))
+           AND ($3::text IS NULL OR a.kind = $3)
+         ORDER BY a.created_at DESC
+         LIMIT $4 OFFSET $5",
+        GEOFENCE_ALERT_COLUMNS
+    ))
+    .bind(params.include_resolved.unwrap_or(false))
+    .bind(branch)
+    .bind(params.kind.map(|kind| kind.as_str()))
+    .bind(limit)
+    .bind((page - 1) * limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": alerts,
+        "page": page,
+        "limit": limit
+    })))
+}
+
+// Tandai alert sudah ditangani. Setelah resolve, unit yang masih di luar zona / masih bergerak
+// akan memicu alert baru di telemetry berikutnya.
+async fn resolve_geofence_alert(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ResolveGeofenceAlertRequest>,
+) -> Result<RespJson<GeofenceAlert>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+
+    let alert_branch: Option<String> = sqlx::query_scalar::<_, Option<String>>("SELECT branch FROM geofence_alerts WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Geofence alert not found"}))))?;
+    user.require_branch(alert_branch.as_deref())?;
+
+    let note = payload.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let alert: GeofenceAlert = sqlx::query_as(&format!(
+        "WITH resolved AS (
+             UPDATE geofence_alerts SET resolved_at = NOW(), resolved_by = $2, resolution_note = $3
+             WHERE id = $1 AND resolved_at IS NULL
+             RETURNING *
+         )
+         SELECT {} FROM resolved a JOIN motor_units u ON u.id = a.unit_id",
+        GEOFENCE_ALERT_COLUMNS
+    ))
+    .bind(id)
+    .bind(user.id)
+    .bind(note)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Geofence alert is already resolved"}))))?;
+
+    println!("🗺️ Geofence alert {} ({}) resolved by {}", alert.id, alert.kind, user.id);
+    Ok(RespJson(alert))
+}
+
+// Cek posisi terbaru unit terhadap zona cabangnya dan status sewanya. Dipanggil di background setelah
+// telemetry masuk; alert yang sama (unit + jenis) tidak dibuat ulang selama belum di-resolve.
+pub(crate) async fn check_geofences(pool: &PgPool, mailer: &SharedMailer, units: &[FleetUnit]) {
+    let mut branches: Vec<String> = units.iter().filter_map(|unit| unit.branch.clone()).collect();
+    branches.sort_unstable();
+    branches.dedup();
+    let zones: Vec<GeofenceZone> = match sqlx::query_as(&format!(
+        "SELECT {} FROM geofence_zones
+         WHERE active AND LOWER(TRIM(branch)) IN (SELECT LOWER(TRIM(b)) FROM UNNEST($1::text[]) b)",
+        GEOFENCE_ZONE_COLUMNS
+    ))
+    .bind(&branches)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(zones) => zones,
+        Err(e) => {
+            eprintln!("⚠️  Gagal ambil zona geofence: {}", e);
+            return;
+        }
+    };
+
+    let moving_speed = config::get().geofence_moving_speed_kmh;
+    for unit in units {
+        let (Some(latitude), Some(longitude), Some(recorded_at)) = (unit.latitude, unit.longitude, unit.recorded_at) else {
+            continue;
+        };
+
+        let mut kinds = Vec::new();
+        // Cabang tanpa zona aktif tidak dibatasi areanya
+        let branch_zones: Vec<&GeofenceZone> = zones
+            .iter()
+            .filter(|zone| unit.branch.as_deref().is_some_and(|b| b.trim().eq_ignore_ascii_case(zone.branch.trim())))
+            .collect();
+        let inside = branch_zones.iter().any(|zone| {
+            distance_m(latitude, longitude, zone.center_latitude, zone.center_longitude) <= zone.radius_m as f64
+        });
+        if !branch_zones.is_empty() && !inside {
+            kinds.push(GeofenceAlertKind::OutOfZone);
+        }
+        if unit.order_number.is_none() && unit.speed_kmh.is_some_and(|speed| speed > moving_speed) {
+            kinds.push(GeofenceAlertKind::MovingWithoutOrder);
+        }
+
+        for kind in kinds {
+            let created = sqlx::query(
+                "INSERT INTO geofence_alerts (id, unit_id, branch, kind, order_number, latitude, longitude, speed_kmh, recorded_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (unit_id, kind) WHERE resolved_at IS NULL DO NOTHING"
+            )
+            .bind(Uuid::new_v4())
+            .bind(unit.unit_id)
+            .bind(&unit.branch)
+            .bind(kind.as_str())
+            .bind(&unit.order_number)
+            .bind(latitude)
+            .bind(longitude)
+            .bind(unit.speed_kmh)
+            .bind(recorded_at)
+            .execute(pool)
+            .await;
+
+            match created {
+                Ok(result) if result.rows_affected() > 0 => {
+                    println!("🚨 Geofence alert {} for unit {} ({})", kind.as_str(), unit.plate_number, unit.motor_name);
+                    notify_geofence_staff(pool, mailer, unit, kind, latitude, longitude).await;
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️  Gagal simpan alert geofence unit {}: {}", unit.unit_id, e),
+            }
+        }
+    }
+}
+
+// Email ke staff aktif di cabang unit
+async fn notify_geofence_staff(
+    pool: &PgPool,
+    mailer: &SharedMailer,
+    unit: &FleetUnit,
+    kind: GeofenceAlertKind,
+    latitude: f64,
+    longitude: f64,
+) {
+    let Some(branch) = unit.branch.as_deref() else {
+        return;
+    };
+    let staff_emails: Vec<String> = match sqlx::query_scalar(
+        "SELECT email FROM users
+         WHERE role = 'staff' AND status = 'active' AND deleted_at IS NULL
+           AND LOWER(TRIM(branch_id)) = LOWER(TRIM($1))"
+    )
+    .bind(branch)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(emails) => emails,
+        Err(e) => {
+            eprintln!("⚠️  Gagal ambil staff cabang {} untuk alert geofence: {}", branch, e);
+            return;
+        }
+    };
+
+    if staff_emails.is_empty() {
+        println!("⚠️  Tidak ada staff aktif di cabang {} untuk alert geofence unit {}", branch, unit.plate_number);
+        return;
+    }
+
+    let (subject, reason) = match kind {
+        GeofenceAlertKind::OutOfZone => (
+            format!("Motor {} keluar area {}", unit.plate_number, branch),
+            "berada di luar area operasional cabang".to_string(),
+        ),
+        GeofenceAlertKind::MovingWithoutOrder => (
+            format!("Motor {} bergerak tanpa order", unit.plate_number),
+            format!("bergerak {:.0} km/jam padahal tidak sedang disewa", unit.speed_kmh.unwrap_or_default()),
+        ),
+    };
+    let body = format!(
+        "Unit {} ({}) {}.\n\nPosisi terakhir: https://maps.google.com/?q={},{}\n\n\
+         Mohon cek unit dan tandai alert sebagai selesai di dashboard setelah ditindaklanjuti.",
+        unit.plate_number, unit.motor_name, reason, latitude, longitude
+    );
+    for email in staff_emails {
+        if let Err(e) = mailer.send(&email, &subject, &body).await {
+            eprintln!("⚠️  Gagal kirim alert geofence unit {} ke {}: {}", unit.plate_number, email, e);
+        }
+    }
+}
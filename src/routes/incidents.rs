@@ -0,0 +1,235 @@
+use axum::{
+    Router,
+    routing::{get, patch, post},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use serde_json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::auth::scope;
+use crate::model::incident::{CreateIncidentRequest, Incident, IncidentQuery, IncidentStatus, UpdateIncidentRequest};
+use crate::routes::orders::{authorize_order_access, authorize_order_staff};
+
+const MAX_INCIDENT_PHOTOS: usize = 10;
+
+// SELECT insiden + nomor order + motor (dipetakan ke Incident)
+const INCIDENT_SELECT: &str = "SELECT i.id, i.order_id, o.order_number, i.motor_id, m.motor_name, o.pilih_cabang AS branch,
+            i.severity, i.description, i.photos, i.estimated_cost, i.status, i.resolution_notes,
+            i.reported_by, i.reported_at, i.resolved_at
+     FROM incidents i
+     JOIN orders o ON o.id = i.order_id
+     LEFT JOIN motors m ON m.motor_id = i.motor_id";
+
+pub fn incident_router() -> Router {
+    Router::new()
+        .route("/api/orders/:id/incidents", post(create_incident))
+        .route("/api/orders/:id/incidents", get(list_order_incidents))
+        .route("/api/incidents", get(list_incidents))         // Admin / staff: insiden yang belum selesai
+        .route("/api/incidents/:id", patch(update_incident))  // Admin / staff: update penanganan
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+async fn fetch_incident(pool: &PgPool, incident_id: Uuid) -> Result<Option<Incident>, sqlx::Error> {
+    sqlx::query_as(&format!("{} WHERE i.id = $1", INCIDENT_SELECT))
+        .bind(incident_id)
+        .fetch_optional(pool)
+        .await
+}
+
+// Lapor kerusakan / kecelakaan pada order. Bisa oleh pemilik order maupun staff cabang;
+// unit motor diambil dari order.
+async fn create_incident(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<CreateIncidentRequest>,
+) -> Result<RespJson<Incident>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let description = payload.description.trim();
+    if description.is_empty() || description.chars().count() > 2000 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Description must be between 1 and 2000 characters"
+        }))));
+    }
+    if payload.estimated_cost.is_some_and(|cost| cost < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "estimatedCost cannot be negative"}))));
+    }
+    if payload.photos.len() > MAX_INCIDENT_PHOTOS {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("At most {} photos per incident", MAX_INCIDENT_PHOTOS)
+        }))));
+    }
+    if payload.photos.iter().any(|url| !(url.starts_with("https://") || url.starts_with("http://"))) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Photos must be http(s) URLs"}))));
+    }
+
+    let incident_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO incidents (id, order_id, motor_id, severity, description, photos, estimated_cost, status, reported_by)
+         SELECT $1, o.id, o.motor_id, $2, $3, $4, $5, $6, $7 FROM orders o WHERE o.id = $8"
+    )
+    .bind(incident_id)
+    .bind(payload.severity.as_str())
+    .bind(description)
+    .bind(&payload.photos)
+    .bind(payload.estimated_cost)
+    .bind(IncidentStatus::Open.as_str())
+    .bind(user.id)
+    .bind(order_uuid)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let incident = fetch_incident(&pool, incident_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    println!("🚨 {} incident {} reported for order {} by {}", incident.severity, incident_id, order_uuid, user.id);
+    Ok(RespJson(incident))
+}
+
+// Daftar insiden pada satu order
+async fn list_order_incidents(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+
+    let incidents: Vec<Incident> = sqlx::query_as(&format!(
+        "{} WHERE i.order_id = $1 ORDER BY i.reported_at DESC",
+        INCIDENT_SELECT
+    ))
+    .bind(order_uuid)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": incidents,
+        "total": incidents.len()
+    })))
+}
+
+// List insiden untuk admin / staff. Default hanya yang belum selesai (open + in_progress);
+// ?status=resolved / ?status=all untuk melihat yang lain. Staff hanya melihat cabangnya.
+async fn list_incidents(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<IncidentQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    user.require_scope(scope::ORDERS_READ)?;
+
+    let status = match params.status.as_deref() {
+        None | Some("all") => None,
+        Some(status) => Some(status.parse::<IncidentStatus>()
+            .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?),
+    };
+    let unresolved_only = params.status.is_none();
+
+    let branch = match user.branch_scope()? {
+        Some(own_branch) => Some(own_branch.to_string()),
+        None => params.branch.filter(|b| !b.trim().is_empty()),
+    };
+
+    let incidents: Vec<Incident> = sqlx::query_as(&format!(
+        "{}
+         WHERE ($1::text IS NULL OR i.status = $1)
+           AND ($2 = FALSE OR i.status <> $3)
+           AND ($4::text IS NULL OR LOWER(TRIM(o.pilih_cabang)) = LOWER(TRIM($4)))
+         ORDER BY CASE i.severity WHEN 'major' THEN 0 WHEN 'moderate' THEN 1 ELSE 2 END, i.reported_at ASC",
+        INCIDENT_SELECT
+    ))
+    .bind(status.map(|s| s.as_str()))
+    .bind(unresolved_only)
+    .bind(IncidentStatus::Resolved.as_str())
+    .bind(&branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": incidents,
+        "total": incidents.len()
+    })))
+}
+
+// Staff memperbarui penanganan insiden (status, estimasi biaya, catatan penyelesaian)
+async fn update_incident(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(incident_id): Path<String>,
+    Json(payload): Json<UpdateIncidentRequest>,
+) -> Result<RespJson<Incident>, (StatusCode, RespJson<serde_json::Value>)> {
+    let incident_uuid = Uuid::parse_str(&incident_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid incident ID"}))))?;
+
+    let existing = fetch_incident(&pool, incident_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Incident not found"}))))?;
+
+    authorize_order_staff(&pool, &user, existing.order_id, scope::ORDERS_WRITE).await?;
+
+    if payload.estimated_cost.is_some_and(|cost| cost < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "estimatedCost cannot be negative"}))));
+    }
+    let resolution_notes = payload.resolution_notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if payload.status == Some(IncidentStatus::Resolved) && resolution_notes.is_none() && existing.resolution_notes.is_none() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "resolutionNotes is required to resolve an incident"
+        }))));
+    }
+
+    sqlx::query(
+        "UPDATE incidents
+         SET status = COALESCE($1, status),
+             severity = COALESCE($2, severity),
+             estimated_cost = COALESCE($3, estimated_cost),
+             resolution_notes = COALESCE($4, resolution_notes),
+             resolved_at = CASE
+                 WHEN COALESCE($1, status) = $5 THEN COALESCE(resolved_at, NOW())
+                 ELSE NULL
+             END,
+             updated_by = $6,
+             updated_at = NOW()
+         WHERE id = $7"
+    )
+    .bind(payload.status.map(|s| s.as_str()))
+    .bind(payload.severity.map(|s| s.as_str()))
+    .bind(payload.estimated_cost)
+    .bind(resolution_notes)
+    .bind(IncidentStatus::Resolved.as_str())
+    .bind(user.id)
+    .bind(incident_uuid)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let incident = fetch_incident(&pool, incident_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Incident not found"}))))?;
+
+    println!("🛠️  Incident {} updated to {} by {}", incident_uuid, incident.status, user.id);
+    Ok(RespJson(incident))
+}
@@ -0,0 +1,334 @@
+use axum::{
+    Router,
+    routing::{get, patch, post},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    middleware,
+    response::Json as RespJson,
+};
+use chrono::NaiveDateTime;
+use serde_json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::auth::scope;
+use crate::middleware::scope::{require_scope, RequireScope};
+use crate::model::maintenance::{
+    CreateMaintenanceRequest, MaintenanceRecord, MaintenanceStatus, UpcomingMaintenanceQuery, UpdateMaintenanceRequest,
+};
+use crate::routes::motor::authorize_motor_branch;
+
+const MAINTENANCE_COLUMNS: &str = "mt.id, mt.unit_id, u.plate_number, mt.kind, mt.status, mt.starts_at, mt.ends_at, mt.performed_at,
+    mt.odometer_km, mt.cost, mt.notes, mt.created_by, mt.created_at, mt.updated_at";
+
+pub fn maintenance_router() -> Router {
+    Router::new()
+        .route("/api/motors/:id/units/:unit_id/maintenance", get(list_unit_maintenance))
+        .route("/api/motors/:id/units/:unit_id/maintenance", post(create_maintenance).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/units/:unit_id/maintenance/:maintenance_id", patch(update_maintenance).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/maintenance/upcoming", get(list_upcoming_maintenance)) // Admin / staff
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Staff hanya untuk motor cabangnya, dan unit harus milik motor di path
+async fn authorize_unit(
+    pool: &PgPool,
+    user: &AuthUser,
+    motor_id: i32,
+    unit_id: Uuid,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(pool, user, motor_id).await?;
+
+    let unit: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM motor_units WHERE id = $1 AND motor_id = $2")
+        .bind(unit_id)
+        .bind(motor_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?;
+
+    unit.map(|_| ())
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Motor unit not found"}))))
+}
+
+fn validate_readings(odometer_km: Option<i32>, cost: Option<i64>) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    if odometer_km.is_some_and(|km| km < 0) || cost.is_some_and(|cost| cost < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "odometerKm and cost cannot be negative"}))));
+    }
+    Ok(())
+}
+
+// Booking aktif unit ini yang bertabrakan dengan jendela perawatan; perawatan tidak boleh dijadwalkan
+// di atas booking yang sudah ada (pindahkan dulu booking-nya ke unit lain)
+async fn ensure_no_unit_bookings(
+    conn: &mut PgConnection,
+    unit_id: Uuid,
+    starts_at: NaiveDateTime,
+    ends_at: NaiveDateTime,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let bookings: Vec<(String, NaiveDateTime, NaiveDateTime)> = sqlx::query_as(
+        "SELECT o.order_number, o.tanggal_peminjaman + o.jam_peminjaman, o.tanggal_pengembalian + o.jam_pengembalian
+         FROM orders o
+         WHERE (o.unit_id = $1 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.unit_id = $1))
+           AND o.status NOT IN ('cancelled', 'returned', 'completed')
+           AND tsrange(o.tanggal_peminjaman + o.jam_peminjaman, o.tanggal_pengembalian + o.jam_pengembalian, '[)')
+               && tsrange($2, $3, '[)')
+         ORDER BY 2"
+    )
+    .bind(unit_id)
+    .bind(starts_at)
+    .bind(ends_at)
+    .fetch_all(conn)
+    .await
+    .map_err(db_error)?;
+
+    if bookings.is_empty() {
+        return Ok(());
+    }
+
+    Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+        "error": "Motor unit is booked during the maintenance window",
+        "bookings": bookings.iter()
+            .map(|(order_number, start, end)| serde_json::json!({"bookingCode": order_number, "start": start, "end": end}))
+            .collect::<Vec<_>>()
+    }))))
+}
+
+async fn fetch_maintenance(conn: &mut PgConnection, id: Uuid) -> Result<MaintenanceRecord, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {} FROM unit_maintenance mt JOIN motor_units u ON u.id = mt.unit_id WHERE mt.id = $1",
+        MAINTENANCE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(conn)
+    .await
+}
+
+// Riwayat servis dan jadwal perawatan satu unit, terbaru dulu
+async fn list_unit_maintenance(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path((motor_id, unit_id)): Path<(i32, Uuid)>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    authorize_unit(&pool, &user, motor_id, unit_id).await?;
+
+    let records: Vec<MaintenanceRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM unit_maintenance mt JOIN motor_units u ON u.id = mt.unit_id
+         WHERE mt.unit_id = $1
+         ORDER BY COALESCE(mt.starts_at, mt.performed_at) DESC, mt.created_at DESC",
+        MAINTENANCE_COLUMNS
+    ))
+    .bind(unit_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let total_cost: i64 = records.iter()
+        .filter(|r| r.status == MaintenanceStatus::Completed.as_str())
+        .filter_map(|r| r.cost)
+        .sum();
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "unitId": unit_id,
+        "totalCost": total_cost,
+        "data": records
+    })))
+}
+
+async fn create_maintenance(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path((motor_id, unit_id)): Path<(i32, Uuid)>,
+    Json(payload): Json<CreateMaintenanceRequest>,
+) -> Result<RespJson<MaintenanceRecord>, (StatusCode, RespJson<serde_json::Value>)> {
+    authorize_unit(&pool, &user, motor_id, unit_id).await?;
+    validate_readings(payload.odometer_km, payload.cost)?;
+
+    // Dengan performedAt = catatan servis yang sudah selesai; selain itu jadwal dengan jendela waktu
+    let status = match (payload.performed_at, payload.starts_at, payload.ends_at) {
+        (Some(_), _, _) => MaintenanceStatus::Completed,
+        (None, Some(start), Some(end)) if end > start => MaintenanceStatus::Scheduled,
+        (None, Some(_), Some(_)) => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "endsAt must be after startsAt"}))));
+        }
+        _ => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": "Provide performedAt for a completed service, or startsAt and endsAt to schedule maintenance"
+            }))));
+        }
+    };
+    let notes = payload.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Kunci unit supaya booking baru tidak masuk di jendela perawatan selagi dijadwalkan
+    sqlx::query("SELECT id FROM motor_units WHERE id = $1 FOR UPDATE")
+        .bind(unit_id)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    if let (MaintenanceStatus::Scheduled, Some(start), Some(end)) = (status, payload.starts_at, payload.ends_at) {
+        ensure_no_unit_bookings(&mut tx, unit_id, start, end).await?;
+    }
+
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO unit_maintenance (id, unit_id, kind, status, starts_at, ends_at, performed_at, odometer_km, cost, notes, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+    )
+    .bind(id)
+    .bind(unit_id)
+    .bind(payload.kind.as_str())
+    .bind(status.as_str())
+    .bind(payload.starts_at)
+    .bind(payload.ends_at)
+    .bind(payload.performed_at)
+    .bind(payload.odometer_km)
+    .bind(payload.cost)
+    .bind(notes)
+    .bind(user.id)
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let record = fetch_maintenance(&mut tx, id).await.map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🔧 Maintenance {} ({}, {}) for unit {} by {}", record.id, record.kind, record.status, record.plate_number, user.id);
+    Ok(RespJson(record))
+}
+
+// Mulai / selesaikan / batalkan perawatan. Perawatan yang sudah selesai atau batal tidak bisa diubah lagi.
+async fn update_maintenance(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path((motor_id, unit_id, maintenance_id)): Path<(i32, Uuid, Uuid)>,
+    Json(payload): Json<UpdateMaintenanceRequest>,
+) -> Result<RespJson<MaintenanceRecord>, (StatusCode, RespJson<serde_json::Value>)> {
+    authorize_unit(&pool, &user, motor_id, unit_id).await?;
+    validate_readings(payload.odometer_km, payload.cost)?;
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let current: Option<(String, Option<NaiveDateTime>, Option<NaiveDateTime>)> = sqlx::query_as(
+        "SELECT status, starts_at, ends_at FROM unit_maintenance WHERE id = $1 AND unit_id = $2 FOR UPDATE"
+    )
+    .bind(maintenance_id)
+    .bind(unit_id)
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let (current_status, current_start, current_end) = current
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Maintenance record not found"}))))?;
+
+    let allowed: &[MaintenanceStatus] = match current_status.as_str() {
+        "scheduled" => &[MaintenanceStatus::InProgress, MaintenanceStatus::Completed, MaintenanceStatus::Cancelled],
+        "in_progress" => &[MaintenanceStatus::Completed, MaintenanceStatus::Cancelled],
+        _ => &[],
+    };
+    if allowed.is_empty() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": format!("Maintenance is already {}", current_status)
+        }))));
+    }
+    if let Some(status) = payload.status.filter(|status| status.as_str() != current_status && !allowed.contains(status)) {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, RespJson(serde_json::json!({
+            "error": format!("Cannot change maintenance from {} to {}", current_status, status.as_str()),
+            "allowed": allowed.iter().map(|s| s.as_str()).collect::<Vec<_>>()
+        }))));
+    }
+
+    // Jendela waktu baru dicek lagi terhadap booking unit
+    let starts_at = payload.starts_at.or(current_start);
+    let ends_at = payload.ends_at.or(current_end);
+    if payload.starts_at.is_some() || payload.ends_at.is_some() {
+        match (starts_at, ends_at) {
+            (Some(start), Some(end)) if end > start => ensure_no_unit_bookings(&mut tx, unit_id, start, end).await?,
+            _ => {
+                return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "endsAt must be after startsAt"}))));
+            }
+        }
+    }
+
+    // Selesai tanpa performedAt berarti selesai sekarang
+    let performed_at = match payload.status {
+        Some(MaintenanceStatus::Completed) => payload.performed_at.or_else(|| Some(chrono::Local::now().naive_local())),
+        _ => payload.performed_at,
+    };
+    let notes = payload.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    sqlx::query(
+        "UPDATE unit_maintenance
+         SET status = COALESCE($2, status),
+             starts_at = $3,
+             ends_at = $4,
+             performed_at = COALESCE($5, performed_at),
+             odometer_km = COALESCE($6, odometer_km),
+             cost = COALESCE($7, cost),
+             notes = COALESCE($8, notes),
+             updated_at = NOW()
+         WHERE id = $1"
+    )
+    .bind(maintenance_id)
+    .bind(payload.status.map(|status| status.as_str()))
+    .bind(starts_at)
+    .bind(ends_at)
+    .bind(performed_at)
+    .bind(payload.odometer_km)
+    .bind(payload.cost)
+    .bind(notes)
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let record = fetch_maintenance(&mut tx, maintenance_id).await.map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🔧 Maintenance {} for unit {} is now {} ({})", record.id, record.plate_number, record.status, user.id);
+    Ok(RespJson(record))
+}
+
+// Jadwal perawatan yang belum selesai dalam beberapa hari ke depan; staff hanya cabangnya
+async fn list_upcoming_maintenance(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<UpcomingMaintenanceQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = user.branch_scope()?.or(params.branch.as_deref());
+    let days = params.days.unwrap_or(14).clamp(1, 90);
+
+    let records: Vec<MaintenanceRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM unit_maintenance mt
+         JOIN motor_units u ON u.id = mt.unit_id
+         JOIN motors m ON m.motor_id = u.motor_id
+         WHERE mt.status IN ('scheduled', 'in_progress')
+           AND mt.starts_at < LOCALTIMESTAMP + make_interval(days => $1::int)
+           AND ($2::text IS NULL OR LOWER(TRIM(COALESCE(u.branch, m.branch))) = LOWER(TRIM($2)))
+         ORDER BY mt.starts_at",
+        MAINTENANCE_COLUMNS
+    ))
+    .bind(days as i32)
+    .bind(branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "days": days,
+        "data": records
+    })))
+}
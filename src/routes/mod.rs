@@ -1,5 +1,22 @@
+pub mod admin;
 pub mod auth;
 pub mod orders;
+pub mod partner;
 pub mod motor;
 pub mod profils;
-pub mod users;
\ No newline at end of file
+pub mod users;
+pub mod incidents;
+pub mod drivers;
+pub mod blackouts;
+pub mod terms;
+pub mod branches;
+pub mod reviews;
+pub mod maintenance;
+pub mod transfers;
+pub mod pricing_rules;
+pub mod favorites;
+pub mod motor_images;
+pub mod telemetry;
+pub mod fleet;
+pub mod geofences;
+pub mod accessories;
\ No newline at end of file
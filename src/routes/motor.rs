@@ -1,28 +1,350 @@
 use axum::{
     Router,
-    routing::{get, post, put, delete},
+    routing::{get, post, put, patch, delete},
     extract::{Extension, Json, Path, Query},
     http::StatusCode,
+    middleware,
     response::Json as RespJson,
 };
-use sqlx::{PgPool, Row};
+use chrono::{Datelike, NaiveDateTime};
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
 use serde_json;
 use crate::model::motor::{
     Motor,
+    BranchInventory,
+    MotorWithInventory,
     CreateMotorRequest,
     UpdateMotorRequest,
     MotorQuery,
     MotorListResponse,
+    MotorTypeDeposit,
+    SetDepositRequest,
+    MotorDetailResponse,
+    MotorReview,
+    AvailabilityQuery,
+    UnavailablePeriod,
+    RentalDurationRule,
+    SetDurationRuleRequest,
+    MotorImportQuery,
+    SimilarMotorQuery,
+    MotorImportRow,
+    MotorStatus,
+    UpdateMotorStatusRequest,
+    Transmission,
+    MotorUnit,
+    MotorUnitStatus,
+    CreateMotorUnitRequest,
+    UpdateMotorUnitRequest,
 };
+use crate::auth::AuthUser;
+use crate::auth::scope;
+use crate::middleware::scope::{require_scope, RequireScope};
+use crate::auth::extractor::same_branch;
+use crate::config;
+use crate::routes::branches::find_branch;
+
+pub(crate) const MOTOR_COLUMNS: &str = "motor_id, motor_slug, motor_name, motor_type, price_per_day, price_per_week, price_per_month, description, image_url, image_thumbnails, available, status, branch, rating_avg, rating_count, engine_cc, transmission, fuel_capacity, seat_height, year";
+
+// Order `o` yang masih menahan motor / unit di periode start..end; hold kedaluwarsa dianggap kosong.
+// start / end adalah ekspresi SQL, contoh "$2" atau kolom "w.starts_at".
+fn active_order_overlap(start: &str, end: &str) -> String {
+    format!(
+        "o.status <> 'cancelled'
+         AND NOT (o.status = 'held' AND o.hold_expires_at <= NOW())
+         AND tsrange(o.tanggal_peminjaman + o.jam_peminjaman, o.tanggal_pengembalian + o.jam_pengembalian, '[)')
+             && tsrange({start}, {end}, '[)')",
+        start = start,
+        end = end
+    )
+}
+
+// Unit `u` siap disewa, belum dibooking, dan tidak dijadwalkan perawatan di periode start..end
+// (sama dengan find_free_unit di orders.rs)
+fn unit_free_conditions(start: &str, end: &str) -> String {
+    format!(
+        "u.status = 'available'
+         AND NOT EXISTS (
+             SELECT 1 FROM orders o
+             WHERE (o.unit_id = u.id OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.unit_id = u.id))
+               AND {active_order}
+         )
+         AND NOT EXISTS (
+             SELECT 1 FROM unit_maintenance mt
+             WHERE mt.unit_id = u.id
+               AND mt.status IN ('scheduled', 'in_progress')
+               AND tsrange(mt.starts_at,
+                           CASE WHEN mt.status = 'in_progress' THEN GREATEST(mt.ends_at, LOCALTIMESTAMP) ELSE mt.ends_at END,
+                           '[)')
+                   && tsrange({start}, {end}, '[)')
+         )",
+        start = start,
+        end = end,
+        active_order = active_order_overlap(start, end)
+    )
+}
+
+// Motor (alias tabel `motors`) yang masih bisa dibooking di periode start..end: tidak nonaktif,
+// tidak kena blackout, dan belum dibooking (motor tanpa unit) atau masih punya unit yang kosong dan
+// tidak sedang dirawat. Aturannya sama dengan reserve_booked_motors.
+fn motor_available_clause(start: &str, end: &str) -> String {
+    format!(
+        "motors.available IS NOT FALSE
+         AND NOT EXISTS (
+             SELECT 1 FROM blackout_periods b
+             WHERE tsrange(b.starts_at, b.ends_at, '[)') && tsrange({start}, {end}, '[)')
+               AND (b.motor_id = motors.motor_id
+                    OR (b.motor_id IS NULL AND (b.branch IS NULL OR LOWER(b.branch) = LOWER(motors.branch))))
+         )
+         AND (
+             (NOT EXISTS (SELECT 1 FROM motor_units mu WHERE mu.motor_id = motors.motor_id AND mu.status <> 'retired')
+              AND NOT EXISTS (
+                  SELECT 1 FROM orders o
+                  WHERE (o.motor_id = motors.motor_id
+                         OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.motor_id = motors.motor_id))
+                    AND {active_order}
+              ))
+             OR EXISTS (
+                 SELECT 1 FROM motor_units u
+                 WHERE u.motor_id = motors.motor_id AND {unit_free}
+             )
+         )",
+        start = start,
+        end = end,
+        active_order = active_order_overlap(start, end),
+        unit_free = unit_free_conditions(start, end)
+    )
+}
+
+// Jumlah unit (motor_id, branch, quantity, available_count) per motor per cabang pada periode $2..$3,
+// untuk motor di $1 (NULL = semua motor). Motor tanpa unit dihitung satu kendaraan di cabang motornya.
+fn branch_inventory_sql() -> String {
+    format!(
+        "SELECT u.motor_id, COALESCE(u.branch, m.branch) AS branch, COUNT(*) AS quantity,
+                COUNT(*) FILTER (WHERE {bookable} AND {unit_free}) AS available_count
+         FROM motor_units u JOIN motors m ON m.motor_id = u.motor_id
+         WHERE ($1::int[] IS NULL OR u.motor_id = ANY($1)) AND u.status <> 'retired' AND m.deleted_at IS NULL
+         GROUP BY u.motor_id, COALESCE(u.branch, m.branch)
+         UNION ALL
+         SELECT m.motor_id, m.branch, 1,
+                CASE WHEN {bookable} AND NOT EXISTS (
+                    SELECT 1 FROM orders o
+                    WHERE (o.motor_id = m.motor_id
+                           OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.motor_id = m.motor_id))
+                      AND {active_order}
+                ) THEN 1 ELSE 0 END
+         FROM motors m
+         WHERE ($1::int[] IS NULL OR m.motor_id = ANY($1)) AND m.deleted_at IS NULL
+           AND NOT EXISTS (SELECT 1 FROM motor_units mu WHERE mu.motor_id = m.motor_id AND mu.status <> 'retired')",
+        bookable = "m.available IS NOT FALSE
+                    AND NOT EXISTS (
+                        SELECT 1 FROM blackout_periods b
+                        WHERE tsrange(b.starts_at, b.ends_at, '[)') && tsrange($2, $3, '[)')
+                          AND (b.motor_id = m.motor_id
+                               OR (b.motor_id IS NULL AND (b.branch IS NULL OR LOWER(b.branch) = LOWER(m.branch))))
+                    )",
+        unit_free = unit_free_conditions("$2", "$3"),
+        active_order = active_order_overlap("$2", "$3")
+    )
+}
+
+// Lengkapi motor-motor di daftar dengan jumlah unit (quantity) dan unit yang masih kosong
+// (available_count) per cabang, pada periode start..end
+async fn attach_inventory(
+    pool: &PgPool,
+    motors: Vec<Motor>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Vec<MotorWithInventory>, sqlx::Error> {
+    let motor_ids: Vec<i32> = motors.iter().map(|m| m.motor_id).collect();
+    let rows: Vec<(i32, Option<String>, i64, i64)> = sqlx::query_as(&format!("{} ORDER BY 1, 2", branch_inventory_sql()))
+        .bind(&motor_ids)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(motors
+        .into_iter()
+        .map(|motor| {
+            let inventory: Vec<BranchInventory> = rows.iter()
+                .filter(|(motor_id, ..)| *motor_id == motor.motor_id)
+                .map(|(_, branch, quantity, available_count)| BranchInventory {
+                    branch: branch.clone(),
+                    quantity: *quantity,
+                    available_count: *available_count,
+                })
+                .collect();
+            MotorWithInventory {
+                motor,
+                quantity: inventory.iter().map(|i| i.quantity).sum(),
+                available_count: inventory.iter().map(|i| i.available_count).sum(),
+                inventory,
+            }
+        })
+        .collect())
+}
+
+// Total unit dan unit kosong per (cabang, tipe motor) di semua motor pada periode start..end, untuk
+// peringatan stok menipis (jobs::spawn_low_availability_check)
+pub(crate) async fn availability_by_branch_type(
+    pool: &PgPool,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Vec<(String, String, i64, i64)>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT inv.branch, m.motor_type, SUM(inv.quantity)::bigint, SUM(inv.available_count)::bigint
+         FROM ({}) inv JOIN motors m ON m.motor_id = inv.motor_id
+         WHERE inv.branch IS NOT NULL AND m.status <> 'retired'
+         GROUP BY inv.branch, m.motor_type
+         ORDER BY inv.branch, m.motor_type",
+        branch_inventory_sql()
+    ))
+    .bind(None::<Vec<i32>>)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+}
+
+const MOTOR_UNIT_COLUMNS: &str = "id, motor_id, plate_number, vin, year, branch, status, created_at, updated_at";
+
+pub(crate) fn motor_from_row(row: &PgRow) -> Motor {
+    Motor {
+        motor_id: row.try_get("motor_id").unwrap(),
+        motor_slug: row.try_get("motor_slug").unwrap(),
+        motor_name: row.try_get("motor_name").unwrap(),
+        motor_type: row.try_get("motor_type").unwrap(),
+        price_per_day: row.try_get("price_per_day").unwrap(),
+        price_per_week: row.try_get("price_per_week").ok(),
+        price_per_month: row.try_get("price_per_month").ok(),
+        description: row.try_get("description").ok(),
+        image_url: row.try_get("image_url").ok(),
+        image_thumbnails: row.try_get("image_thumbnails").ok().flatten(),
+        available: row.try_get("available").ok(),
+        status: row.try_get("status").unwrap_or_else(|_| MotorStatus::Active.as_str().to_string()),
+        branch: row.try_get("branch").ok(),
+        rating_avg: row.try_get("rating_avg").ok().flatten(),
+        rating_count: row.try_get("rating_count").unwrap_or(0),
+        engine_cc: row.try_get("engine_cc").ok().flatten(),
+        transmission: row.try_get("transmission").ok().flatten(),
+        fuel_capacity: row.try_get("fuel_capacity").ok().flatten(),
+        seat_height: row.try_get("seat_height").ok().flatten(),
+        year: row.try_get("year").ok().flatten(),
+    }
+}
+
+// Rentang wajar spesifikasi motor sewaan; field yang tidak diisi dilewati
+fn validate_motor_specs(
+    engine_cc: Option<i32>,
+    fuel_capacity: Option<f64>,
+    seat_height: Option<i32>,
+    year: Option<i32>,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let max_year = chrono::Local::now().date_naive().year() + 1;
+    let invalid = if engine_cc.is_some_and(|cc| !(50..=2000).contains(&cc)) {
+        Some("engine_cc must be between 50 and 2000")
+    } else if fuel_capacity.is_some_and(|liters| !(liters > 0.0 && liters <= 50.0)) {
+        Some("fuel_capacity must be between 0 and 50 liters")
+    } else if seat_height.is_some_and(|mm| !(500..=1200).contains(&mm)) {
+        Some("seat_height must be between 500 and 1200 mm")
+    } else if year.is_some_and(|year| !(1980..=max_year).contains(&year)) {
+        Some("year is out of range")
+    } else {
+        None
+    };
+
+    match invalid {
+        Some(error) => Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": error})))),
+        None => Ok(()),
+    }
+}
+
+// Nama cabang motor harus terdaftar di tabel branches; hasilnya nama sesuai penulisan di sana
+async fn canonical_branch(pool: &PgPool, name: &str) -> Result<String, (StatusCode, RespJson<serde_json::Value>)> {
+    let branch = find_branch(pool, name).await.map_err(|e| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    branch.map(|b| b.name).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown branch: {}", name.trim())
+        })))
+    })
+}
+
+// Pastikan motor ada dan berada di cabang yang boleh dikelola user
+pub(crate) async fn authorize_motor_branch(
+    pool: &PgPool,
+    user: &AuthUser,
+    motor_id: i32,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let motor: Option<(Option<String>,)> = sqlx::query_as("SELECT branch FROM motors WHERE motor_id = $1 AND deleted_at IS NULL")
+        .bind(motor_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            println!("🚨 Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
+
+    let (branch,) = motor.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Motor not found"
+        })))
+    })?;
+
+    user.require_branch(branch.as_deref())
+}
 
 pub fn motor_router() -> Router {
     println!("🔧 Registering motor routes...");
     Router::new()
         .route("/api/motors", get(list_motors))
-        .route("/api/motors", post(create_motor))
+        .route("/api/motors", post(create_motor).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/import", post(import_motors).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
         .route("/api/motors/:id", get(get_motor))
-        .route("/api/motors/:id", put(update_motor))
-        .route("/api/motors/:id", delete(delete_motor))
+        .route("/api/motors/:id/availability", get(get_motor_availability))
+        .route("/api/motors/:id/similar", get(list_similar_motors))
+        .route("/api/motors/:id", put(update_motor).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id", delete(delete_motor).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/status", patch(update_motor_status).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/restore", post(restore_motor).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/deposits", get(list_deposits))
+        .route("/api/motors/deposits/:motor_type", put(set_deposit).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/duration-rules", get(list_duration_rules))
+        .route("/api/motors/duration-rules/:motor_type", put(set_duration_rule).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/units", get(list_motor_units))
+        .route("/api/motors/:id/units", post(create_motor_unit).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/units/:unit_id", put(update_motor_unit).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/units/:unit_id", delete(delete_motor_unit).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
         .route("/api/motors/test", get(test_endpoint))
 }
 
@@ -35,6 +357,115 @@ async fn test_endpoint() -> RespJson<serde_json::Value> {
     }))
 }
 
+// Jumlah kata maksimal yang dipakai dari kata kunci pencarian
+const MAX_SEARCH_TERMS: usize = 8;
+
+// Ubah kata kunci pencarian menjadi tsquery prefix: "nmax malang" -> "nmax:* & malang:*", supaya
+// kata yang baru diketik sebagian tetap cocok. None jika tidak ada kata yang bisa dicari.
+fn motor_search_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = search
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .take(MAX_SEARCH_TERMS)
+        .map(|term| format!("{}:*", term.to_lowercase()))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+// Filter list motor yang sudah divalidasi; dipakai query count dan fetch supaya selalu sama
+#[derive(Default)]
+struct MotorListFilters<'a> {
+    motor_type: Option<&'a str>,
+    available_only: bool,
+    min_price: Option<i32>,
+    max_price: Option<i32>,
+    branch: Option<&'a str>,
+    available_window: Option<(NaiveDateTime, NaiveDateTime)>,
+    transmission: Option<Transmission>,
+    min_cc: Option<i32>,
+    max_cc: Option<i32>,
+    status: Option<MotorStatus>,
+    // tsquery hasil motor_search_query
+    search: Option<&'a str>,
+}
+
+// Tambahkan WHERE list motor; tiap filter langsung mengikat nilainya sendiri.
+// Motor yang sudah dihapus tidak pernah ikut.
+fn push_motor_filters<'a>(query: &mut QueryBuilder<'a, Postgres>, filters: &MotorListFilters<'a>) {
+    query.push(" WHERE deleted_at IS NULL");
+
+    if let Some(motor_type) = filters.motor_type {
+        query.push(" AND motor_type = ").push_bind(motor_type);
+    }
+    if filters.available_only {
+        query.push(" AND available = ").push_bind(true);
+    }
+    if let Some(min_price) = filters.min_price {
+        query.push(" AND price_per_day >= ").push_bind(min_price);
+    }
+    if let Some(max_price) = filters.max_price {
+        query.push(" AND price_per_day <= ").push_bind(max_price);
+    }
+    if let Some(branch) = filters.branch {
+        query.push(" AND LOWER(TRIM(branch)) = LOWER(TRIM(").push_bind(branch).push("))");
+    }
+    if let Some((start, end)) = filters.available_window {
+        // Rentang diikat sekali lewat subquery `w`, lalu dipakai berulang di klausa ketersediaan
+        query
+            .push(" AND EXISTS (SELECT 1 FROM (SELECT ")
+            .push_bind(start)
+            .push("::timestamp AS starts_at, ")
+            .push_bind(end)
+            .push("::timestamp AS ends_at) w WHERE ")
+            .push(motor_available_clause("w.starts_at", "w.ends_at"))
+            .push(")");
+    }
+    if let Some(transmission) = filters.transmission {
+        query.push(" AND transmission = ").push_bind(transmission.as_str());
+    }
+    if let Some(min_cc) = filters.min_cc {
+        query.push(" AND engine_cc >= ").push_bind(min_cc);
+    }
+    if let Some(max_cc) = filters.max_cc {
+        query.push(" AND engine_cc <= ").push_bind(max_cc);
+    }
+    if let Some(status) = filters.status {
+        query.push(" AND status = ").push_bind(status.as_str());
+    }
+    // Full-text search di kolom search_vector (lihat database/add_motor_search.sql)
+    if let Some(search) = filters.search {
+        query.push(" AND search_vector @@ to_tsquery('simple', ").push_bind(search).push(")");
+    }
+}
+
+// Urutan hasil list motor; relevansi hanya berlaku jika ada kata kunci pencarian (None = urut ts_rank)
+fn motor_order_by(sort: Option<&str>, has_search: bool) -> Result<Option<&'static str>, (StatusCode, RespJson<serde_json::Value>)> {
+    match (sort, has_search) {
+        (None | Some("") | Some("relevance"), true) => Ok(None),
+        (None | Some("") | Some("id") | Some("relevance"), _) => Ok(Some("motor_id ASC")),
+        (Some("price_asc"), _) => Ok(Some("price_per_day ASC, motor_id ASC")),
+        (Some("price_desc"), _) => Ok(Some("price_per_day DESC, motor_id ASC")),
+        (Some("newest"), _) => Ok(Some("motor_id DESC")),
+        (Some("rating"), _) => Ok(Some("rating_avg DESC NULLS LAST, rating_count DESC, motor_id ASC")),
+        (Some(other), _) => Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unsupported sort: {}", other),
+            "supportedSorts": ["id", "relevance", "price_asc", "price_desc", "newest", "rating"]
+        })))),
+    }
+}
+
+fn push_motor_order<'a>(query: &mut QueryBuilder<'a, Postgres>, order_by: Option<&'static str>, search: Option<&'a str>) {
+    if let (None, Some(search)) = (order_by, search) {
+        query
+            .push(" ORDER BY ts_rank(search_vector, to_tsquery('simple', ")
+            .push_bind(search)
+            .push(")) DESC, motor_id ASC");
+    } else {
+        query.push(" ORDER BY ").push(order_by.unwrap_or("motor_id ASC"));
+    }
+}
+
 // List all motors with pagination and filtering
 async fn list_motors(
     Extension(pool): Extension<PgPool>,
@@ -45,39 +476,64 @@ async fn list_motors(
     let page = params.page.unwrap_or(1).max(1);
     let limit = params.limit.unwrap_or(10).min(100).max(1);
     let offset = (page - 1) * limit;
-    
-    // Build base query
-    let mut where_clauses = Vec::new();
-    let mut param_count = 1;
-    
-    if params.motor_type.is_some() {
-        where_clauses.push(format!("motor_type = ${}", param_count));
-        param_count += 1;
+
+    let search = params.search.as_deref().and_then(motor_search_query);
+    let branch = params.branch.as_deref().map(str::trim).filter(|b| !b.is_empty());
+
+    if params.min_price.is_some_and(|price| price < 0) || params.max_price.is_some_and(|price| price < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "min_price and max_price cannot be negative"
+        }))));
     }
-    
-    if params.available_only.unwrap_or(false) {
-        where_clauses.push(format!("available = ${}", param_count));
-        param_count += 1;
+    if let (Some(min), Some(max)) = (params.min_price, params.max_price) {
+        if min > max {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": "min_price cannot be greater than max_price"
+            }))));
+        }
     }
-    
-    let where_clause = if where_clauses.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", where_clauses.join(" AND "))
+
+    // Rentang ketersediaan: dari awal available_from sampai akhir available_to
+    let available_window = match (params.available_from, params.available_to) {
+        (None, None) => None,
+        (Some(from), Some(to)) if to >= from && to - from <= chrono::Duration::days(92) => Some((
+            from.and_hms_opt(0, 0, 0).unwrap_or_default(),
+            (to + chrono::Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default(),
+        )),
+        (Some(_), Some(_)) => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": "Date range must be between 0 and 92 days"
+            }))));
+        }
+        _ => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": "available_from and available_to must be provided together"
+            }))));
+        }
+    };
+
+    let order_by = motor_order_by(params.sort.as_deref(), search.is_some())?;
+
+    let filters = MotorListFilters {
+        motor_type: params.motor_type.as_deref(),
+        available_only: params.available_only.unwrap_or(false),
+        min_price: params.min_price,
+        max_price: params.max_price,
+        branch,
+        available_window,
+        transmission: params.transmission,
+        min_cc: params.min_cc,
+        max_cc: params.max_cc,
+        status: params.status,
+        search: search.as_deref(),
     };
     
     // Count total records
-    let count_query = format!("SELECT COUNT(*) as total FROM motors {}", where_clause);
-    let mut count_query_builder = sqlx::query(&count_query);
-    
-    if let Some(motor_type) = &params.motor_type {
-        count_query_builder = count_query_builder.bind(motor_type);
-    }
-    if params.available_only.unwrap_or(false) {
-        count_query_builder = count_query_builder.bind(true);
-    }
+    let mut count_query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) AS total FROM motors");
+    push_motor_filters(&mut count_query, &filters);
     
-    let total_row = count_query_builder
+    let total_row = count_query
+        .build()
         .fetch_one(&pool)
         .await
         .map_err(|e| {
@@ -90,24 +546,13 @@ async fn list_motors(
     let total: i64 = total_row.try_get("total").unwrap_or(0);
     
     // Fetch records
-    let fetch_query = format!(
-        "SELECT motor_id, motor_slug, motor_name, motor_type, price_per_day, description, image_url, available, branch
-         FROM motors {} ORDER BY motor_id ASC LIMIT ${} OFFSET ${}",
-        where_clause, param_count, param_count + 1
-    );
-    
-    let mut fetch_query_builder = sqlx::query(&fetch_query);
-    
-    if let Some(motor_type) = &params.motor_type {
-        fetch_query_builder = fetch_query_builder.bind(motor_type);
-    }
-    if params.available_only.unwrap_or(false) {
-        fetch_query_builder = fetch_query_builder.bind(true);
-    }
+    let mut fetch_query = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM motors", MOTOR_COLUMNS));
+    push_motor_filters(&mut fetch_query, &filters);
+    push_motor_order(&mut fetch_query, order_by, filters.search);
+    fetch_query.push(" LIMIT ").push_bind(limit).push(" OFFSET ").push_bind(offset);
     
-    fetch_query_builder = fetch_query_builder.bind(limit).bind(offset);
-    
-    let rows = fetch_query_builder
+    let rows = fetch_query
+        .build()
         .fetch_all(&pool)
         .await
         .map_err(|e| {
@@ -119,20 +564,22 @@ async fn list_motors(
     
     let motors: Vec<Motor> = rows
         .iter()
-        .map(|row| {
-            Motor {
-                motor_id: row.try_get("motor_id").unwrap(),
-                motor_slug: row.try_get("motor_slug").unwrap_or_else(|_| "unknown".to_string()),
-                motor_name: row.try_get("motor_name").unwrap(),
-                motor_type: row.try_get("motor_type").unwrap(),
-                price_per_day: row.try_get("price_per_day").unwrap(),
-                description: row.try_get("description").ok(),
-                image_url: row.try_get("image_url").ok(),
-                available: row.try_get("available").ok(),
-                branch: row.try_get("branch").ok(),
-            }
-        })
+        .map(motor_from_row)
         .collect();
+
+    // Stok per cabang untuk periode yang dicari, atau saat ini jika tanpa rentang tanggal
+    let (stock_start, stock_end) = available_window.unwrap_or_else(|| {
+        let now = chrono::Local::now().naive_local();
+        (now, now + chrono::Duration::minutes(1))
+    });
+    let motors = attach_inventory(&pool, motors, stock_start, stock_end)
+        .await
+        .map_err(|e| {
+            println!("🚨 Database error loading inventory: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
     
     let response = MotorListResponse {
         motors,
@@ -144,42 +591,51 @@ async fn list_motors(
     Ok(RespJson(response))
 }
 
-// Get motor by ID
+// Get motor by ID (beserta rating dan ulasan terbaru)
 async fn get_motor(
     Extension(pool): Extension<PgPool>,
     Path(motor_id): Path<i32>,
-) -> Result<RespJson<Motor>, (StatusCode, RespJson<serde_json::Value>)> {
+) -> Result<RespJson<MotorDetailResponse>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔍 Getting motor with ID: {}", motor_id);
     
-    let row = sqlx::query(
-        "SELECT motor_id, motor_slug, motor_name, motor_type, price_per_day, description, image_url, available, branch
-         FROM motors WHERE motor_id = $1"
-    )
-    .bind(motor_id)
-    .fetch_optional(&pool)
+    let row = sqlx::query(&format!("SELECT {} FROM motors WHERE motor_id = $1 AND deleted_at IS NULL", MOTOR_COLUMNS))
+        .bind(motor_id)
+        .fetch_optional(&pool)
         .await
-    .map_err(|e| {
-        println!("🚨 Database error: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
-            "error": "Database error"
-        })))
-    })?;
+        .map_err(|e| {
+            println!("🚨 Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
     
     match row {
         Some(motor_row) => {
-            let motor = Motor {
-                motor_id: motor_row.try_get("motor_id").unwrap(),
-                motor_slug: motor_row.try_get("motor_slug").unwrap_or_else(|_| "unknown".to_string()),
-                motor_name: motor_row.try_get("motor_name").unwrap(),
-                motor_type: motor_row.try_get("motor_type").unwrap(),
-                price_per_day: motor_row.try_get("price_per_day").unwrap(),
-                description: motor_row.try_get("description").ok(),
-                image_url: motor_row.try_get("image_url").ok(),
-                available: motor_row.try_get("available").ok(),
-                branch: motor_row.try_get("branch").ok(),
+            let motor = motor_from_row(&motor_row);
+
+            let db_error = |e: sqlx::Error| {
+                println!("🚨 Database error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                    "error": "Database error"
+                })))
             };
-            
-            Ok(RespJson(motor))
+
+            let rating_average = motor.rating_avg;
+            let review_count = i64::from(motor.rating_count);
+
+            let reviews: Vec<MotorReview> = sqlx::query_as(
+                "SELECT r.rating, r.comment, u.username AS reviewer, r.created_at
+                 FROM order_reviews r JOIN users u ON u.id = r.user_id
+                 WHERE r.motor_id = $1 AND r.status = 'approved'
+                 ORDER BY r.created_at DESC
+                 LIMIT 20"
+            )
+            .bind(motor_id)
+            .fetch_all(&pool)
+            .await
+            .map_err(db_error)?;
+
+            Ok(RespJson(MotorDetailResponse { motor, rating_average, review_count, reviews }))
         }
         None => {
             Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
@@ -189,32 +645,207 @@ async fn get_motor(
     }
 }
 
+// Periode motor tidak bisa dibooking dalam rentang from..to: booking aktif (termasuk sebagai salah satu
+// motor di order rombongan) dan blackout motor / cabang / semua cabang
+async fn get_motor_availability(
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+    Query(params): Query<AvailabilityQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let db_error = |e: sqlx::Error| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    let from = params.from.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let to = params.to.unwrap_or(from + chrono::Duration::days(30));
+    if to < from || to - from > chrono::Duration::days(92) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Date range must be between 0 and 92 days"
+        }))));
+    }
+
+    let motor: Option<(Option<bool>, Option<String>)> = sqlx::query_as("SELECT available, branch FROM motors WHERE motor_id = $1 AND deleted_at IS NULL")
+        .bind(motor_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let (available, branch) = motor.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Motor not found"
+        })))
+    })?;
+
+    let range_start = from.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let range_end = (to + chrono::Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default();
+
+    let booked: Vec<UnavailablePeriod> = sqlx::query_as(
+        "SELECT tanggal_peminjaman + jam_peminjaman AS start, tanggal_pengembalian + jam_pengembalian AS end, NULL::text AS reason
+         FROM orders
+         WHERE (motor_id = $1 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = orders.id AND l.motor_id = $1))
+           AND status <> 'cancelled'
+           AND tsrange(tanggal_peminjaman + jam_peminjaman, tanggal_pengembalian + jam_pengembalian, '[)')
+               && tsrange($2, $3, '[)')
+         ORDER BY 1"
+    )
+    .bind(motor_id)
+    .bind(range_start)
+    .bind(range_end)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let blackouts: Vec<UnavailablePeriod> = sqlx::query_as(
+        "SELECT starts_at AS start, ends_at AS end, reason
+         FROM blackout_periods
+         WHERE tsrange(starts_at, ends_at, '[)') && tsrange($2, $3, '[)')
+           AND (motor_id = $1
+                OR (motor_id IS NULL AND (branch IS NULL OR LOWER(branch) = LOWER($4))))
+         ORDER BY 1"
+    )
+    .bind(motor_id)
+    .bind(range_start)
+    .bind(range_end)
+    .bind(&branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "motorId": motor_id,
+        "available": available.unwrap_or(true),
+        "from": from,
+        "to": to,
+        "bookedPeriods": booked,
+        "blackouts": blackouts
+    })))
+}
+
+// Selisih harga harian maksimal (persen) supaya motor dianggap sekelas
+const SIMILAR_PRICE_BAND_PERCENT: i32 = 30;
+
+// Motor serupa untuk bagian "mungkin kamu juga suka" di halaman detail: tipe sama, harga harian
+// dalam rentang ±30%, dan di cabang yang sama. Yang harganya paling dekat didahulukan.
+async fn list_similar_motors(
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+    Query(params): Query<SimilarMotorQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let db_error = |e: sqlx::Error| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+    let limit = params.limit.unwrap_or(4).clamp(1, 20);
+
+    let motor: Option<(String, i32, Option<String>)> = sqlx::query_as(
+        "SELECT motor_type, price_per_day, branch FROM motors WHERE motor_id = $1 AND deleted_at IS NULL"
+    )
+    .bind(motor_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let (motor_type, price_per_day, branch) = motor.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Motor not found"
+        })))
+    })?;
+
+    let band = price_per_day * SIMILAR_PRICE_BAND_PERCENT / 100;
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM motors
+         WHERE motor_id <> $1 AND deleted_at IS NULL AND available IS NOT FALSE
+           AND motor_type = $2
+           AND price_per_day BETWEEN $3 AND $4
+           AND ($5::text IS NULL OR LOWER(TRIM(branch)) = LOWER(TRIM($5)))
+         ORDER BY ABS(price_per_day - $6), rating_avg DESC NULLS LAST, motor_id
+         LIMIT $7",
+        MOTOR_COLUMNS
+    ))
+    .bind(motor_id)
+    .bind(&motor_type)
+    .bind(price_per_day - band)
+    .bind(price_per_day + band)
+    .bind(&branch)
+    .bind(price_per_day)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let motors: Vec<Motor> = rows.iter().map(motor_from_row).collect();
+
+    Ok(RespJson(serde_json::json!({
+        "motorId": motor_id,
+        "motors": motors
+    })))
+}
+
 // Create new motor
 async fn create_motor(
     Extension(pool): Extension<PgPool>,
+    user: AuthUser,
     Json(payload): Json<CreateMotorRequest>,
 ) -> Result<RespJson<Motor>, (StatusCode, RespJson<serde_json::Value>)> {
+    // Hanya admin / staff yang boleh mengelola data motor
+    user.require_staff()?;
+
+    // Staff cabang hanya boleh menambah motor untuk cabangnya sendiri (default ke cabang staff)
+    let mut payload = payload;
+    if let Some(own_branch) = user.branch_scope()? {
+        match payload.branch.as_deref() {
+            None => payload.branch = Some(own_branch.to_string()),
+            Some(branch) if same_branch(branch, own_branch) => {}
+            Some(_) => user.require_branch(payload.branch.as_deref())?,
+        }
+    }
+
+    if let Some(branch) = payload.branch.as_deref() {
+        payload.branch = Some(canonical_branch(&pool, branch).await?);
+    }
+
+    validate_motor_specs(payload.engine_cc, payload.fuel_capacity, payload.seat_height, payload.year)?;
+    if payload.initial_status() == MotorStatus::Retired {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "New motors cannot be retired"
+        }))));
+    }
+
     println!("=== CREATE MOTOR DEBUG ===");
     println!("Motor slug: {}", payload.motor_slug);
     println!("Motor name: {}", payload.motor_name);
     println!("Motor type: {}", payload.motor_type);
     println!("Price per day: {}", payload.price_per_day);
-    println!("Available: {:?}", payload.available);
+    println!("Status: {}", payload.initial_status().as_str());
     
     // Insert motor into database
-    let result = sqlx::query(
-        "INSERT INTO motors (motor_slug, motor_name, motor_type, price_per_day, description, image_url, available, branch) 
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8) 
-         RETURNING motor_id, motor_slug, motor_name, motor_type, price_per_day, description, image_url, available, branch"
-    )
+    let result = sqlx::query(&format!(
+        "INSERT INTO motors (motor_slug, motor_name, motor_type, price_per_day, price_per_week, price_per_month, description, image_url, status, branch,
+                             engine_cc, transmission, fuel_capacity, seat_height, year) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) 
+         RETURNING {}",
+        MOTOR_COLUMNS
+    ))
     .bind(&payload.motor_slug)
     .bind(&payload.motor_name)
     .bind(&payload.motor_type)
     .bind(payload.price_per_day)
+    .bind(payload.price_per_week)
+    .bind(payload.price_per_month)
     .bind(&payload.description)
     .bind(&payload.image_url)
-    .bind(payload.available.unwrap_or(true))
+    .bind(payload.initial_status().as_str())
     .bind(&payload.branch)
+    .bind(payload.engine_cc)
+    .bind(payload.transmission.map(|t| t.as_str()))
+    .bind(payload.fuel_capacity)
+    .bind(payload.seat_height)
+    .bind(payload.year)
     .fetch_one(&pool)
     .await
     .map_err(|e| {
@@ -224,117 +855,300 @@ async fn create_motor(
         })))
     })?;
 
-    let motor = Motor {
-        motor_id: result.try_get("motor_id").unwrap(),
-        motor_slug: result.try_get("motor_slug").unwrap(),
-        motor_name: result.try_get("motor_name").unwrap(),
-        motor_type: result.try_get("motor_type").unwrap(),
-        price_per_day: result.try_get("price_per_day").unwrap(),
-        description: result.try_get("description").ok(),
-        image_url: result.try_get("image_url").ok(),
-        available: result.try_get("available").ok(),
-        branch: result.try_get("branch").ok(),
-    };
+    let motor = motor_from_row(&result);
 
     println!("Motor created successfully with ID: {}", motor.motor_id);
     Ok(RespJson(motor))
 }
 
-// Update motor
-async fn update_motor(
-    Extension(pool): Extension<PgPool>,
-    Path(motor_id): Path<i32>,
-    Json(payload): Json<UpdateMotorRequest>,
-) -> Result<RespJson<Motor>, (StatusCode, RespJson<serde_json::Value>)> {
-    println!("🔄 Updating motor with ID: {}", motor_id);
-    
-    // Build dynamic update query
-    let mut query_parts = Vec::new();
-    let mut param_count = 1;
-    
-    if payload.motor_slug.is_some() {
-        query_parts.push(format!("motor_slug = ${}", param_count));
-        param_count += 1;
-    }
-    
-    if payload.motor_name.is_some() {
-        query_parts.push(format!("motor_name = ${}", param_count));
-        param_count += 1;
-    }
-    
-    if payload.motor_type.is_some() {
-        query_parts.push(format!("motor_type = ${}", param_count));
-        param_count += 1;
+// Maksimal baris per file import
+const MAX_IMPORT_ROWS: usize = 1000;
+
+// Validasi satu baris import: field wajib, harga, spesifikasi, dan cabang (dinormalisasi ke tabel branches)
+async fn validate_import_row(
+    pool: &PgPool,
+    row: &mut CreateMotorRequest,
+) -> Result<(), String> {
+    row.motor_slug = row.motor_slug.trim().to_string();
+    row.motor_name = row.motor_name.trim().to_string();
+    row.motor_type = row.motor_type.trim().to_string();
+    if row.motor_slug.is_empty() || row.motor_name.is_empty() || row.motor_type.is_empty() {
+        return Err("motor_slug, motor_name and motor_type are required".to_string());
     }
-    
-    if payload.price_per_day.is_some() {
-        query_parts.push(format!("price_per_day = ${}", param_count));
-        param_count += 1;
+    if row.price_per_day <= 0 {
+        return Err("price_per_day must be greater than 0".to_string());
     }
-    
-    if payload.description.is_some() {
-        query_parts.push(format!("description = ${}", param_count));
-        param_count += 1;
+    if row.price_per_week.is_some_and(|price| price <= 0) || row.price_per_month.is_some_and(|price| price <= 0) {
+        return Err("price_per_week and price_per_month must be greater than 0".to_string());
     }
-    
-    if payload.image_url.is_some() {
-        query_parts.push(format!("image_url = ${}", param_count));
-        param_count += 1;
+    validate_motor_specs(row.engine_cc, row.fuel_capacity, row.seat_height, row.year)
+        .map_err(|(_, RespJson(body))| body["error"].as_str().unwrap_or("Invalid specification").to_string())?;
+    if row.initial_status() == MotorStatus::Retired {
+        return Err("New motors cannot be retired".to_string());
     }
-    
-    if payload.available.is_some() {
-        query_parts.push(format!("available = ${}", param_count));
-        param_count += 1;
+
+    if let Some(branch) = row.branch.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+        let branch = canonical_branch(pool, branch)
+            .await
+            .map_err(|(_, RespJson(body))| body["error"].as_str().unwrap_or("Invalid branch").to_string())?;
+        row.branch = Some(branch);
+    } else {
+        row.branch = None;
     }
-    
-    if payload.branch.is_some() {
-        query_parts.push(format!("branch = ${}", param_count));
-        param_count += 1;
+
+    Ok(())
+}
+
+// Import banyak motor sekaligus dari CSV (admin). Header memakai nama field CreateMotorRequest
+// (motor_slug, motor_name, motor_type, price_per_day, ..., branch, engine_cc, transmission, ...).
+// Semua baris divalidasi dulu; jika ada satu saja yang gagal tidak ada yang disimpan, dan laporan
+// per baris dikembalikan supaya file bisa diperbaiki lalu diunggah ulang.
+async fn import_motors(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Query(params): Query<MotorImportQuery>,
+    body: String,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+    let dry_run = params.dry_run.unwrap_or(false);
+
+    let db_error = |e: sqlx::Error| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let mut rows: Vec<(MotorImportRow, Option<CreateMotorRequest>)> = Vec::new();
+    for (index, record) in reader.deserialize::<CreateMotorRequest>().enumerate() {
+        if index >= MAX_IMPORT_ROWS {
+            return Err((StatusCode::PAYLOAD_TOO_LARGE, RespJson(serde_json::json!({
+                "error": format!("CSV can contain at most {} motors", MAX_IMPORT_ROWS)
+            }))));
+        }
+
+        let mut report = MotorImportRow { row: index + 2, motor_slug: None, motor_id: None, error: None };
+        match record {
+            Ok(mut motor) => {
+                report.motor_slug = Some(motor.motor_slug.trim().to_string());
+                match validate_import_row(&pool, &mut motor).await {
+                    Ok(()) => rows.push((report, Some(motor))),
+                    Err(e) => {
+                        report.error = Some(e);
+                        rows.push((report, None));
+                    }
+                }
+            }
+            Err(e) => {
+                report.error = Some(format!("Invalid row: {}", e));
+                rows.push((report, None));
+            }
+        }
     }
-    
-    if query_parts.is_empty() {
+
+    if rows.is_empty() {
         return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
-            "error": "No valid fields to update"
+            "error": "CSV has no motor rows"
         }))));
     }
-    
-    let query_str = format!(
-        "UPDATE motors SET {} WHERE motor_id = ${} RETURNING motor_id, motor_slug, motor_name, motor_type, price_per_day, description, image_url, available, branch",
-        query_parts.join(", "),
-        param_count
-    );
-    
-    let mut query = sqlx::query(&query_str);
-    
-    // Bind parameters in the same order as query_parts
-    if let Some(motor_slug) = &payload.motor_slug {
-        query = query.bind(motor_slug);
+
+    // Slug harus unik, baik di dalam file maupun terhadap motor yang sudah ada
+    let slugs: Vec<String> = rows.iter().filter_map(|(_, motor)| motor.as_ref().map(|m| m.motor_slug.clone())).collect();
+    let existing: Vec<String> = sqlx::query_scalar("SELECT motor_slug FROM motors WHERE motor_slug = ANY($1) AND deleted_at IS NULL")
+        .bind(&slugs)
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+    let mut seen: Vec<String> = Vec::new();
+    for (report, motor) in rows.iter_mut() {
+        let Some(slug) = motor.as_ref().map(|m| m.motor_slug.clone()) else {
+            continue;
+        };
+        if existing.contains(&slug) {
+            report.error = Some(format!("motor_slug {} already exists", slug));
+        } else if seen.contains(&slug) {
+            report.error = Some(format!("motor_slug {} appears more than once in the file", slug));
+        } else {
+            seen.push(slug);
+            continue;
+        }
+        *motor = None;
+    }
+
+    let failed = rows.iter().filter(|(report, _)| report.error.is_some()).count();
+    if failed > 0 || dry_run {
+        let status = if failed > 0 { StatusCode::UNPROCESSABLE_ENTITY } else { StatusCode::OK };
+        let body = serde_json::json!({
+            "success": failed == 0,
+            "dryRun": dry_run,
+            "total": rows.len(),
+            "imported": 0,
+            "failed": failed,
+            "rows": rows.into_iter().map(|(report, _)| report).collect::<Vec<_>>()
+        });
+        return if failed > 0 { Err((status, RespJson(body))) } else { Ok(RespJson(body)) };
+    }
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let mut reports = Vec::with_capacity(rows.len());
+    for (mut report, motor) in rows {
+        let Some(motor) = motor else {
+            continue;
+        };
+        let (motor_id,): (i32,) = sqlx::query_as(
+            "INSERT INTO motors (motor_slug, motor_name, motor_type, price_per_day, price_per_week, price_per_month, description, image_url, status, branch,
+                                 engine_cc, transmission, fuel_capacity, seat_height, year)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+             RETURNING motor_id"
+        )
+        .bind(&motor.motor_slug)
+        .bind(&motor.motor_name)
+        .bind(&motor.motor_type)
+        .bind(motor.price_per_day)
+        .bind(motor.price_per_week)
+        .bind(motor.price_per_month)
+        .bind(&motor.description)
+        .bind(&motor.image_url)
+        .bind(motor.initial_status().as_str())
+        .bind(&motor.branch)
+        .bind(motor.engine_cc)
+        .bind(motor.transmission.map(|t| t.as_str()))
+        .bind(motor.fuel_capacity)
+        .bind(motor.seat_height)
+        .bind(motor.year)
+        .fetch_one(&mut tx)
+        .await
+        .map_err(db_error)?;
+        report.motor_id = Some(motor_id);
+        reports.push(report);
+    }
+    tx.commit().await.map_err(db_error)?;
+
+    println!("📥 {} motors imported from CSV by {}", reports.len(), user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "dryRun": false,
+        "total": reports.len(),
+        "imported": reports.len(),
+        "failed": 0,
+        "rows": reports
+    })))
+}
+
+// UPDATE motors dinamis: hanya kolom yang dikirim yang diubah, setiap kolom langsung mengikat nilainya
+fn motor_update_query(motor_id: i32, payload: &UpdateMotorRequest) -> QueryBuilder<'_, Postgres> {
+    let mut query = QueryBuilder::<Postgres>::new("UPDATE motors SET ");
+    let mut fields = query.separated(", ");
+    
+    if let Some(motor_slug) = &payload.motor_slug {
+        fields.push("motor_slug = ").push_bind_unseparated(motor_slug);
     }
     if let Some(motor_name) = &payload.motor_name {
-        query = query.bind(motor_name);
+        fields.push("motor_name = ").push_bind_unseparated(motor_name);
     }
     if let Some(motor_type) = &payload.motor_type {
-        query = query.bind(motor_type);
+        fields.push("motor_type = ").push_bind_unseparated(motor_type);
     }
     if let Some(price_per_day) = payload.price_per_day {
-        query = query.bind(price_per_day);
+        fields.push("price_per_day = ").push_bind_unseparated(price_per_day);
+    }
+    if let Some(price_per_week) = payload.price_per_week {
+        fields.push("price_per_week = ").push_bind_unseparated(price_per_week);
+    }
+    if let Some(price_per_month) = payload.price_per_month {
+        fields.push("price_per_month = ").push_bind_unseparated(price_per_month);
     }
     if let Some(description) = &payload.description {
-        query = query.bind(description);
+        fields.push("description = ").push_bind_unseparated(description);
     }
     if let Some(image_url) = &payload.image_url {
-        query = query.bind(image_url);
-    }
-    if let Some(available) = payload.available {
-        query = query.bind(available);
+        // Thumbnail lama milik gambar sebelumnya
+        fields.push("image_url = ").push_bind_unseparated(image_url);
+        fields.push("image_thumbnails = NULL");
     }
     if let Some(branch) = &payload.branch {
-        query = query.bind(branch);
+        fields.push("branch = ").push_bind_unseparated(branch);
+    }
+    if let Some(engine_cc) = payload.engine_cc {
+        fields.push("engine_cc = ").push_bind_unseparated(engine_cc);
+    }
+    if let Some(transmission) = payload.transmission {
+        fields.push("transmission = ").push_bind_unseparated(transmission.as_str());
+    }
+    if let Some(fuel_capacity) = payload.fuel_capacity {
+        fields.push("fuel_capacity = ").push_bind_unseparated(fuel_capacity);
+    }
+    if let Some(seat_height) = payload.seat_height {
+        fields.push("seat_height = ").push_bind_unseparated(seat_height);
+    }
+    if let Some(year) = payload.year {
+        fields.push("year = ").push_bind_unseparated(year);
     }
     
-    query = query.bind(motor_id);
+    query
+        .push(" WHERE motor_id = ")
+        .push_bind(motor_id)
+        .push(format!(" RETURNING {}", MOTOR_COLUMNS));
+
+    query
+}
+
+// Update motor
+async fn update_motor(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_id): Path<i32>,
+    Json(payload): Json<UpdateMotorRequest>,
+) -> Result<RespJson<Motor>, (StatusCode, RespJson<serde_json::Value>)> {
+    // Hanya admin / staff yang boleh mengelola data motor, staff hanya untuk cabangnya
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    // Memindahkan motor ke cabang lain hanya boleh oleh admin
+    let mut payload = payload;
+    if let Some(branch) = payload.branch.as_deref() {
+        user.require_branch(Some(branch))?;
+        payload.branch = Some(canonical_branch(&pool, branch).await?);
+    }
+
+    validate_motor_specs(payload.engine_cc, payload.fuel_capacity, payload.seat_height, payload.year)?;
+
+    // available sekarang turunan dari status, yang hanya bisa diubah lewat endpoint status
+    if payload.available.is_some() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Use PATCH /api/motors/:id/status to change motor availability"
+        }))));
+    }
+
+    println!("🔄 Updating motor with ID: {}", motor_id);
+    
+    let has_fields = payload.motor_slug.is_some()
+        || payload.motor_name.is_some()
+        || payload.motor_type.is_some()
+        || payload.price_per_day.is_some()
+        || payload.price_per_week.is_some()
+        || payload.price_per_month.is_some()
+        || payload.description.is_some()
+        || payload.image_url.is_some()
+        || payload.branch.is_some()
+        || payload.engine_cc.is_some()
+        || payload.transmission.is_some()
+        || payload.fuel_capacity.is_some()
+        || payload.seat_height.is_some()
+        || payload.year.is_some();
+    if !has_fields {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "No valid fields to update"
+        }))));
+    }
     
+    let mut query = motor_update_query(motor_id, &payload);
     let row = query
+        .build()
         .fetch_optional(&pool)
         .await
         .map_err(|e| {
@@ -346,17 +1160,7 @@ async fn update_motor(
     
     match row {
         Some(motor_row) => {
-            let motor = Motor {
-                motor_id: motor_row.try_get("motor_id").unwrap(),
-                motor_slug: motor_row.try_get("motor_slug").unwrap_or_else(|_| "unknown".to_string()),
-                motor_name: motor_row.try_get("motor_name").unwrap(),
-                motor_type: motor_row.try_get("motor_type").unwrap(),
-                price_per_day: motor_row.try_get("price_per_day").unwrap(),
-                description: motor_row.try_get("description").ok(),
-                image_url: motor_row.try_get("image_url").ok(),
-                available: motor_row.try_get("available").ok(),
-                branch: motor_row.try_get("branch").ok(),
-            };
+            let motor = motor_from_row(&motor_row);
             
             Ok(RespJson(motor))
         }
@@ -368,14 +1172,96 @@ async fn update_motor(
     }
 }
 
+// Ubah status motor (active / maintenance / reserved / retired). Staff untuk motor di cabangnya;
+// mempensiunkan motor hanya admin dan ditolak selama masih ada booking yang berjalan.
+async fn update_motor_status(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_id): Path<i32>,
+    Json(payload): Json<UpdateMotorStatusRequest>,
+) -> Result<RespJson<Motor>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+    if payload.status == MotorStatus::Retired {
+        user.require_admin()?;
+    }
+
+    let db_error = |e: sqlx::Error| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Kunci motor supaya perubahan status tidak balapan dengan booking baru
+    let current: Option<(String,)> = sqlx::query_as("SELECT status FROM motors WHERE motor_id = $1 AND deleted_at IS NULL FOR UPDATE")
+        .bind(motor_id)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(db_error)?;
+    let (current,) = current.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Motor not found"
+        })))
+    })?;
+
+    let current_status = MotorStatus::parse(&current).unwrap_or(MotorStatus::Active);
+    if !current_status.can_transition_to(payload.status) {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": format!("Cannot change motor status from {} to {}", current_status.as_str(), payload.status.as_str()),
+            "status": current_status.as_str()
+        }))));
+    }
+
+    if payload.status == MotorStatus::Retired {
+        let active_bookings: Vec<String> = sqlx::query_scalar(
+            "SELECT o.order_number FROM orders o
+             WHERE (o.motor_id = $1 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.motor_id = $1))
+               AND o.status NOT IN ('cancelled', 'returned', 'completed')
+               AND NOT (o.status = 'held' AND o.hold_expires_at <= NOW())
+             ORDER BY o.tanggal_peminjaman, o.jam_peminjaman"
+        )
+        .bind(motor_id)
+        .fetch_all(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+        if !active_bookings.is_empty() {
+            return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+                "error": "Motor still has active bookings",
+                "bookings": active_bookings
+            }))));
+        }
+    }
+
+    let row = sqlx::query(&format!("UPDATE motors SET status = $2 WHERE motor_id = $1 RETURNING {}", MOTOR_COLUMNS))
+        .bind(motor_id)
+        .bind(payload.status.as_str())
+        .fetch_one(&mut tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🚦 Motor {} status {} -> {} by {}", motor_id, current_status.as_str(), payload.status.as_str(), user.id);
+    Ok(RespJson(motor_from_row(&row)))
+}
+
 // Delete motor
 async fn delete_motor(
     Extension(pool): Extension<PgPool>,
+    user: AuthUser,
     Path(motor_id): Path<i32>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    // Hanya admin / staff yang boleh mengelola data motor, staff hanya untuk cabangnya
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
     println!("🗑️ Deleting motor with ID: {}", motor_id);
-    
-    let result = sqlx::query("DELETE FROM motors WHERE motor_id = $1")
+
+    // Soft delete: baris motor tetap ada supaya order lama masih merujuk ke motornya
+    let result = sqlx::query("UPDATE motors SET deleted_at = NOW() WHERE motor_id = $1 AND deleted_at IS NULL")
         .bind(motor_id)
         .execute(&pool)
         .await
@@ -396,3 +1282,546 @@ async fn delete_motor(
         })))
     }
 }
+
+// Kembalikan motor yang sudah dihapus (admin)
+async fn restore_motor(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_id): Path<i32>,
+) -> Result<RespJson<Motor>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let row = sqlx::query(&format!(
+        "UPDATE motors SET deleted_at = NULL WHERE motor_id = $1 AND deleted_at IS NOT NULL RETURNING {}",
+        MOTOR_COLUMNS
+    ))
+    .bind(motor_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let motor = row.as_ref().map(motor_from_row).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Deleted motor not found"
+        })))
+    })?;
+
+    println!("♻️ Motor {} restored by {}", motor_id, user.id);
+    Ok(RespJson(motor))
+}
+
+// Daftar nominal deposit jaminan per tipe motor
+async fn list_deposits(
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let deposits: Vec<MotorTypeDeposit> = sqlx::query_as(
+        "SELECT motor_type, amount FROM motor_type_deposits ORDER BY motor_type"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "deposits": deposits
+    })))
+}
+
+// Atur nominal deposit untuk satu tipe motor (admin). Berlaku untuk order yang dibuat setelahnya.
+async fn set_deposit(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_type): Path<String>,
+    Json(payload): Json<SetDepositRequest>,
+) -> Result<RespJson<MotorTypeDeposit>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    if payload.amount < 0 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Deposit amount cannot be negative"
+        }))));
+    }
+
+    let deposit: MotorTypeDeposit = sqlx::query_as(
+        "INSERT INTO motor_type_deposits (motor_type, amount) VALUES ($1, $2)
+         ON CONFLICT (motor_type) DO UPDATE SET amount = EXCLUDED.amount, updated_at = NOW()
+         RETURNING motor_type, amount"
+    )
+    .bind(motor_type.trim())
+    .bind(payload.amount)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    println!("💰 Deposit for {} set to {}", deposit.motor_type, deposit.amount);
+    Ok(RespJson(deposit))
+}
+
+// Aturan lama sewa per tipe motor, beserta aturan global dari konfigurasi
+async fn list_duration_rules(
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let rules: Vec<RentalDurationRule> = sqlx::query_as(
+        "SELECT motor_type, min_days, max_days FROM rental_duration_rules ORDER BY motor_type"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let cfg = config::get();
+    Ok(RespJson(serde_json::json!({
+        "global": {
+            "min_days": cfg.min_rental_days,
+            "max_days": cfg.max_rental_days
+        },
+        "rules": rules
+    })))
+}
+
+// Atur minimal / maksimal lama sewa untuk satu tipe motor (admin). Kosongkan keduanya untuk
+// kembali ke aturan global.
+async fn set_duration_rule(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_type): Path<String>,
+    Json(payload): Json<SetDurationRuleRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    if payload.min_days.is_some_and(|days| days < 0) || payload.max_days.is_some_and(|days| days < 1) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "min_days cannot be negative and max_days must be at least 1"
+        }))));
+    }
+
+    if let (Some(min), Some(max)) = (payload.min_days, payload.max_days) {
+        if min > max {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": "min_days cannot be greater than max_days"
+            }))));
+        }
+    }
+
+    let db_error = |e: sqlx::Error| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    let motor_type = motor_type.trim();
+    if payload.min_days.is_none() && payload.max_days.is_none() {
+        sqlx::query("DELETE FROM rental_duration_rules WHERE motor_type = $1")
+            .bind(motor_type)
+            .execute(&pool)
+            .await
+            .map_err(db_error)?;
+
+        println!("⏱️  Duration rule for {} removed", motor_type);
+        return Ok(RespJson(serde_json::json!({
+            "message": "Duration rule removed",
+            "motor_type": motor_type
+        })));
+    }
+
+    let rule: RentalDurationRule = sqlx::query_as(
+        "INSERT INTO rental_duration_rules (motor_type, min_days, max_days) VALUES ($1, $2, $3)
+         ON CONFLICT (motor_type) DO UPDATE SET min_days = EXCLUDED.min_days, max_days = EXCLUDED.max_days, updated_at = NOW()
+         RETURNING motor_type, min_days, max_days"
+    )
+    .bind(motor_type)
+    .bind(payload.min_days)
+    .bind(payload.max_days)
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    println!("⏱️  Duration rule for {} set to {:?} - {:?} days", rule.motor_type, rule.min_days, rule.max_days);
+    Ok(RespJson(serde_json::json!(rule)))
+}
+
+
+fn unit_db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({
+                "error": "Plate number or VIN is already registered"
+            })))
+        }
+        _ => {
+            println!("🚨 Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        }
+    }
+}
+
+// Plat nomor disimpan huruf besar dengan spasi tunggal, contoh: "B 1234 XYZ"
+fn normalize_plate_number(plate_number: &str) -> Option<String> {
+    let plate = plate_number.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+    (!plate.is_empty()).then_some(plate)
+}
+
+fn validate_unit_year(year: Option<i32>) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    match year {
+        Some(year) if !(1900..=2100).contains(&year) => Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid year"
+        })))),
+        _ => Ok(()),
+    }
+}
+
+// Daftar unit fisik satu model motor (staff; staff cabang hanya untuk motor cabangnya)
+async fn list_motor_units(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_id): Path<i32>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    let units: Vec<MotorUnit> = sqlx::query_as(&format!(
+        "SELECT {} FROM motor_units WHERE motor_id = $1 ORDER BY plate_number",
+        MOTOR_UNIT_COLUMNS
+    ))
+    .bind(motor_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(unit_db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "motorId": motor_id,
+        "units": units,
+        "total": units.len()
+    })))
+}
+
+// Tambah unit untuk model motor; cabang unit default ke cabang motornya
+async fn create_motor_unit(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_id): Path<i32>,
+    Json(payload): Json<CreateMotorUnitRequest>,
+) -> Result<RespJson<MotorUnit>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    let plate_number = normalize_plate_number(&payload.plate_number).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Plate number is required"
+        })))
+    })?;
+    validate_unit_year(payload.year)?;
+
+    let branch = match payload.branch.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+        Some(branch) => {
+            user.require_branch(Some(branch))?;
+            Some(canonical_branch(&pool, branch).await?)
+        }
+        None => None,
+    };
+    let vin = payload.vin.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_uppercase);
+    let status = payload.status.unwrap_or(MotorUnitStatus::Available);
+
+    let unit: MotorUnit = sqlx::query_as(&format!(
+        "INSERT INTO motor_units (id, motor_id, plate_number, vin, year, branch, status)
+         SELECT $1, m.motor_id, $3, $4, $5, COALESCE($6, m.branch), $7 FROM motors m WHERE m.motor_id = $2
+         RETURNING {}",
+        MOTOR_UNIT_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(motor_id)
+    .bind(&plate_number)
+    .bind(vin)
+    .bind(payload.year)
+    .bind(branch)
+    .bind(status.as_str())
+    .fetch_one(&pool)
+    .await
+    .map_err(unit_db_error)?;
+
+    println!("🏍️ Unit {} ({}) added to motor #{}", unit.plate_number, unit.id, motor_id);
+    Ok(RespJson(unit))
+}
+
+// Ubah data unit; memindahkan unit ke cabang lain hanya boleh ke cabang yang boleh dikelola user
+async fn update_motor_unit(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path((motor_id, unit_id)): Path<(i32, Uuid)>,
+    Json(payload): Json<UpdateMotorUnitRequest>,
+) -> Result<RespJson<MotorUnit>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    let plate_number = match payload.plate_number.as_deref() {
+        Some(plate) => Some(normalize_plate_number(plate).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": "Plate number cannot be empty"
+            })))
+        })?),
+        None => None,
+    };
+    validate_unit_year(payload.year)?;
+
+    let branch = match payload.branch.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+        Some(branch) => {
+            user.require_branch(Some(branch))?;
+            Some(canonical_branch(&pool, branch).await?)
+        }
+        None => None,
+    };
+    let vin = payload.vin.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_uppercase);
+
+    let unit: Option<MotorUnit> = sqlx::query_as(&format!(
+        "UPDATE motor_units
+         SET plate_number = COALESCE($3, plate_number),
+             vin = COALESCE($4, vin),
+             year = COALESCE($5, year),
+             branch = COALESCE($6, branch),
+             status = COALESCE($7, status),
+             updated_at = NOW()
+         WHERE id = $1 AND motor_id = $2
+         RETURNING {}",
+        MOTOR_UNIT_COLUMNS
+    ))
+    .bind(unit_id)
+    .bind(motor_id)
+    .bind(plate_number)
+    .bind(vin)
+    .bind(payload.year)
+    .bind(branch)
+    .bind(payload.status.map(|status| status.as_str()))
+    .fetch_optional(&pool)
+    .await
+    .map_err(unit_db_error)?;
+
+    let unit = unit.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Motor unit not found"
+        })))
+    })?;
+
+    println!("🔄 Unit {} ({}) of motor #{} updated", unit.plate_number, unit.id, motor_id);
+    Ok(RespJson(unit))
+}
+
+// Hapus unit yang tidak sedang / akan disewa. Unit yang pernah disewa sebaiknya diberi status
+// retired supaya riwayat order tetap menunjuk ke plat nomornya.
+async fn delete_motor_unit(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path((motor_id, unit_id)): Path<(i32, Uuid)>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    let (booked,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (
+             SELECT 1 FROM orders o
+             WHERE (o.unit_id = $1 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.unit_id = $1))
+               AND o.status NOT IN ('cancelled', 'returned', 'completed')
+         )"
+    )
+    .bind(unit_id)
+    .fetch_one(&pool)
+    .await
+    .map_err(unit_db_error)?;
+
+    if booked {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Motor unit still has active bookings; set its status to retired instead"
+        }))));
+    }
+
+    let result = sqlx::query("DELETE FROM motor_units WHERE id = $1 AND motor_id = $2")
+        .bind(unit_id)
+        .bind(motor_id)
+        .execute(&pool)
+        .await
+        .map_err(unit_db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Motor unit not found"
+        }))));
+    }
+
+    println!("🗑️ Unit {} of motor #{} deleted", unit_id, motor_id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Motor unit deleted successfully"
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Jumlah placeholder $n di SQL = jumlah nilai yang di-bind lewat push_bind
+    fn bind_count(sql: &str) -> usize {
+        sql.match_indices('$')
+            .filter(|(i, _)| sql[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+            .count()
+    }
+
+    fn count_sql(filters: &MotorListFilters) -> String {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) AS total FROM motors");
+        push_motor_filters(&mut query, filters);
+        query.sql().to_string()
+    }
+
+    fn fetch_sql(filters: &MotorListFilters, sort: Option<&str>) -> String {
+        let order_by = motor_order_by(sort, filters.search.is_some()).unwrap();
+        let mut query = QueryBuilder::<Postgres>::new("SELECT motor_id FROM motors");
+        push_motor_filters(&mut query, filters);
+        push_motor_order(&mut query, order_by, filters.search);
+        query.sql().to_string()
+    }
+
+    #[test]
+    fn list_without_filters_only_excludes_deleted() {
+        let sql = count_sql(&MotorListFilters::default());
+        assert_eq!(sql, "SELECT COUNT(*) AS total FROM motors WHERE deleted_at IS NULL");
+        assert_eq!(bind_count(&sql), 0);
+    }
+
+    #[test]
+    fn list_binds_every_scalar_filter_in_order() {
+        let filters = MotorListFilters {
+            motor_type: Some("matic"),
+            available_only: true,
+            min_price: Some(50_000),
+            max_price: Some(150_000),
+            branch: Some("Malang"),
+            transmission: Some(Transmission::Manual),
+            min_cc: Some(110),
+            max_cc: Some(160),
+            status: Some(MotorStatus::Active),
+            ..Default::default()
+        };
+
+        let sql = count_sql(&filters);
+        assert_eq!(
+            sql,
+            "SELECT COUNT(*) AS total FROM motors WHERE deleted_at IS NULL AND motor_type = $1 AND available = $2 \
+             AND price_per_day >= $3 AND price_per_day <= $4 AND LOWER(TRIM(branch)) = LOWER(TRIM($5)) \
+             AND transmission = $6 AND engine_cc >= $7 AND engine_cc <= $8 AND status = $9"
+        );
+        assert_eq!(bind_count(&sql), 9);
+    }
+
+    #[test]
+    fn list_availability_window_binds_range_once() {
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let filters = MotorListFilters {
+            branch: Some("Malang"),
+            available_window: Some((start, start + chrono::Duration::days(3))),
+            ..Default::default()
+        };
+
+        let sql = count_sql(&filters);
+        assert!(sql.contains(" AND LOWER(TRIM(branch)) = LOWER(TRIM($1))"));
+        assert!(sql.contains(" AND EXISTS (SELECT 1 FROM (SELECT $2::timestamp AS starts_at, $3::timestamp AS ends_at) w WHERE "));
+        assert!(sql.ends_with(')'));
+        assert_eq!(bind_count(&sql), 3);
+    }
+
+    #[test]
+    fn list_search_without_sort_orders_by_relevance() {
+        let filters = MotorListFilters {
+            motor_type: Some("matic"),
+            search: Some("nmax:* & malang:*"),
+            ..Default::default()
+        };
+
+        let sql = fetch_sql(&filters, None);
+        assert_eq!(
+            sql,
+            "SELECT motor_id FROM motors WHERE deleted_at IS NULL AND motor_type = $1 \
+             AND search_vector @@ to_tsquery('simple', $2) \
+             ORDER BY ts_rank(search_vector, to_tsquery('simple', $3)) DESC, motor_id ASC"
+        );
+        assert_eq!(bind_count(&sql), 3);
+        assert_eq!(fetch_sql(&filters, Some("relevance")), sql);
+    }
+
+    #[test]
+    fn list_search_with_explicit_sort_skips_rank() {
+        let filters = MotorListFilters {
+            search: Some("nmax:*"),
+            ..Default::default()
+        };
+
+        let sql = fetch_sql(&filters, Some("price_asc"));
+        assert_eq!(
+            sql,
+            "SELECT motor_id FROM motors WHERE deleted_at IS NULL AND search_vector @@ to_tsquery('simple', $1) \
+             ORDER BY price_per_day ASC, motor_id ASC"
+        );
+        assert_eq!(bind_count(&sql), 1);
+    }
+
+    #[test]
+    fn list_sort_without_search() {
+        let filters = MotorListFilters::default();
+
+        assert!(fetch_sql(&filters, None).ends_with(" ORDER BY motor_id ASC"));
+        assert!(fetch_sql(&filters, Some("relevance")).ends_with(" ORDER BY motor_id ASC"));
+        assert!(fetch_sql(&filters, Some("rating")).ends_with(" ORDER BY rating_avg DESC NULLS LAST, rating_count DESC, motor_id ASC"));
+        assert_eq!(bind_count(&fetch_sql(&filters, Some("newest"))), 0);
+
+        let (status, _) = motor_order_by(Some("cheapest"), false).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn update_sets_only_present_fields() {
+        let payload = UpdateMotorRequest {
+            motor_name: Some("Yamaha NMAX".to_string()),
+            price_per_day: Some(120_000),
+            image_url: Some("/media/motors/nmax.jpg".to_string()),
+            ..Default::default()
+        };
+
+        let query = motor_update_query(7, &payload);
+        let sql = query.sql();
+        assert!(sql.starts_with(
+            "UPDATE motors SET motor_name = $1, price_per_day = $2, image_url = $3, image_thumbnails = NULL \
+             WHERE motor_id = $4 RETURNING "
+        ));
+        assert_eq!(bind_count(sql), 4);
+    }
+
+    #[test]
+    fn update_single_field() {
+        let payload = UpdateMotorRequest {
+            transmission: Some(Transmission::SemiAutomatic),
+            ..Default::default()
+        };
+
+        let query = motor_update_query(7, &payload);
+        let sql = query.sql();
+        assert!(sql.starts_with("UPDATE motors SET transmission = $1 WHERE motor_id = $2 RETURNING "));
+        assert_eq!(bind_count(sql), 2);
+    }
+}
@@ -0,0 +1,106 @@
+use axum::{
+    Router,
+    routing::post,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension, Path},
+    http::StatusCode,
+    middleware,
+    response::Json as RespJson,
+};
+use serde_json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::auth::scope;
+use crate::config;
+use crate::media::{generate_thumbnails, image_extension, motor_media_dir};
+use crate::middleware::scope::{require_scope, RequireScope};
+use crate::model::motor::Motor;
+use crate::routes::motor::{authorize_motor_branch, motor_from_row, MOTOR_COLUMNS};
+
+pub fn motor_image_router() -> Router {
+    Router::new()
+        .route("/api/motors/:id/image", post(upload_motor_image).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        // Foto dari kamera HP bisa beberapa MB, di atas batas default axum (2 MB)
+        .layer(DefaultBodyLimit::max(config::get().max_image_upload_bytes))
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+fn io_error(e: std::io::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Failed to store image: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Failed to store image"})))
+}
+
+// Unggah foto motor (body = isi file JPEG / PNG / WebP). Original langsung dipakai sebagai image_url,
+// thumbnail webp (card / detail / zoom) dibuat di background dan muncul di image_thumbnails setelah
+// selesai. Staff hanya untuk motor di cabangnya.
+async fn upload_motor_image(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(motor_id): Path<i32>,
+    body: Bytes,
+) -> Result<(StatusCode, RespJson<Motor>), (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    if body.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Image file is required"}))));
+    }
+    let extension = image_extension(&body).ok_or_else(|| {
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, RespJson(serde_json::json!({"error": "Image must be JPEG, PNG or WebP"})))
+    })?;
+
+    let (dir, url_dir) = motor_media_dir(motor_id);
+    let stem = Uuid::new_v4().to_string();
+    let original = dir.join(format!("{}.{}", stem, extension));
+    tokio::fs::create_dir_all(&dir).await.map_err(io_error)?;
+    tokio::fs::write(&original, &body).await.map_err(io_error)?;
+    let image_url = format!("{}/{}.{}", url_dir, stem, extension);
+
+    let row = sqlx::query(&format!(
+        "UPDATE motors SET image_url = $2, image_thumbnails = NULL
+         WHERE motor_id = $1 AND deleted_at IS NULL
+         RETURNING {}",
+        MOTOR_COLUMNS
+    ))
+    .bind(motor_id)
+    .bind(&image_url)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+    let motor = row.as_ref().map(motor_from_row).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Motor not found"})))
+    })?;
+
+    let task_pool = pool.clone();
+    tokio::spawn(async move {
+        let result = tokio::task::spawn_blocking(move || generate_thumbnails(&original, &dir, &url_dir, &stem)).await;
+        match result {
+            Ok(Ok(thumbnails)) => {
+                // Lewati jika foto motor sudah diganti lagi selama thumbnail diproses
+                let saved = sqlx::query("UPDATE motors SET image_thumbnails = $3 WHERE motor_id = $1 AND image_url = $2")
+                    .bind(motor_id)
+                    .bind(&image_url)
+                    .bind(sqlx::types::Json(&thumbnails))
+                    .execute(&task_pool)
+                    .await;
+                match saved {
+                    Ok(_) => println!("🖼️ Thumbnails ready for motor #{}", motor_id),
+                    Err(e) => println!("❌ Failed to save thumbnails for motor #{}: {}", motor_id, e),
+                }
+            }
+            Ok(Err(e)) => println!("❌ Failed to generate thumbnails for motor #{}: {}", motor_id, e),
+            Err(e) => println!("❌ Thumbnail task for motor #{} failed: {}", motor_id, e),
+        }
+    });
+
+    println!("🖼️ Image uploaded for motor #{} ({} bytes) by {}", motor_id, body.len(), user.id);
+    Ok((StatusCode::ACCEPTED, RespJson(motor)))
+}
@@ -1,361 +1,3379 @@
 use axum::{
     Router,
-    routing::{get, post, put, delete},
-    extract::{Extension, Json, Path},
-    http::{StatusCode, HeaderMap},
-    response::Json as RespJson,
+    routing::{get, post, put, patch, delete},
+    extract::{ConnectInfo, Extension, Json, Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    middleware,
+    body::Body,
+    response::{IntoResponse, Json as RespJson, Response},
 };
-use sqlx::PgPool;
+use futures_util::TryStreamExt;
+use std::net::{IpAddr, SocketAddr};
+use chrono::{NaiveDateTime, NaiveTime};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use serde_json;
 
-// Helper function untuk ambil user dari token
-async fn get_user_from_token(headers: &HeaderMap, pool: &PgPool) -> Result<Uuid, StatusCode> {
-    // Ambil token dari header Authorization
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|header| header.to_str().ok())
-        .and_then(|header| {
-            if header.starts_with("Bearer ") {
-                Some(&header[7..])
-            } else {
-                None
-            }
+use crate::auth::AuthUser;
+use crate::auth::audit::client_ip;
+use crate::auth::guest::{create_guest, create_walk_in_customer, parse_guest_contact, parse_walk_in_contact, WalkInContact};
+use crate::auth::scope;
+use crate::auth::token::hash_token;
+use crate::booking_code::next_booking_code;
+use crate::config;
+use crate::invoice::{render_invoice, InvoiceData, InvoiceInsurance};
+use crate::mailer::SharedMailer;
+use crate::notifications::{self, language_from_header, notify_order};
+use crate::middleware::scope::{require_scope, RequireScope};
+use crate::model::motor::{format_price_per_day, MotorStatus};
+use crate::model::orders::{AdminOrderRow, OrderExportQuery, OrderListResponse, OrderQuery, OrderResponse, OrderSearchQuery, OrderStatus, OrderStatusHistory};
+use crate::model::orders::{OrderCursor, OrderCursorQuery};
+use crate::model::orders::{DepositStatus, EarlyReturnRequest, FulfillmentMode, HoldDepositRequest, OrderRefund, ReleaseDepositRequest};
+use crate::model::orders::{Addon, AddonSelection, InsuranceTier, OrderItem, OrderMotor, UpsertInsuranceTierRequest};
+use crate::model::orders::{BulkStatusRequest, CreateOrderNoteRequest, NoteVisibility, OrderNote, TimelineEntry, TimelineQuery};
+use crate::model::orders::{CreateReviewRequest, CreateHandoverRequest, CreateReadingRequest, HandoverKind, OrderHandover, OrderReading};
+use crate::model::driver::Driver;
+use crate::model::user::{normalize_phone, Role};
+use crate::routes::blackouts::ensure_no_blackout;
+use crate::routes::branches::{branch_hours, find_branch, resolve_booking_branch};
+use crate::routes::terms::accepted_terms_version;
+use crate::routes::reviews::{new_review_status, validate_review};
+use crate::routes::pricing_rules::pricing_rules_for_period;
+use crate::routes::accessories::{checkout_order_accessories, return_order_accessories};
+use crate::routes::drivers::{apply_driver_fee, parse_driver_request, reserve_driver, DRIVER_ITEM_CODE};
+use crate::webhooks;
+use crate::pricing::{self, PriceBreakdown, PriceItem, RateCard};
+
+// Motor yang dipesan, diambil dari tabel motors (beserta deposit untuk tipe motornya)
+#[derive(sqlx::FromRow)]
+struct BookedMotor {
+    motor_id: i32,
+    motor_name: String,
+    price_per_day: i32,
+    price_per_week: Option<i32>,
+    price_per_month: Option<i32>,
+    motor_type: String,
+    status: String,
+    deposit_amount: i32,
+    // Unit fisik (plat nomor) yang dipesan, diisi reserve_booked_motors jika motor punya unit
+    unit_id: Option<Uuid>,
+}
+
+// Cari motor dari `motorId`, atau dari `pilihMotor` (nama / slug) untuk frontend lama
+async fn resolve_booked_motor(
+    pool: &PgPool,
+    payload: &serde_json::Value,
+) -> Result<BookedMotor, (StatusCode, RespJson<serde_json::Value>)> {
+    let motor_id = match payload.get("motorId") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(value) => Some(
+            value.as_i64()
+                .or_else(|| value.as_str().and_then(|v| v.trim().parse().ok()))
+                .and_then(|id| i32::try_from(id).ok())
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid motorId"}))))?,
+        ),
+    };
+
+    let pilih_motor = payload.get("pilihMotor").and_then(|v| v.as_str()).map(str::trim);
+    if motor_id.is_none() && pilih_motor.is_none() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing motorId"}))));
+    }
+
+    let motor: Option<BookedMotor> = sqlx::query_as(
+        "SELECT m.motor_id, m.motor_name, m.price_per_day, m.price_per_week, m.price_per_month, m.motor_type, m.status,
+                COALESCE(d.amount, 0) AS deposit_amount, NULL::uuid AS unit_id
+         FROM motors m
+         LEFT JOIN motor_type_deposits d ON d.motor_type = m.motor_type
+         WHERE m.deleted_at IS NULL
+           AND (($1::int IS NOT NULL AND m.motor_id = $1)
+             OR ($1::int IS NULL AND (LOWER(m.motor_name) = LOWER($2) OR m.motor_slug = $2)))
+         ORDER BY m.motor_id ASC
+         LIMIT 1"
+    )
+    .bind(motor_id)
+    .bind(pilih_motor)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    let motor = motor
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Motor not found"}))))?;
+
+    if motor.status != MotorStatus::Active.as_str() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Motor is not available", "status": motor.status}))));
+    }
+
+    Ok(motor)
+}
+
+// Jumlah motor maksimal dalam satu order
+const MAX_MOTORS_PER_ORDER: usize = 10;
+
+// Motor-motor yang dipesan: `motorIds` untuk beberapa motor dalam satu order (keluarga / rombongan),
+// atau satu motor dari `motorId` / `pilihMotor`. Motor pertama menjadi motor utama order.
+async fn resolve_booked_motors(
+    pool: &PgPool,
+    payload: &serde_json::Value,
+) -> Result<Vec<BookedMotor>, (StatusCode, RespJson<serde_json::Value>)> {
+    let ids = match payload.get("motorIds") {
+        None | Some(serde_json::Value::Null) => return Ok(vec![resolve_booked_motor(pool, payload).await?]),
+        Some(serde_json::Value::Array(ids)) => ids,
+        Some(_) => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "motorIds must be an array"}))));
+        }
+    };
+
+    if ids.is_empty() || ids.len() > MAX_MOTORS_PER_ORDER {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("motorIds must contain between 1 and {} motors", MAX_MOTORS_PER_ORDER)
+        }))));
+    }
+
+    let mut motors: Vec<BookedMotor> = Vec::with_capacity(ids.len());
+    for id in ids {
+        // Error per motor menyertakan motorId supaya frontend tahu motor mana yang bermasalah
+        let motor = resolve_booked_motor(pool, &serde_json::json!({"motorId": id}))
+            .await
+            .map_err(|(status, RespJson(mut body))| {
+                body["motorId"] = id.clone();
+                (status, RespJson(body))
+            })?;
+        if motors.iter().any(|m| m.motor_id == motor.motor_id) {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": format!("Motor #{} is listed more than once", motor.motor_id)
+            }))));
+        }
+        motors.push(motor);
+    }
+
+    Ok(motors)
+}
+
+// pilih_motor / motor_price order: nama semua motor dan total tarif hariannya (Rp)
+fn motor_summary(motors: &[BookedMotor]) -> (String, i64) {
+    let names: Vec<&str> = motors.iter().map(|m| m.motor_name.as_str()).collect();
+    let price_per_day = motors.iter().map(|m| i64::from(m.price_per_day)).sum();
+    (names.join(", "), price_per_day)
+}
+
+// Baris order_motors: subtotal sewa tiap motor diambil dari rincian harga order
+fn order_motor_lines(motors: &[BookedMotor], breakdown: &PriceBreakdown) -> Vec<OrderMotor> {
+    motors.iter()
+        .map(|motor| OrderMotor {
+            motor_id: Some(motor.motor_id),
+            motor_name: motor.motor_name.clone(),
+            rental_subtotal: breakdown.motor_subtotal(motor.motor_id),
+            deposit_amount: motor.deposit_amount,
+            unit_id: motor.unit_id,
+            plate_number: None,
         })
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .collect()
+}
+
+// Simpan ulang daftar motor order (order baru, atau setelah booking diubah)
+async fn replace_order_motors(conn: &mut PgConnection, order_id: Uuid, lines: &[OrderMotor]) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM order_motors WHERE order_id = $1")
+        .bind(order_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for (position, line) in lines.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO order_motors (id, order_id, motor_id, motor_name, rental_subtotal, deposit_amount, position, unit_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        )
+        .bind(Uuid::new_v4())
+        .bind(order_id)
+        .bind(line.motor_id)
+        .bind(&line.motor_name)
+        .bind(line.rental_subtotal)
+        .bind(line.deposit_amount)
+        .bind(position as i32)
+        .bind(line.unit_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Daftar motor dalam satu order, motor utama lebih dulu
+async fn fetch_order_motors(pool: &PgPool, order_id: Uuid) -> Result<Vec<OrderMotor>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT l.motor_id, l.motor_name, l.rental_subtotal, l.deposit_amount, l.unit_id, u.plate_number
+         FROM order_motors l
+         LEFT JOIN motor_units u ON u.id = l.unit_id
+         WHERE l.order_id = $1 ORDER BY l.position"
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Aturan tanggal / jam sewa: kembali setelah ambil, ambil tidak di masa lalu, dan jam ambil di dalam
+// jam operasional cabang (`hours`, lihat branch_hours). Lama sewa dicek terpisah di check_duration_rules.
+fn validate_rental_period(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    now: NaiveDateTime,
+    hours: (NaiveTime, NaiveTime),
+) -> Result<(), String> {
+    if end <= start {
+        return Err("Return time must be after pickup time".to_string());
+    }
+
+    if start < now {
+        return Err("Pickup time cannot be in the past".to_string());
+    }
+
+    let (open, close) = hours;
+    if start.time() < open || start.time() > close {
+        return Err(format!(
+            "Pickup time must be within branch operating hours ({} - {})",
+            open.format("%H:%M"),
+            close.format("%H:%M")
+        ));
+    }
+
+    Ok(())
+}
+
+// Minimal / maksimal lama sewa: aturan global (MIN_RENTAL_DAYS / MAX_RENTAL_DAYS) dan aturan per tipe
+// motor di rental_duration_rules; untuk order rombongan berlaku aturan yang paling ketat. Batas
+// maksimal boleh dilewati jika disetujui manajer (`approved`), batas minimal tidak.
+async fn check_duration_rules(
+    pool: &PgPool,
+    motors: &[BookedMotor],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    approved: bool,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let cfg = config::get();
+    let duration = end - start;
+    let rule_error = |message: String| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": message, "code": "duration_rule"})))
+    };
+
+    if cfg.min_rental_days > 0 && duration < chrono::Duration::days(cfg.min_rental_days) {
+        return Err(rule_error(format!("Rental duration must be at least {} days", cfg.min_rental_days)));
+    }
+    if !approved && duration > chrono::Duration::days(cfg.max_rental_days) {
+        return Err(rule_error(format!(
+            "Rental duration cannot exceed {} days without manager approval",
+            cfg.max_rental_days
+        )));
+    }
+
+    let motor_types: Vec<String> = motors.iter().map(|m| m.motor_type.clone()).collect();
+    let rules: Vec<(String, Option<i32>, Option<i32>)> = sqlx::query_as(
+        "SELECT motor_type, min_days, max_days FROM rental_duration_rules WHERE motor_type = ANY($1) ORDER BY motor_type"
+    )
+    .bind(&motor_types)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    for (motor_type, min_days, max_days) in rules {
+        if let Some(min) = min_days.filter(|days| duration < chrono::Duration::days(i64::from(*days))) {
+            return Err(rule_error(format!("Minimum rental for {} is {} days", motor_type, min)));
+        }
+        if let Some(max) = max_days.filter(|days| !approved && duration > chrono::Duration::days(i64::from(*days))) {
+            return Err(rule_error(format!("Maximum rental for {} is {} days without manager approval", motor_type, max)));
+        }
+    }
+
+    Ok(())
+}
+
+// Persetujuan manajer untuk melewati batas maksimal lama sewa: hanya berlaku jika yang membuat /
+// mengubah booking adalah staff / admin dan payload berisi "durationApproved": true
+fn duration_approved(user: Option<&AuthUser>, payload: &serde_json::Value) -> bool {
+    user.is_some_and(|user| user.role.is_staff())
+        && payload.get("durationApproved").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+// Kunci baris motor (SELECT ... FOR UPDATE) sampai transaksi booking selesai. Booking bersamaan
+// untuk motor yang sama jadi antre: request kedua baru mengecek bentrok setelah booking pertama
+// commit, sehingga mendapat 409 yang jelas. Hasilnya status motor saat ini (active / maintenance / ...).
+async fn lock_motor_for_booking(conn: &mut PgConnection, motor_id: i32) -> Result<Option<String>, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT status FROM motors WHERE motor_id = $1 FOR UPDATE")
+        .bind(motor_id)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(row.map(|(status,)| status))
+}
+
+// Kunci semua motor yang dipesan (urut motor_id supaya dua booking rombongan tidak saling menunggu),
+// lepas hold kedaluwarsa, lalu tolak jika salah satu motor sudah dibooking di periode yang sama.
+// Motor yang punya unit (motor_units) memesan satu unit yang masih kosong, motor tanpa unit tetap
+// dihitung satu kendaraan. Constraint exclusion di database (orders_no_overlapping_motor_booking /
+// orders_no_overlapping_unit_booking) tetap menjadi jaring pengaman terakhir untuk motor utama.
+async fn reserve_booked_motors(
+    conn: &mut PgConnection,
+    motors: &mut [BookedMotor],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_order: Option<Uuid>,
+    user_id: Uuid,
+    branch: Option<&str>,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut order: Vec<usize> = (0..motors.len()).collect();
+    order.sort_unstable_by_key(|&i| motors[i].motor_id);
+
+    for i in order {
+        let motor_id = motors[i].motor_id;
+        // Motor bisa saja dinonaktifkan / dihapus sejak dicek di resolve_booked_motor
+        match lock_motor_for_booking(&mut *conn, motor_id).await.map_err(db_error)? {
+            Some(status) if status == MotorStatus::Active.as_str() => {}
+            Some(status) => {
+                return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Motor is not available", "motorId": motor_id, "status": status}))));
+            }
+            None => {
+                return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Motor not found", "motorId": motor_id}))));
+            }
+        }
+
+        // Hold yang sudah kedaluwarsa untuk motor ini dilepas dulu supaya tidak dianggap bentrok
+        expire_holds(&mut *conn, Some(motor_id)).await.map_err(db_error)?;
+
+        if has_motor_units(&mut *conn, motor_id).await.map_err(db_error)? {
+            // Unit lain yang masih kosong tidak boleh menutupi booking ganda user yang sama
+            // (misalnya submit dua kali) untuk motor dan periode ini
+            if let Some(existing) = find_conflicting_booking(&mut *conn, motor_id, start, end, exclude_order, Some(user_id))
+                .await
+                .map_err(db_error)?
+            {
+                println!("⛔ User {} already has booking {} for motor #{} in {} - {}", user_id, existing.order_number, motor_id, start, end);
+                let (status, RespJson(mut body)) = booking_conflict_error(Some(existing), Some(user_id));
+                body["motorId"] = serde_json::json!(motor_id);
+                return Err((status, RespJson(body)));
+            }
+
+            let unit = find_free_unit(&mut *conn, motor_id, start, end, exclude_order, branch)
+                .await
+                .map_err(db_error)?;
+            if unit.is_none() {
+                println!("⛔ Motor #{} has no free unit for {} - {}", motor_id, start, end);
+                let conflict = find_conflicting_booking(&mut *conn, motor_id, start, end, exclude_order, None)
+                    .await
+                    .map_err(db_error)?;
+                let (status, RespJson(mut body)) = booking_conflict_error(conflict, Some(user_id));
+                body["motorId"] = serde_json::json!(motor_id);
+                if body.get("code").is_none() {
+                    body["code"] = serde_json::json!("no_unit_available");
+                }
+                return Err((status, RespJson(body)));
+            }
+            motors[i].unit_id = unit;
+            continue;
+        }
+
+        if let Some(conflict) = find_conflicting_booking(&mut *conn, motor_id, start, end, exclude_order, None)
+            .await
+            .map_err(db_error)?
+        {
+            println!("⛔ Motor #{} already booked {} - {} ({})", motor_id, conflict.start, conflict.end, conflict.order_number);
+            let (status, RespJson(mut body)) = booking_conflict_error(Some(conflict), Some(user_id));
+            body["motorId"] = serde_json::json!(motor_id);
+            return Err((status, RespJson(body)));
+        }
+    }
+
+    Ok(())
+}
+
+// Motor sudah dipecah menjadi unit fisik (minimal satu unit yang belum pensiun)
+async fn has_motor_units(conn: &mut PgConnection, motor_id: i32) -> Result<bool, sqlx::Error> {
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM motor_units WHERE motor_id = $1 AND status <> 'retired')"
+    )
+    .bind(motor_id)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(exists)
+}
+
+// Unit motor yang siap disewa, tidak sedang dijadwalkan perawatan, dan belum dipesan order aktif lain
+// di periode ini. Unit yang sudah dipegang `exclude_order` (booking yang sedang diubah) didahulukan,
+// lalu unit di cabang booking.
+async fn find_free_unit(
+    conn: &mut PgConnection,
+    motor_id: i32,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_order: Option<Uuid>,
+    branch: Option<&str>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let unit: Option<(Uuid,)> = sqlx::query_as(
+        "SELECT u.id FROM motor_units u
+         WHERE u.motor_id = $1 AND u.status = 'available'
+           AND NOT EXISTS (
+               SELECT 1 FROM orders o
+               WHERE (o.unit_id = u.id OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.unit_id = u.id))
+                 AND o.status <> 'cancelled'
+                 AND ($4::uuid IS NULL OR o.id <> $4)
+                 AND tsrange(o.tanggal_peminjaman + o.jam_peminjaman, o.tanggal_pengembalian + o.jam_pengembalian, '[)')
+                     && tsrange($2, $3, '[)')
+           )
+           AND NOT EXISTS (
+               SELECT 1 FROM unit_maintenance mt
+               WHERE mt.unit_id = u.id
+                 AND mt.status IN ('scheduled', 'in_progress')
+                 -- Perawatan yang molor (masih in_progress) menahan unit sampai ditandai selesai
+                 AND tsrange(mt.starts_at,
+                             CASE WHEN mt.status = 'in_progress' THEN GREATEST(mt.ends_at, LOCALTIMESTAMP) ELSE mt.ends_at END,
+                             '[)')
+                     && tsrange($2, $3, '[)')
+           )
+         ORDER BY EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = $4 AND l.unit_id = u.id) DESC,
+                  COALESCE(LOWER(TRIM(u.branch)) = LOWER(TRIM($5)), FALSE) DESC,
+                  u.plate_number
+         LIMIT 1
+         FOR UPDATE OF u"
+    )
+    .bind(motor_id)
+    .bind(start)
+    .bind(end)
+    .bind(exclude_order)
+    .bind(branch)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(unit.map(|(id,)| id))
+}
+
+// Order aktif yang bentrok dengan periode booking baru
+#[derive(sqlx::FromRow)]
+struct ConflictingBooking {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    user_id: Uuid,
+    order_number: String,
+}
+
+// Cari order aktif (bukan cancelled) berisi motor yang sama (motor utama atau salah satu motor
+// di order_motors) dengan periode yang bertabrakan, selain `exclude_order` (order yang sedang diubah).
+// Dengan `owner` hanya order milik user itu yang dicari (deteksi booking ganda).
+async fn find_conflicting_booking(
+    conn: &mut PgConnection,
+    motor_id: i32,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_order: Option<Uuid>,
+    owner: Option<Uuid>,
+) -> Result<Option<ConflictingBooking>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT tanggal_peminjaman + jam_peminjaman AS start, tanggal_pengembalian + jam_pengembalian AS end,
+                user_id, order_number
+         FROM orders
+         WHERE (motor_id = $1 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = orders.id AND l.motor_id = $1))
+           AND status <> 'cancelled'
+           AND ($4::uuid IS NULL OR id <> $4)
+           AND ($5::uuid IS NULL OR user_id = $5)
+           AND tsrange(tanggal_peminjaman + jam_peminjaman, tanggal_pengembalian + jam_pengembalian, '[)')
+               && tsrange($2, $3, '[)')
+         ORDER BY tanggal_peminjaman, jam_peminjaman
+         LIMIT 1"
+    )
+    .bind(motor_id)
+    .bind(start)
+    .bind(end)
+    .bind(exclude_order)
+    .bind(owner)
+    .fetch_optional(conn)
+    .await
+}
+
+// Error 409 berisi periode yang sudah dibooking, supaya frontend bisa menyarankan tanggal lain.
+// Jika yang bentrok booking milik user itu sendiri (misalnya tombol submit tertekan dua kali),
+// kode booking-nya ikut dikirim supaya frontend bisa langsung mengarahkan ke booking tersebut.
+fn booking_conflict_error(conflict: Option<ConflictingBooking>, user_id: Option<Uuid>) -> (StatusCode, RespJson<serde_json::Value>) {
+    if let Some(existing) = conflict.as_ref().filter(|c| Some(c.user_id) == user_id) {
+        return (StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": format!("You already have booking {} for this motor in the selected period", existing.order_number),
+            "code": "duplicate_booking",
+            "existingBookingCode": existing.order_number,
+            "conflictingPeriod": {"start": existing.start, "end": existing.end}
+        })));
+    }
+
+    let conflicting_period = conflict.map(|c| serde_json::json!({"start": c.start, "end": c.end}));
+    (StatusCode::CONFLICT, RespJson(serde_json::json!({
+        "error": "Motor is already booked for the selected period",
+        "conflictingPeriod": conflicting_period
+    })))
+}
+
+// Batalkan hold yang sudah kedaluwarsa (semua motor, atau hanya motor tertentu) supaya motornya
+// tersedia lagi. Dipakai juga oleh background job di jobs.rs.
+pub async fn expire_holds(conn: &mut PgConnection, motor_id: Option<i32>) -> Result<u64, sqlx::Error> {
+    let expired: Vec<(Uuid,)> = sqlx::query_as(
+        "WITH expired AS (
+             UPDATE orders SET status = $1
+             WHERE status = $2 AND hold_expires_at <= NOW()
+               AND ($3::int IS NULL OR motor_id = $3
+                    OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = orders.id AND l.motor_id = $3))
+             RETURNING id
+         ), history AS (
+             INSERT INTO order_status_history (order_id, old_status, new_status, note)
+             SELECT id, $2, $1, 'Hold kedaluwarsa' FROM expired
+         )
+         SELECT id FROM expired"
+    )
+    .bind(OrderStatus::Cancelled.as_str())
+    .bind(OrderStatus::Held.as_str())
+    .bind(motor_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(expired.len() as u64)
+}
+
+// Ubah status satu order sesuai state machine OrderStatus dan catat ke riwayat.
+// Hasilnya status sebelum diubah; transisi yang tidak valid ditolak 422 beserta daftar status berikutnya,
+// transisi yang tidak boleh dilakukan role ini ditolak 403 beserta status yang boleh dipilih role tersebut.
+async fn change_order_status(
+    pool: &PgPool,
+    order_id: Uuid,
+    next_status: OrderStatus,
+    changed_by: Uuid,
+    role: Role,
+    note: Option<&str>,
+) -> Result<OrderStatus, (StatusCode, RespJson<serde_json::Value>)> {
+    let db_error = |e: sqlx::Error| {
+        println!("Update booking error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Kunci baris order supaya dua update bersamaan tidak melompati state machine
+    let current: Option<(String,)> = sqlx::query_as("SELECT status FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_id)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    let (current_status,) = current
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    // Status lama yang tidak dikenal diperlakukan seperti pending
+    let current = current_status.parse::<OrderStatus>().unwrap_or(OrderStatus::Pending);
+    if !current.can_transition_to(next_status) {
+        let allowed: Vec<&str> = current.allowed_next().iter().map(OrderStatus::as_str).collect();
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, RespJson(serde_json::json!({
+            "error": format!("Cannot change status from {} to {}", current.as_str(), next_status.as_str()),
+            "currentStatus": current.as_str(),
+            "allowedNextStates": allowed
+        }))));
+    }
+    if !current.allowed_next_for(role).contains(&next_status) {
+        let allowed: Vec<&str> = current.allowed_next_for(role).iter().map(OrderStatus::as_str).collect();
+        println!("⛔ User {} ({}) tried to change order {} from {} to {}", changed_by, role.as_str(), order_id, current.as_str(), next_status.as_str());
+        return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": format!("{} cannot change status from {} to {}", role.as_str(), current.as_str(), next_status.as_str()),
+            "currentStatus": current.as_str(),
+            "allowedNextStates": allowed
+        }))));
+    }
+
+    sqlx::query!(
+        "UPDATE orders SET status = $1 WHERE id = $2",
+        next_status.as_str(),
+        order_id
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    record_status_change(&mut tx, order_id, Some(&current_status), next_status, Some(changed_by), note)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(current)
+}
+
+// Ambil periode sewa (tanggal + jam ambil dan kembali) dari payload booking
+fn parse_rental_period(payload: &serde_json::Value) -> Result<(NaiveDateTime, NaiveDateTime), (StatusCode, RespJson<serde_json::Value>)> {
+    let field = |name: &str| {
+        payload.get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Missing {}", name)}))))
+    };
+    let date = |name: &str| {
+        chrono::NaiveDate::parse_from_str(field(name)?, "%Y-%m-%d")
+            .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Invalid {} format", name)}))))
+    };
+    let time = |name: &str| {
+        chrono::NaiveTime::parse_from_str(field(name)?, "%H:%M")
+            .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Invalid {} format", name)}))))
+    };
+
+    Ok((
+        date("tanggalPeminjaman")?.and_time(time("jamPeminjaman")?),
+        date("tanggalPengembalian")?.and_time(time("jamPengembalian")?),
+    ))
+}
+
+// Mode serah terima dari payload ("fulfillmentMode"); frontend lama selalu mengirim alamat, jadi default delivery
+fn fulfillment_mode(payload: &serde_json::Value) -> Result<FulfillmentMode, (StatusCode, RespJson<serde_json::Value>)> {
+    match payload.get("fulfillmentMode") {
+        None | Some(serde_json::Value::Null) => Ok(FulfillmentMode::Delivery),
+        Some(value) => value.as_str()
+            .ok_or_else(|| "Invalid fulfillmentMode".to_string())
+            .and_then(|mode| mode.trim().parse::<FulfillmentMode>())
+            .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e})))),
+    }
+}
+
+// (alamat pengantaran, alamat pengembalian); keduanya None untuk ambil di cabang
+type DeliveryAddresses = (Option<String>, Option<String>);
+
+// Alamat antar & jemput sesuai mode: ambil di cabang tidak memakai alamat, delivery wajib keduanya
+fn delivery_addresses(
+    payload: &serde_json::Value,
+    mode: FulfillmentMode,
+) -> Result<DeliveryAddresses, (StatusCode, RespJson<serde_json::Value>)> {
+    if mode == FulfillmentMode::Pickup {
+        return Ok((None, None));
+    }
+
+    let address = |name: &str| {
+        payload.get(name)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Missing {}", name)}))))
+    };
+    Ok((Some(address("alamatPengantaran")?), Some(address("alamatPengembalian")?)))
+}
+
+// Hitung rincian harga booking: tarif sewa termurah per motor (disesuaikan aturan harga dinamis), add-on, ongkir antar / jemput motor,
+// dan sopir (jika booking dengan sopir)
+async fn price_booking(
+    pool: &PgPool,
+    motors: &[BookedMotor],
+    driver: Option<&Driver>,
+    payload: &serde_json::Value,
+    rental_start: NaiveDateTime,
+    rental_end: NaiveDateTime,
+    (delivery_address, return_address): (Option<&str>, Option<&str>),
+) -> Result<PriceBreakdown, (StatusCode, RespJson<serde_json::Value>)> {
+    // Harga sewa tiap motor: kombinasi tarif harian / mingguan / bulanan termurah untuk lama sewa ini
+    let days = pricing::rental_days(rental_start, rental_end);
+    let mut quotes = motors.iter().map(|motor| {
+        let rates = RateCard {
+            per_day: motor.price_per_day,
+            per_week: motor.price_per_week,
+            per_month: motor.price_per_month,
+        };
+        pricing::quote(rates, days).for_motor(motor.motor_id, &motor.motor_name)
+    });
+    let mut breakdown = quotes.next()
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing motorId"}))))?;
+    quotes.for_each(|quote| breakdown.add_rental(quote));
+
+    // Aturan harga musim ramai / weekend yang berlaku di tanggal sewa dan cabang booking
+    let branch = payload.get("pilihCabang").and_then(|v| v.as_str());
+    let first_day = rental_start.date();
+    let last_day = first_day + chrono::Duration::days(days - 1);
+    let rules = pricing_rules_for_period(pool, branch, first_day, last_day).await.map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+    pricing::apply_pricing_rules(&mut breakdown, first_day, &rules);
+
+    // Add-on pilihan customer, harga diambil dari katalog addons
+    let addons = parse_addon_selection(payload)?;
+    apply_addons(pool, &addons, &mut breakdown).await?;
+
+    // Asuransi pilihan customer, biaya per motor per hari
+    if let Some(code) = insurance_tier(payload) {
+        let tier = find_insurance_tier(pool, &code).await?;
+        let quantity = breakdown.rental_days * motors.len() as i64;
+        breakdown.add_item(INSURANCE_ITEM_CODE, &format!("Asuransi {}", tier.name), quantity, i64::from(tier.daily_fee));
+    }
+
+    // Ongkir sesuai zona alamat antar dan alamat jemput
+    let legs = [("delivery", "Ongkir antar", delivery_address), ("return_pickup", "Ongkir jemput", return_address)];
+    for (code, label, address) in legs {
+        let Some(address) = address.map(str::trim).filter(|a| !a.is_empty()) else {
+            continue;
+        };
+        let (zone, fee) = delivery_fee(pool, address).await.map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+        })?;
+        if fee > 0 {
+            breakdown.add_item(code, &format!("{} ({})", label, zone), 1, fee);
+        }
+    }
+
+    if let Some(driver) = driver {
+        apply_driver_fee(&mut breakdown, driver);
+    }
+
+    // Kode promo, dihitung dari total setelah add-on dan ongkir
+    if let Some(code) = promo_code(payload) {
+        let discount = promo_discount_for(pool, &code, breakdown.total).await?;
+        if discount > 0 {
+            breakdown.add_item(PROMO_ITEM_CODE, &format!("Promo {}", code), 1, -discount);
+        }
+    }
+
+    Ok(breakdown)
+}
+
+const PROMO_ITEM_CODE: &str = "promo";
+
+const INSURANCE_ITEM_CODE: &str = "insurance";
+
+const MILEAGE_ITEM_CODE: &str = "mileage";
+
+// Kode asuransi dari payload ("insuranceTier"), dinormalisasi ke huruf kecil
+fn insurance_tier(payload: &serde_json::Value) -> Option<String> {
+    payload.get("insuranceTier")
+        .and_then(|v| v.as_str())
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+}
+
+// Pilihan asuransi aktif; kode yang tidak dikenal / nonaktif ditolak 400
+async fn find_insurance_tier(pool: &PgPool, code: &str) -> Result<InsuranceTier, (StatusCode, RespJson<serde_json::Value>)> {
+    let tier: Option<InsuranceTier> = sqlx::query_as(
+        "SELECT code, name, daily_fee, coverage, active FROM insurance_tiers WHERE code = $1 AND active = TRUE"
+    )
+    .bind(code)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    tier.ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Unknown insurance tier: {}", code)}))))
+}
+
+// Constraint exclusion untuk sopir yang bertugas di dua order yang bertabrakan
+const DRIVER_OVERLAP_CONSTRAINT: &str = "orders_no_overlapping_driver_booking";
+
+// Kode promo dari payload ("promoCode"), dinormalisasi ke huruf besar
+fn promo_code(payload: &serde_json::Value) -> Option<String> {
+    payload.get("promoCode")
+        .and_then(|v| v.as_str())
+        .map(|code| code.trim().to_uppercase())
+        .filter(|code| !code.is_empty())
+}
+
+// Cari promo aktif yang berlaku hari ini dan hitung potongannya. Kode tidak dikenal,
+// kedaluwarsa, atau total di bawah minimum ditolak 400.
+async fn promo_discount_for(pool: &PgPool, code: &str, total: i64) -> Result<i64, (StatusCode, RespJson<serde_json::Value>)> {
+    let promo: Option<(Option<i32>, Option<i32>, i64)> = sqlx::query_as(
+        "SELECT percent_off, amount_off, min_total FROM promo_codes
+         WHERE code = $1 AND active = TRUE
+           AND (valid_from IS NULL OR valid_from <= CURRENT_DATE)
+           AND (valid_until IS NULL OR valid_until >= CURRENT_DATE)"
+    )
+    .bind(code)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    let (percent_off, amount_off, min_total) = promo
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid or expired promo code"}))))?;
+
+    if total < min_total {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Promo code requires a minimum total of {}", pricing::format_rupiah(min_total))
+        }))));
+    }
+
+    Ok(pricing::promo_discount(total, percent_off, amount_off))
+}
+
+// Cari zona pengantaran dari alamat (kata kunci zona muncul di alamat). Alamat yang tidak cocok
+// dengan zona mana pun dikenai DELIVERY_DEFAULT_FEE.
+async fn delivery_fee(pool: &PgPool, address: &str) -> Result<(String, i64), sqlx::Error> {
+    let zone: Option<(String, i32)> = sqlx::query_as(
+        "SELECT name, fee FROM delivery_zones
+         WHERE active = TRUE
+           AND EXISTS (SELECT 1 FROM unnest(keywords) AS k WHERE LOWER($1) LIKE '%' || LOWER(k) || '%')
+         ORDER BY fee ASC
+         LIMIT 1"
+    )
+    .bind(address)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match zone {
+        Some((name, fee)) => (name, i64::from(fee)),
+        None => ("di luar zona".to_string(), config::get().delivery_default_fee),
+    })
+}
+
+// Ambil daftar add-on dari payload booking: "addons": [{"code": "helmet", "quantity": 1}]
+fn parse_addon_selection(payload: &serde_json::Value) -> Result<Vec<AddonSelection>, (StatusCode, RespJson<serde_json::Value>)> {
+    match payload.get("addons") {
+        None | Some(serde_json::Value::Null) => Ok(Vec::new()),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid addons format"})))),
+    }
+}
+
+// Hitung harga add-on dari katalog dan masukkan ke rincian harga. Kode yang tidak dikenal /
+// nonaktif, atau jumlah di luar 1-10, ditolak.
+async fn apply_addons(
+    pool: &PgPool,
+    selections: &[AddonSelection],
+    breakdown: &mut PriceBreakdown,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    if selections.is_empty() {
+        return Ok(());
+    }
+
+    let codes: Vec<String> = selections.iter().map(|s| s.code.trim().to_string()).collect();
+    let catalog: Vec<Addon> = sqlx::query_as(
+        "SELECT code, name, price, per_day FROM addons WHERE active = TRUE AND code = ANY($1)"
+    )
+    .bind(&codes)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    for selection in selections {
+        let code = selection.code.trim();
+        let addon = catalog.iter().find(|addon| addon.code == code).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Unknown addon: {}", code)})))
+        })?;
+
+        let quantity = selection.quantity.unwrap_or(1);
+        if !(1..=10).contains(&quantity) {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+                "error": format!("Quantity for {} must be between 1 and 10", code)
+            }))));
+        }
+
+        let unit_price = if addon.per_day {
+            i64::from(addon.price) * breakdown.rental_days
+        } else {
+            i64::from(addon.price)
+        };
+        breakdown.add_item(&addon.code, &addon.name, quantity, unit_price);
+    }
+
+    Ok(())
+}
+
+// Simpan item tambahan (add-on, ongkir, dll) sebagai baris order_items
+async fn insert_order_items(conn: &mut PgConnection, order_id: Uuid, items: &[PriceItem]) -> Result<(), sqlx::Error> {
+    for item in items {
+        sqlx::query(
+            "INSERT INTO order_items (id, order_id, code, name, quantity, unit_price, subtotal)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        )
+        .bind(Uuid::new_v4())
+        .bind(order_id)
+        .bind(&item.code)
+        .bind(&item.name)
+        .bind(item.quantity)
+        .bind(item.unit_price)
+        .bind(item.subtotal)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Catat perubahan status order ke order_status_history (old_status NULL = order baru dibuat)
+// sekaligus mengantrekan webhook event-nya
+async fn record_status_change(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    old_status: Option<&str>,
+    new_status: OrderStatus,
+    changed_by: Option<Uuid>,
+    note: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO order_status_history (order_id, old_status, new_status, changed_by, note)
+         VALUES ($1, $2, $3, $4, $5)"
+    )
+    .bind(order_id)
+    .bind(old_status)
+    .bind(new_status.as_str())
+    .bind(changed_by)
+    .bind(note)
+    .execute(&mut *conn)
+    .await?;
+
+    for event in webhooks::events_for_transition(old_status, new_status) {
+        webhooks::enqueue_order_event(conn, event, order_id).await?;
+    }
+
+    // Aksesoris add-on keluar dari stok cabang saat motor diambil dan masuk lagi saat dikembalikan
+    if old_status != Some(new_status.as_str()) {
+        match new_status {
+            OrderStatus::PickedUp => checkout_order_accessories(conn, order_id).await?,
+            OrderStatus::Returned => return_order_accessories(conn, order_id).await?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// SELECT order + user + motor untuk tampilan admin (dipetakan ke AdminOrderRow)
+const ADMIN_ORDER_SELECT: &str = "SELECT o.id, o.order_number, o.user_id, u.username, o.tanggal_peminjaman, o.jam_peminjaman, o.alamat_pengantaran,
+            o.tanggal_pengembalian, o.jam_pengembalian, o.alamat_pengembalian, o.fulfillment_mode, o.pilih_cabang, o.pilih_motor,
+            o.motor_price, o.status, o.tanggal_booking, o.waktu_booking,
+            m.motor_id, m.motor_name, m.image_url AS motor_image, m.motor_type
+     FROM orders o JOIN users u ON o.user_id = u.id LEFT JOIN motors m ON m.motor_id = o.motor_id";
+
+fn admin_order_response(row: AdminOrderRow) -> OrderResponse {
+    OrderResponse {
+        booking_id: row.order_number,
+        motor: motor_json(row.motor_id, row.motor_name, row.motor_image, row.motor_type),
+        id: row.id,
+        user_id: row.user_id,
+        username: row.username,
+        tanggal_peminjaman: row.tanggal_peminjaman,
+        jam_peminjaman: row.jam_peminjaman,
+        alamat_pengantaran: row.alamat_pengantaran,
+        tanggal_pengembalian: row.tanggal_pengembalian,
+        jam_pengembalian: row.jam_pengembalian,
+        alamat_pengembalian: row.alamat_pengembalian,
+        fulfillment_mode: row.fulfillment_mode,
+        pilih_cabang: row.pilih_cabang,
+        pilih_motor: row.pilih_motor,
+        motor_price: format_price_per_day(row.motor_price),
+        motor_price_amount: row.motor_price,
+        status: row.status,
+        tanggal_booking: row.tanggal_booking,
+        waktu_booking: row.waktu_booking,
+    }
+}
+
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+// Response booking yang pernah dibuat dengan Idempotency-Key yang sama (retry dari client).
+// Key milik user lain, atau body request yang berbeda, ditolak dengan 422.
+async fn find_idempotent_response(
+    pool: &PgPool,
+    key: &str,
+    user_id: Option<Uuid>,
+    request_hash: &str,
+) -> Result<Option<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let ttl = chrono::Duration::seconds(config::get().idempotency_key_ttl_secs);
+    let stored: Option<(Option<Uuid>, String, serde_json::Value)> = sqlx::query_as(
+        "SELECT user_id, request_hash, response_body FROM order_idempotency_keys
+         WHERE key = $1 AND created_at > $2"
+    )
+    .bind(key)
+    .bind(chrono::Utc::now() - ttl)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    match stored {
+        Some((owner, hash, response)) if owner == user_id && hash == request_hash => Ok(Some(response)),
+        Some(_) => Err((StatusCode::UNPROCESSABLE_ENTITY, RespJson(serde_json::json!({
+            "error": "Idempotency-Key was already used for a different request"
+        })))),
+        None => Ok(None),
+    }
+}
+
+// Simpan Idempotency-Key bersama order & response-nya, di transaksi yang sama dengan insert order
+async fn store_idempotent_response(
+    conn: &mut PgConnection,
+    key: &str,
+    user_id: Option<Uuid>,
+    request_hash: &str,
+    order_id: Uuid,
+    response: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let ttl = chrono::Duration::seconds(config::get().idempotency_key_ttl_secs);
+
+    // Key yang sudah kedaluwarsa boleh dipakai ulang
+    sqlx::query("DELETE FROM order_idempotency_keys WHERE key = $1 AND created_at <= $2")
+        .bind(key)
+        .bind(chrono::Utc::now() - ttl)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO order_idempotency_keys (key, user_id, request_hash, order_id, response_body)
+         VALUES ($1, $2, $3, $4, $5)"
+    )
+    .bind(key)
+    .bind(user_id)
+    .bind(request_hash)
+    .bind(order_id)
+    .bind(response)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Detail motor yang di-join ke response order (null jika order lama belum punya motor_id)
+fn motor_json(
+    motor_id: Option<i32>,
+    name: Option<String>,
+    image_url: Option<String>,
+    motor_type: Option<String>,
+) -> serde_json::Value {
+    match motor_id {
+        Some(motor_id) => serde_json::json!({
+            "motorId": motor_id,
+            "name": name,
+            "image": image_url,
+            "type": motor_type
+        }),
+        None => serde_json::Value::Null,
+    }
+}
+
+// Pastikan user boleh mengakses order ini: pemilik order, atau admin / staff cabang order tersebut
+// yang punya scope yang dibutuhkan. Hasilnya id pemilik order.
+pub(crate) async fn authorize_order_access(
+    pool: &PgPool,
+    user: &AuthUser,
+    order_id: Uuid,
+    required_scope: &str,
+) -> Result<Uuid, (StatusCode, RespJson<serde_json::Value>)> {
+    let order: Option<(Uuid, String, bool)> = sqlx::query_as(
+        "SELECT user_id, pilih_cabang, archived_at IS NOT NULL FROM orders WHERE id = $1"
+    )
+    .bind(order_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    let (owner_id, branch, archived) = order
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    if owner_id == user.id {
+        // Order yang sudah dihapus (diarsipkan) hanya bisa dilihat staff
+        if archived && !user.role.is_staff() {
+            return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))));
+        }
+        return Ok(owner_id);
+    }
+
+    user.require_scope(required_scope)?;
+    user.require_branch(Some(&branch))?;
+    Ok(owner_id)
+}
+
+// Seperti authorize_order_access, tapi khusus admin / staff cabang (pemilik order tidak termasuk),
+// misalnya untuk mencatat deposit
+pub(crate) async fn authorize_order_staff(
+    pool: &PgPool,
+    user: &AuthUser,
+    order_id: Uuid,
+    required_scope: &str,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    user.require_scope(required_scope)?;
+
+    let order: Option<(String,)> = sqlx::query_as("SELECT pilih_cabang FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+        })?;
+
+    let (branch,) = order
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    user.require_branch(Some(&branch))
+}
+
+pub fn order_router() -> Router {
+    println!("🔧 Registering order routes...");
+    Router::new()
+        .route("/api/orders", post(create_booking))
+        .route("/api/orders/quote", post(quote_booking))
+        .route("/api/orders/hold", post(create_hold))
+        .route("/api/orders/:id/hold/confirm", post(confirm_hold))
+        .route("/api/orders/:id", get(get_booking))
+        .route("/api/orders/:id", put(update_booking))
+        .route("/api/orders/:id", patch(modify_booking))
+        .route("/api/orders/:id", delete(delete_booking))
+        .route("/api/orders/:id/history", get(get_booking_history))
+        .route("/api/orders/:id/timeline", get(get_booking_timeline))   // Admin / staff
+        .route("/api/orders/:id/invoice.pdf", get(get_invoice_pdf))
+        .route("/api/orders/:id/notes", get(list_booking_notes))
+        .route("/api/orders/:id/notes", post(create_booking_note))
+        .route("/api/orders/:id/handovers", get(list_handovers))
+        .route("/api/orders/:id/handovers", post(create_handover))
+        .route("/api/orders/:id/readings", get(list_readings))
+        .route("/api/orders/:id/readings", post(record_reading))
+        .route("/api/orders/:id/review", post(create_review))
+        .route("/api/orders/:id/deposit/hold", post(hold_deposit))
+        .route("/api/orders/:id/deposit/release", post(release_deposit))
+        .route("/api/orders/:id/return-early", post(return_early))
+        .route("/api/orders", get(list_bookings))           // User orders only (with auth)
+        .route("/api/orders/all", get(list_all_bookings).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::ORDERS_READ), require_scope),
+        ))   // Admin: all orders
+        .route("/api/admin/orders", post(create_booking_for_customer).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::ORDERS_WRITE), require_scope),
+        ))   // Admin / staff: booking untuk customer walk-in
+        .route("/api/orders/archived", get(list_archived_bookings).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::ORDERS_READ), require_scope),
+        ))   // Admin: order yang sudah dihapus (diarsipkan)
+        .route("/api/users/:id/orders", get(list_customer_bookings).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::ORDERS_READ), require_scope),
+        ))   // Admin / staff: semua order satu customer
+        .route("/api/orders/bulk-status", patch(bulk_update_status).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::ORDERS_WRITE), require_scope),
+        ))   // Admin / staff: ubah status banyak order
+        .route("/api/orders/export.csv", get(export_orders_csv).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::REPORTS_READ), require_scope),
+        ))   // Admin: export order ke CSV
+        .route("/api/orders/search", get(search_bookings).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::ORDERS_READ), require_scope),
+        ))   // Admin / staff: cari booking
+        .route("/api/addons", get(list_addons))
+        .route("/api/insurance-tiers", get(list_insurance_tiers))
+        .route("/api/insurance-tiers/:code", put(upsert_insurance_tier)) // Admin
+        .route("/api/orders/test", get(test_endpoint))
+}
+
+// Test endpoint
+async fn test_endpoint() -> RespJson<serde_json::Value> {
+    RespJson(serde_json::json!({
+        "status": "ok",
+        "message": "Orders API is working",
+        "timestamp": chrono::Utc::now()
+    }))
+}
+
+// Create new booking dari form sewa motor.
+// Tanpa login (guest checkout) wajib mengisi guestName, guestPhone, guestEmail; response berisi
+// claimToken untuk menautkan order ke akun lewat POST /api/auth/claim setelah register.
+async fn create_booking(
+    user: Option<AuthUser>,
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let request = BookingRequest { client_ip: client_ip(&headers, &addr), headers };
+    place_booking(user, pool, mailer, request, payload, false, None).await
+}
+
+// Tahan (hold) motor selama HOLD_TTL_SECS selagi customer menyelesaikan pembayaran.
+// Payload sama dengan create_booking; hold dikonfirmasi lewat POST /api/orders/:id/hold/confirm,
+// atau otomatis dibatalkan setelah kedaluwarsa sehingga motornya tersedia lagi.
+async fn create_hold(
+    user: Option<AuthUser>,
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let request = BookingRequest { client_ip: client_ip(&headers, &addr), headers };
+    place_booking(user, pool, mailer, request, payload, true, None).await
+}
+
+// Hitung harga booking tanpa membuat order (untuk ditampilkan di form sebelum checkout).
+// Payload sama dengan create_booking; alamat antar / jemput opsional.
+async fn quote_booking(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let motors = resolve_booked_motors(&pool, &payload).await?;
+    let address = |name: &str| payload.get(name).and_then(|v| v.as_str());
+    // Cabang opsional saat quote; jika dikirim harus cabang aktif
+    let branch = match address("pilihCabang") {
+        Some(name) => Some(resolve_booking_branch(&pool, name).await?),
+        None => None,
+    };
+    let (rental_start, rental_end) = parse_rental_period(&payload)?;
+    validate_rental_period(rental_start, rental_end, chrono::Local::now().naive_local(), branch_hours(branch.as_ref()))
+        .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?;
+    check_duration_rules(&pool, &motors, rental_start, rental_end, false).await?;
+
+    // Ambil di cabang tidak dikenai ongkir
+    let mode = fulfillment_mode(&payload)?;
+    let delivery_address = |name: &str| address(name).filter(|_| mode == FulfillmentMode::Delivery);
+
+    let mut conn = pool.acquire().await.map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    let branch_name = branch.as_ref().map(|b| b.name.as_str());
+    let motor_ids: Vec<i32> = motors.iter().map(|m| m.motor_id).collect();
+    ensure_no_blackout(&mut conn, &motor_ids, branch_name, rental_start, rental_end).await?;
+
+    // Sopir yang akan dipilih saat booking dibuat (belum dikunci)
+    let driver = match parse_driver_request(&payload)? {
+        Some(request) => Some(reserve_driver(&mut conn, &request, rental_start, rental_end, None, branch_name).await?),
+        None => None,
+    };
+
+    let breakdown = price_booking(
+        &pool, &motors, driver.as_ref(), &payload, rental_start, rental_end,
+        (delivery_address("alamatPengantaran"), delivery_address("alamatPengembalian")),
+    ).await?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "motorId": motors[0].motor_id,
+        "motors": order_motor_lines(&motors, &breakdown),
+        "driver": driver.map(|d| serde_json::json!({"id": d.id, "name": d.name, "dailyRate": d.daily_rate})),
+        "insuranceTier": insurance_tier(&payload),
+        "fulfillmentMode": mode.as_str(),
+        "promoCode": promo_code(&payload),
+        "depositAmount": motors.iter().map(|m| m.deposit_amount).sum::<i32>(),
+        "totalPrice": breakdown.total,
+        "priceBreakdown": breakdown
+    })))
+}
+
+// Konfirmasi hold menjadi order confirmed, selama hold belum kedaluwarsa
+async fn confirm_hold(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let order: Option<(String, Option<chrono::DateTime<chrono::Utc>>)> =
+        sqlx::query_as("SELECT status, hold_expires_at FROM orders WHERE id = $1 FOR UPDATE")
+            .bind(order_uuid)
+            .fetch_optional(&mut tx)
+            .await
+            .map_err(db_error)?;
+
+    let (status, hold_expires_at) = order
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    if status != OrderStatus::Held.as_str() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Booking is not on hold",
+            "currentStatus": status
+        }))));
+    }
+
+    if hold_expires_at.is_none_or(|expires_at| expires_at <= chrono::Utc::now()) {
+        // Belum tersapu job; batalkan sekarang supaya motornya langsung tersedia lagi
+        expire_holds(&mut tx, None).await.map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        return Err((StatusCode::GONE, RespJson(serde_json::json!({"error": "Hold has expired"}))));
+    }
+
+    sqlx::query("UPDATE orders SET status = $1, hold_expires_at = NULL WHERE id = $2")
+        .bind(OrderStatus::Confirmed.as_str())
+        .bind(order_uuid)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    record_status_change(&mut tx, order_uuid, Some(&status), OrderStatus::Confirmed, Some(user.id), Some("Hold dikonfirmasi"))
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("✅ Hold {} confirmed", order_uuid);
+    notify_order(&pool, &mailer, order_uuid, notifications::ORDER_CONFIRMED);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Booking berhasil dikonfirmasi",
+        "status": OrderStatus::Confirmed.as_str()
+    })))
+}
+
+// Customer pemilik order yang dibuat staff (POST /api/admin/orders): akun yang sudah ada, atau
+// customer walk-in yang akunnya dibuat di transaksi booking yang sama
+enum BookingCustomer {
+    Existing(Uuid),
+    WalkIn(WalkInContact),
+}
+
+// Staff membuat booking untuk customer walk-in di cabang. Pemilik order dari "customerId" (akun yang
+// sudah ada) atau "customer": {"fullName", "phone"} (akun walk-in baru); sisa payload sama dengan
+// POST /api/orders dan tetap melewati cek ketersediaan, blackout, dan harga.
+async fn create_booking_for_customer(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    if let Some(branch) = payload.get("pilihCabang").and_then(|v| v.as_str()) {
+        user.require_branch(Some(branch))?;
+    }
+
+    let customer = match (payload.get("customerId").and_then(|v| v.as_str()), payload.get("customer")) {
+        (Some(customer_id), _) => {
+            let customer_id = Uuid::parse_str(customer_id)
+                .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid customerId"}))))?;
+            let exists: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM users WHERE id = $1 AND deleted_at IS NULL")
+                .bind(customer_id)
+                .fetch_optional(&pool)
+                .await
+                .map_err(|e| {
+                    println!("❌ Database error: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+                })?;
+            if exists.is_none() {
+                return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Customer not found"}))));
+            }
+            BookingCustomer::Existing(customer_id)
+        }
+        (None, Some(contact)) => BookingCustomer::WalkIn(
+            parse_walk_in_contact(contact).map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?,
+        ),
+        (None, None) => {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing customerId or customer"}))));
+        }
+    };
+
+    println!("🧾 Staff {} creating booking for a walk-in customer", user.id);
+    let request = BookingRequest { client_ip: client_ip(&headers, &addr), headers };
+    place_booking(Some(user), pool, mailer, request, payload, false, Some(customer)).await
+}
+
+// Data request HTTP pembuatan booking: header (Idempotency-Key, Accept-Language) dan IP klien
+// (dicatat bersama persetujuan syarat & ketentuan)
+struct BookingRequest {
+    headers: HeaderMap,
+    client_ip: IpAddr,
+}
+
+async fn place_booking(
+    user: Option<AuthUser>,
+    pool: PgPool,
+    mailer: SharedMailer,
+    request: BookingRequest,
+    payload: serde_json::Value,
+    hold: bool,
+    customer: Option<BookingCustomer>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let initial_status = if hold { OrderStatus::Held } else { OrderStatus::Pending };
+    let hold_expires_at = hold.then(|| chrono::Utc::now() + chrono::Duration::seconds(config::get().hold_ttl_secs));
+
+    // Header Idempotency-Key: retry dengan key yang sama mengembalikan response booking yang pertama
+    let headers = &request.headers;
+    let idempotency_key = match headers.get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => Some(
+            value.to_str().ok()
+                .map(str::trim)
+                .filter(|key| !key.is_empty() && key.len() <= 255)
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid Idempotency-Key header"}))))?,
+        ),
+        None => None,
+    };
+    let request_hash = hash_token(&format!("{}:{}", initial_status.as_str(), payload));
+    let requester_id = user.as_ref().map(|user| user.id);
+
+    if let Some(key) = idempotency_key {
+        if let Some(response) = find_idempotent_response(&pool, key, requester_id, &request_hash).await? {
+            println!("🔁 Returning stored booking response for Idempotency-Key {}", key);
+            return Ok(RespJson(response));
+        }
+    }
+
+    let guest = match &user {
+        Some(_) => None,
+        None => Some(parse_guest_contact(&payload)
+            .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?),
+    };
+    
+    // Extract booking data dari payload sesuai dengan form sewa motor
+    let tanggal_peminjaman = payload.get("tanggalPeminjaman")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing tanggalPeminjaman"}))))?;
+    
+    let jam_peminjaman = payload.get("jamPeminjaman")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing jamPeminjaman"}))))?;
+    
+    let tanggal_pengembalian = payload.get("tanggalPengembalian")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing tanggalPengembalian"}))))?;
+    
+    let jam_pengembalian = payload.get("jamPengembalian")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing jamPengembalian"}))))?;
+    
+    // Ambil di cabang (pickup) tidak butuh alamat; delivery wajib alamat antar & jemput
+    let mode = fulfillment_mode(&payload)?;
+    let (alamat_pengantaran, alamat_pengembalian) = delivery_addresses(&payload, mode)?;
+    
+    let pilih_cabang = payload.get("pilihCabang")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing pilihCabang"}))))?;
+    // Cabang harus terdaftar & aktif; nama disimpan sesuai penulisan di tabel branches
+    let branch = resolve_booking_branch(&pool, pilih_cabang).await?;
+    let pilih_cabang = branch.name.as_str();
+    
+    // Motor harus benar-benar ada di tabel motors; nama & harga diambil dari sana, bukan dari payload.
+    // Satu order bisa berisi beberapa motor; motor pertama menjadi motor utama (orders.motor_id).
+    let mut motors = resolve_booked_motors(&pool, &payload).await?;
+    let (pilih_motor, motor_price) = motor_summary(&motors);
+    let deposit_amount: i32 = motors.iter().map(|m| m.deposit_amount).sum();
+
+    // Parse tanggal
+    let (rental_start, rental_end) = parse_rental_period(&payload)?;
+    // Payload berisi data pribadi (nama, HP, email, alamat), jadi yang dicatat hanya motor dan periodenya
+    println!(
+        "Creating {} for motor {:?} ({} - {})",
+        if hold { "hold" } else { "booking" },
+        motors.iter().map(|m| m.motor_id).collect::<Vec<_>>(),
+        rental_start,
+        rental_end
+    );
+    let tanggal_peminjaman_date = rental_start.date();
+    let jam_peminjaman_time = rental_start.time();
+    let tanggal_pengembalian_date = rental_end.date();
+    let jam_pengembalian_time = rental_end.time();
+
+    validate_rental_period(rental_start, rental_end, chrono::Local::now().naive_local(), branch_hours(Some(&branch)))
+        .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?;
+    check_duration_rules(&pool, &motors, rental_start, rental_end, duration_approved(user.as_ref(), &payload)).await?;
+
+    let driver_request = parse_driver_request(&payload)?;
+
+    // Versi syarat & ketentuan yang disetujui customer, dicatat bersama waktu dan IP-nya
+    let terms_version = accepted_terms_version(&pool, &payload).await?;
+    let terms_accepted_at = terms_version.is_some().then(chrono::Utc::now);
+    let terms_accepted_ip = terms_version.is_some().then(|| request.client_ip.to_string());
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Order tamu ditampung di akun guest sampai di-claim; order buatan staff milik customer-nya
+    let (user_id, claim_token) = match (&customer, &user, &guest) {
+        (Some(BookingCustomer::Existing(customer_id)), _, _) => (*customer_id, None),
+        (Some(BookingCustomer::WalkIn(contact)), _, _) => {
+            let customer_id = create_walk_in_customer(&mut tx, contact).await.map_err(db_error)?;
+            println!("👤 Walk-in customer {} ({})", contact.full_name, customer_id);
+            (customer_id, None)
+        }
+        (None, Some(user), _) => (user.id, None),
+        (None, None, Some(contact)) => {
+            let (guest_id, claim_token) = create_guest(&mut tx, contact).await.map_err(db_error)?;
+            println!("👤 Guest checkout for {} ({})", contact.full_name, guest_id);
+            (guest_id, Some(claim_token))
+        }
+        (None, None, None) => unreachable!("guest contact is parsed when there is no logged-in user"),
+    };
+
+    // Cek ketersediaan setiap motor + insert dalam satu transaksi dengan motor-motornya terkunci
+    reserve_booked_motors(&mut tx, &mut motors, rental_start, rental_end, None, user_id, Some(pilih_cabang)).await?;
+    let motor = &motors[0];
+
+    // Tutup booking di periode blackout (libur nasional, servis armada) cabang / motor ini
+    let motor_ids: Vec<i32> = motors.iter().map(|m| m.motor_id).collect();
+    ensure_no_blackout(&mut tx, &motor_ids, Some(pilih_cabang), rental_start, rental_end).await?;
+
+    // Sopir dikunci di transaksi yang sama supaya tidak tertugaskan ke dua booking sekaligus
+    let driver = match &driver_request {
+        Some(request) => Some(reserve_driver(&mut tx, request, rental_start, rental_end, None, Some(pilih_cabang)).await?),
+        None => None,
+    };
+
+    let price_breakdown = price_booking(
+        &pool, &motors, driver.as_ref(), &payload, rental_start, rental_end,
+        (alamat_pengantaran.as_deref(), alamat_pengembalian.as_deref()),
+    ).await?;
+    let motor_lines = order_motor_lines(&motors, &price_breakdown);
+
+    let price_breakdown_json = serde_json::to_value(&price_breakdown).unwrap_or_default();
+
+    // Insert ke database orders
+    let order_id = Uuid::new_v4();
+    let booking_id = next_booking_code(&mut tx).await.map_err(db_error)?;
+    
+    println!("=== SEWA MOTOR INSERT DEBUG ===");
+    println!("Order ID: {}", order_id);
+    println!("User ID: {}", user_id);
+    println!("Booking ID: {}", booking_id);
+    println!("Motor: #{} {} - {} ({} motor)", motor.motor_id, pilih_motor, format_price_per_day(motor_price), motors.len());
+    println!("Tanggal: {} s/d {}", tanggal_peminjaman, tanggal_pengembalian);
+    println!("Cabang: {}", pilih_cabang);
+    
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO orders (
+            id, order_number, user_id,
+            tanggal_peminjaman, jam_peminjaman, alamat_pengantaran,
+            tanggal_pengembalian, jam_pengembalian, alamat_pengembalian,
+            pilih_cabang, motor_id, pilih_motor, motor_price,
+            status, hold_expires_at, deposit_amount,
+            rental_days, total_price, price_breakdown, promo_code, language,
+            with_driver, driver_id, insurance_tier, fulfillment_mode,
+            terms_version, terms_accepted_at, terms_accepted_ip, unit_id, tanggal_booking, waktu_booking
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25,
+            $26, $27, $28, $29, CURRENT_DATE, CURRENT_TIME
+        )
+        "#,
+        order_id,
+        booking_id,
+        user_id,
+        tanggal_peminjaman_date,
+        jam_peminjaman_time,
+        alamat_pengantaran,
+        tanggal_pengembalian_date,
+        jam_pengembalian_time,
+        alamat_pengembalian,
+        pilih_cabang,
+        motor.motor_id,
+        pilih_motor,
+        motor_price,
+        initial_status.as_str(),
+        hold_expires_at,
+        deposit_amount,
+        price_breakdown.rental_days as i32,
+        price_breakdown.total,
+        price_breakdown_json,
+        promo_code(&payload),
+        language_from_header(headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok())),
+        driver.is_some(),
+        driver.as_ref().map(|d| d.id),
+        insurance_tier(&payload),
+        mode.as_str(),
+        terms_version.as_deref(),
+        terms_accepted_at,
+        terms_accepted_ip,
+        motor.unit_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    match result {
+        Ok(_) => {
+            let note = customer.is_some().then_some("Dibuat staff untuk customer walk-in");
+            record_status_change(&mut tx, order_id, None, initial_status, requester_id, note)
+                .await
+                .map_err(db_error)?;
+
+            insert_order_items(&mut tx, order_id, &price_breakdown.items)
+                .await
+                .map_err(db_error)?;
+
+            replace_order_motors(&mut tx, order_id, &motor_lines)
+                .await
+                .map_err(db_error)?;
+
+            let response = serde_json::json!({
+                "success": true,
+                "message": "Booking sewa motor berhasil dibuat",
+                "booking_id": booking_id,
+                "order_id": order_id,
+                "claimToken": claim_token,
+                "holdExpiresAt": hold_expires_at,
+                "data": {
+                    "id": order_id,
+                    "bookingId": booking_id,
+                    "userId": user_id,
+                    "tanggalPeminjaman": tanggal_peminjaman,
+                    "jamPeminjaman": jam_peminjaman,
+                    "alamatPengantaran": alamat_pengantaran,
+                    "tanggalPengembalian": tanggal_pengembalian,
+                    "jamPengembalian": jam_pengembalian,
+                    "alamatPengembalian": alamat_pengembalian,
+                    "fulfillmentMode": mode.as_str(),
+                    "pilihCabang": pilih_cabang,
+                    "motorId": motor.motor_id,
+                    "pilihMotor": pilih_motor,
+                    "motorPrice": format_price_per_day(motor_price),
+                    "motorPriceAmount": motor_price,
+                    "motors": motor_lines,
+                    "withDriver": driver.is_some(),
+                    "driver": driver.as_ref().map(|d| serde_json::json!({"id": d.id, "name": d.name, "phone": d.phone})),
+                    "insuranceTier": insurance_tier(&payload),
+                    "termsVersion": terms_version,
+                    "depositAmount": deposit_amount,
+                    "totalPrice": price_breakdown.total,
+                    "priceBreakdown": price_breakdown,
+                    "items": price_breakdown.items,
+                    "status": initial_status.as_str()
+                }
+            });
+
+            if let Some(key) = idempotency_key {
+                store_idempotent_response(&mut tx, key, requester_id, &request_hash, order_id, &response)
+                    .await
+                    .map_err(|e| match &e {
+                        // Request lain dengan key yang sama sedang / sudah diproses; order ini dibatalkan (rollback)
+                        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+                            (StatusCode::CONFLICT, RespJson(serde_json::json!({
+                                "error": "A request with this Idempotency-Key is already being processed"
+                            })))
+                        }
+                        _ => db_error(e),
+                    })?;
+            }
+
+            tx.commit().await.map_err(db_error)?;
+            println!("✅ Sewa motor booking berhasil disimpan ke database");
+
+            // Hold baru dikirimi email setelah dikonfirmasi
+            if !hold {
+                notify_order(&pool, &mailer, order_id, notifications::ORDER_CREATED);
+            }
+            Ok(RespJson(response))
+        }
+        Err(sqlx::Error::Database(e)) if e.constraint() == Some(DRIVER_OVERLAP_CONSTRAINT) => {
+            println!("⛔ Driver booked concurrently for an overlapping period");
+            Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Driver is not available for the selected period"}))))
+        }
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23P01") => {
+            println!("⛔ Motor #{} booked concurrently for an overlapping period", motor.motor_id);
+            Err(booking_conflict_error(None, None))
+        }
+        Err(e) => {
+            println!("❌ Sewa motor booking database insert failed");
+            Err(db_error(e))
+        }
+    }
+}
+
+// Katalog add-on yang bisa dipilih di form booking
+async fn list_addons(
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let addons: Vec<Addon> = sqlx::query_as(
+        "SELECT code, name, price, per_day FROM addons WHERE active = TRUE ORDER BY name"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": addons
+    })))
+}
+
+// Pilihan asuransi yang bisa dipilih di form booking
+async fn list_insurance_tiers(
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let tiers: Vec<InsuranceTier> = sqlx::query_as(
+        "SELECT code, name, daily_fee, coverage, active FROM insurance_tiers WHERE active = TRUE ORDER BY daily_fee"
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": tiers
+    })))
+}
+
+// Admin: buat atau ubah pilihan asuransi. Order yang sudah dibuat tetap memakai harga lama.
+async fn upsert_insurance_tier(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(code): Path<String>,
+    Json(payload): Json<UpsertInsuranceTierRequest>,
+) -> Result<RespJson<InsuranceTier>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let code = code.trim().to_lowercase();
+    let name = payload.name.trim();
+    let coverage = payload.coverage.trim();
+    if code.is_empty() || name.is_empty() || coverage.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Code, name and coverage are required"}))));
+    }
+    if payload.daily_fee < 0 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "dailyFee cannot be negative"}))));
+    }
+
+    let tier: InsuranceTier = sqlx::query_as(
+        "INSERT INTO insurance_tiers (code, name, daily_fee, coverage, active) VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (code) DO UPDATE
+         SET name = EXCLUDED.name, daily_fee = EXCLUDED.daily_fee, coverage = EXCLUDED.coverage, active = EXCLUDED.active
+         RETURNING code, name, daily_fee, coverage, active"
+    )
+    .bind(&code)
+    .bind(name)
+    .bind(payload.daily_fee)
+    .bind(coverage)
+    .bind(payload.active.unwrap_or(true))
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    println!("🛡️  Insurance tier {} updated by {}", tier.code, user.id);
+    Ok(RespJson(tier))
+}
+
+// Get booking by ID
+async fn get_booking(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+    
+    let row = sqlx::query!(
+        r#"SELECT o.id, o.order_number, o.user_id, o.tanggal_peminjaman, o.jam_peminjaman, o.alamat_pengantaran, o.tanggal_pengembalian, o.jam_pengembalian, o.alamat_pengembalian, o.pilih_cabang, o.pilih_motor, o.motor_price, o.status, o.tanggal_booking, o.waktu_booking,
+                  o.rental_days, o.total_price, o.price_breakdown,
+                  o.deposit_amount, o.deposit_status, o.deposit_deduction, o.deposit_deduction_reason, o.deposit_held_at, o.deposit_released_at,
+                  o.with_driver, o.driver_id, d.name AS "driver_name?", d.phone AS "driver_phone?", o.insurance_tier, o.fulfillment_mode,
+                  o.terms_version, o.terms_accepted_at, o.terms_accepted_ip,
+                  m.motor_id AS "motor_id?", m.motor_name AS "motor_name?", m.image_url AS "motor_image?", m.motor_type AS "motor_type?"
+           FROM orders o LEFT JOIN motors m ON m.motor_id = o.motor_id LEFT JOIN drivers d ON d.id = o.driver_id
+           WHERE o.id = $1"#,
+        order_uuid
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))?;
+
+    let items: Vec<OrderItem> = sqlx::query_as(
+        "SELECT code, name, quantity, unit_price, subtotal FROM order_items WHERE order_id = $1 ORDER BY created_at, name"
+    )
+    .bind(order_uuid)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))?;
+
+    let motors = fetch_order_motors(&pool, order_uuid)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))?;
+    
+    match row {
+        Some(order) => {
+            Ok(RespJson(serde_json::json!({
+                "id": order.id,
+                "user_id": order.user_id,
+                "bookingId": order.order_number,
+                "tanggalPeminjaman": order.tanggal_peminjaman,
+                "jamPeminjaman": order.jam_peminjaman,
+                "alamatPengantaran": order.alamat_pengantaran,
+                "tanggalPengembalian": order.tanggal_pengembalian,
+                "jamPengembalian": order.jam_pengembalian,
+                "alamatPengembalian": order.alamat_pengembalian,
+                "fulfillmentMode": order.fulfillment_mode,
+                "pilihCabang": order.pilih_cabang,
+                "pilihMotor": order.pilih_motor,
+                "motorPrice": format_price_per_day(order.motor_price),
+                "motorPriceAmount": order.motor_price,
+                "motor": motor_json(order.motor_id, order.motor_name, order.motor_image, order.motor_type),
+                "motors": motors,
+                "withDriver": order.with_driver,
+                "driver": order.driver_id.map(|id| serde_json::json!({
+                    "id": id,
+                    "name": order.driver_name,
+                    "phone": order.driver_phone
+                })),
+                "insuranceTier": order.insurance_tier,
+                "terms": order.terms_version.map(|version| serde_json::json!({
+                    "version": version,
+                    "acceptedAt": order.terms_accepted_at,
+                    "acceptedIp": order.terms_accepted_ip
+                })),
+                "rentalDays": order.rental_days,
+                "totalPrice": order.total_price,
+                "priceBreakdown": order.price_breakdown,
+                "items": items,
+                "deposit": {
+                    "amount": order.deposit_amount,
+                    "status": order.deposit_status,
+                    "deduction": order.deposit_deduction,
+                    "deductionReason": order.deposit_deduction_reason,
+                    "heldAt": order.deposit_held_at,
+                    "releasedAt": order.deposit_released_at
+                },
+                "status": order.status,
+                "tanggalBooking": order.tanggal_booking,
+                "waktuBooking": order.waktu_booking
+            })))
+        }
+        None => Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))
+    }
+}
+
+// Update booking status
+async fn update_booking(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    let next_status = payload.get("status")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing status"}))))?
+        .parse::<OrderStatus>()
+        .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?;
+
+    // Pemilik order hanya boleh membatalkan; perubahan status lain (konfirmasi, pickup, return, selesai)
+    // khusus staff cabang order dengan scope orders:write
+    if next_status == OrderStatus::Cancelled {
+        authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+    } else {
+        authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+    }
+
+    let note = payload.get("note").and_then(|v| v.as_str()).map(str::trim).filter(|n| !n.is_empty());
+
+    change_order_status(&pool, order_uuid, next_status, user.id, user.role, note).await?;
+
+    if next_status == OrderStatus::Confirmed {
+        notify_order(&pool, &mailer, order_uuid, notifications::ORDER_CONFIRMED);
+    }
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Booking status updated successfully",
+        "status": next_status.as_str()
+    })))
+}
+
+// Order yang akan diubah lewat PATCH /api/orders/:id
+#[derive(sqlx::FromRow)]
+struct ModifiableOrder {
+    status: String,
+    motor_id: Option<i32>,
+    pilih_motor: String,
+    tanggal_peminjaman: chrono::NaiveDate,
+    jam_peminjaman: chrono::NaiveTime,
+    alamat_pengantaran: Option<String>,
+    tanggal_pengembalian: chrono::NaiveDate,
+    jam_pengembalian: chrono::NaiveTime,
+    alamat_pengembalian: Option<String>,
+    fulfillment_mode: String,
+    pilih_cabang: String,
+    total_price: Option<i64>,
+    promo_code: Option<String>,
+    with_driver: bool,
+    driver_id: Option<Uuid>,
+    insurance_tier: Option<String>,
+}
+
+// Field booking yang boleh diubah sebelum motor diambil (nama sama dengan payload POST /api/orders)
+const MODIFIABLE_FIELDS: &[&str] = &[
+    "tanggalPeminjaman", "jamPeminjaman", "alamatPengantaran",
+    "tanggalPengembalian", "jamPengembalian", "alamatPengembalian", "fulfillmentMode",
+    "motorId", "motorIds", "addons", "promoCode", "withDriver", "driverId", "insuranceTier",
+];
+
+// Ubah tanggal, jam, alamat, motor, atau add-on booking sebelum motor diambil. Ketersediaan setiap
+// motor dan harga dihitung ulang, dan perubahannya dicatat ke riwayat status order.
+async fn modify_booking(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    let owner_id = authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    if !MODIFIABLE_FIELDS.iter().any(|field| payload.get(*field).is_some()) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "No modifiable fields provided",
+            "modifiableFields": MODIFIABLE_FIELDS
+        }))));
+    }
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let order: ModifiableOrder = sqlx::query_as(
+        "SELECT status, motor_id, pilih_motor, tanggal_peminjaman, jam_peminjaman, alamat_pengantaran,
+                tanggal_pengembalian, jam_pengembalian, alamat_pengembalian, total_price, promo_code,
+                with_driver, driver_id, insurance_tier, fulfillment_mode, pilih_cabang
+         FROM orders WHERE id = $1 FOR UPDATE"
+    )
+    .bind(order_uuid)
+    .fetch_one(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let modifiable = [OrderStatus::Held, OrderStatus::Pending, OrderStatus::Confirmed];
+    if !modifiable.iter().any(|status| status.as_str() == order.status) {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Booking can only be modified before pickup",
+            "currentStatus": order.status
+        }))));
+    }
+
+    // Add-on yang sudah dipilih tetap dipakai jika payload tidak mengirim "addons"
+    let current_addons: Vec<(String, i32)> = sqlx::query_as(
+        "SELECT i.code, i.quantity FROM order_items i JOIN addons a ON a.code = i.code WHERE i.order_id = $1"
+    )
+    .bind(order_uuid)
+    .fetch_all(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let current_motor_ids: Vec<(Option<i32>,)> = sqlx::query_as(
+        "SELECT motor_id FROM order_motors WHERE order_id = $1 ORDER BY position"
+    )
+    .bind(order_uuid)
+    .fetch_all(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    // Payload lengkap = data booking saat ini, ditimpa field yang dikirim
+    let current = serde_json::json!({
+        "tanggalPeminjaman": order.tanggal_peminjaman.format("%Y-%m-%d").to_string(),
+        "jamPeminjaman": order.jam_peminjaman.format("%H:%M").to_string(),
+        "alamatPengantaran": order.alamat_pengantaran,
+        "tanggalPengembalian": order.tanggal_pengembalian.format("%Y-%m-%d").to_string(),
+        "jamPengembalian": order.jam_pengembalian.format("%H:%M").to_string(),
+        "alamatPengembalian": order.alamat_pengembalian,
+        "fulfillmentMode": order.fulfillment_mode,
+        "motorId": order.motor_id,
+        "motorIds": current_motor_ids.iter().filter_map(|(id,)| *id).collect::<Vec<_>>(),
+        "pilihMotor": order.pilih_motor,
+        "pilihCabang": order.pilih_cabang,
+        "promoCode": order.promo_code,
+        "withDriver": order.with_driver,
+        "driverId": order.driver_id,
+        "insuranceTier": order.insurance_tier,
+        "addons": current_addons.iter()
+            .map(|(code, quantity)| serde_json::json!({"code": code, "quantity": quantity}))
+            .collect::<Vec<_>>()
+    });
+    let mut merged = current.clone();
+    for field in MODIFIABLE_FIELDS {
+        if let Some(value) = payload.get(*field) {
+            merged[*field] = value.clone();
+        }
+    }
+    // Ganti motor lewat "motorId" saja berarti order menjadi satu motor
+    if payload.get("motorId").is_some() && payload.get("motorIds").is_none() {
+        merged["motorIds"] = serde_json::Value::Null;
+    }
+    if merged["motorIds"].as_array().is_some_and(|ids| ids.is_empty()) {
+        merged["motorIds"] = serde_json::Value::Null;
+    }
+    // Tanpa sopir berarti sopir yang ditugaskan juga dilepas
+    if merged["withDriver"] == serde_json::Value::Bool(false) && payload.get("driverId").is_none() {
+        merged["driverId"] = serde_json::Value::Null;
+    }
+    let driver_request = parse_driver_request(&merged)?;
+
+    let mut motors = resolve_booked_motors(&pool, &merged).await?;
+    merged["motorId"] = serde_json::json!(motors[0].motor_id);
+    merged["motorIds"] = serde_json::json!(motors.iter().map(|m| m.motor_id).collect::<Vec<_>>());
+    let (rental_start, rental_end) = parse_rental_period(&merged)?;
+    // Jam operasional cabang order (cabang tidak bisa diganti lewat ubah booking)
+    let branch = find_branch(&pool, &order.pilih_cabang).await.map_err(db_error)?;
+    validate_rental_period(rental_start, rental_end, chrono::Local::now().naive_local(), branch_hours(branch.as_ref()))
+        .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?;
+    check_duration_rules(&pool, &motors, rental_start, rental_end, duration_approved(Some(&user), &payload)).await?;
+
+    // Pindah ke ambil di cabang berarti alamat antar / jemput tidak dipakai lagi
+    let mode = fulfillment_mode(&merged)?;
+    let (alamat_pengantaran, alamat_pengembalian) = delivery_addresses(&merged, mode)?;
+    merged["fulfillmentMode"] = serde_json::json!(mode.as_str());
+    merged["alamatPengantaran"] = serde_json::json!(alamat_pengantaran);
+    merged["alamatPengembalian"] = serde_json::json!(alamat_pengembalian);
+
+    reserve_booked_motors(&mut tx, &mut motors, rental_start, rental_end, Some(order_uuid), owner_id, Some(&order.pilih_cabang)).await?;
+    let motor = &motors[0];
+
+    let motor_ids: Vec<i32> = motors.iter().map(|m| m.motor_id).collect();
+    ensure_no_blackout(&mut tx, &motor_ids, Some(&order.pilih_cabang), rental_start, rental_end).await?;
+
+    let driver = match &driver_request {
+        Some(request) => Some(reserve_driver(&mut tx, request, rental_start, rental_end, Some(order_uuid), None).await?),
+        None => None,
+    };
+    merged["withDriver"] = serde_json::json!(driver.is_some());
+    merged["driverId"] = serde_json::json!(driver.as_ref().map(|d| d.id));
+
+    let price_breakdown = price_booking(
+        &pool, &motors, driver.as_ref(), &merged, rental_start, rental_end,
+        (alamat_pengantaran.as_deref(), alamat_pengembalian.as_deref()),
+    ).await?;
+    let motor_lines = order_motor_lines(&motors, &price_breakdown);
+    let (pilih_motor, motor_price) = motor_summary(&motors);
+
+    let updated = sqlx::query(
+        "UPDATE orders
+         SET tanggal_peminjaman = $1, jam_peminjaman = $2, alamat_pengantaran = $3,
+             tanggal_pengembalian = $4, jam_pengembalian = $5, alamat_pengembalian = $6,
+             motor_id = $7, pilih_motor = $8, motor_price = $9,
+             deposit_amount = CASE WHEN deposit_status = $10 THEN $11 ELSE deposit_amount END,
+             rental_days = $12, total_price = $13, price_breakdown = $14, promo_code = $15,
+             with_driver = $16, driver_id = $17, insurance_tier = $18, fulfillment_mode = $19, unit_id = $20
+         WHERE id = $21"
+    )
+    .bind(rental_start.date())
+    .bind(rental_start.time())
+    .bind(&alamat_pengantaran)
+    .bind(rental_end.date())
+    .bind(rental_end.time())
+    .bind(&alamat_pengembalian)
+    .bind(motor.motor_id)
+    .bind(&pilih_motor)
+    .bind(motor_price)
+    .bind(DepositStatus::Pending.as_str())
+    .bind(motor_lines.iter().map(|line| line.deposit_amount).sum::<i32>())
+    .bind(price_breakdown.rental_days as i32)
+    .bind(price_breakdown.total)
+    .bind(serde_json::to_value(&price_breakdown).unwrap_or_default())
+    .bind(promo_code(&merged))
+    .bind(driver.is_some())
+    .bind(driver.as_ref().map(|d| d.id))
+    .bind(insurance_tier(&merged))
+    .bind(mode.as_str())
+    .bind(motor.unit_id)
+    .bind(order_uuid)
+    .execute(&mut tx)
+    .await;
+
+    match updated {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.constraint() == Some(DRIVER_OVERLAP_CONSTRAINT) => {
+            return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Driver is not available for the selected period"}))));
+        }
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23P01") => {
+            return Err(booking_conflict_error(None, None));
+        }
+        Err(e) => return Err(db_error(e)),
+    }
+
+    sqlx::query("DELETE FROM order_items WHERE order_id = $1")
+        .bind(order_uuid)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+    insert_order_items(&mut tx, order_uuid, &price_breakdown.items)
+        .await
+        .map_err(db_error)?;
+    replace_order_motors(&mut tx, order_uuid, &motor_lines)
+        .await
+        .map_err(db_error)?;
+
+    // Invoice lama tidak berlaku lagi; dibuat ulang saat di-download
+    sqlx::query("DELETE FROM order_invoices WHERE order_id = $1")
+        .bind(order_uuid)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    // Catat field yang berubah ke riwayat status (status sendiri tidak berubah)
+    let mut changes: Vec<String> = MODIFIABLE_FIELDS.iter()
+        .filter(|field| current[**field] != merged[**field])
+        .map(|field| format!("{}: {} → {}", field, current[*field], merged[*field]))
+        .collect();
+    if order.total_price != Some(price_breakdown.total) {
+        changes.push(format!(
+            "totalPrice: {} → {}",
+            order.total_price.map_or("-".to_string(), |p| p.to_string()),
+            price_breakdown.total
+        ));
+    }
+    let note = if changes.is_empty() {
+        "Booking diubah tanpa perubahan data".to_string()
+    } else {
+        format!("Booking diubah: {}", changes.join("; "))
+    };
+
+    let status = order.status.parse::<OrderStatus>()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": e}))))?;
+    record_status_change(&mut tx, order_uuid, Some(&order.status), status, Some(user.id), Some(&note))
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("✏️  Order {} modified by {}: {}", order_uuid, user.id, note);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Booking berhasil diubah",
+        "changes": changes,
+        "data": {
+            "id": order_uuid,
+            "tanggalPeminjaman": rental_start.date(),
+            "jamPeminjaman": rental_start.time(),
+            "alamatPengantaran": alamat_pengantaran,
+            "tanggalPengembalian": rental_end.date(),
+            "jamPengembalian": rental_end.time(),
+            "alamatPengembalian": alamat_pengembalian,
+            "fulfillmentMode": mode.as_str(),
+            "motorId": motor.motor_id,
+            "pilihMotor": pilih_motor,
+            "motors": motor_lines,
+            "withDriver": driver.is_some(),
+            "driverId": driver.as_ref().map(|d| d.id),
+            "insuranceTier": insurance_tier(&merged),
+            "totalPrice": price_breakdown.total,
+            "priceBreakdown": price_breakdown,
+            "items": price_breakdown.items,
+            "status": order.status
+        }
+    })))
+}
+
+// Ubah status banyak order sekaligus (admin / staff). Setiap order divalidasi dan disimpan sendiri-sendiri,
+// jadi order yang gagal tidak membatalkan yang lain; hasilnya per order.
+async fn bulk_update_status(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Extension(mailer): Extension<SharedMailer>,
+    Json(payload): Json<BulkStatusRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+
+    if payload.order_ids.is_empty() || payload.order_ids.len() > 100 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "orderIds must contain between 1 and 100 items"
+        }))));
+    }
+
+    let note = payload.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    let mut results = Vec::with_capacity(payload.order_ids.len());
+    for order_id in &payload.order_ids {
+        let outcome = match authorize_order_staff(&pool, &user, *order_id, scope::ORDERS_WRITE).await {
+            Ok(()) => change_order_status(&pool, *order_id, payload.status, user.id, user.role, note).await,
+            Err(e) => Err(e),
+        };
+
+        if outcome.is_ok() && payload.status == OrderStatus::Confirmed {
+            notify_order(&pool, &mailer, *order_id, notifications::ORDER_CONFIRMED);
+        }
+
+        results.push(match outcome {
+            Ok(previous) => serde_json::json!({
+                "orderId": order_id,
+                "success": true,
+                "previousStatus": previous.as_str(),
+                "status": payload.status.as_str()
+            }),
+            Err((status, RespJson(body))) => serde_json::json!({
+                "orderId": order_id,
+                "success": false,
+                "httpStatus": status.as_u16(),
+                "error": body.get("error").cloned().unwrap_or_default(),
+                "allowedNextStates": body.get("allowedNextStates").cloned()
+            }),
+        });
+    }
+
+    let updated = results.iter().filter(|r| r["success"] == true).count();
+    println!("📦 Bulk status {}: {}/{} orders updated by {}", payload.status.as_str(), updated, results.len(), user.id);
+
+    Ok(RespJson(serde_json::json!({
+        "success": updated == results.len(),
+        "updated": updated,
+        "failed": results.len() - updated,
+        "results": results
+    })))
+}
+
+// Data order untuk invoice (kontak tamu diambil dari guest_checkouts)
+#[derive(sqlx::FromRow)]
+struct InvoiceOrderRow {
+    order_number: String,
+    full_name: String,
+    phone: String,
+    email: String,
+    pilih_motor: String,
+    pilih_cabang: String,
+    rental_start: NaiveDateTime,
+    rental_end: NaiveDateTime,
+    price_breakdown: Option<serde_json::Value>,
+    insurance_name: Option<String>,
+    insurance_coverage: Option<String>,
+}
+
+// Download invoice PDF. Invoice dibuat sekali lalu disimpan, jadi download berikutnya
+// selalu mendapat dokumen yang sama.
+async fn get_invoice_pdf(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<Response, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let stored: Option<(String, Vec<u8>)> = sqlx::query_as(
+        "SELECT invoice_number, pdf FROM order_invoices WHERE order_id = $1"
+    )
+    .bind(order_uuid)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let (invoice_number, pdf) = match stored {
+        Some(invoice) => invoice,
+        None => {
+            let order: InvoiceOrderRow = sqlx::query_as(
+                "SELECT o.order_number, COALESCE(g.full_name, u.full_name) AS full_name, COALESCE(g.phone, u.phone) AS phone,
+                        COALESCE(g.email, u.email) AS email, o.pilih_motor, o.pilih_cabang,
+                        o.tanggal_peminjaman + o.jam_peminjaman AS rental_start,
+                        o.tanggal_pengembalian + o.jam_pengembalian AS rental_end,
+                        o.price_breakdown, t.name AS insurance_name, t.coverage AS insurance_coverage
+                 FROM orders o
+                 JOIN users u ON u.id = o.user_id
+                 LEFT JOIN guest_checkouts g ON g.guest_user_id = u.id
+                 LEFT JOIN insurance_tiers t ON t.code = o.insurance_tier
+                 WHERE o.id = $1"
+            )
+            .bind(order_uuid)
+            .fetch_one(&pool)
+            .await
+            .map_err(db_error)?;
+
+            // Order lama (sebelum ada pricing engine) tidak punya rincian harga
+            let breakdown = order.price_breakdown
+                .and_then(|value| serde_json::from_value(value).ok())
+                .ok_or_else(|| (StatusCode::CONFLICT, RespJson(serde_json::json!({
+                    "error": "Invoice is not available for this booking"
+                }))))?;
+
+            let invoice_number = order.order_number;
+            let pdf = render_invoice(&InvoiceData {
+                invoice_number: invoice_number.clone(),
+                customer_name: order.full_name,
+                customer_phone: order.phone,
+                customer_email: order.email,
+                motor_name: order.pilih_motor,
+                branch: order.pilih_cabang,
+                rental_start: order.rental_start,
+                rental_end: order.rental_end,
+                breakdown,
+                insurance: order.insurance_name.zip(order.insurance_coverage)
+                    .map(|(name, coverage)| InvoiceInsurance { name, coverage }),
+                tax_percent: config::get().invoice_tax_percent,
+            })
+            .map_err(|e| {
+                println!("❌ Invoice render error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Failed to generate invoice"})))
+            })?;
+
+            // Request bersamaan: yang pertama tersimpan yang dipakai
+            sqlx::query_as(
+                "INSERT INTO order_invoices (order_id, invoice_number, pdf) VALUES ($1, $2, $3)
+                 ON CONFLICT (order_id) DO UPDATE SET order_id = EXCLUDED.order_id
+                 RETURNING invoice_number, pdf"
+            )
+            .bind(order_uuid)
+            .bind(&invoice_number)
+            .bind(&pdf)
+            .fetch_one(&pool)
+            .await
+            .map_err(db_error)?
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.pdf\"", invoice_number)),
+        ],
+        pdf,
+    ).into_response())
+}
+
+// Riwayat perubahan status order (siapa, kapan, dari status apa ke apa)
+async fn get_booking_history(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+
+    let history: Vec<OrderStatusHistory> = sqlx::query_as(
+        "SELECT h.id, h.old_status, h.new_status, h.changed_by, u.username AS changed_by_username, h.note, h.changed_at
+         FROM order_status_history h
+         LEFT JOIN users u ON u.id = h.changed_by
+         WHERE h.order_id = $1
+         ORDER BY h.changed_at ASC, h.id ASC"
+    )
+    .bind(order_uuid)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": history,
+        "total": history.len()
+    })))
+}
+
+const TIMELINE_KINDS: &[&str] = &["status", "payment", "note", "incident", "notification"];
+
+// Timeline order untuk halaman detail admin: riwayat status, pembayaran (deposit & refund), catatan,
+// insiden, dan notifikasi digabung berurutan waktu
+async fn get_booking_timeline(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Query(params): Query<TimelineQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+
+    let kinds: Option<Vec<String>> = params.kinds
+        .as_deref()
+        .map(|kinds| kinds.split(',').map(|kind| kind.trim().to_lowercase()).filter(|kind| !kind.is_empty()).collect());
+    if let Some(unknown) = kinds.iter().flatten().find(|kind| !TIMELINE_KINDS.contains(&kind.as_str())) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown timeline kind: {}", unknown),
+            "allowed": TIMELINE_KINDS
+        }))));
+    }
+
+    let timeline: Vec<TimelineEntry> = sqlx::query_as(
+        "SELECT t.kind, t.at, t.actor_id, u.username AS actor_username, t.summary, t.details
+         FROM (
+             SELECT 'status' AS kind, h.changed_at AS at, h.changed_by AS actor_id,
+                    COALESCE(h.old_status || ' → ', '') || h.new_status AS summary,
+                    jsonb_build_object('oldStatus', h.old_status, 'newStatus', h.new_status, 'note', h.note) AS details
+             FROM order_status_history h WHERE h.order_id = $1
+             UNION ALL
+             SELECT 'payment', o.deposit_held_at, CASE WHEN o.deposit_released_at IS NULL THEN o.deposit_handled_by END,
+                    'Deposit diterima',
+                    jsonb_build_object('event', 'deposit_held', 'amount', o.deposit_amount)
+             FROM orders o WHERE o.id = $1 AND o.deposit_held_at IS NOT NULL
+             UNION ALL
+             SELECT 'payment', o.deposit_released_at, o.deposit_handled_by,
+                    'Deposit dikembalikan',
+                    jsonb_build_object('event', 'deposit_released', 'amount', o.deposit_amount,
+                                       'deduction', o.deposit_deduction, 'reason', o.deposit_deduction_reason,
+                                       'refund', o.deposit_amount - COALESCE(o.deposit_deduction, 0))
+             FROM orders o WHERE o.id = $1 AND o.deposit_released_at IS NOT NULL
+             UNION ALL
+             SELECT 'payment', r.created_at, r.created_by,
+                    'Refund ' || r.kind,
+                    jsonb_build_object('event', 'refund', 'refundId', r.id, 'kind', r.kind, 'amount', r.amount,
+                                       'status', r.status, 'reason', r.reason)
+             FROM order_refunds r WHERE r.order_id = $1
+             UNION ALL
+             SELECT 'note', n.created_at, n.author_id, n.body,
+                    jsonb_build_object('noteId', n.id, 'visibility', n.visibility)
+             FROM order_notes n WHERE n.order_id = $1
+             UNION ALL
+             SELECT 'incident', i.reported_at, i.reported_by,
+                    'Insiden ' || i.severity || ': ' || i.description,
+                    jsonb_build_object('incidentId', i.id, 'event', 'reported', 'severity', i.severity,
+                                       'status', i.status, 'estimatedCost', i.estimated_cost)
+             FROM incidents i WHERE i.order_id = $1
+             UNION ALL
+             SELECT 'incident', i.resolved_at, i.updated_by,
+                    'Insiden diselesaikan',
+                    jsonb_build_object('incidentId', i.id, 'event', 'resolved', 'resolutionNotes', i.resolution_notes)
+             FROM incidents i WHERE i.order_id = $1 AND i.resolved_at IS NOT NULL
+             UNION ALL
+             SELECT 'notification', n.sent_at, NULL::uuid, n.subject,
+                    jsonb_build_object('channel', n.channel, 'template', n.template_key, 'recipient', n.recipient,
+                                       'status', n.status, 'error', n.error)
+             FROM order_notifications n WHERE n.order_id = $1
+         ) t
+         LEFT JOIN users u ON u.id = t.actor_id
+         WHERE ($2::text[] IS NULL OR t.kind = ANY($2))
+         ORDER BY t.at ASC, t.kind ASC"
+    )
+    .bind(order_uuid)
+    .bind(kinds)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": timeline,
+        "total": timeline.len()
+    })))
+}
+
+// Catatan order. Staff cabang bisa menulis catatan internal (tidak terlihat customer) atau shared;
+// pemilik order hanya bisa menulis catatan shared.
+async fn create_booking_note(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<CreateOrderNoteRequest>,
+) -> Result<RespJson<OrderNote>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    let owner_id = authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+    let is_customer_view = owner_id == user.id && !user.role.is_staff();
+
+    let body = payload.body.trim();
+    if body.is_empty() || body.chars().count() > 2000 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Note must be between 1 and 2000 characters"
+        }))));
+    }
+
+    let visibility = match (payload.visibility, is_customer_view) {
+        (Some(NoteVisibility::Internal), true) => {
+            return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+                "error": "Customers can only add shared notes"
+            }))));
+        }
+        (Some(visibility), _) => visibility,
+        (None, true) => NoteVisibility::Shared,
+        (None, false) => NoteVisibility::Internal,
+    };
+
+    let note: OrderNote = sqlx::query_as(
+        "WITH inserted AS (
+             INSERT INTO order_notes (id, order_id, author_id, body, visibility)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id, author_id, body, visibility, created_at
+         )
+         SELECT i.id, i.author_id, u.username AS author_username, i.body, i.visibility, i.created_at
+         FROM inserted i LEFT JOIN users u ON u.id = i.author_id"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_uuid)
+    .bind(user.id)
+    .bind(body)
+    .bind(visibility.as_str())
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    println!("📝 {} note added to order {} by {}", visibility.as_str(), order_uuid, user.id);
+    Ok(RespJson(note))
+}
+
+// Daftar catatan order. Customer hanya melihat catatan shared.
+async fn list_booking_notes(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    let owner_id = authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+    let shared_only = owner_id == user.id && !user.role.is_staff();
+
+    let notes: Vec<OrderNote> = sqlx::query_as(
+        "SELECT n.id, n.author_id, u.username AS author_username, n.body, n.visibility, n.created_at
+         FROM order_notes n
+         LEFT JOIN users u ON u.id = n.author_id
+         WHERE n.order_id = $1 AND ($2 = FALSE OR n.visibility = $3)
+         ORDER BY n.created_at ASC"
+    )
+    .bind(order_uuid)
+    .bind(shared_only)
+    .bind(NoteVisibility::Shared.as_str())
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": notes,
+        "total": notes.len()
+    })))
+}
+
+// Customer memberi rating dan ulasan setelah order selesai (completed). Satu ulasan per order;
+// ulasan tampil di detail motor.
+async fn create_review(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<CreateReviewRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    let comment = validate_review(payload.rating, payload.comment.as_deref())?;
+
+    let order: Option<(Uuid, String, Option<i32>)> = sqlx::query_as(
+        "SELECT user_id, status, motor_id FROM orders WHERE id = $1"
+    )
+    .bind(order_uuid)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    // Order milik orang lain dianggap tidak ada
+    let (owner_id, status, motor_id) = order
+        .filter(|(owner_id, _, _)| *owner_id == user.id)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    if status != OrderStatus::Completed.as_str() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Only completed bookings can be reviewed",
+            "currentStatus": status
+        }))));
+    }
+
+    let review_status = new_review_status();
+    let result = sqlx::query(
+        "INSERT INTO order_reviews (order_id, motor_id, user_id, rating, comment, status) VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+    .bind(order_uuid)
+    .bind(motor_id)
+    .bind(owner_id)
+    .bind(payload.rating)
+    .bind(comment)
+    .bind(review_status.as_str())
+    .execute(&pool)
+    .await;
+
+    match result {
+        Ok(_) => {
+            println!("⭐ Review {} for order {} by {}", payload.rating, order_uuid, user.id);
+            Ok(RespJson(serde_json::json!({
+                "success": true,
+                "message": "Terima kasih atas ulasannya",
+                "review": {"rating": payload.rating, "comment": comment, "status": review_status.as_str()}
+            })))
+        }
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23505") => {
+            Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "This booking has already been reviewed"}))))
+        }
+        Err(e) => {
+            println!("❌ Database error: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))
+        }
+    }
+}
+
+const MAX_HANDOVER_PHOTOS: usize = 10;
+
+// Staff mencatat checklist serah terima saat motor diambil / dikembalikan. Satu checklist per
+// jenis per order dan tidak bisa diubah, supaya tetap sah sebagai bukti.
+async fn create_handover(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<CreateHandoverRequest>,
+) -> Result<RespJson<OrderHandover>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let body_condition = payload.body_condition.trim();
+    if body_condition.is_empty() || body_condition.chars().count() > 1000 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "bodyCondition must be between 1 and 1000 characters"
+        }))));
+    }
+    if !(0..=10).contains(&payload.helmet_count) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid helmetCount"}))));
+    }
+    if payload.photos.len() > MAX_HANDOVER_PHOTOS {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("At most {} photos per handover", MAX_HANDOVER_PHOTOS)
+        }))));
+    }
+    if payload.photos.iter().any(|url| !(url.starts_with("https://") || url.starts_with("http://"))) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Photos must be http(s) URLs"}))));
+    }
+    let notes = payload.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    let result: Result<OrderHandover, sqlx::Error> = sqlx::query_as(
+        "WITH inserted AS (
+             INSERT INTO order_handovers (id, order_id, kind, body_condition, mirrors_ok, helmet_count, notes, photos, recorded_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING id, kind, body_condition, mirrors_ok, helmet_count, notes, photos, recorded_by, recorded_at
+         )
+         SELECT i.*, u.username AS recorded_by_username
+         FROM inserted i LEFT JOIN users u ON u.id = i.recorded_by"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_uuid)
+    .bind(payload.kind.as_str())
+    .bind(body_condition)
+    .bind(payload.mirrors_ok)
+    .bind(payload.helmet_count)
+    .bind(notes)
+    .bind(&payload.photos)
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await;
+
+    match result {
+        Ok(handover) => {
+            println!("📋 {} handover recorded for order {} by {}", payload.kind.as_str(), order_uuid, user.id);
+            Ok(RespJson(handover))
+        }
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23505") => {
+            Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+                "error": format!("A {} handover has already been recorded for this booking", payload.kind.as_str())
+            }))))
+        }
+        Err(e) => {
+            println!("❌ Database error: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))
+        }
+    }
+}
 
-    // Parse dummy token format: "dummy_token_for_{user_id}"
-    let user_id_str = auth_header.strip_prefix("dummy_token_for_")
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-    
-    let user_id = Uuid::parse_str(user_id_str)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+// Checklist serah terima order, bisa dilihat pemilik order maupun staff cabang
+async fn list_handovers(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
 
-    // Verify user exists in database
-    let exists = sqlx::query!("SELECT id FROM users WHERE id = $1", user_id)
-        .fetch_optional(pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .is_some();
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
 
-    if !exists {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
+    let handovers: Vec<OrderHandover> = sqlx::query_as(
+        "SELECT h.id, h.kind, h.body_condition, h.mirrors_ok, h.helmet_count, h.notes, h.photos,
+                h.recorded_by, u.username AS recorded_by_username, h.recorded_at
+         FROM order_handovers h
+         LEFT JOIN users u ON u.id = h.recorded_by
+         WHERE h.order_id = $1
+         ORDER BY h.recorded_at ASC"
+    )
+    .bind(order_uuid)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
 
-    Ok(user_id)
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": handovers,
+        "total": handovers.len()
+    })))
 }
 
-pub fn order_router() -> Router {
-    println!("🔧 Registering order routes...");
-    Router::new()
-        .route("/api/orders", post(create_booking))
-        .route("/api/orders/:id", get(get_booking))
-        .route("/api/orders/:id", put(update_booking))
-        .route("/api/orders/:id", delete(delete_booking))
-        .route("/api/orders", get(list_bookings))           // User orders only (with auth)
-        .route("/api/orders/all", get(list_all_bookings))   // Admin: all orders
-        .route("/api/orders/test", get(test_endpoint))
+// Jarak tempuh selama sewa, jika odometer pickup dan return sudah dicatat
+fn mileage_km(readings: &[OrderReading]) -> Option<i32> {
+    let odometer = |kind: HandoverKind| readings.iter().find(|r| r.kind == kind.as_str()).map(|r| r.odometer_km);
+    Some(odometer(HandoverKind::Return)? - odometer(HandoverKind::Pickup)?)
 }
 
-// Test endpoint
-async fn test_endpoint() -> RespJson<serde_json::Value> {
-    RespJson(serde_json::json!({
-        "status": "ok",
-        "message": "Orders API is working",
-        "timestamp": chrono::Utc::now()
-    }))
+// Biaya kelebihan km (MILEAGE_INCLUDED_KM_PER_DAY x lama sewa gratis, sisanya MILEAGE_OVERAGE_RATE per km)
+// dicatat sebagai item "mileage" di rincian harga, order_items, dan total order. Koreksi odometer
+// menghitung ulang item ini; invoice yang tersimpan dibuang supaya dibuat ulang dengan total baru.
+async fn apply_mileage_charge(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    mut breakdown: PriceBreakdown,
+    mileage_km: i64,
+) -> Result<Option<PriceItem>, sqlx::Error> {
+    let config = config::get();
+    let excess_km = pricing::excess_km(mileage_km, breakdown.rental_days, config.mileage_included_km_per_day);
+    let had_charge = breakdown.items.iter().any(|item| item.code == MILEAGE_ITEM_CODE);
+    if config.mileage_overage_rate <= 0 || (excess_km == 0 && !had_charge) {
+        return Ok(None);
+    }
+
+    breakdown.remove_item(MILEAGE_ITEM_CODE);
+    if excess_km > 0 {
+        let name = format!(
+            "Kelebihan km ({} km di atas jatah {} km/hari)",
+            excess_km, config.mileage_included_km_per_day
+        );
+        breakdown.add_item(MILEAGE_ITEM_CODE, &name, excess_km, config.mileage_overage_rate);
+    }
+    let charge = breakdown.items.iter().find(|item| item.code == MILEAGE_ITEM_CODE).cloned();
+
+    sqlx::query("UPDATE orders SET price_breakdown = $2, total_price = $3 WHERE id = $1")
+        .bind(order_id)
+        .bind(serde_json::to_value(&breakdown).ok())
+        .bind(breakdown.total)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM order_items WHERE order_id = $1 AND code = $2")
+        .bind(order_id)
+        .bind(MILEAGE_ITEM_CODE)
+        .execute(&mut *conn)
+        .await?;
+    insert_order_items(&mut *conn, order_id, charge.as_slice()).await?;
+    sqlx::query("DELETE FROM order_invoices WHERE order_id = $1")
+        .bind(order_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if let Some(charge) = &charge {
+        println!("🛣️ Mileage charge for order {}: {} km x {} = {}", order_id, charge.quantity, charge.unit_price, charge.subtotal);
+    }
+    Ok(charge)
 }
 
-// Create new booking dari form sewa motor
-async fn create_booking(
-    headers: HeaderMap,
+// Staff mencatat odometer dan bensin saat motor diambil / dikembalikan. Odometer return tidak
+// boleh lebih kecil dari odometer pickup.
+async fn record_reading(
+    user: AuthUser,
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<serde_json::Value>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<CreateReadingRequest>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
-    println!("Creating booking with payload: {:?}", payload);
-    
-    // Authenticate user
-    let user_id = match get_user_from_token(&headers, &pool).await {
-        Ok(id) => id,
-        Err(status) => return Err((status, RespJson(serde_json::json!({"error": "Authentication required"}))))
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    if payload.odometer_km < 0 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "odometerKm cannot be negative"}))));
+    }
+    if !(0..=100).contains(&payload.fuel_percent) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "fuelPercent must be between 0 and 100"}))));
+    }
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
     };
-    
-    // Extract booking data dari payload sesuai dengan form sewa motor
-    let tanggal_peminjaman = payload.get("tanggalPeminjaman")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing tanggalPeminjaman"}))))?;
-    
-    let jam_peminjaman = payload.get("jamPeminjaman")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing jamPeminjaman"}))))?;
-    
-    let alamat_pengantaran = payload.get("alamatPengantaran")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing alamatPengantaran"}))))?;
-    
-    let tanggal_pengembalian = payload.get("tanggalPengembalian")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing tanggalPengembalian"}))))?;
-    
-    let jam_pengembalian = payload.get("jamPengembalian")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing jamPengembalian"}))))?;
-    
-    let alamat_pengembalian = payload.get("alamatPengembalian")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing alamatPengembalian"}))))?;
-    
-    let pilih_cabang = payload.get("pilihCabang")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing pilihCabang"}))))?;
-    
-    let pilih_motor = payload.get("pilihMotor")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Missing pilihMotor"}))))?;
 
-    // Optional fields  
-    let booking_id_value = format!("BWK{}", chrono::Utc::now().timestamp_millis() % 1000000);
-    let booking_id = payload.get("bookingId")
-        .and_then(|v| v.as_str())
-        .unwrap_or(&booking_id_value);
-        
-    let motor_price = payload.get("motorPrice")
-        .and_then(|v| v.as_str())
-        .unwrap_or("Rp 50.000/hari");
+    let mut tx = pool.begin().await.map_err(db_error)?;
 
-    // Parse tanggal
-    let tanggal_peminjaman_date = chrono::NaiveDate::parse_from_str(tanggal_peminjaman, "%Y-%m-%d")
-        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid tanggalPeminjaman format"}))))?;
-    
-    let tanggal_pengembalian_date = chrono::NaiveDate::parse_from_str(tanggal_pengembalian, "%Y-%m-%d")
-        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid tanggalPengembalian format"}))))?;
-    
-    let jam_peminjaman_time = chrono::NaiveTime::parse_from_str(jam_peminjaman, "%H:%M")
-        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid jamPeminjaman format"}))))?;
-    
-    let jam_pengembalian_time = chrono::NaiveTime::parse_from_str(jam_pengembalian, "%H:%M")
-        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid jamPengembalian format"}))))?;
+    // Kunci order supaya pencatatan pickup & return tidak balapan saat validasi odometer
+    let price_breakdown: Option<serde_json::Value> = sqlx::query_scalar("SELECT price_breakdown FROM orders WHERE id = $1 FOR UPDATE")
+        .bind(order_uuid)
+        .fetch_one(&mut tx)
+        .await
+        .map_err(db_error)?;
 
-    // Insert ke database orders
-    let order_id = Uuid::new_v4();
-    
-    println!("=== SEWA MOTOR INSERT DEBUG ===");
-    println!("Order ID: {}", order_id);
-    println!("User ID: {}", user_id);
-    println!("Booking ID: {}", booking_id);
-    println!("Motor: {} - {}", pilih_motor, motor_price);
-    println!("Tanggal: {} s/d {}", tanggal_peminjaman, tanggal_pengembalian);
-    println!("Cabang: {}", pilih_cabang);
-    
-    let result = sqlx::query!(
-        r#"
-        INSERT INTO orders (
-            id, user_id, 
-            tanggal_peminjaman, jam_peminjaman, alamat_pengantaran,
-            tanggal_pengembalian, jam_pengembalian, alamat_pengembalian,
-            pilih_cabang, pilih_motor, motor_price,
-            status, tanggal_booking, waktu_booking
-        ) VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'pending', CURRENT_DATE, CURRENT_TIME
-        )
-        "#,
-        order_id,
-        user_id,
-        tanggal_peminjaman_date,
-        jam_peminjaman_time,
-        alamat_pengantaran,
-        tanggal_pengembalian_date,
-        jam_pengembalian_time,
-        alamat_pengembalian,
-        pilih_cabang,
-        pilih_motor,
-        motor_price
+    let existing: Vec<OrderReading> = sqlx::query_as(
+        "SELECT kind, odometer_km, fuel_percent, recorded_by, recorded_at FROM order_readings WHERE order_id = $1"
     )
-    .execute(&pool)
-    .await;
+    .bind(order_uuid)
+    .fetch_all(&mut tx)
+    .await
+    .map_err(db_error)?;
 
-    match result {
-        Ok(_) => {
-            println!("✅ Sewa motor booking berhasil disimpan ke database");
-            Ok(RespJson(serde_json::json!({
-                "success": true,
-                "message": "Booking sewa motor berhasil dibuat",
-                "booking_id": booking_id,
-                "order_id": order_id,
-                "data": {
-                    "id": order_id,
-                    "bookingId": booking_id,
-                    "tanggalPeminjaman": tanggal_peminjaman,
-                    "jamPeminjaman": jam_peminjaman,
-                    "alamatPengantaran": alamat_pengantaran,
-                    "tanggalPengembalian": tanggal_pengembalian,
-                    "jamPengembalian": jam_pengembalian,
-                    "alamatPengembalian": alamat_pengembalian,
-                    "pilihCabang": pilih_cabang,
-                    "pilihMotor": pilih_motor,
-                    "motorPrice": motor_price,
-                    "status": "pending"
-                }
-            })))
-        }
-        Err(e) => {
-            println!("❌ Sewa motor booking database insert failed: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": format!("Database error: {}", e)}))))
-        }
+    let other_odometer = |kind: HandoverKind| existing.iter().find(|r| r.kind == kind.as_str()).map(|r| r.odometer_km);
+    let out_of_order = match payload.kind {
+        HandoverKind::Pickup => other_odometer(HandoverKind::Return).is_some_and(|km| payload.odometer_km > km),
+        HandoverKind::Return => other_odometer(HandoverKind::Pickup).is_some_and(|km| payload.odometer_km < km),
+    };
+    if out_of_order {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Return odometer cannot be lower than pickup odometer"
+        }))));
     }
+
+    // Koreksi salah input diperbolehkan: pencatatan ulang menimpa angka sebelumnya
+    sqlx::query(
+        "INSERT INTO order_readings (order_id, kind, odometer_km, fuel_percent, recorded_by)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (order_id, kind) DO UPDATE
+         SET odometer_km = EXCLUDED.odometer_km, fuel_percent = EXCLUDED.fuel_percent,
+             recorded_by = EXCLUDED.recorded_by, recorded_at = NOW()"
+    )
+    .bind(order_uuid)
+    .bind(payload.kind.as_str())
+    .bind(payload.odometer_km)
+    .bind(payload.fuel_percent)
+    .bind(user.id)
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let readings: Vec<OrderReading> = sqlx::query_as(
+        "SELECT kind, odometer_km, fuel_percent, recorded_by, recorded_at FROM order_readings
+         WHERE order_id = $1 ORDER BY recorded_at ASC"
+    )
+    .bind(order_uuid)
+    .fetch_all(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    // Order lama (sebelum ada pricing engine) tidak punya rincian harga untuk ditambah biaya km
+    let breakdown: Option<PriceBreakdown> = price_breakdown.and_then(|value| serde_json::from_value(value).ok());
+    let mileage_charge = match (mileage_km(&readings), breakdown) {
+        (Some(mileage), Some(breakdown)) => apply_mileage_charge(&mut tx, order_uuid, breakdown, i64::from(mileage))
+            .await
+            .map_err(db_error)?,
+        _ => None,
+    };
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("⛽ {} reading for order {}: {} km, {}%", payload.kind.as_str(), order_uuid, payload.odometer_km, payload.fuel_percent);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "mileageKm": mileage_km(&readings),
+        "mileageCharge": mileage_charge,
+        "data": readings
+    })))
 }
 
-// Get booking by ID
-async fn get_booking(
+// Odometer & bensin yang sudah dicatat untuk order, beserta jarak tempuhnya
+async fn list_readings(
+    user: AuthUser,
     Extension(pool): Extension<PgPool>,
     Path(booking_id): Path<String>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
     let order_uuid = Uuid::parse_str(&booking_id)
         .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
-    
-    let row = sqlx::query!(
-        "SELECT id, user_id, tanggal_peminjaman, jam_peminjaman, alamat_pengantaran, tanggal_pengembalian, jam_pengembalian, alamat_pengembalian, pilih_cabang, pilih_motor, motor_price, status, tanggal_booking, waktu_booking FROM orders WHERE id = $1",
-        order_uuid
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_READ).await?;
+
+    let readings: Vec<OrderReading> = sqlx::query_as(
+        "SELECT kind, odometer_km, fuel_percent, recorded_by, recorded_at FROM order_readings
+         WHERE order_id = $1 ORDER BY recorded_at ASC"
+    )
+    .bind(order_uuid)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "mileageKm": mileage_km(&readings),
+        "data": readings
+    })))
+}
+
+// Staff mencatat deposit jaminan sudah diterima dan dipegang cabang
+async fn hold_deposit(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<HoldDepositRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    if payload.amount.is_some_and(|amount| amount < 0) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Deposit amount cannot be negative"}))));
+    }
+
+    let updated: Option<(i32,)> = sqlx::query_as(
+        "UPDATE orders
+         SET deposit_status = $1, deposit_amount = COALESCE($2, deposit_amount),
+             deposit_held_at = NOW(), deposit_handled_by = $3
+         WHERE id = $4 AND deposit_status = $5
+         RETURNING deposit_amount"
     )
+    .bind(DepositStatus::Held.as_str())
+    .bind(payload.amount)
+    .bind(user.id)
+    .bind(order_uuid)
+    .bind(DepositStatus::Pending.as_str())
     .fetch_optional(&pool)
     .await
-    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))?;
-    
-    match row {
-        Some(order) => {
-            Ok(RespJson(serde_json::json!({
-                "id": order.id,
-                "user_id": order.user_id,
-                "bookingId": booking_id,
-                "tanggalPeminjaman": order.tanggal_peminjaman,
-                "jamPeminjaman": order.jam_peminjaman,
-                "alamatPengantaran": order.alamat_pengantaran,
-                "tanggalPengembalian": order.tanggal_pengembalian,
-                "jamPengembalian": order.jam_pengembalian,
-                "alamatPengembalian": order.alamat_pengembalian,
-                "pilihCabang": order.pilih_cabang,
-                "pilihMotor": order.pilih_motor,
-                "motorPrice": order.motor_price,
-                "status": order.status,
-                "tanggalBooking": order.tanggal_booking,
-                "waktuBooking": order.waktu_booking
-            })))
-        }
-        None => Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    })?;
+
+    let (amount,) = updated.ok_or_else(|| (StatusCode::CONFLICT, RespJson(serde_json::json!({
+        "error": "Deposit has already been collected for this booking"
+    }))))?;
+
+    println!("💰 Deposit {} held for order {} by {}", amount, order_uuid, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Deposit dicatat sudah diterima",
+        "deposit": {"amount": amount, "status": DepositStatus::Held.as_str()}
+    })))
+}
+
+// Staff mengembalikan deposit ke customer, dikurangi potongan kerusakan jika ada
+async fn release_deposit(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<ReleaseDepositRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let order_uuid = Uuid::parse_str(&booking_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let deduction = payload.deduction.unwrap_or(0);
+    let reason = payload.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    if deduction < 0 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Deduction cannot be negative"}))));
+    }
+    if deduction > 0 && reason.is_none() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "A reason is required when deducting from the deposit"}))));
+    }
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let deposit: Option<(i32, String)> = sqlx::query_as(
+        "SELECT deposit_amount, deposit_status FROM orders WHERE id = $1 FOR UPDATE"
+    )
+    .bind(order_uuid)
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let (amount, status) = deposit
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    if status != DepositStatus::Held.as_str() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Deposit is not currently held",
+            "depositStatus": status
+        }))));
+    }
+
+    if deduction > amount {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Deduction cannot exceed the deposit amount ({})", amount)
+        }))));
     }
+
+    sqlx::query(
+        "UPDATE orders
+         SET deposit_status = $1, deposit_deduction = $2, deposit_deduction_reason = $3,
+             deposit_released_at = NOW(), deposit_handled_by = $4
+         WHERE id = $5"
+    )
+    .bind(DepositStatus::Released.as_str())
+    .bind(deduction)
+    .bind(reason)
+    .bind(user.id)
+    .bind(order_uuid)
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("💰 Deposit for order {} released: refund {} (deduction {})", order_uuid, amount - deduction, deduction);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Deposit dikembalikan",
+        "deposit": {
+            "amount": amount,
+            "deduction": deduction,
+            "deductionReason": reason,
+            "refund": amount - deduction,
+            "status": DepositStatus::Released.as_str()
+        }
+    })))
 }
 
-// Update booking status
-async fn update_booking(
+// Order yang sedang disewa, untuk dihitung ulang saat motor dikembalikan lebih awal
+#[derive(sqlx::FromRow)]
+struct RentedOrder {
+    status: String,
+    rental_start: NaiveDateTime,
+    rental_end: NaiveDateTime,
+    price_breakdown: Option<serde_json::Value>,
+}
+
+// Motor dikembalikan sebelum jadwal: harga dihitung ulang untuk lama sewa sebenarnya, order selesai
+// disewa (returned) dengan jadwal kembali = waktu pengembalian, dan selisihnya (sesuai
+// EARLY_RETURN_REFUND_PERCENT) dicatat di order_refunds untuk diproses alur pembayaran
+async fn return_early(
+    user: AuthUser,
     Extension(pool): Extension<PgPool>,
     Path(booking_id): Path<String>,
-    Json(payload): Json<serde_json::Value>,
+    Json(payload): Json<EarlyReturnRequest>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
     let order_uuid = Uuid::parse_str(&booking_id)
         .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
-    
-    let status = payload.get("status").and_then(|v| v.as_str()).unwrap_or("pending");
-    
-    let result = sqlx::query!(
-        "UPDATE orders SET status = $1 WHERE id = $2",
-        status,
-        order_uuid
+
+    authorize_order_staff(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let now = chrono::Local::now().naive_local();
+    let returned_at = payload.returned_at.unwrap_or(now);
+    if returned_at > now {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "returnedAt cannot be in the future"}))));
+    }
+    let reason = payload.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let order: Option<RentedOrder> = sqlx::query_as(
+        "SELECT status, tanggal_peminjaman + jam_peminjaman AS rental_start,
+                tanggal_pengembalian + jam_pengembalian AS rental_end, price_breakdown
+         FROM orders WHERE id = $1 FOR UPDATE"
     )
-    .execute(&pool)
-    .await;
+    .bind(order_uuid)
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(db_error)?;
 
-    match result {
-        Ok(query_result) => {
-            if query_result.rows_affected() > 0 {
-                Ok(RespJson(serde_json::json!({
-                    "success": true,
-                    "message": "Booking status updated successfully"
-                })))
-            } else {
-                Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))
-            }
-        }
-        Err(e) => {
-            println!("Update booking error: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))
-        }
+    let order = order
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+
+    if order.status != OrderStatus::PickedUp.as_str() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Only bookings whose motor has been picked up can be returned early",
+            "status": order.status
+        }))));
+    }
+    if returned_at <= order.rental_start || returned_at >= order.rental_end {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "returnedAt must be between the pickup time and the scheduled return time"
+        }))));
     }
+
+    // Order lama (sebelum ada pricing engine) tidak punya rincian harga untuk dihitung ulang
+    let original: PriceBreakdown = order.price_breakdown
+        .and_then(|value| serde_json::from_value(value).ok())
+        .ok_or_else(|| (StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "This booking has no price breakdown to recalculate"
+        }))))?;
+
+    let motor_ids: Vec<i32> = original.lines.iter().filter_map(|line| line.motor_id).collect();
+    let rates: Vec<(i32, i32, Option<i32>, Option<i32>)> = sqlx::query_as(
+        "SELECT motor_id, price_per_day, price_per_week, price_per_month FROM motors WHERE motor_id = ANY($1)"
+    )
+    .bind(&motor_ids)
+    .fetch_all(&mut tx)
+    .await
+    .map_err(db_error)?;
+    let rates: Vec<(i32, RateCard)> = rates.into_iter()
+        .map(|(motor_id, per_day, per_week, per_month)| (motor_id, RateCard { per_day, per_week, per_month }))
+        .collect();
+
+    let days = pricing::rental_days(order.rental_start, returned_at);
+    let recalculated = pricing::recalculate_for_days(&original, &rates, days, &[INSURANCE_ITEM_CODE, DRIVER_ITEM_CODE]);
+    let difference = (original.total - recalculated.total).max(0);
+    let refund_amount = difference * config::get().early_return_refund_percent.clamp(0, 100) / 100;
+
+    sqlx::query(
+        "UPDATE orders SET status = $1, tanggal_pengembalian = $2, jam_pengembalian = $3 WHERE id = $4"
+    )
+    .bind(OrderStatus::Returned.as_str())
+    .bind(returned_at.date())
+    .bind(returned_at.time())
+    .bind(order_uuid)
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    let note = format!("Dikembalikan lebih awal ({} dari jadwal {})", returned_at, order.rental_end);
+    record_status_change(&mut tx, order_uuid, Some(&order.status), OrderStatus::Returned, Some(user.id), Some(&note))
+        .await
+        .map_err(db_error)?;
+
+    let refund: OrderRefund = sqlx::query_as(
+        "INSERT INTO order_refunds (id, order_id, kind, amount, original_total, recalculated_total,
+                                    recalculated_breakdown, original_rental_end, returned_at, reason, created_by)
+         VALUES ($1, $2, 'early_return', $3, $4, $5, $6, $7, $8, $9, $10)
+         RETURNING id, order_id, kind, amount, original_total, recalculated_total, status, reason, created_by, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_uuid)
+    .bind(refund_amount)
+    .bind(original.total)
+    .bind(recalculated.total)
+    .bind(serde_json::to_value(&recalculated).ok())
+    .bind(order.rental_end)
+    .bind(returned_at)
+    .bind(reason)
+    .bind(user.id)
+    .fetch_one(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("↩️  Order {} returned early at {}: refund {} of {}", order_uuid, returned_at, refund_amount, difference);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Motor dikembalikan lebih awal",
+        "status": OrderStatus::Returned.as_str(),
+        "returnedAt": returned_at,
+        "originalBreakdown": original,
+        "recalculatedBreakdown": recalculated,
+        "difference": difference,
+        "refund": refund
+    })))
 }
 
-// Delete booking
+// Hapus booking: order tidak dihapus permanen (riwayat keuangan tetap ada) tapi diarsipkan dan
+// disembunyikan dari list order. Order yang belum diambil sekaligus dibatalkan supaya motornya tersedia lagi;
+// order yang sedang berjalan ditolak (409).
 async fn delete_booking(
+    user: AuthUser,
     Extension(pool): Extension<PgPool>,
     Path(booking_id): Path<String>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
     let order_uuid = Uuid::parse_str(&booking_id)
         .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid booking ID"}))))?;
-    
-    let result = sqlx::query!(
-        "DELETE FROM orders WHERE id = $1",
-        order_uuid
+
+    authorize_order_access(&pool, &user, order_uuid, scope::ORDERS_WRITE).await?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("Delete booking error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let order: Option<(String, Option<chrono::DateTime<chrono::Utc>>)> = sqlx::query_as(
+        "SELECT status, archived_at FROM orders WHERE id = $1 FOR UPDATE"
     )
-    .execute(&pool)
-    .await;
+    .bind(order_uuid)
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(db_error)?;
 
-    match result {
-        Ok(query_result) => {
-            if query_result.rows_affected() > 0 {
-                Ok(RespJson(serde_json::json!({
-                    "success": true,
-                    "message": "Booking deleted successfully"
-                })))
-            } else {
-                Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))
-            }
-        }
-        Err(e) => {
-            println!("Delete booking error: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"}))))
-        }
+    let (status, archived_at) = order
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Booking not found"}))))?;
+    if archived_at.is_some() {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Booking is already archived"}))));
+    }
+
+    let current = status.parse::<OrderStatus>().ok();
+    let cancel = current.is_some_and(|current| current.can_transition_to(OrderStatus::Cancelled));
+    // Customer hanya boleh menghapus (membatalkan) order yang belum dikonfirmasi
+    if let Some(current) = current.filter(|current| cancel && !current.allowed_next_for(user.role).contains(&OrderStatus::Cancelled)) {
+        return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": format!("{} bookings can only be cancelled by staff", current.as_str()),
+            "currentStatus": current.as_str(),
+            "allowedNextStates": current.allowed_next_for(user.role).iter().map(OrderStatus::as_str).collect::<Vec<_>>()
+        }))));
+    }
+    // Order yang sedang berjalan (sudah diambil, overdue, menunggu diselesaikan) tidak bisa dibatalkan,
+    // jadi juga tidak boleh diarsipkan: order itu harus tetap terlihat di list order staff
+    let finished = matches!(current, Some(OrderStatus::Completed | OrderStatus::Cancelled));
+    if !cancel && !finished {
+        return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "Booking is in progress and cannot be deleted until it is completed",
+            "currentStatus": status
+        }))));
+    }
+    let new_status = if cancel { OrderStatus::Cancelled.as_str() } else { status.as_str() };
+
+    sqlx::query("UPDATE orders SET archived_at = NOW(), archived_by = $1, status = $2 WHERE id = $3")
+        .bind(user.id)
+        .bind(new_status)
+        .bind(order_uuid)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    if cancel {
+        record_status_change(&mut tx, order_uuid, Some(&status), OrderStatus::Cancelled, Some(user.id), Some("Booking dihapus (diarsipkan)"))
+            .await
+            .map_err(db_error)?;
     }
+
+    tx.commit().await.map_err(db_error)?;
+
+    println!("🗄️  Order {} archived by {}", order_uuid, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Booking deleted successfully",
+        "archived": true,
+        "status": new_status
+    })))
+}
+
+// Admin: order yang sudah diarsipkan (dihapus), terbaru dulu, dengan pagination seperti /api/orders/all
+async fn list_archived_bookings(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<OrderQuery>,
+) -> Result<RespJson<OrderListResponse>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let cursor = parse_order_cursor(params.after.as_deref())?;
+    let page = if cursor.is_some() { 1 } else { params.page.unwrap_or(1).max(1) };
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * limit;
+    let branch = params.branch.clone().filter(|b| !b.trim().is_empty());
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    const FILTERS: &str = "o.archived_at IS NOT NULL
+           AND ($1::text IS NULL OR LOWER(TRIM(o.pilih_cabang)) = LOWER(TRIM($1)))";
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM orders o WHERE {}", FILTERS))
+        .bind(&branch)
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let mut rows: Vec<AdminOrderRow> = sqlx::query_as(&format!(
+        "{} WHERE {} AND {} ORDER BY {} LIMIT $5 OFFSET $6",
+        ADMIN_ORDER_SELECT, FILTERS, order_keyset_filter(2), ORDER_LIST_ORDER_BY
+    ))
+    .bind(&branch)
+    .bind(cursor.map(|c| c.tanggal_booking))
+    .bind(cursor.map(|c| c.waktu_booking))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    let next_cursor = next_order_cursor(&mut rows, limit);
+
+    let orders = rows.into_iter().map(admin_order_response).collect();
+
+    Ok(RespJson(OrderListResponse { orders, total, page, limit, next_cursor }))
 }
 
 // List bookings untuk user yang sedang login (dengan authentication)
 async fn list_bookings(
-    headers: HeaderMap,
+    user: AuthUser,
     Extension(pool): Extension<PgPool>,
+    Query(params): Query<OrderCursorQuery>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
-    // Authenticate user
-    let user_id = match get_user_from_token(&headers, &pool).await {
-        Ok(id) => id,
-        Err(status) => return Err((status, RespJson(serde_json::json!({"error": "Authentication required"}))))
-    };
+    let user_id = user.id;
+    let cursor = parse_order_cursor(params.after.as_deref())?;
+    let limit = params.limit.map(|limit| limit.clamp(1, 100));
 
     println!("🔍 Fetching orders for user: {}", user_id);
 
-    // Query orders hanya untuk user yang sedang login
-    let rows = sqlx::query!(
-        "SELECT id, user_id, tanggal_peminjaman, jam_peminjaman, alamat_pengantaran, tanggal_pengembalian, jam_pengembalian, alamat_pengembalian, pilih_cabang, pilih_motor, motor_price, status, tanggal_booking, waktu_booking FROM orders WHERE user_id = $1 ORDER BY tanggal_booking DESC, waktu_booking DESC",
-        user_id
-    )
+    // Query orders hanya untuk user yang sedang login; tanpa limit semua order dikembalikan
+    let mut rows: Vec<AdminOrderRow> = sqlx::query_as(&format!(
+        "{} WHERE o.user_id = $1 AND o.archived_at IS NULL AND {} ORDER BY {} LIMIT $5",
+        ADMIN_ORDER_SELECT, order_keyset_filter(2), ORDER_LIST_ORDER_BY
+    ))
+    .bind(user_id)
+    .bind(cursor.map(|c| c.tanggal_booking))
+    .bind(cursor.map(|c| c.waktu_booking))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit.map(|limit| i64::from(limit) + 1))
     .fetch_all(&pool)
     .await
     .map_err(|e| {
         println!("❌ Database error: {}", e);
         (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
     })?;
-    
+
+    let next_cursor = limit.and_then(|limit| next_order_cursor(&mut rows, limit));
+
     println!("✅ Found {} orders for user {}", rows.len(), user_id);
     
     let bookings: Vec<serde_json::Value> = rows.into_iter().map(|row| {
         serde_json::json!({
             "id": row.id,
             "user_id": row.user_id,
-            "bookingId": format!("BWK{}", row.id.to_string().chars().take(6).collect::<String>()),
+            "bookingId": row.order_number,
             "tanggalPeminjaman": row.tanggal_peminjaman,
             "jamPeminjaman": row.jam_peminjaman,
             "alamatPengantaran": row.alamat_pengantaran,
             "tanggalPengembalian": row.tanggal_pengembalian,
             "jamPengembalian": row.jam_pengembalian,
             "alamatPengembalian": row.alamat_pengembalian,
+            "fulfillmentMode": row.fulfillment_mode,
             "pilihCabang": row.pilih_cabang,
             "pilihMotor": row.pilih_motor,
-            "motorPrice": row.motor_price,
+            "motorPrice": format_price_per_day(row.motor_price),
+            "motorPriceAmount": row.motor_price,
+            "motor": motor_json(row.motor_id, row.motor_name, row.motor_image, row.motor_type),
             "status": row.status,
             "tanggalBooking": row.tanggal_booking,
             "waktuBooking": row.waktu_booking
@@ -366,53 +3384,574 @@ async fn list_bookings(
         "success": true,
         "data": bookings,
         "total": bookings.len(),
+        "next_cursor": next_cursor,
         "user_id": user_id
     })))
 }
 
-// Admin endpoint: List ALL bookings (tanpa filter user_id)
+// Urutan list order (terbaru dulu); id sebagai pemutus supaya urutan tetap untuk cursor pagination
+const ORDER_LIST_ORDER_BY: &str = "o.tanggal_booking DESC, o.waktu_booking DESC, o.id DESC";
+
+// Kondisi keyset untuk cursor pagination: tiga parameter mulai $first (tanggal, jam, id), NULL = halaman pertama
+fn order_keyset_filter(first: usize) -> String {
+    format!(
+        "(${0}::date IS NULL OR (o.tanggal_booking, o.waktu_booking, o.id) < (${0}::date, ${1}::time, ${2}::uuid))",
+        first, first + 1, first + 2
+    )
+}
+
+fn parse_order_cursor(after: Option<&str>) -> Result<Option<OrderCursor>, (StatusCode, RespJson<serde_json::Value>)> {
+    match after.map(str::trim).filter(|a| !a.is_empty()) {
+        None => Ok(None),
+        Some(after) => OrderCursor::decode(after)
+            .map(Some)
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid cursor"})))),
+    }
+}
+
+// Baris diambil limit + 1 untuk tahu apakah masih ada halaman berikutnya; kelebihannya dibuang dan
+// cursor halaman berikutnya diambil dari baris terakhir yang dikembalikan
+fn next_order_cursor(rows: &mut Vec<AdminOrderRow>, limit: i32) -> Option<String> {
+    if rows.len() <= limit as usize {
+        return None;
+    }
+    rows.truncate(limit as usize);
+    rows.last().map(|row| OrderCursor {
+        tanggal_booking: row.tanggal_booking,
+        waktu_booking: row.waktu_booking,
+        id: row.id,
+    }.encode())
+}
+
+// Admin endpoint: List ALL bookings (tanpa filter user_id), dengan pagination dan filter
 async fn list_all_bookings(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<OrderQuery>,
+) -> Result<RespJson<OrderListResponse>, (StatusCode, RespJson<serde_json::Value>)> {
+    // Hanya admin / staff yang boleh melihat order semua user; staff dibatasi ke cabangnya sendiri
+    user.require_staff()?;
+    let branch = match user.branch_scope()? {
+        Some(own_branch) => {
+            user.require_branch(Some(params.branch.as_deref().unwrap_or(own_branch)))?;
+            Some(own_branch.to_string())
+        }
+        None => params.branch.clone().filter(|b| !b.trim().is_empty()),
+    };
+
+    let status = match params.status.as_deref().filter(|s| !s.is_empty()) {
+        Some(status) => Some(
+            status.parse::<OrderStatus>()
+                .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?,
+        ),
+        None => None,
+    };
+
+    if let (Some(from), Some(to)) = (params.date_from, params.date_to) {
+        if from > to {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "date_from must not be after date_to"}))));
+        }
+    }
+
+    // Dengan cursor (?after=) halaman diambil lewat keyset, page diabaikan
+    let cursor = parse_order_cursor(params.after.as_deref())?;
+    let page = if cursor.is_some() { 1 } else { params.page.unwrap_or(1).max(1) };
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * limit;
+
+    println!("🔍 Admin: Fetching orders page {} (branch: {:?}, filters: {:?})", page, branch, params);
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    // Semua filter opsional: NULL berarti tidak difilter
+    const FILTERS: &str = "o.archived_at IS NULL
+           AND ($1::text IS NULL OR LOWER(TRIM(o.pilih_cabang)) = LOWER(TRIM($1)))
+           AND ($2::text IS NULL OR o.status = $2)
+           AND ($3::int IS NULL OR o.motor_id = $3)
+           AND ($4::date IS NULL OR o.tanggal_pengembalian >= $4)
+           AND ($5::date IS NULL OR o.tanggal_peminjaman <= $5)";
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM orders o WHERE {}", FILTERS))
+        .bind(&branch)
+        .bind(status.map(|s| s.as_str()))
+        .bind(params.motor_id)
+        .bind(params.date_from)
+        .bind(params.date_to)
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let mut rows: Vec<AdminOrderRow> = sqlx::query_as(&format!(
+        "{} WHERE {} AND {} ORDER BY {} LIMIT $9 OFFSET $10",
+        ADMIN_ORDER_SELECT, FILTERS, order_keyset_filter(6), ORDER_LIST_ORDER_BY
+    ))
+    .bind(&branch)
+    .bind(status.map(|s| s.as_str()))
+    .bind(params.motor_id)
+    .bind(params.date_from)
+    .bind(params.date_to)
+    .bind(cursor.map(|c| c.tanggal_booking))
+    .bind(cursor.map(|c| c.waktu_booking))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    let next_cursor = next_order_cursor(&mut rows, limit);
+
+    println!("✅ Found {} of {} orders", rows.len(), total);
+
+    let orders = rows.into_iter().map(admin_order_response).collect();
+
+    Ok(RespJson(OrderListResponse { orders, total, page, limit, next_cursor }))
+}
+
+// Admin / staff: semua order milik satu customer (untuk support), dengan pagination dan filter status.
+// Staff hanya melihat order customer itu di cabangnya sendiri.
+async fn list_customer_bookings(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(customer_id): Path<String>,
+    Query(params): Query<OrderQuery>,
+) -> Result<RespJson<OrderListResponse>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    let branch = user.branch_scope()?.map(str::to_string);
+
+    let customer_uuid = Uuid::parse_str(&customer_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid user ID"}))))?;
+
+    let status = match params.status.as_deref().filter(|s| !s.is_empty()) {
+        Some(status) => Some(
+            status.parse::<OrderStatus>()
+                .map_err(|e| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": e}))))?,
+        ),
+        None => None,
+    };
+
+    let cursor = parse_order_cursor(params.after.as_deref())?;
+    let page = if cursor.is_some() { 1 } else { params.page.unwrap_or(1).max(1) };
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * limit;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+    };
+
+    let exists: Option<(Uuid,)> = sqlx::query_as("SELECT id FROM users WHERE id = $1")
+        .bind(customer_uuid)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+    if exists.is_none() {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "User not found"}))));
+    }
+
+    println!("🔍 Admin: Fetching orders of user {} page {} (branch: {:?})", customer_uuid, page, branch);
+
+    const FILTERS: &str = "o.user_id = $1 AND o.archived_at IS NULL
+           AND ($2::text IS NULL OR LOWER(TRIM(o.pilih_cabang)) = LOWER(TRIM($2)))
+           AND ($3::text IS NULL OR o.status = $3)";
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM orders o WHERE {}", FILTERS))
+        .bind(customer_uuid)
+        .bind(&branch)
+        .bind(status.map(|s| s.as_str()))
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let mut rows: Vec<AdminOrderRow> = sqlx::query_as(&format!(
+        "{} WHERE {} AND {} ORDER BY {} LIMIT $7 OFFSET $8",
+        ADMIN_ORDER_SELECT, FILTERS, order_keyset_filter(4), ORDER_LIST_ORDER_BY
+    ))
+    .bind(customer_uuid)
+    .bind(&branch)
+    .bind(status.map(|s| s.as_str()))
+    .bind(cursor.map(|c| c.tanggal_booking))
+    .bind(cursor.map(|c| c.waktu_booking))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+    let next_cursor = next_order_cursor(&mut rows, limit);
+
+    let orders = rows.into_iter().map(admin_order_response).collect();
+
+    Ok(RespJson(OrderListResponse { orders, total, page, limit, next_cursor }))
+}
+
+// Baris export CSV order
+#[derive(sqlx::FromRow)]
+struct OrderExportRow {
+    order_number: String,
+    status: String,
+    full_name: String,
+    email: String,
+    phone: String,
+    pilih_motor: String,
+    pilih_cabang: String,
+    rental_start: NaiveDateTime,
+    rental_end: NaiveDateTime,
+    rental_days: Option<i32>,
+    total_price: Option<i64>,
+    deposit_amount: i32,
+    booked_at: NaiveDateTime,
+}
+
+const ORDER_EXPORT_HEADER: &str =
+    "order_number,status,customer_name,customer_email,customer_phone,motor,branch,pickup,return,rental_days,total_price,deposit_amount,booked_at\r\n";
+
+// Escape satu kolom CSV. Nilai yang diawali =, +, -, @, tab atau carriage return diberi prefix '
+// supaya tidak dijalankan sebagai formula oleh spreadsheet.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn order_export_line(row: &OrderExportRow) -> String {
+    let fields = [
+        csv_field(&row.order_number),
+        csv_field(&row.status),
+        csv_field(&row.full_name),
+        csv_field(&row.email),
+        csv_field(&row.phone),
+        csv_field(&row.pilih_motor),
+        csv_field(&row.pilih_cabang),
+        row.rental_start.format("%Y-%m-%d %H:%M").to_string(),
+        row.rental_end.format("%Y-%m-%d %H:%M").to_string(),
+        row.rental_days.map(|d| d.to_string()).unwrap_or_default(),
+        row.total_price.map(|p| p.to_string()).unwrap_or_default(),
+        row.deposit_amount.to_string(),
+        row.booked_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+    ];
+    format!("{}\r\n", fields.join(","))
+}
+
+// Export order ke CSV untuk rekap di spreadsheet (admin saja). Baris di-stream langsung dari
+// database supaya export rentang panjang tidak perlu dimuat ke memori sekaligus.
+async fn export_orders_csv(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<OrderExportQuery>,
+) -> Result<Response, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from > to {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "from must not be after to"}))));
+        }
+    }
+    let branch = params.branch.filter(|b| !b.trim().is_empty());
+
+    println!("📤 Exporting orders CSV (from: {:?}, to: {:?}, branch: {:?})", params.from, params.to, branch);
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, sqlx::Error>>(64);
+    let (from, to) = (params.from, params.to);
+    tokio::spawn(async move {
+        if tx.send(Ok(ORDER_EXPORT_HEADER.to_string())).await.is_err() {
+            return;
+        }
+
+        let mut rows = sqlx::query_as::<_, OrderExportRow>(
+            "SELECT o.order_number, o.status,
+                    COALESCE(g.full_name, u.full_name) AS full_name, COALESCE(g.email, u.email) AS email,
+                    COALESCE(g.phone, u.phone) AS phone, o.pilih_motor, o.pilih_cabang,
+                    o.tanggal_peminjaman + o.jam_peminjaman AS rental_start,
+                    o.tanggal_pengembalian + o.jam_pengembalian AS rental_end,
+                    o.rental_days, o.total_price, o.deposit_amount,
+                    o.tanggal_booking + o.waktu_booking AS booked_at
+             FROM orders o
+             JOIN users u ON u.id = o.user_id
+             LEFT JOIN guest_checkouts g ON g.guest_user_id = u.id
+             WHERE ($1::date IS NULL OR o.tanggal_peminjaman >= $1)
+               AND ($2::date IS NULL OR o.tanggal_peminjaman <= $2)
+               AND ($3::text IS NULL OR LOWER(TRIM(o.pilih_cabang)) = LOWER(TRIM($3)))
+             ORDER BY o.tanggal_peminjaman, o.jam_peminjaman"
+        )
+        .bind(from)
+        .bind(to)
+        .bind(branch)
+        .fetch(&pool);
+
+        loop {
+            let chunk = match rows.try_next().await {
+                Ok(Some(row)) => Ok(order_export_line(&row)),
+                Ok(None) => break,
+                Err(e) => {
+                    println!("❌ Orders CSV export failed: {}", e);
+                    Err(e)
+                }
+            };
+            let failed = chunk.is_err();
+            // Client menutup koneksi: hentikan query
+            if tx.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let body = Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }));
+
+    let filename = format!("orders-{}.csv", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    ).into_response())
+}
+
+// Cari order berdasarkan kode booking, nama, nomor HP, atau email customer (termasuk data tamu).
+// Untuk admin dan staff cabang, misalnya mencari booking customer walk-in.
+async fn search_bookings(
+    user: AuthUser,
     Extension(pool): Extension<PgPool>,
+    Query(params): Query<OrderSearchQuery>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
-    println!("🔍 Admin: Fetching all orders");
+    user.require_staff()?;
+    let branch_scope = user.branch_scope()?;
 
-    let rows = sqlx::query!(
-        "SELECT o.id, o.user_id, u.username, o.tanggal_peminjaman, o.jam_peminjaman, o.alamat_pengantaran, o.tanggal_pengembalian, o.jam_pengembalian, o.alamat_pengembalian, o.pilih_cabang, o.pilih_motor, o.motor_price, o.status, o.tanggal_booking, o.waktu_booking FROM orders o JOIN users u ON o.user_id = u.id ORDER BY o.tanggal_booking DESC, o.waktu_booking DESC"
-    )
+    let q = params.q.trim();
+    if q.chars().count() < 3 {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Search query must be at least 3 characters"
+        }))));
+    }
+
+    let pattern = format!("%{}%", q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+    let phone = normalize_phone(q);
+
+    println!("🔎 Searching orders for {:?} (branch: {:?})", q, branch_scope);
+
+    let rows: Vec<AdminOrderRow> = sqlx::query_as(&format!(
+        "{}
+         LEFT JOIN guest_checkouts g ON g.guest_user_id = u.id
+         WHERE o.archived_at IS NULL
+           AND ($1::text IS NULL OR LOWER(TRIM(o.pilih_cabang)) = LOWER(TRIM($1)))
+           AND (
+                o.order_number ILIKE $2
+             OR u.full_name ILIKE $2
+             OR u.email ILIKE $2
+             OR g.full_name ILIKE $2
+             OR g.email ILIKE $2
+             OR ($3::text IS NOT NULL AND (
+                    regexp_replace(regexp_replace(u.phone, '[^0-9]', '', 'g'), '^0', '62') = $3
+                 OR g.phone = $3))
+           )
+         ORDER BY o.tanggal_booking DESC, o.waktu_booking DESC
+         LIMIT 50",
+        ADMIN_ORDER_SELECT
+    ))
+    .bind(branch_scope)
+    .bind(&pattern)
+    .bind(phone)
     .fetch_all(&pool)
     .await
     .map_err(|e| {
         println!("❌ Database error: {}", e);
         (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
     })?;
-    
-    println!("✅ Found {} total orders", rows.len());
-    
-    let bookings: Vec<serde_json::Value> = rows.into_iter().map(|row| {
-        serde_json::json!({
-            "id": row.id,
-            "user_id": row.user_id,
-            "username": row.username,  // Include username for admin
-            "bookingId": format!("BWK{}", row.id.to_string().chars().take(6).collect::<String>()),
-            "tanggalPeminjaman": row.tanggal_peminjaman,
-            "jamPeminjaman": row.jam_peminjaman,
-            "alamatPengantaran": row.alamat_pengantaran,
-            "tanggalPengembalian": row.tanggal_pengembalian,
-            "jamPengembalian": row.jam_pengembalian,
-            "alamatPengembalian": row.alamat_pengembalian,
-            "pilihCabang": row.pilih_cabang,
-            "pilihMotor": row.pilih_motor,
-            "motorPrice": row.motor_price,
-            "status": row.status,
-            "tanggalBooking": row.tanggal_booking,
-            "waktuBooking": row.waktu_booking
-        })
-    }).collect();
+
+    let orders: Vec<OrderResponse> = rows.into_iter().map(admin_order_response).collect();
 
     Ok(RespJson(serde_json::json!({
         "success": true,
-        "data": bookings,
-        "total": bookings.len(),
-        "type": "admin_view"
+        "data": orders,
+        "total": orders.len()
     })))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    use crate::auth::scope::default_scopes;
+    use crate::auth::session::create_session;
+    use crate::auth::token::create_access_token;
+
+    // Test yang butuh database memakai TEST_DATABASE_URL (schema dari folder database/ sudah dijalankan):
+    // `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`
+    async fn test_pool() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        PgPool::connect(&url).await.expect("failed to connect to TEST_DATABASE_URL")
+    }
+
+    // Buat user dengan role tertentu beserta sesi aktif, lalu terbitkan access token untuknya
+    async fn token_for(pool: &PgPool, role: Role, branch: Option<&str>) -> String {
+        let id = Uuid::new_v4();
+        let username = format!("test-{}", id.simple());
+
+        // users.branch_id harus menunjuk ke cabang yang ada (users_branch_id_fkey)
+        if let Some(branch) = branch {
+            sqlx::query("INSERT INTO branches (name, address) VALUES ($1, 'Alamat test') ON CONFLICT (name) DO NOTHING")
+                .bind(branch)
+                .execute(pool)
+                .await
+                .expect("failed to insert test branch");
+        }
+
+        sqlx::query(
+            "INSERT INTO users (id, full_name, username, email, phone, password_hash, role, branch_id)
+             VALUES ($1, $2, $3, $4, '', 'test', $5, $6)"
+        )
+        .bind(id)
+        .bind(&username)
+        .bind(&username)
+        .bind(format!("{}@test.local", username))
+        .bind(role.as_str())
+        .bind(branch)
+        .execute(pool)
+        .await
+        .expect("failed to insert test user");
+
+        let session_id = create_session(pool, id, Some("order-router-test"), None)
+            .await
+            .expect("failed to create test session");
+        create_access_token(id, session_id, default_scopes(role)).expect("failed to create test token")
+    }
+
+    async fn get_all_orders(pool: PgPool, token: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/api/orders/all");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+
+        order_router()
+            .layer(Extension(pool))
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn list_all_orders_without_token_is_unauthorized() {
+        // Ditolak sebelum query apa pun, jadi pool tidak perlu benar-benar terhubung
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        assert_eq!(get_all_orders(pool, None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_all_orders_as_customer_is_forbidden() {
+        let pool = test_pool().await;
+        let token = token_for(&pool, Role::Customer, None).await;
+        assert_eq!(get_all_orders(pool, Some(&token)).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_all_orders_as_staff_is_ok() {
+        let pool = test_pool().await;
+        let token = token_for(&pool, Role::Staff, Some("Cabang Test")).await;
+        assert_eq!(get_all_orders(pool, Some(&token)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_all_orders_as_admin_is_ok() {
+        let pool = test_pool().await;
+        let token = token_for(&pool, Role::Admin, None).await;
+        assert_eq!(get_all_orders(pool, Some(&token)).await, StatusCode::OK);
+    }
+
+    // Baris export contoh untuk test CSV (tanpa database)
+    fn export_row(full_name: &str, pilih_motor: &str) -> OrderExportRow {
+        let at = |day, hour| chrono::NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        OrderExportRow {
+            order_number: "ORD-0001".to_string(),
+            status: "confirmed".to_string(),
+            full_name: full_name.to_string(),
+            email: "budi@example.com".to_string(),
+            phone: "08123456789".to_string(),
+            pilih_motor: pilih_motor.to_string(),
+            pilih_cabang: "Cabang Test".to_string(),
+            rental_start: at(1, 9),
+            rental_end: at(3, 9),
+            rental_days: Some(2),
+            total_price: Some(150000),
+            deposit_amount: 500000,
+            booked_at: at(1, 8),
+        }
+    }
+
+    #[test]
+    fn csv_field_leaves_plain_values_untouched() {
+        assert_eq!(csv_field("Budi Santoso"), "Budi Santoso");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn csv_field_quotes_separators_and_newlines() {
+        assert_eq!(csv_field("Jl. Merdeka 1, Bandung"), "\"Jl. Merdeka 1, Bandung\"");
+        assert_eq!(csv_field("baris satu\nbaris dua"), "\"baris satu\nbaris dua\"");
+        assert_eq!(csv_field("baris satu\r\nbaris dua"), "\"baris satu\r\nbaris dua\"");
+    }
+
+    #[test]
+    fn csv_field_doubles_embedded_quotes() {
+        assert_eq!(csv_field("Honda \"Beat\""), "\"Honda \"\"Beat\"\"\"");
+    }
+
+    #[test]
+    fn csv_field_neutralizes_formula_prefixes() {
+        for (input, expected) in [
+            ("=SUM(A1:A9)", "'=SUM(A1:A9)"),
+            ("+62812", "'+62812"),
+            ("-1", "'-1"),
+            ("@cmd", "'@cmd"),
+            ("\t=1+1", "'\t=1+1"),
+            ("\r=1+1", "\"'\r=1+1\""),
+        ] {
+            assert_eq!(csv_field(input), expected, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn order_export_line_matches_header_columns() {
+        let line = order_export_line(&export_row("Budi Santoso", "Honda Beat"));
+        assert_eq!(
+            line,
+            "ORD-0001,confirmed,Budi Santoso,budi@example.com,08123456789,Honda Beat,Cabang Test,\
+             2026-03-01 09:00,2026-03-03 09:00,2,150000,500000,2026-03-01 08:00:00\r\n"
+        );
+        assert_eq!(line.trim_end().split(',').count(), ORDER_EXPORT_HEADER.trim_end().split(',').count());
+    }
+
+    #[test]
+    fn order_export_line_escapes_customer_fields() {
+        let line = order_export_line(&export_row("Budi \"Bud\", Santoso", "Honda\nBeat"));
+        assert!(line.starts_with("ORD-0001,confirmed,\"Budi \"\"Bud\"\", Santoso\",budi@example.com,"));
+        assert!(line.contains(",\"Honda\nBeat\",Cabang Test,"));
+        assert!(line.ends_with("\r\n"));
+    }
+
+    #[test]
+    fn order_export_line_leaves_missing_totals_empty() {
+        let mut row = export_row("Budi Santoso", "Honda Beat");
+        row.rental_days = None;
+        row.total_price = None;
+        assert!(order_export_line(&row).ends_with(",,,500000,2026-03-01 08:00:00\r\n"));
+    }
+}
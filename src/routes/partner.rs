@@ -0,0 +1,65 @@
+use axum::{
+    Router,
+    routing::get,
+    extract::{Extension, Query},
+    http::StatusCode,
+    middleware,
+    response::Json as RespJson,
+};
+use serde::Deserialize;
+use serde_json;
+use sqlx::PgPool;
+
+use crate::middleware::api_key::{require_api_key, require_scope};
+use crate::model::api_key::ApiClient;
+use crate::model::motor::Motor;
+
+// Query untuk listing motor partner
+#[derive(Debug, Deserialize)]
+pub struct PartnerMotorQuery {
+    pub motor_type: Option<String>,
+    pub branch: Option<String>,
+}
+
+// Create partner router (di-nest ke /api/partner, semua route butuh header X-Api-Key)
+pub fn partner_router() -> Router {
+    Router::new()
+        .route("/motors", get(list_available_motors))  // GET /api/partner/motors
+        .route_layer(middleware::from_fn(require_api_key))
+}
+
+// List motor yang tersedia untuk partner (scope motors:read)
+async fn list_available_motors(
+    Extension(pool): Extension<PgPool>,
+    Extension(client): Extension<ApiClient>,
+    Query(params): Query<PartnerMotorQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    require_scope(&client, "motors:read")?;
+
+    println!("🤝 Partner {} listing available motors: {:?}", client.name, params);
+
+    let motors: Vec<Motor> = sqlx::query_as(
+        "SELECT motor_id, motor_slug, motor_name, motor_type, price_per_day, price_per_week, price_per_month, description, image_url, image_thumbnails, available, status, branch, rating_avg, rating_count,
+                engine_cc, transmission, fuel_capacity, seat_height, year
+         FROM motors
+         WHERE available = TRUE AND deleted_at IS NULL
+           AND ($1::TEXT IS NULL OR motor_type = $1)
+           AND ($2::TEXT IS NULL OR branch = $2)
+         ORDER BY motor_id ASC"
+    )
+    .bind(&params.motor_type)
+    .bind(&params.branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("🚨 Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    Ok(RespJson(serde_json::json!({
+        "motors": motors,
+        "total": motors.len()
+    })))
+}
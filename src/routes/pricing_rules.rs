@@ -0,0 +1,232 @@
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use chrono::NaiveDate;
+use serde_json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::model::pricing_rule::{CreatePricingRuleRequest, PricingRule, PricingRuleKind, PricingRuleQuery, UpdatePricingRuleRequest};
+use crate::routes::branches::find_branch;
+
+const PRICING_RULE_COLUMNS: &str = "id, name, kind, starts_on, ends_on, branch, multiplier_percent, priority, active, created_at, updated_at";
+
+pub fn pricing_rule_router() -> Router {
+    Router::new()
+        .route("/api/pricing-rules", get(list_pricing_rules))           // Admin
+        .route("/api/pricing-rules", post(create_pricing_rule))         // Admin
+        .route("/api/pricing-rules/:id", put(update_pricing_rule))      // Admin
+        .route("/api/pricing-rules/:id", delete(delete_pricing_rule))   // Admin
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Aturan aktif yang mungkin berlaku untuk sewa di cabang `branch` pada tanggal from..=to.
+// Pemilihan aturan per hari ada di pricing::rule_for_date.
+pub(crate) async fn pricing_rules_for_period(
+    pool: &PgPool,
+    branch: Option<&str>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PricingRule>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {} FROM pricing_rules
+         WHERE active
+           AND (branch IS NULL OR LOWER(TRIM(branch)) = LOWER(TRIM($1)))
+           AND (starts_on IS NULL OR starts_on <= $3)
+           AND (ends_on IS NULL OR ends_on >= $2)",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(branch)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+// Aturan season wajib punya rentang tanggal; multiplier 1-1000% (di bawah 100 = diskon)
+fn validate_rule(
+    kind: &str,
+    starts_on: Option<NaiveDate>,
+    ends_on: Option<NaiveDate>,
+    multiplier_percent: i32,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    if kind == PricingRuleKind::Season.as_str() && (starts_on.is_none() || ends_on.is_none()) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Season rules require startsOn and endsOn"}))));
+    }
+    if let (Some(start), Some(end)) = (starts_on, ends_on) {
+        if end < start {
+            return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "endsOn cannot be before startsOn"}))));
+        }
+    }
+    if !(1..=1000).contains(&multiplier_percent) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "multiplierPercent must be between 1 and 1000"}))));
+    }
+    Ok(())
+}
+
+// Cabang aturan harus terdaftar; hasilnya nama sesuai penulisan di tabel branches
+async fn canonical_rule_branch(
+    pool: &PgPool,
+    branch: Option<&str>,
+) -> Result<Option<String>, (StatusCode, RespJson<serde_json::Value>)> {
+    let Some(name) = branch.map(str::trim).filter(|b| !b.is_empty()) else {
+        return Ok(None);
+    };
+    let branch = find_branch(pool, name).await.map_err(db_error)?
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": format!("Unknown branch: {}", name)}))))?;
+    Ok(Some(branch.name))
+}
+
+async fn list_pricing_rules(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<PricingRuleQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+    let branch = params.branch.as_deref().map(str::trim).filter(|b| !b.is_empty());
+
+    let rules: Vec<PricingRule> = sqlx::query_as(&format!(
+        "SELECT {} FROM pricing_rules
+         WHERE ($1 OR active)
+           AND ($2::text IS NULL OR LOWER(TRIM(branch)) = LOWER(TRIM($2)))
+         ORDER BY starts_on NULLS FIRST, priority DESC, name",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(params.include_inactive.unwrap_or(false))
+    .bind(branch)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": rules
+    })))
+}
+
+async fn create_pricing_rule(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreatePricingRuleRequest>,
+) -> Result<RespJson<PricingRule>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Rule name is required"}))));
+    }
+    validate_rule(payload.kind.as_str(), payload.starts_on, payload.ends_on, payload.multiplier_percent)?;
+    let branch = canonical_rule_branch(&pool, payload.branch.as_deref()).await?;
+
+    let rule: PricingRule = sqlx::query_as(&format!(
+        "INSERT INTO pricing_rules (id, name, kind, starts_on, ends_on, branch, multiplier_percent, priority, active)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING {}",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(name)
+    .bind(payload.kind.as_str())
+    .bind(payload.starts_on)
+    .bind(payload.ends_on)
+    .bind(&branch)
+    .bind(payload.multiplier_percent)
+    .bind(payload.priority.unwrap_or(0))
+    .bind(payload.active.unwrap_or(true))
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    println!("💹 Pricing rule {} ({}, {}%) created by {}", rule.name, rule.kind, rule.multiplier_percent, user.id);
+    Ok(RespJson(rule))
+}
+
+async fn update_pricing_rule(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdatePricingRuleRequest>,
+) -> Result<RespJson<PricingRule>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let current: PricingRule = sqlx::query_as(&format!("SELECT {} FROM pricing_rules WHERE id = $1", PRICING_RULE_COLUMNS))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Pricing rule not found"}))))?;
+
+    if payload.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Rule name cannot be empty"}))));
+    }
+    validate_rule(
+        &current.kind,
+        payload.starts_on.or(current.starts_on),
+        payload.ends_on.or(current.ends_on),
+        payload.multiplier_percent.unwrap_or(current.multiplier_percent),
+    )?;
+    let branch = canonical_rule_branch(&pool, payload.branch.as_deref()).await?;
+
+    let rule: PricingRule = sqlx::query_as(&format!(
+        "UPDATE pricing_rules
+         SET name = COALESCE($2, name),
+             starts_on = COALESCE($3, starts_on),
+             ends_on = COALESCE($4, ends_on),
+             branch = COALESCE($5, branch),
+             multiplier_percent = COALESCE($6, multiplier_percent),
+             priority = COALESCE($7, priority),
+             active = COALESCE($8, active),
+             updated_at = NOW()
+         WHERE id = $1
+         RETURNING {}",
+        PRICING_RULE_COLUMNS
+    ))
+    .bind(id)
+    .bind(payload.name.as_deref().map(str::trim))
+    .bind(payload.starts_on)
+    .bind(payload.ends_on)
+    .bind(&branch)
+    .bind(payload.multiplier_percent)
+    .bind(payload.priority)
+    .bind(payload.active)
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    println!("💹 Pricing rule {} updated by {}", rule.name, user.id);
+    Ok(RespJson(rule))
+}
+
+// Order lama tidak terpengaruh: rincian harganya sudah tersimpan di order
+async fn delete_pricing_rule(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let result = sqlx::query("DELETE FROM pricing_rules WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Pricing rule not found"}))));
+    }
+
+    println!("💹 Pricing rule {} deleted by {}", id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Aturan harga dihapus"
+    })))
+}
@@ -1,16 +1,20 @@
 use axum::{
     Router,
     routing::{get, post, put, delete},
-    extract::{Extension, Json, Path},
-    http::{StatusCode, HeaderMap},
+    extract::{ConnectInfo, Extension, Json, Path},
+    http::{HeaderMap, StatusCode},
     response::Json as RespJson,
 };
 use serde_json;
 use sqlx::PgPool;
+use std::net::SocketAddr;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use crate::auth::AuthUser;
+use crate::auth::audit::{self, client_ip, AuthEvent};
 use crate::model::profils::{CreateProfilRequest, UpdateProfilRequest, ProfilResponse};
+use crate::routes::users::anonymize_account;
 
 // Helper struct for query results - simplified to match profil needs
 #[derive(Debug)]
@@ -22,42 +26,6 @@ struct UserRow {
     pub created_at: Option<DateTime<Utc>>,
 }
 
-// Helper function untuk ambil user dari token
-async fn get_user_from_token(headers: &HeaderMap, pool: &PgPool) -> Result<Uuid, StatusCode> {
-    // Ambil token dari header Authorization
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|header| header.to_str().ok())
-        .and_then(|header| {
-            if header.starts_with("Bearer ") {
-                Some(&header[7..])
-            } else {
-                None
-            }
-        })
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    // Parse dummy token format: "dummy_token_for_{user_id}"
-    let user_id_str = auth_header.strip_prefix("dummy_token_for_")
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-    
-    let user_id = Uuid::parse_str(user_id_str)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    // Verify user exists in database
-    let exists = sqlx::query!("SELECT id FROM users WHERE id = $1", user_id)
-        .fetch_optional(pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .is_some();
-
-    if !exists {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    Ok(user_id)
-}
-
 // Create profils router
 pub fn profils_router() -> Router {
     Router::new()
@@ -90,79 +58,43 @@ async fn test_endpoint() -> RespJson<serde_json::Value> {
     }))
 }
 
-// Create new profil
+// Create new profil: mengisi data profil akun yang sedang login. Akun baru dibuat lewat
+// POST /api/auth/register (validasi, captcha, rate limit, dan password policy berlaku di sana).
 async fn create_profil(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
     Json(request): Json<CreateProfilRequest>,
 ) -> Result<RespJson<ProfilResponse>, (StatusCode, RespJson<serde_json::Value>)> {
-    println!("🔧 Creating new profil: {:?}", request);
-
-    // Prioritas user_id: 1. Dari request body, 2. Dari token, 3. Generate baru
-    let user_id = if let Some(req_user_id) = request.user_id {
-        // Konversi i32 ke Uuid (anggap user_id adalah integer di frontend)
-        // Untuk sementara kita buat UUID baru jika user_id dari frontend
-        println!("📝 Using user_id from request: {}", req_user_id);
-        get_user_from_token(&headers, &pool).await.unwrap_or_else(|_| Uuid::new_v4())
-    } else {
-        // Fallback ke token authentication
-        get_user_from_token(&headers, &pool).await.unwrap_or_else(|_| Uuid::new_v4())
-    };
+    println!("🔧 Saving profil for user {}", user.id);
 
-    println!("🔑 Using user_id: {}", user_id);
-
-    // Check if user already exists, if not create new one
-    let existing_user = sqlx::query!("SELECT id FROM users WHERE id = $1", user_id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            println!("❌ Database error: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
-                "error": "Database error"
-            })))
-        })?;
-
-    let result = if existing_user.is_some() {
-        // Update existing user - hanya data profil
-        sqlx::query_as!(
-            UserRow,
-            "UPDATE users SET full_name = $2, email = $3, phone = $4 
-             WHERE id = $1 
-             RETURNING id, full_name, email, phone, created_at",
-            user_id,
-            request.nama,
-            request.email,
-            request.no_hp
-        )
-        .fetch_one(&pool)
-        .await
-    } else {
-        // Insert new user - generate username otomatis untuk keperluan sistem
-        let username = request.nama.to_lowercase().replace(" ", "");
-        let default_password = "password123";
-        
-        sqlx::query_as!(
-            UserRow,
-            "INSERT INTO users (id, full_name, username, email, phone, password_hash, created_at) 
-             VALUES ($1, $2, $3, $4, $5, $6, NOW()) 
-             RETURNING id, full_name, email, phone, created_at",
-            user_id,
-            request.nama,
-            username,
-            request.email,
-            request.no_hp,
-            default_password
-        )
-        .fetch_one(&pool)
-        .await
-    };
+    if let Some(req_user_id) = request.user_id {
+        // user_id integer dari frontend tidak dipakai (id user berupa UUID)
+        println!("📝 Ignoring user_id from request: {}", req_user_id);
+    }
+
+    // Hanya data profil user yang login yang diubah
+    let result = sqlx::query_as!(
+        UserRow,
+        "UPDATE users SET full_name = $2, email = $3, phone = $4 
+         WHERE id = $1 AND deleted_at IS NULL
+         RETURNING id, full_name, email, phone, created_at",
+        user.id,
+        request.nama,
+        request.email,
+        request.no_hp
+    )
+    .fetch_optional(&pool)
+    .await;
 
     let user = result.map_err(|e| {
         println!("❌ Database operation failed: {}", e);
         (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
             "error": "Failed to save profil"
         })))
-    })?;
+    })?
+    .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+        "error": "User not found"
+    }))))?;
 
     let response = ProfilResponse {
         id: user.id.to_string(),
@@ -174,23 +106,18 @@ async fn create_profil(
         updated_at: user.created_at.unwrap_or_else(|| Utc::now()).format("%Y-%m-%d %H:%M:%S").to_string(),
     };
 
-    println!("✅ Profil created/updated successfully");
+    println!("✅ Profil saved successfully");
     Ok(RespJson(response))
 }
 
 // Get profil user yang sedang login dari tabel users
 async fn get_my_profil(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
 ) -> Result<RespJson<ProfilResponse>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Getting my profil from users table");
 
-    // Ambil user ID dari token
-    let current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
+    let current_user_id = user.id;
 
     println!("🔑 Current user ID: {}", current_user_id);
 
@@ -235,18 +162,11 @@ async fn get_my_profil(
 // Get profil by user ID
 async fn get_profil_by_user_id(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
     Path(user_id): Path<String>,
 ) -> Result<RespJson<ProfilResponse>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Getting profil for user ID: {}", user_id);
 
-    // Verify user authentication
-    let _current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
-
     // Handle special case for default-id or invalid UUIDs
     if user_id == "default-id" || user_id.is_empty() {
         println!("❌ Invalid user ID: {}", user_id);
@@ -261,6 +181,7 @@ async fn get_profil_by_user_id(
             "error": format!("Invalid user ID format: {}", e)
         })))
     })?;
+    user.require_self_or_admin(user_uuid)?;
 
     let result = sqlx::query!(
         "SELECT id, full_name, email, phone, created_at FROM users WHERE id = $1",
@@ -302,18 +223,11 @@ async fn get_profil_by_user_id(
 // Get profil by ID
 async fn get_profil(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
     Path(id): Path<String>,
 ) -> Result<RespJson<ProfilResponse>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Getting profil with ID: {}", id);
 
-    // Verify user authentication
-    let _current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
-
     // Handle special case for default-id or invalid UUIDs
     if id == "default-id" || id.is_empty() {
         println!("❌ Invalid profil ID: {}", id);
@@ -328,6 +242,7 @@ async fn get_profil(
             "error": format!("Invalid profil ID format: {}", e)
         })))
     })?;
+    user.require_self_or_admin(user_id)?;
 
     let result = sqlx::query!(
         "SELECT id, full_name, email, phone, created_at FROM users WHERE id = $1",
@@ -369,24 +284,18 @@ async fn get_profil(
 // Update profil
 async fn update_profil(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
     Path(id): Path<String>,
     Json(request): Json<UpdateProfilRequest>,
 ) -> Result<RespJson<ProfilResponse>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Updating profil with ID: {}", id);
 
-    // Verify user authentication
-    let _current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
-
     let user_id = Uuid::parse_str(&id).map_err(|_| {
         (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
             "error": "Invalid ID format"
         })))
     })?;
+    user.require_self_or_admin(user_id)?;
 
     // Get current user data
     let current_user = sqlx::query!(
@@ -446,44 +355,44 @@ async fn update_profil(
     Ok(RespJson(response))
 }
 
-// Delete profil
+// Delete profil: akun dianonimkan seperti DELETE /api/users/me (bukan dihapus permanen) supaya
+// order milik user tetap utuh
 async fn delete_profil(
     Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Deleting profil with ID: {}", id);
 
-    // Verify user authentication
-    let _current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
-
     let user_id = Uuid::parse_str(&id).map_err(|_| {
         (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
             "error": "Invalid ID format"
         })))
     })?;
+    user.require_self_or_admin(user_id)?;
 
-    let result = sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
-        .execute(&pool)
-        .await
-        .map_err(|e| {
-            println!("❌ Database delete failed: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
-                "error": "Failed to delete profil"
-            })))
-        })?;
-
-    if result.rows_affected() == 0 {
-        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
-            "error": "Profil not found"
+    if user.impersonated_by.is_some() {
+        return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Account deletion is not allowed while impersonating"
         }))));
     }
 
-    println!("✅ Profil deleted successfully");
+    let ip = client_ip(&headers, &addr);
+    anonymize_account(&pool, user_id, ip).await?;
+
+    audit::record(
+        &pool,
+        AuthEvent::AccountDeleted,
+        Some(user_id),
+        Some(ip),
+        &headers,
+        serde_json::json!({ "deleted_by": user.id }),
+    )
+    .await;
+
+    println!("✅ Profil {} anonymized by {}", user_id, user.id);
     Ok(RespJson(serde_json::json!({
         "message": "Profil deleted successfully"
     })))
@@ -492,16 +401,12 @@ async fn delete_profil(
 // List all profils (admin function)
 async fn list_profils(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
 ) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Getting list of profils");
 
-    // Verify user authentication
-    let _current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
+    // Hanya admin / staff yang boleh melihat daftar semua profil
+    user.require_staff()?;
 
     let results = sqlx::query!(
         "SELECT id, full_name, email, phone, created_at FROM users ORDER BY created_at DESC LIMIT 50"
@@ -0,0 +1,244 @@
+use axum::{
+    Router,
+    routing::{get, patch},
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use serde_json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::config;
+use crate::model::motor::MotorReview;
+use crate::model::review::{CreateMotorReviewRequest, ModerateReviewRequest, ModeratedReview, ReviewModerationQuery, ReviewQuery, ReviewStatus};
+
+const MODERATED_REVIEW_COLUMNS: &str = "r.order_id, r.motor_id, m.motor_name, r.user_id, u.username AS reviewer, r.rating, r.comment,
+    r.status, r.moderation_note, r.moderated_by, r.moderated_at, r.created_at";
+
+pub fn review_router() -> Router {
+    Router::new()
+        .route("/api/motors/:id/reviews", get(list_motor_reviews).post(create_motor_review)) // Publik / customer
+        .route("/api/motors/reviews", get(list_reviews_for_moderation))                     // Admin
+        .route("/api/motors/reviews/:order_id", patch(moderate_review))                      // Admin
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Rating 1-5 dan komentar maksimal 1000 karakter; hasilnya komentar yang sudah di-trim (kosong = None)
+pub(crate) fn validate_review(rating: i32, comment: Option<&str>) -> Result<Option<&str>, (StatusCode, RespJson<serde_json::Value>)> {
+    if !(1..=5).contains(&rating) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Rating must be between 1 and 5"}))));
+    }
+    let comment = comment.map(str::trim).filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > 1000) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Comment must be at most 1000 characters"}))));
+    }
+    Ok(comment)
+}
+
+// Status ulasan baru: langsung tampil, atau menunggu moderasi jika REVIEW_AUTO_APPROVE=false
+pub(crate) fn new_review_status() -> ReviewStatus {
+    if config::get().review_auto_approve {
+        ReviewStatus::Approved
+    } else {
+        ReviewStatus::Pending
+    }
+}
+
+fn page_params(page: Option<i64>, limit: Option<i64>) -> (i64, i64) {
+    (page.unwrap_or(1).max(1), limit.unwrap_or(10).clamp(1, 50))
+}
+
+// Ulasan motor yang sudah disetujui, terbaru dulu
+async fn list_motor_reviews(
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+    Query(params): Query<ReviewQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let (page, limit) = page_params(params.page, params.limit);
+
+    let motor: Option<(Option<f64>, i32)> = sqlx::query_as("SELECT rating_avg, rating_count FROM motors WHERE motor_id = $1 AND deleted_at IS NULL")
+        .bind(motor_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+    let (rating_average, review_count) = motor
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Motor not found"}))))?;
+
+    let reviews: Vec<MotorReview> = sqlx::query_as(
+        "SELECT r.rating, r.comment, u.username AS reviewer, r.created_at
+         FROM order_reviews r JOIN users u ON u.id = r.user_id
+         WHERE r.motor_id = $1 AND r.status = 'approved'
+         ORDER BY r.created_at DESC
+         LIMIT $2 OFFSET $3"
+    )
+    .bind(motor_id)
+    .bind(limit)
+    .bind((page - 1) * limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "motorId": motor_id,
+        "ratingAverage": rating_average,
+        "reviewCount": review_count,
+        "reviews": reviews,
+        "total": review_count,
+        "page": page,
+        "limit": limit
+    })))
+}
+
+// Ulasan hanya dari customer yang sudah menyelesaikan sewa motor ini; satu ulasan per order
+async fn create_motor_review(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+    Json(payload): Json<CreateMotorReviewRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let comment = validate_review(payload.rating, payload.comment.as_deref())?;
+
+    // Order selesai yang belum diulas didahulukan
+    let order: Option<(Uuid, bool)> = sqlx::query_as(
+        "SELECT o.id, EXISTS (SELECT 1 FROM order_reviews r WHERE r.order_id = o.id) AS reviewed
+         FROM orders o
+         WHERE o.user_id = $1 AND o.status = 'completed'
+           AND (o.motor_id = $2 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.motor_id = $2))
+           AND ($3::uuid IS NULL OR o.id = $3)
+         ORDER BY reviewed, o.tanggal_pengembalian DESC, o.jam_pengembalian DESC
+         LIMIT 1"
+    )
+    .bind(user.id)
+    .bind(motor_id)
+    .bind(payload.order_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let order_id = match order {
+        None => {
+            return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+                "error": "Only customers who completed a rental of this motor can review it"
+            }))));
+        }
+        Some((_, true)) => {
+            return Err((StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "This booking has already been reviewed"}))));
+        }
+        Some((order_id, false)) => order_id,
+    };
+
+    let status = new_review_status();
+    sqlx::query(
+        "INSERT INTO order_reviews (order_id, motor_id, user_id, rating, comment, status) VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+    .bind(order_id)
+    .bind(motor_id)
+    .bind(user.id)
+    .bind(payload.rating)
+    .bind(comment)
+    .bind(status.as_str())
+    .execute(&pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "This booking has already been reviewed"})))
+        }
+        _ => db_error(e),
+    })?;
+
+    println!("⭐ Review {} for motor #{} (order {}) by {}", payload.rating, motor_id, order_id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "message": "Terima kasih atas ulasannya",
+        "review": {"orderId": order_id, "rating": payload.rating, "comment": comment, "status": status.as_str()}
+    })))
+}
+
+// Antrian moderasi: default ulasan yang menunggu persetujuan
+async fn list_reviews_for_moderation(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<ReviewModerationQuery>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+    let (page, limit) = page_params(params.page, params.limit);
+    let status = params.status.unwrap_or(ReviewStatus::Pending);
+
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM order_reviews WHERE status = $1")
+        .bind(status.as_str())
+        .fetch_one(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let reviews: Vec<ModeratedReview> = sqlx::query_as(&format!(
+        "SELECT {} FROM order_reviews r
+         JOIN users u ON u.id = r.user_id
+         LEFT JOIN motors m ON m.motor_id = r.motor_id
+         WHERE r.status = $1
+         ORDER BY r.created_at DESC
+         LIMIT $2 OFFSET $3",
+        MODERATED_REVIEW_COLUMNS
+    ))
+    .bind(status.as_str())
+    .bind(limit)
+    .bind((page - 1) * limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": reviews,
+        "total": total,
+        "page": page,
+        "limit": limit
+    })))
+}
+
+// Setujui / sembunyikan ulasan. Rating motor dihitung ulang oleh trigger di order_reviews.
+async fn moderate_review(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(order_id): Path<String>,
+    Json(payload): Json<ModerateReviewRequest>,
+) -> Result<RespJson<ModeratedReview>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let order_id = Uuid::parse_str(&order_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Invalid review ID"}))))?;
+    if payload.status == ReviewStatus::Pending {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "Status must be approved or hidden"}))));
+    }
+    let note = payload.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    let review: Option<ModeratedReview> = sqlx::query_as(&format!(
+        "WITH r AS (
+             UPDATE order_reviews
+             SET status = $2, moderation_note = $3, moderated_by = $4, moderated_at = NOW()
+             WHERE order_id = $1
+             RETURNING *
+         )
+         SELECT {} FROM r
+         JOIN users u ON u.id = r.user_id
+         LEFT JOIN motors m ON m.motor_id = r.motor_id",
+        MODERATED_REVIEW_COLUMNS
+    ))
+    .bind(order_id)
+    .bind(payload.status.as_str())
+    .bind(note)
+    .bind(user.id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let review = review.ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Review not found"}))))?;
+
+    println!("🛡️ Review for order {} set to {} by {}", order_id, review.status, user.id);
+    Ok(RespJson(review))
+}
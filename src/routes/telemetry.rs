@@ -0,0 +1,163 @@
+use axum::{
+    Router,
+    routing::post,
+    extract::{Extension, Json},
+    http::StatusCode,
+    middleware,
+    response::Json as RespJson,
+};
+use chrono::{Duration, Utc};
+use serde_json;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::fleet::FleetFeed;
+use crate::mailer::SharedMailer;
+use crate::middleware::api_key::{require_api_key, require_scope};
+use crate::model::api_key::ApiClient;
+use crate::model::telemetry::{TelemetryBatchRequest, TelemetryPoint, TelemetryRejection};
+use crate::routes::fleet::fleet_positions;
+use crate::routes::geofences::check_geofences;
+
+// Maksimal titik per request; tracker yang sempat offline mengirim sisanya di batch berikutnya
+const MAX_TELEMETRY_POINTS: usize = 1000;
+
+// Titik lebih tua dari ini dianggap basi (tracker offline terlalu lama / jam perangkat salah)
+const MAX_TELEMETRY_AGE_DAYS: i64 = 30;
+
+// Toleransi jam perangkat yang sedikit lebih cepat dari server
+const MAX_CLOCK_SKEW_SECS: i64 = 5 * 60;
+
+// Route untuk perangkat GPS tracker (header X-Api-Key dengan scope telemetry:write)
+pub fn telemetry_router() -> Router {
+    Router::new()
+        .route("/api/telemetry", post(ingest_telemetry))
+        .route_layer(middleware::from_fn(require_api_key))
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+fn validate_point(point: &TelemetryPoint) -> Result<(), &'static str> {
+    let now = Utc::now();
+    let valid_coordinates = point.latitude.is_finite() && (-90.0..=90.0).contains(&point.latitude)
+        && point.longitude.is_finite() && (-180.0..=180.0).contains(&point.longitude);
+    if !valid_coordinates {
+        return Err("Invalid coordinates");
+    }
+    if point.recorded_at > now + Duration::seconds(MAX_CLOCK_SKEW_SECS) {
+        return Err("recordedAt is in the future");
+    }
+    if point.recorded_at < now - Duration::days(MAX_TELEMETRY_AGE_DAYS) {
+        return Err("recordedAt is too old");
+    }
+    if point.speed_kmh.is_some_and(|speed| !(0.0..=400.0).contains(&speed)) {
+        return Err("speedKmh must be between 0 and 400");
+    }
+    if point.heading.is_some_and(|heading| !(0.0..=360.0).contains(&heading)) {
+        return Err("heading must be between 0 and 360");
+    }
+    if point.accuracy_m.is_some_and(|accuracy| accuracy < 0.0) {
+        return Err("accuracyM cannot be negative");
+    }
+    if point.battery_percent.is_some_and(|battery| !(0..=100).contains(&battery)) {
+        return Err("batteryPercent must be between 0 and 100");
+    }
+    Ok(())
+}
+
+// Terima batch titik GPS dari tracker. Titik yang tidak valid atau untuk unit yang tidak dikenal
+// ditolak per titik (dilaporkan di `rejected`), sisanya tetap disimpan. Titik yang sama
+// (unit + recordedAt) yang terkirim ulang diabaikan.
+async fn ingest_telemetry(
+    Extension(pool): Extension<PgPool>,
+    Extension(client): Extension<ApiClient>,
+    Extension(fleet): Extension<FleetFeed>,
+    Extension(mailer): Extension<SharedMailer>,
+    Json(payload): Json<TelemetryBatchRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    require_scope(&client, "telemetry:write")?;
+
+    if payload.points.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "points cannot be empty"}))));
+    }
+    if payload.points.len() > MAX_TELEMETRY_POINTS {
+        return Err((StatusCode::PAYLOAD_TOO_LARGE, RespJson(serde_json::json!({
+            "error": format!("A batch can contain at most {} points", MAX_TELEMETRY_POINTS)
+        }))));
+    }
+
+    // Unit harus terdaftar dan belum pensiun
+    let mut unit_ids: Vec<Uuid> = payload.points.iter().map(|p| p.unit_id).collect();
+    unit_ids.sort_unstable();
+    unit_ids.dedup();
+    let known_units: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM motor_units WHERE id = ANY($1) AND status <> 'retired'")
+        .bind(&unit_ids)
+        .fetch_all(&pool)
+        .await
+        .map_err(db_error)?;
+
+    let mut rejected = Vec::new();
+    let mut accepted: Vec<&TelemetryPoint> = Vec::with_capacity(payload.points.len());
+    for (index, point) in payload.points.iter().enumerate() {
+        let result = if known_units.contains(&point.unit_id) {
+            validate_point(point)
+        } else {
+            Err("Unknown motor unit")
+        };
+        match result {
+            Ok(()) => accepted.push(point),
+            Err(error) => rejected.push(TelemetryRejection { index, error: error.to_string() }),
+        }
+    }
+
+    let mut stored = 0;
+    if !accepted.is_empty() {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO unit_telemetry (unit_id, recorded_at, latitude, longitude, speed_kmh, heading, accuracy_m, battery_percent, api_key_id) "
+        );
+        query.push_values(&accepted, |mut row, point| {
+            row.push_bind(point.unit_id)
+                .push_bind(point.recorded_at)
+                .push_bind(point.latitude)
+                .push_bind(point.longitude)
+                .push_bind(point.speed_kmh)
+                .push_bind(point.heading)
+                .push_bind(point.accuracy_m)
+                .push_bind(point.battery_percent)
+                .push_bind(client.key_id);
+        });
+        query.push(" ON CONFLICT (unit_id, recorded_at) DO NOTHING");
+
+        stored = query.build().execute(&pool).await.map_err(db_error)?.rows_affected();
+    }
+
+    // Kabari peta armada live lalu cek geofence di background; gagal di sini tidak menggagalkan tracker
+    if stored > 0 {
+        let mut updated_units: Vec<Uuid> = accepted.iter().map(|p| p.unit_id).collect();
+        updated_units.sort_unstable();
+        updated_units.dedup();
+        match fleet_positions(&pool, Some(&updated_units), None).await {
+            Ok(units) => {
+                units.iter().cloned().for_each(|unit| fleet.publish(unit));
+                let task_pool = pool.clone();
+                tokio::spawn(async move { check_geofences(&task_pool, &mailer, &units).await });
+            }
+            Err(e) => println!("⚠️  Gagal mengirim posisi ke fleet live: {}", e),
+        }
+    }
+
+    println!(
+        "📡 Telemetry from {}: {} points, {} stored, {} rejected",
+        client.name, payload.points.len(), stored, rejected.len()
+    );
+    Ok(RespJson(serde_json::json!({
+        "success": rejected.is_empty(),
+        "received": payload.points.len(),
+        "stored": stored,
+        "duplicates": accepted.len() as u64 - stored,
+        "rejected": rejected
+    })))
+}
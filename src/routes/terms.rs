@@ -0,0 +1,147 @@
+use axum::{
+    Router,
+    routing::{get, post},
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::Json as RespJson,
+};
+use serde_json;
+use sqlx::PgPool;
+
+use crate::auth::AuthUser;
+use crate::model::terms::{CreateTermsVersionRequest, TermsVersion};
+
+const TERMS_COLUMNS: &str = "version, title, content, published_at, created_by";
+
+pub fn terms_router() -> Router {
+    Router::new()
+        .route("/api/terms", get(get_current_terms))         // Publik: versi yang berlaku
+        .route("/api/terms", post(create_terms_version))     // Admin
+        .route("/api/terms/versions", get(list_terms_versions)) // Admin / staff
+        .route("/api/terms/:version", get(get_terms_version)) // Publik: versi tertentu (misalnya yang disetujui di order)
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Versi syarat & ketentuan yang sedang berlaku (None = belum ada T&C, booking tidak perlu persetujuan)
+pub(crate) async fn current_terms(pool: &PgPool) -> Result<Option<TermsVersion>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {} FROM terms_versions WHERE published_at <= NOW() ORDER BY published_at DESC LIMIT 1",
+        TERMS_COLUMNS
+    ))
+    .fetch_optional(pool)
+    .await
+}
+
+// Versi T&C yang disetujui customer di payload booking ("termsVersion"). Jika ada T&C yang berlaku,
+// versinya wajib dikirim dan harus sama dengan versi terbaru.
+pub(crate) async fn accepted_terms_version(
+    pool: &PgPool,
+    payload: &serde_json::Value,
+) -> Result<Option<String>, (StatusCode, RespJson<serde_json::Value>)> {
+    let Some(current) = current_terms(pool).await.map_err(db_error)? else {
+        return Ok(None);
+    };
+
+    let accepted = payload.get("termsVersion").and_then(|v| v.as_str()).map(str::trim).filter(|v| !v.is_empty());
+    match accepted {
+        None => Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "You must accept the terms and conditions",
+            "code": "terms_required",
+            "currentVersion": current.version
+        })))),
+        Some(version) if version != current.version => Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+            "error": "The terms and conditions have been updated, please review and accept the latest version",
+            "code": "terms_outdated",
+            "currentVersion": current.version
+        })))),
+        Some(version) => Ok(Some(version.to_string())),
+    }
+}
+
+async fn get_current_terms(
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<TermsVersion>, (StatusCode, RespJson<serde_json::Value>)> {
+    current_terms(&pool)
+        .await
+        .map_err(db_error)?
+        .map(RespJson)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "No terms and conditions published"}))))
+}
+
+async fn get_terms_version(
+    Extension(pool): Extension<PgPool>,
+    Path(version): Path<String>,
+) -> Result<RespJson<TermsVersion>, (StatusCode, RespJson<serde_json::Value>)> {
+    let terms: Option<TermsVersion> = sqlx::query_as(&format!("SELECT {} FROM terms_versions WHERE version = $1", TERMS_COLUMNS))
+        .bind(&version)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+
+    terms
+        .map(RespJson)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Terms version not found"}))))
+}
+
+async fn list_terms_versions(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+
+    let versions: Vec<TermsVersion> = sqlx::query_as(&format!(
+        "SELECT {} FROM terms_versions ORDER BY published_at DESC",
+        TERMS_COLUMNS
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "data": versions
+    })))
+}
+
+// Terbitkan versi T&C baru. Versi lama tidak bisa diubah supaya persetujuan di order tetap bisa dilacak.
+async fn create_terms_version(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreateTermsVersionRequest>,
+) -> Result<RespJson<TermsVersion>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let version = payload.version.trim();
+    let title = payload.title.trim();
+    let content = payload.content.trim();
+    if version.is_empty() || title.is_empty() || content.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "version, title, and content are required"}))));
+    }
+
+    let terms: TermsVersion = sqlx::query_as(&format!(
+        "INSERT INTO terms_versions (version, title, content, published_at, created_by)
+         VALUES ($1, $2, $3, COALESCE($4, NOW()), $5)
+         RETURNING {}",
+        TERMS_COLUMNS
+    ))
+    .bind(version)
+    .bind(title)
+    .bind(content)
+    .bind(payload.published_at)
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({"error": "Terms version already exists"})))
+        }
+        _ => db_error(e),
+    })?;
+
+    println!("📜 Terms version {} published by {}", terms.version, user.id);
+    Ok(RespJson(terms))
+}
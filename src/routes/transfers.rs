@@ -0,0 +1,269 @@
+use axum::{
+    Router,
+    routing::{delete, get, post},
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    middleware,
+    response::Json as RespJson,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::auth::scope;
+use crate::middleware::scope::{require_scope, RequireScope};
+use crate::model::transfer::{CreateTransferRequest, MotorTransfer, TransferStatus};
+use crate::routes::branches::find_branch;
+use crate::routes::motor::authorize_motor_branch;
+
+const TRANSFER_COLUMNS: &str = "id, motor_id, from_branch, to_branch, effective_date, status, units_moved, notes, created_by, created_at, completed_at";
+
+pub fn transfer_router() -> Router {
+    Router::new()
+        .route("/api/motors/:id/transfer", post(create_transfer).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+        .route("/api/motors/:id/transfers", get(list_transfers))
+        .route("/api/motors/:id/transfers/:transfer_id", delete(cancel_transfer).route_layer(
+            middleware::from_fn_with_state(RequireScope(scope::MOTORS_WRITE), require_scope),
+        ))
+}
+
+fn db_error(e: sqlx::Error) -> (StatusCode, RespJson<serde_json::Value>) {
+    println!("❌ Database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({"error": "Database error"})))
+}
+
+// Booking aktif motor ini yang diambil di cabang selain tujuan dan masih berjalan pada / setelah
+// tanggal efektif; motor tidak boleh dipindah dari bawah booking tersebut
+async fn ensure_no_branch_bookings(
+    conn: &mut PgConnection,
+    motor_id: i32,
+    to_branch: &str,
+    effective_date: NaiveDate,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    let bookings: Vec<(String, String, NaiveDateTime, NaiveDateTime)> = sqlx::query_as(
+        "SELECT o.order_number, o.pilih_cabang, o.tanggal_peminjaman + o.jam_peminjaman, o.tanggal_pengembalian + o.jam_pengembalian
+         FROM orders o
+         WHERE (o.motor_id = $1 OR EXISTS (SELECT 1 FROM order_motors l WHERE l.order_id = o.id AND l.motor_id = $1))
+           AND o.status NOT IN ('cancelled', 'returned', 'completed')
+           AND LOWER(TRIM(o.pilih_cabang)) <> LOWER(TRIM($2))
+           AND o.tanggal_pengembalian >= $3
+         ORDER BY 3"
+    )
+    .bind(motor_id)
+    .bind(to_branch)
+    .bind(effective_date)
+    .fetch_all(conn)
+    .await
+    .map_err(db_error)?;
+
+    if bookings.is_empty() {
+        return Ok(());
+    }
+
+    Err((StatusCode::CONFLICT, RespJson(serde_json::json!({
+        "error": "Motor has active bookings at its current branch after the effective date",
+        "bookings": bookings.iter()
+            .map(|(order_number, branch, start, end)| serde_json::json!({
+                "bookingCode": order_number, "branch": branch, "start": start, "end": end
+            }))
+            .collect::<Vec<_>>()
+    }))))
+}
+
+// Jalankan transfer: cabang motor dan unit-unitnya yang masih di cabang asal (atau belum punya
+// cabang) pindah ke cabang tujuan. Unit yang sudah pensiun tidak ikut dipindah.
+async fn apply_transfer(conn: &mut PgConnection, transfer_id: Uuid) -> Result<MotorTransfer, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "WITH t AS (
+             SELECT * FROM motor_transfers WHERE id = $1
+         ), motor AS (
+             UPDATE motors m SET branch = t.to_branch FROM t WHERE m.motor_id = t.motor_id
+         ), units AS (
+             UPDATE motor_units u
+             SET branch = t.to_branch, updated_at = NOW()
+             FROM t
+             WHERE u.motor_id = t.motor_id
+               AND u.status <> 'retired'
+               AND (u.branch IS NULL OR t.from_branch IS NULL OR LOWER(TRIM(u.branch)) = LOWER(TRIM(t.from_branch)))
+             RETURNING u.id
+         )
+         UPDATE motor_transfers
+         SET status = 'completed', completed_at = NOW(), units_moved = (SELECT COUNT(*) FROM units)
+         WHERE id = $1
+         RETURNING {}",
+        TRANSFER_COLUMNS
+    ))
+    .bind(transfer_id)
+    .fetch_one(conn)
+    .await
+}
+
+// Jalankan transfer terjadwal yang tanggal efektifnya sudah tiba (dipanggil job berkala)
+pub(crate) async fn apply_due_transfers(pool: &PgPool) -> Result<Vec<MotorTransfer>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let due: Vec<(Uuid,)> = sqlx::query_as(
+        "SELECT id FROM motor_transfers
+         WHERE status = 'scheduled' AND effective_date <= CURRENT_DATE
+         ORDER BY effective_date, created_at
+         FOR UPDATE SKIP LOCKED"
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    let mut applied = Vec::with_capacity(due.len());
+    for (id,) in due {
+        applied.push(apply_transfer(&mut tx, id).await?);
+    }
+
+    tx.commit().await?;
+    Ok(applied)
+}
+
+// Pindahkan motor ke cabang lain, langsung atau pada tanggal efektif. Hanya admin (sama seperti
+// mengganti cabang lewat PUT /api/motors/:id).
+async fn create_transfer(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+    Json(payload): Json<CreateTransferRequest>,
+) -> Result<RespJson<MotorTransfer>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let to_branch = find_branch(&pool, &payload.to_branch).await.map_err(db_error)?.ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Unknown branch: {}", payload.to_branch.trim())
+        })))
+    })?;
+    if !to_branch.active {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Branch {} is inactive", to_branch.name)
+        }))));
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let effective_date = payload.effective_date.unwrap_or(today);
+    if effective_date < today {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({"error": "effectiveDate cannot be in the past"}))));
+    }
+    let notes = payload.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Kunci motor supaya tidak ada dua transfer / edit cabang yang balapan
+    let motor: Option<(Option<String>,)> = sqlx::query_as("SELECT branch FROM motors WHERE motor_id = $1 AND deleted_at IS NULL FOR UPDATE")
+        .bind(motor_id)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(db_error)?;
+    let (from_branch,) = motor
+        .ok_or_else(|| (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Motor not found"}))))?;
+
+    if from_branch.as_deref().is_some_and(|b| b.trim().eq_ignore_ascii_case(&to_branch.name)) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Motor is already at branch {}", to_branch.name)
+        }))));
+    }
+
+    ensure_no_branch_bookings(&mut tx, motor_id, &to_branch.name, effective_date).await?;
+
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO motor_transfers (id, motor_id, from_branch, to_branch, effective_date, status, notes, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+    )
+    .bind(id)
+    .bind(motor_id)
+    .bind(&from_branch)
+    .bind(&to_branch.name)
+    .bind(effective_date)
+    .bind(TransferStatus::Scheduled.as_str())
+    .bind(notes)
+    .bind(user.id)
+    .execute(&mut tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
+            (StatusCode::CONFLICT, RespJson(serde_json::json!({
+                "error": "Motor already has a scheduled transfer; cancel it first"
+            })))
+        }
+        _ => db_error(e),
+    })?;
+
+    let transfer: MotorTransfer = if effective_date <= today {
+        apply_transfer(&mut tx, id).await.map_err(db_error)?
+    } else {
+        sqlx::query_as(&format!("SELECT {} FROM motor_transfers WHERE id = $1", TRANSFER_COLUMNS))
+            .bind(id)
+            .fetch_one(&mut tx)
+            .await
+            .map_err(db_error)?
+    };
+    tx.commit().await.map_err(db_error)?;
+
+    println!(
+        "🚚 Motor #{} transfer {} -> {} ({}, {}) by {}",
+        motor_id, from_branch.as_deref().unwrap_or("-"), transfer.to_branch, transfer.effective_date, transfer.status, user.id
+    );
+    Ok(RespJson(transfer))
+}
+
+// Riwayat transfer motor, terbaru dulu; staff hanya untuk motor di cabangnya
+async fn list_transfers(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path(motor_id): Path<i32>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_staff()?;
+    authorize_motor_branch(&pool, &user, motor_id).await?;
+
+    let transfers: Vec<MotorTransfer> = sqlx::query_as(&format!(
+        "SELECT {} FROM motor_transfers WHERE motor_id = $1 ORDER BY effective_date DESC, created_at DESC",
+        TRANSFER_COLUMNS
+    ))
+    .bind(motor_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(RespJson(serde_json::json!({
+        "success": true,
+        "motorId": motor_id,
+        "data": transfers
+    })))
+}
+
+// Batalkan transfer yang masih terjadwal; transfer yang sudah jalan dibalik dengan transfer baru
+async fn cancel_transfer(
+    user: AuthUser,
+    Extension(pool): Extension<PgPool>,
+    Path((motor_id, transfer_id)): Path<(i32, Uuid)>,
+) -> Result<RespJson<MotorTransfer>, (StatusCode, RespJson<serde_json::Value>)> {
+    user.require_admin()?;
+
+    let transfer: Option<MotorTransfer> = sqlx::query_as(&format!(
+        "UPDATE motor_transfers SET status = $3
+         WHERE id = $1 AND motor_id = $2 AND status = $4
+         RETURNING {}",
+        TRANSFER_COLUMNS
+    ))
+    .bind(transfer_id)
+    .bind(motor_id)
+    .bind(TransferStatus::Cancelled.as_str())
+    .bind(TransferStatus::Scheduled.as_str())
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let transfer = transfer.ok_or_else(|| {
+        (StatusCode::NOT_FOUND, RespJson(serde_json::json!({"error": "Scheduled transfer not found"})))
+    })?;
+
+    println!("🚚 Transfer {} of motor #{} cancelled by {}", transfer.id, motor_id, user.id);
+    Ok(RespJson(transfer))
+}
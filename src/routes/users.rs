@@ -1,15 +1,25 @@
     use axum::{
     Router,
-    routing::get,
-    extract::{Extension, Path},
-    http::{StatusCode, HeaderMap},
+    routing::{get, put, delete},
+    extract::{ConnectInfo, Extension, Json, Path},
+    http::{HeaderMap, StatusCode},
     response::Json as RespJson,
 };
 use serde_json;
 use sqlx::PgPool;
+use std::net::{IpAddr, SocketAddr};
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::auth::AuthUser;
+use crate::auth::audit::{self, client_ip, AuthEvent};
+use crate::auth::password::{hash_password, verify_password};
+use crate::auth::password_policy;
+use crate::auth::session::revoke_session;
+use crate::auth::token::{random_token, revoke_all_user_tokens};
+use crate::model::user::{ChangePasswordRequest, UserSession};
+use crate::routes::favorites::list_my_favorites;
+
 #[derive(Debug, serde::Serialize)]
 struct UserResponse {
     pub id: String,
@@ -20,63 +30,25 @@ struct UserResponse {
     pub created_at: String,
 }
 
-// Helper function untuk ambil user dari token
-async fn get_user_from_token(headers: &HeaderMap, pool: &PgPool) -> Result<Uuid, StatusCode> {
-    // Ambil token dari header Authorization
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|header| header.to_str().ok())
-        .and_then(|header| {
-            if header.starts_with("Bearer ") {
-                Some(&header[7..])
-            } else {
-                None
-            }
-        })
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    // Parse dummy token format: "dummy_token_for_{user_id}"
-    let user_id_str = auth_header.strip_prefix("dummy_token_for_")
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-    
-    let user_id = Uuid::parse_str(user_id_str)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    // Verify user exists in database
-    let exists = sqlx::query!("SELECT id FROM users WHERE id = $1", user_id)
-        .fetch_optional(pool)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .is_some();
-
-    if !exists {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    Ok(user_id)
-}
-
 // Create users router
 pub fn users_router() -> Router {
     Router::new()
+        .route("/me", delete(delete_my_account))  // DELETE /api/users/me
         .route("/:id", get(get_user))  // GET /api/users/{id}
+        .route("/me/password", put(change_password))  // PUT /api/users/me/password
+        .route("/me/sessions", get(list_my_sessions))  // GET /api/users/me/sessions
+        .route("/me/sessions/:id", delete(revoke_my_session))  // DELETE /api/users/me/sessions/{id}
+        .route("/me/favorites", get(list_my_favorites))  // GET /api/users/me/favorites
 }
 
 // Get user by ID
 async fn get_user(
     Extension(pool): Extension<PgPool>,
-    headers: HeaderMap,
+    user: AuthUser,
     Path(id): Path<String>,
 ) -> Result<RespJson<UserResponse>, (StatusCode, RespJson<serde_json::Value>)> {
     println!("🔧 Getting user with ID: {}", id);
 
-    // Verify user authentication
-    let _current_user_id = get_user_from_token(&headers, &pool).await
-        .map_err(|status| {
-            println!("❌ Authentication failed");
-            (status, RespJson(serde_json::json!({"error": "Authentication required"})))
-        })?;
-
     // Handle special case for default-id or invalid UUIDs
     if id == "default-id" || id.is_empty() {
         println!("❌ Invalid user ID: {}", id);
@@ -91,6 +63,7 @@ async fn get_user(
             "error": format!("Invalid user ID format: {}", e)
         })))
     })?;
+    user.require_self_or_admin(user_id)?;
 
     let result = sqlx::query!(
         "SELECT id, username, full_name, email, phone, created_at FROM users WHERE id = $1",
@@ -127,3 +100,260 @@ async fn get_user(
         }
     }
 }
+
+// Ganti password user yang sedang login
+async fn change_password(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    println!("🔧 Changing password for user: {}", user.id);
+
+    if let Err(violations) = password_policy::validate(&request.new_password) {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": format!("Password tidak memenuhi syarat: password {}", violations.join(", ")),
+            "violations": violations
+        }))));
+    }
+
+    if request.new_password == request.old_password {
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Password baru harus berbeda dari password lama"
+        }))));
+    }
+
+    let current: (String,) = sqlx::query_as("SELECT password_hash FROM users WHERE id = $1")
+        .bind(user.id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| {
+            println!("❌ Database error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+                "error": "Database error"
+            })))
+        })?;
+
+    if !verify_password(&request.old_password, &current.0) {
+        println!("❌ Old password mismatch");
+        return Err((StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Password lama salah"
+        }))));
+    }
+
+    let password_hash = hash_password(&request.new_password).map_err(|e| {
+        println!("❌ Password hashing failed: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Failed to update password"
+        })))
+    })?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Failed to update password"
+        })))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+        .bind(password_hash)
+        .bind(user.id)
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    // Semua token lama (termasuk yang sedang dipakai) dicabut
+    revoke_all_user_tokens(&mut tx, user.id).await.map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    audit::record(
+        &pool,
+        AuthEvent::PasswordChange,
+        Some(user.id),
+        Some(client_ip(&headers, &addr)),
+        &headers,
+        serde_json::json!({ "impersonated_by": user.impersonated_by }),
+    )
+    .await;
+    println!("✅ Password changed, all sessions revoked");
+    Ok(RespJson(serde_json::json!({
+        "message": "Password berhasil diganti, silakan login kembali"
+    })))
+}
+
+// List sesi login (device) aktif milik user yang sedang login
+async fn list_my_sessions(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let sessions: Vec<UserSession> = sqlx::query_as(
+        "SELECT id, user_agent, ip_address, created_at, last_used_at
+         FROM user_sessions
+         WHERE user_id = $1 AND revoked_at IS NULL
+         ORDER BY COALESCE(last_used_at, created_at) DESC"
+    )
+    .bind(user.id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    })?;
+
+    let data: Vec<serde_json::Value> = sessions.iter().map(|session| {
+        serde_json::json!({
+            "id": session.id,
+            "user_agent": session.user_agent,
+            "ip_address": session.ip_address,
+            "created_at": session.created_at,
+            "last_used_at": session.last_used_at,
+            "current": user.session_id == Some(session.id)
+        })
+    }).collect();
+
+    Ok(RespJson(serde_json::json!({
+        "sessions": data,
+        "total": data.len()
+    })))
+}
+
+// Logout-kan satu device tertentu
+async fn revoke_my_session(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    let session_id = Uuid::parse_str(&id).map_err(|_| {
+        (StatusCode::BAD_REQUEST, RespJson(serde_json::json!({
+            "error": "Invalid session ID format"
+        })))
+    })?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Database error"
+        })))
+    };
+
+    let mut conn = pool.acquire().await.map_err(db_error)?;
+    let revoked = revoke_session(&mut conn, user.id, session_id).await.map_err(db_error)?;
+
+    if !revoked {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "Session not found"
+        }))));
+    }
+
+    println!("🔒 Session {} revoked by user {}", session_id, user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Session revoked successfully"
+    })))
+}
+
+// Anonimkan akun: data pribadi (nama, email, HP) dihapus tapi baris user tetap ada supaya order lama
+// tetap tercatat. Semua token dicabut dan penghapusan dicatat di account_deletion_audit.
+pub(crate) async fn anonymize_account(
+    pool: &PgPool,
+    user_id: Uuid,
+    ip: IpAddr,
+) -> Result<(), (StatusCode, RespJson<serde_json::Value>)> {
+    // Password diganti hash acak supaya akun tidak bisa dipakai login lagi
+    let unusable_hash = hash_password(&random_token()).map_err(|e| {
+        println!("❌ Password hashing error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Failed to delete account"
+        })))
+    })?;
+
+    let db_error = |e: sqlx::Error| {
+        println!("❌ Database error: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, RespJson(serde_json::json!({
+            "error": "Failed to delete account"
+        })))
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let short_id = user_id.simple().to_string()[..12].to_string();
+
+    let result = sqlx::query(
+        "UPDATE users SET
+            full_name = 'Deleted User',
+            username = $1,
+            email = $2,
+            phone = '',
+            password_hash = $3,
+            google_sub = NULL,
+            phone_verified_at = NULL,
+            deleted_at = NOW()
+         WHERE id = $4 AND deleted_at IS NULL"
+    )
+    .bind(format!("deleted_{}", short_id))
+    .bind(format!("deleted+{}@deleted.invalid", user_id))
+    .bind(unusable_hash)
+    .bind(user_id)
+    .execute(&mut tx)
+    .await
+    .map_err(db_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, RespJson(serde_json::json!({
+            "error": "User not found"
+        }))));
+    }
+
+    revoke_all_user_tokens(&mut tx, user_id).await.map_err(db_error)?;
+
+    sqlx::query("INSERT INTO account_deletion_audit (id, user_id, ip_address) VALUES ($1, $2, $3)")
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(ip.to_string())
+        .execute(&mut tx)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(())
+}
+
+// Hapus akun sendiri: data pribadi (nama, email, HP) dianonimkan, tapi baris user tetap ada
+// supaya order lama tetap tercatat untuk pembukuan. Semua token dicabut dan dicatat di audit.
+async fn delete_my_account(
+    Extension(pool): Extension<PgPool>,
+    user: AuthUser,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<RespJson<serde_json::Value>, (StatusCode, RespJson<serde_json::Value>)> {
+    println!("🗑️ Account deletion requested by user {}", user.id);
+
+    // Admin yang sedang impersonation tidak boleh menghapus akun customer
+    if user.impersonated_by.is_some() {
+        return Err((StatusCode::FORBIDDEN, RespJson(serde_json::json!({
+            "error": "Account deletion is not allowed while impersonating"
+        }))));
+    }
+
+    anonymize_account(&pool, user.id, client_ip(&headers, &addr)).await?;
+
+    audit::record(
+        &pool,
+        AuthEvent::AccountDeleted,
+        Some(user.id),
+        Some(client_ip(&headers, &addr)),
+        &headers,
+        serde_json::json!({}),
+    )
+    .await;
+
+    println!("✅ Account {} anonymized", user.id);
+    Ok(RespJson(serde_json::json!({
+        "message": "Akun berhasil dihapus"
+    })))
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+// Abstraksi pengiriman pesan ke nomor HP (SMS / WhatsApp gateway)
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    async fn send(&self, phone: &str, message: &str) -> Result<(), String>;
+}
+
+pub type SharedSmsSender = Arc<dyn SmsSender>;
+
+// Sender untuk development: pesan hanya dicetak ke console
+pub struct LogSmsSender;
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, phone: &str, message: &str) -> Result<(), String> {
+        println!("📱 [LogSmsSender] To: {} | {}", phone, message);
+        Ok(())
+    }
+}
+
+// Sender via HTTP gateway (JSON: target, message) dengan API key di header Authorization
+pub struct HttpSmsSender {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+}
+
+#[async_trait]
+impl SmsSender for HttpSmsSender {
+    async fn send(&self, phone: &str, message: &str) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&self.api_url)
+            .header("Authorization", &self.api_key)
+            .json(&serde_json::json!({
+                "target": phone,
+                "message": message,
+            }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("SMS gateway returned {}", response.status()));
+        }
+
+        Ok(())
+    }
+}
+
+// Pilih sender dari env: SMS_API_URL di-set -> HttpSmsSender, selain itu LogSmsSender
+pub fn from_env() -> SharedSmsSender {
+    match std::env::var("SMS_API_URL") {
+        Ok(api_url) if !api_url.is_empty() => {
+            println!("📱 SMS sender: HTTP gateway ({})", api_url);
+            Arc::new(HttpSmsSender {
+                client: reqwest::Client::new(),
+                api_url,
+                api_key: std::env::var("SMS_API_KEY").unwrap_or_default(),
+            })
+        }
+        _ => {
+            println!("📱 SMS sender: console log (SMS_API_URL belum di-set)");
+            Arc::new(LogSmsSender)
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::model::orders::OrderStatus;
+use crate::model::webhook::{ORDER_CANCELLED, ORDER_COMPLETED, ORDER_CONFIRMED, ORDER_CREATED};
+
+// Batas percobaan kirim sebelum delivery dianggap gagal permanen
+const MAX_ATTEMPTS: i32 = 8;
+
+// Event webhook untuk perubahan status order. Hold (status held) belum dianggap order,
+// jadi order.created baru dikirim saat hold dikonfirmasi, dan hold yang batal tidak dikirim.
+pub fn events_for_transition(old_status: Option<&str>, new_status: OrderStatus) -> Vec<&'static str> {
+    let from_hold = old_status == Some(OrderStatus::Held.as_str());
+    match (old_status, new_status) {
+        // Perubahan detail booking tanpa ganti status (PATCH /api/orders/:id)
+        (Some(old), new) if old == new.as_str() => vec![],
+        (_, OrderStatus::Held) => vec![],
+        (None, _) => vec![ORDER_CREATED],
+        (_, OrderStatus::Confirmed) if from_hold => vec![ORDER_CREATED, ORDER_CONFIRMED],
+        (_, OrderStatus::Cancelled) if from_hold => vec![],
+        (_, OrderStatus::Confirmed) => vec![ORDER_CONFIRMED],
+        (_, OrderStatus::Cancelled) => vec![ORDER_CANCELLED],
+        (_, OrderStatus::Completed) => vec![ORDER_COMPLETED],
+        _ => vec![],
+    }
+}
+
+// Data order yang dikirim di payload webhook
+#[derive(sqlx::FromRow)]
+struct OrderPayloadRow {
+    order_number: String,
+    status: String,
+    user_id: Uuid,
+    motor_id: Option<i32>,
+    pilih_motor: String,
+    pilih_cabang: String,
+    rental_start: chrono::NaiveDateTime,
+    rental_end: chrono::NaiveDateTime,
+    total_price: Option<i64>,
+}
+
+// Antrekan event untuk semua subscription aktif (outbox). Dipanggil di dalam transaksi yang
+// mengubah order, jadi event hanya terkirim jika perubahan order-nya commit.
+pub async fn enqueue_order_event(conn: &mut PgConnection, event: &str, order_id: Uuid) -> Result<(), sqlx::Error> {
+    let subscriptions: Vec<(Uuid,)> = sqlx::query_as(
+        "SELECT id FROM webhook_subscriptions WHERE active = TRUE AND $1 = ANY(events)"
+    )
+    .bind(event)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+
+    let order: OrderPayloadRow = sqlx::query_as(
+        "SELECT order_number, status, user_id, motor_id, pilih_motor, pilih_cabang,
+                tanggal_peminjaman + jam_peminjaman AS rental_start,
+                tanggal_pengembalian + jam_pengembalian AS rental_end,
+                total_price
+         FROM orders WHERE id = $1"
+    )
+    .bind(order_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    for (subscription_id,) in subscriptions {
+        let delivery_id = Uuid::new_v4();
+        let payload = serde_json::json!({
+            "id": delivery_id,
+            "event": event,
+            "occurredAt": chrono::Utc::now(),
+            "data": {
+                "orderId": order_id,
+                "orderNumber": order.order_number,
+                "status": order.status,
+                "userId": order.user_id,
+                "motorId": order.motor_id,
+                "motorName": order.pilih_motor,
+                "branch": order.pilih_cabang,
+                "rentalStart": order.rental_start,
+                "rentalEnd": order.rental_end,
+                "totalPrice": order.total_price
+            }
+        });
+
+        sqlx::query(
+            "INSERT INTO webhook_deliveries (id, subscription_id, event, payload) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(delivery_id)
+        .bind(subscription_id)
+        .bind(event)
+        .bind(payload)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Delivery yang sudah jatuh tempo untuk dikirim
+#[derive(sqlx::FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    event: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+// Tanda tangan body: hex(HMAC-SHA256(secret, body)), dikirim di header X-Sentor-Signature
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// Jeda sebelum percobaan berikutnya: 30 detik, 1 menit, 2 menit, ... maksimal 6 jam
+fn backoff_secs(attempts: i32) -> f64 {
+    (30.0 * 2f64.powi(attempts.saturating_sub(1))).min(6.0 * 60.0 * 60.0)
+}
+
+// Kirim satu batch delivery yang jatuh tempo. Delivery di-lease 5 menit dulu (next_attempt_at
+// dimajukan) supaya tidak dikirim dobel oleh instance lain selama request berjalan.
+pub async fn dispatch_due(pool: &PgPool, client: &reqwest::Client) -> Result<usize, sqlx::Error> {
+    let due: Vec<DueDelivery> = sqlx::query_as(
+        "WITH leased AS (
+             UPDATE webhook_deliveries SET next_attempt_at = NOW() + INTERVAL '5 minutes'
+             WHERE id IN (
+                 SELECT id FROM webhook_deliveries
+                 WHERE delivered_at IS NULL AND failed_at IS NULL AND next_attempt_at <= NOW()
+                 ORDER BY next_attempt_at
+                 LIMIT 20
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING id, subscription_id, event, payload, attempts
+         )
+         SELECT l.id, l.event, l.payload, l.attempts, s.url, s.secret
+         FROM leased l JOIN webhook_subscriptions s ON s.id = l.subscription_id"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for delivery in &due {
+        let body = delivery.payload.to_string();
+        let result = client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("X-Sentor-Event", &delivery.event)
+            .header("X-Sentor-Delivery", delivery.id.to_string())
+            .header("X-Sentor-Signature", sign(&delivery.secret, body.as_bytes()))
+            .body(body)
+            .send()
+            .await;
+
+        let error = match result {
+            Ok(response) if response.status().is_success() => None,
+            Ok(response) => Some(format!("HTTP {}", response.status())),
+            Err(e) => Some(e.to_string()),
+        };
+
+        let attempts = delivery.attempts + 1;
+        match error {
+            None => {
+                sqlx::query("UPDATE webhook_deliveries SET attempts = $1, delivered_at = NOW(), last_error = NULL WHERE id = $2")
+                    .bind(attempts)
+                    .bind(delivery.id)
+                    .execute(pool)
+                    .await?;
+            }
+            Some(error) => {
+                println!("⚠️  Webhook {} ke {} gagal (percobaan {}): {}", delivery.event, delivery.url, attempts, error);
+                sqlx::query(
+                    "UPDATE webhook_deliveries
+                     SET attempts = $1, last_error = $2,
+                         next_attempt_at = NOW() + make_interval(secs => $3),
+                         failed_at = CASE WHEN $1 >= $4 THEN NOW() END
+                     WHERE id = $5"
+                )
+                .bind(attempts)
+                .bind(&error)
+                .bind(backoff_secs(attempts))
+                .bind(MAX_ATTEMPTS)
+                .bind(delivery.id)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+
+    Ok(due.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OrderStatus::*;
+
+    #[test]
+    fn events_for_transition_per_status_change() {
+        let cases: [(Option<&str>, OrderStatus, &[&str]); 14] = [
+            // Order baru
+            (None, Pending, &[ORDER_CREATED]),
+            (None, Confirmed, &[ORDER_CREATED]),
+            // Hold belum dianggap order: dibuat / batal / kedaluwarsa tanpa event
+            (None, Held, &[]),
+            (Some("held"), Held, &[]),
+            (Some("held"), Cancelled, &[]),
+            // Hold dikonfirmasi: baru dianggap order dan langsung terkonfirmasi
+            (Some("held"), Confirmed, &[ORDER_CREATED, ORDER_CONFIRMED]),
+            // Alur order biasa
+            (Some("pending"), Confirmed, &[ORDER_CONFIRMED]),
+            (Some("pending"), Cancelled, &[ORDER_CANCELLED]),
+            (Some("confirmed"), Cancelled, &[ORDER_CANCELLED]),
+            (Some("confirmed"), PickedUp, &[]),
+            (Some("picked_up"), Overdue, &[]),
+            (Some("overdue"), Returned, &[]),
+            (Some("returned"), Completed, &[ORDER_COMPLETED]),
+            // Edit detail booking tanpa ganti status
+            (Some("confirmed"), Confirmed, &[]),
+        ];
+        for (old_status, new_status, expected) in cases {
+            assert_eq!(
+                events_for_transition(old_status, new_status),
+                expected,
+                "{:?} -> {:?}",
+                old_status,
+                new_status
+            );
+        }
+    }
+}